@@ -0,0 +1,13 @@
+#![no_main]
+
+use {
+    burette::{Filter, OutputTemplate},
+    libfuzzer_sys::fuzz_target,
+};
+
+fuzz_target!(|s: &str| {
+    if let Ok(filter) = s.parse::<Filter>() {
+        assert_eq!(filter.to_string().parse::<Filter>().ok(), Some(filter));
+    }
+    let _ = s.parse::<OutputTemplate>();
+});
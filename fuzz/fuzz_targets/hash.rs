@@ -0,0 +1,10 @@
+#![no_main]
+
+use {burette::sha256::Hash, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|s: &str| {
+    if let Ok(hash) = Hash::from_hex(s) {
+        assert_eq!(hash.to_string(), s.to_lowercase());
+        assert!(hash.starts_with(s));
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use {burette::IndexEntry, libfuzzer_sys::fuzz_target};
+
+// The library index is a JSON array of index entries.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(index) = serde_json::from_slice::<Vec<IndexEntry>>(data) {
+        let json = serde_json::to_string(&index).expect("Index can be serialized");
+        let reparsed: Vec<IndexEntry> =
+            serde_json::from_str(&json).expect("Serialized index can be deserialized");
+        assert_eq!(
+            serde_json::to_string(&reparsed).expect("Index can be serialized"),
+            json
+        );
+    }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use {burette::Isbn13, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|s: &str| {
+    if let Ok(isbn) = s.parse::<Isbn13>() {
+        assert_eq!(isbn.to_string().parse::<Isbn13>().ok(), Some(isbn));
+    }
+});
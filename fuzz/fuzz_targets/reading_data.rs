@@ -0,0 +1,8 @@
+#![no_main]
+
+use {burette::ReadingData, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|s: &str| {
+    let _ = ReadingData::from_kindle_clippings(s);
+    let _ = ReadingData::from_koreader_sidecar(s);
+});
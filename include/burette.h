@@ -0,0 +1,73 @@
+/*
+ * C interface of burette, a document library manager.
+ *
+ * Build the shared library with
+ *
+ *     cargo rustc --lib --release --features ffi --crate-type cdylib
+ *
+ * All strings are UTF-8 and NUL-terminated. Strings returned by burette must be
+ * freed with burette_string_free(). Functions that fail return NULL or -1, and
+ * burette_last_error() describes what went wrong.
+ *
+ * See the documentation of the `burette::ffi` module for details.
+ */
+
+#ifndef BURETTE_H
+#define BURETTE_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A library opened with burette_library_open(). */
+typedef struct BuretteLibrary BuretteLibrary;
+
+/*
+ * Return a description of the last error on the calling thread, or NULL if no
+ * call has failed yet. The string is owned by burette and stays valid until the
+ * next call that fails on the same thread.
+ */
+const char *burette_last_error(void);
+
+/* Free a string returned by burette. Does nothing if s is NULL. */
+void burette_string_free(char *s);
+
+/*
+ * Open the library at path. Returns NULL on error. The library must be freed
+ * with burette_library_free().
+ */
+BuretteLibrary *burette_library_open(const char *path);
+
+/* Free a library. Does nothing if library is NULL. */
+void burette_library_free(BuretteLibrary *library);
+
+/*
+ * Return all documents in the library as a JSON array, in the format of the
+ * entries of the index file. Returns NULL on error.
+ */
+char *burette_library_list(const BuretteLibrary *library);
+
+/*
+ * Add the file at path to the library. metadata is a JSON object with the
+ * same fields as the entries returned by burette_library_list(), e.g.
+ * {"title": "Faust", "authors": ["Goethe"], "isbns": [],
+ *  "file_format": "application/epub+zip", "doi": null}.
+ * Returns 0 on success and -1 on error.
+ */
+int burette_library_add(const BuretteLibrary *library, const char *path,
+                        const char *metadata);
+
+/*
+ * Copy the document identified by identifier (e.g. a hash prefix or an ISBN)
+ * out of the library to out_path, or to the current directory under its
+ * default file name if out_path is NULL. Returns the path of the copy, or NULL
+ * on error.
+ */
+char *burette_library_get(const BuretteLibrary *library, const char *identifier,
+                          const char *out_path);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BURETTE_H */
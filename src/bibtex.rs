@@ -0,0 +1,733 @@
+//! BibTeX files with links to documents, as written by `JabRef`.
+//!
+//! `JabRef` links entries to files with a `file` field of the form
+//! `Description:path:Type`. Multiple links are separated by `;`, and `:`, `;` and `\` in the
+//! parts are escaped with a backslash, e.g. `file = {:C\:\\Papers\\knuth.pdf:PDF}`. Relative
+//! paths are relative to the directory of the BibTeX file.
+
+use {
+    crate::{DocMetadata, DocType, FileFormat, IndexEntry, Isbn13},
+    anyhow::{bail, Context},
+    std::{
+        collections::HashMap,
+        fmt::{self, Display, Formatter},
+        fs,
+        path::Path,
+    },
+};
+
+/// Accented letters written as e.g. `\"o` in LaTeX, by accent and letter.
+const ACCENTS: [(char, &str, &str); 5] = [
+    ('"', "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+    ('\'', "aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+    ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('~', "anoANO", "ãñõÃÑÕ"),
+];
+
+/// Characters that are escaped with a backslash in BibTeX values.
+const SPECIAL_CHARACTERS: &str = "&%$#_{}";
+
+/// An entry of a BibTeX file, e.g. `@book{melville1851, title = {Moby Dick}}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibEntry {
+    /// Type of the entry in lowercase, e.g. `book` or `article`.
+    pub entry_type: String,
+    /// Citation key of the entry.
+    pub key: String,
+    /// Fields of the entry in the order they appear in the file, with lowercase names. The values
+    /// are the LaTeX source without the enclosing braces or quotes.
+    pub fields: Vec<(String, String)>,
+}
+
+/// A link from a BibTeX entry to a file, see [`BibEntry::file_links()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLink {
+    /// Description of the file, usually empty.
+    pub description: String,
+    /// Path of the file, either absolute or relative to the directory of the BibTeX file.
+    pub path: String,
+    /// Type of the file as named by `JabRef`, e.g. `PDF`.
+    pub file_type: String,
+}
+
+impl BibEntry {
+    /// Read all entries from the BibTeX file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file cannot be read or is not a valid BibTeX file.
+    pub fn read_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<Self>> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read BibTeX file at {}", path.display()))?;
+        Self::parse_all(&contents)
+            .with_context(|| format!("Invalid BibTeX file at {}", path.display()))
+    }
+
+    /// Parse all entries of a BibTeX file.
+    ///
+    /// `@string` definitions are expanded, `@comment` and `@preamble` entries as well as text
+    /// outside of entries are ignored.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if an entry is malformed, e.g. if its braces are unbalanced.
+    pub fn parse_all(s: &str) -> anyhow::Result<Vec<Self>> {
+        Parser::new(s).parse_all()
+    }
+
+    /// Return the LaTeX source of the field `name`.
+    #[must_use]
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Return the field `name` as plain text, with braces removed and LaTeX escapes replaced.
+    ///
+    /// Empty fields are treated as missing.
+    #[must_use]
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.field(name)
+            .map(latex_to_text)
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Return the files the entry links to, from its `JabRef` `file` field.
+    #[must_use]
+    pub fn file_links(&self) -> Vec<FileLink> {
+        let Some(field) = self.field("file") else {
+            return Vec::new();
+        };
+        split_escaped(field, ';')
+            .into_iter()
+            .filter(|link| !link.is_empty())
+            .map(|link| {
+                let mut parts = split_escaped(&link, ':');
+                // A link without separators only consists of the path.
+                if parts.len() == 1 {
+                    parts.insert(0, String::new());
+                }
+                let mut parts = parts.into_iter().map(|part| unescape_link_part(&part));
+                FileLink {
+                    description: parts.next().unwrap_or_default(),
+                    path: parts.next().unwrap_or_default(),
+                    file_type: parts.next().unwrap_or_default(),
+                }
+            })
+            .filter(|link| !link.path.is_empty())
+            .collect()
+    }
+
+    /// Return the metadata of a document described by this entry, stored in a file of format
+    /// `file_format`.
+    ///
+    /// Authors written as `Last, First` are turned around, ISBN-10s are converted to ISBN-13s and
+    /// the `booktitle` of papers in proceedings is used as their journal.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the entry has no title or contains an invalid ISBN.
+    pub fn metadata(&self, file_format: FileFormat) -> anyhow::Result<DocMetadata> {
+        let title = self
+            .text("title")
+            .with_context(|| format!("Entry {} has no title", self.key))?;
+        let authors = self.field("author").map(split_authors).unwrap_or_default();
+        let isbns = match self.text("isbn") {
+            Some(isbns) => isbns
+                .split([',', ';', ' '])
+                .filter(|isbn| !isbn.is_empty())
+                .map(parse_isbn)
+                .collect::<anyhow::Result<_>>()
+                .with_context(|| format!("Entry {} has an invalid ISBN", self.key))?,
+            None => Vec::new(),
+        };
+        let doi = self.text("doi").map(|doi| {
+            ["https://doi.org/", "http://dx.doi.org/", "doi:"]
+                .into_iter()
+                .find_map(|prefix| doi.strip_prefix(prefix))
+                .map_or_else(|| doi.clone(), ToOwned::to_owned)
+        });
+
+        Ok(DocMetadata {
+            title,
+            authors,
+            isbns,
+            file_format,
+            doi,
+            doc_type: doc_type(&self.entry_type),
+            rights: None,
+            journal: self.text("journal").or_else(|| self.text("booktitle")),
+            volume: self.text("volume"),
+            issue: self.text("number").or_else(|| self.text("issue")),
+            pages: self.text("pages").map(|pages| pages.replace("--", "-")),
+        })
+    }
+
+    /// Create an entry describing the document of `entry`, linking to the file at `file` if it is
+    /// given.
+    ///
+    /// The citation key is the first alias of the document, or its short hash if it has none.
+    #[must_use]
+    pub fn from_document(entry: &IndexEntry, file: Option<&str>) -> Self {
+        let entry_type = match entry.doc_type() {
+            Some(DocType::Book) => "book",
+            Some(DocType::Article) => "article",
+            Some(DocType::Thesis) => "phdthesis",
+            Some(DocType::Report) => "techreport",
+            Some(DocType::Standard) => "standard",
+            None => "misc",
+        };
+        let key = entry
+            .aliases()
+            .next()
+            .map_or_else(|| entry.hash().to_short_string(), ToOwned::to_owned);
+
+        let mut fields = vec![("title".to_owned(), text_to_latex(entry.title()))];
+        let authors: Vec<_> = entry.authors().map(text_to_latex).collect();
+        if !authors.is_empty() {
+            fields.push(("author".to_owned(), authors.join(" and ")));
+        }
+        let isbns: Vec<_> = entry.isbns().map(ToString::to_string).collect();
+        if !isbns.is_empty() {
+            fields.push(("isbn".to_owned(), isbns.join(", ")));
+        }
+        for (name, value) in [
+            ("doi", entry.doi()),
+            ("journal", entry.journal()),
+            ("volume", entry.volume()),
+            ("number", entry.issue()),
+            ("pages", entry.pages()),
+        ] {
+            if let Some(value) = value {
+                fields.push((name.to_owned(), text_to_latex(value)));
+            }
+        }
+        if let Some(file) = file {
+            let file_type = match entry.file_format() {
+                FileFormat::Epub => "ePUB",
+                FileFormat::Pdf => "PDF",
+            };
+            fields.push((
+                "file".to_owned(),
+                format!(":{}:{file_type}", escape_link_part(file)),
+            ));
+        }
+
+        Self {
+            entry_type: entry_type.to_owned(),
+            key,
+            fields,
+        }
+    }
+}
+
+impl Display for BibEntry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "@{}{{{}", self.entry_type, self.key)?;
+        for (name, value) in &self.fields {
+            write!(f, ",\n  {name} = {{{value}}}")?;
+        }
+        writeln!(f, "\n}}")
+    }
+}
+
+/// Parser for BibTeX files.
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Values defined by `@string` entries, by lowercase name.
+    strings: HashMap<String, String>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            strings: HashMap::new(),
+        }
+    }
+
+    /// Parse all entries of the input.
+    fn parse_all(mut self) -> anyhow::Result<Vec<BibEntry>> {
+        let mut entries = Vec::new();
+        while let Some(offset) = self.input[self.pos..].find('@') {
+            self.pos += offset + 1;
+            let entry_type = self.identifier().to_ascii_lowercase();
+            self.skip_whitespace();
+            let close = match self.peek() {
+                Some('{') => '}',
+                Some('(') => ')',
+                // An `@` outside of an entry, e.g. in an email address in a comment.
+                _ => continue,
+            };
+            self.pos += 1;
+            match entry_type.as_str() {
+                "comment" | "preamble" => self.skip_to(close)?,
+                "string" => {
+                    for (name, value) in self.fields(close)? {
+                        self.strings.insert(name, value);
+                    }
+                }
+                _ => {
+                    let line = self.line();
+                    let end = self.input[self.pos..]
+                        .find([',', close])
+                        .with_context(|| format!("Unterminated entry at line {line}"))?;
+                    let key = self.input[self.pos..self.pos + end].trim().to_owned();
+                    self.pos += end;
+                    let fields = if self.peek() == Some(',') {
+                        self.pos += 1;
+                        self.fields(close)?
+                    } else {
+                        self.pos += 1;
+                        Vec::new()
+                    };
+                    entries.push(BibEntry {
+                        entry_type,
+                        key,
+                        fields,
+                    });
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Parse the fields of an entry up to and including the closing delimiter `close`.
+    fn fields(&mut self, close: char) -> anyhow::Result<Vec<(String, String)>> {
+        let mut fields = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                return Ok(fields);
+            }
+            let name = self.identifier().to_ascii_lowercase();
+            if name.is_empty() {
+                bail!("Expected a field name at line {}", self.line());
+            }
+            self.skip_whitespace();
+            if self.peek() != Some('=') {
+                bail!("Expected `=` after field {} at line {}", name, self.line());
+            }
+            self.pos += 1;
+            let value = self.value()?;
+            fields.push((name, value));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(fields);
+                }
+                Some(_) => bail!("Expected `,` or `{}` at line {}", close, self.line()),
+                None => bail!("Unexpected end of file"),
+            }
+        }
+    }
+
+    /// Parse a field value, which may consist of multiple parts joined by `#`.
+    fn value(&mut self) -> anyhow::Result<String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.pos += 1;
+                    value.push_str(self.delimited('}')?);
+                }
+                Some('"') => {
+                    self.pos += 1;
+                    value.push_str(self.delimited('"')?);
+                }
+                Some(c) if c.is_ascii_digit() => {
+                    let end = self.input[self.pos..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(self.input.len() - self.pos);
+                    value.push_str(&self.input[self.pos..self.pos + end]);
+                    self.pos += end;
+                }
+                Some(_) => {
+                    let name = self.identifier();
+                    if name.is_empty() {
+                        bail!("Expected a value at line {}", self.line());
+                    }
+                    // Undefined strings such as month abbreviations are kept as they are.
+                    match self.strings.get(&name.to_ascii_lowercase()) {
+                        Some(string) => value.push_str(string),
+                        None => value.push_str(name),
+                    }
+                }
+                None => bail!("Unexpected end of file"),
+            }
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.pos += 1;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Return the text up to the delimiter `end` outside of braces and move past the delimiter.
+    fn delimited(&mut self, end: char) -> anyhow::Result<&'a str> {
+        let line = self.line();
+        let start = self.pos;
+        let mut depth = 0_usize;
+        let mut chars = self.input[start..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                c if c == end && depth == 0 => {
+                    self.pos = start + offset + 1;
+                    return Ok(&self.input[start..start + offset]);
+                }
+                '{' => depth += 1,
+                '}' if depth == 0 => bail!("Unbalanced braces in value at line {}", line),
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        bail!("Unterminated value at line {}", line)
+    }
+
+    /// Move past the delimiter `end` outside of braces.
+    fn skip_to(&mut self, end: char) -> anyhow::Result<()> {
+        self.delimited(end).map(|_| ())
+    }
+
+    /// Return the identifier at the current position, which may be empty.
+    fn identifier(&mut self) -> &'a str {
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || "_-:.+/".contains(c)))
+            .unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    /// Return the line number of the current position, for error messages.
+    fn line(&self) -> usize {
+        self.input[..self.pos].matches('\n').count() + 1
+    }
+}
+
+/// Return the type of the documents described by BibTeX entries of type `entry_type`.
+fn doc_type(entry_type: &str) -> Option<DocType> {
+    match entry_type {
+        "book" | "inbook" | "collection" => Some(DocType::Book),
+        "article" | "inproceedings" | "conference" | "incollection" => Some(DocType::Article),
+        "phdthesis" | "mastersthesis" | "thesis" => Some(DocType::Thesis),
+        "techreport" | "report" => Some(DocType::Report),
+        "standard" => Some(DocType::Standard),
+        _ => None,
+    }
+}
+
+/// Split the LaTeX source of an `author` field into names.
+///
+/// Names are separated by `and` outside of braces, so that braced corporate names such as
+/// `{Barnes and Noble}` stay intact. Names of the form `Last, First` are turned around, and
+/// `others` is dropped.
+fn split_authors(field: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (offset, c) in field.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                let rest = &field[offset + c.len_utf8()..];
+                if rest
+                    .get(..3)
+                    .is_some_and(|word| word.eq_ignore_ascii_case("and"))
+                    && rest
+                        .get(3..)
+                        .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+                {
+                    names.push(&field[start..offset]);
+                    start = offset + c.len_utf8() + 3;
+                }
+            }
+            _ => {}
+        }
+    }
+    names.push(&field[start..]);
+
+    names
+        .into_iter()
+        .map(|name| {
+            let parts: Vec<_> = split_escaped(name, ',')
+                .iter()
+                .map(|part| latex_to_text(part))
+                .collect();
+            match parts.as_slice() {
+                [last, first] => format!("{first} {last}"),
+                [last, suffix, first] => format!("{first} {last} {suffix}"),
+                _ => latex_to_text(name),
+            }
+        })
+        .filter(|name| !name.is_empty() && name != "others")
+        .collect()
+}
+
+/// Parse an ISBN-13 or an ISBN-10, which is converted to an ISBN-13.
+pub(crate) fn parse_isbn(isbn: &str) -> anyhow::Result<Isbn13> {
+    let digits: String = isbn.chars().filter(|&c| c != '-').collect();
+    if digits.len() != 10 {
+        return isbn.parse();
+    }
+    // ISBN-10s become ISBN-13s by prefixing them with 978 and recomputing the check digit.
+    let digits = format!("978{}", &digits[..9]);
+    let checksum: u32 = digits
+        .chars()
+        .enumerate()
+        .map(|(i, c)| c.to_digit(10).unwrap_or(0) * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    format!("{digits}{}", (10 - checksum % 10) % 10).parse()
+}
+
+/// Convert LaTeX source to plain text.
+///
+/// Braces are removed, escaped special characters and common accents are replaced by the
+/// characters they stand for, and other commands are dropped. Whitespace is collapsed.
+fn latex_to_text(latex: &str) -> String {
+    let mut text = String::with_capacity(latex.len());
+    let mut chars = latex.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) if SPECIAL_CHARACTERS.contains(c) => text.push(c),
+                Some(accent) if ACCENTS.iter().any(|(a, _, _)| *a == accent) => {
+                    while chars.peek() == Some(&'{') {
+                        chars.next();
+                    }
+                    if let Some(letter) = chars.next() {
+                        text.push(accented(accent, letter).unwrap_or(letter));
+                    }
+                }
+                Some(c) if c.is_ascii_alphabetic() => {
+                    let mut command = String::from(c);
+                    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                        command.push(c);
+                        chars.next();
+                    }
+                    match command.as_str() {
+                        "ss" => text.push('ß'),
+                        "textbackslash" => text.push('\\'),
+                        _ => {}
+                    }
+                }
+                Some(c) if c.is_whitespace() || c == '\\' => text.push(' '),
+                Some(c) => text.push(c),
+                None => {}
+            },
+            '{' | '}' => {}
+            '~' => text.push(' '),
+            c => text.push(c),
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Return the letter `letter` with the accent written as `\<accent>` in LaTeX.
+fn accented(accent: char, letter: char) -> Option<char> {
+    let (_, letters, accented) = ACCENTS.iter().find(|(a, _, _)| *a == accent)?;
+    let index = letters.chars().position(|c| c == letter)?;
+    accented.chars().nth(index)
+}
+
+/// Convert plain text to LaTeX source, escaping special characters.
+fn text_to_latex(text: &str) -> String {
+    let mut latex = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\\' {
+            latex.push_str("\\textbackslash{}");
+            continue;
+        }
+        if SPECIAL_CHARACTERS.contains(c) {
+            latex.push('\\');
+        }
+        latex.push(c);
+    }
+    latex
+}
+
+/// Split `s` at each `separator` that is not escaped with a backslash.
+///
+/// Escapes are kept in the parts.
+fn split_escaped(s: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == separator {
+            parts.push(part);
+            part = String::new();
+            continue;
+        }
+        part.push(c);
+        if c == '\\' {
+            part.extend(chars.next());
+        }
+    }
+    parts.push(part);
+    parts
+}
+
+/// Remove the backslashes escaping characters in a part of a `JabRef` file link.
+fn unescape_link_part(part: &str) -> String {
+    let mut unescaped = String::with_capacity(part.len());
+    let mut chars = part.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Escape `\`, `:` and `;` in a part of a `JabRef` file link.
+fn escape_link_part(part: &str) -> String {
+    let mut escaped = String::with_capacity(part.len());
+    for c in part.chars() {
+        if matches!(c, '\\' | ':' | ';') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{latex_to_text, parse_isbn, split_authors, BibEntry, FileLink},
+        crate::{DocType, FileFormat},
+    };
+
+    const JABREF: &str = r#"% Encoding: UTF-8
+
+@String{mit = {MIT Press}}
+
+@Book{sicp,
+  author    = {Abelson, Harold and Gerald Jay Sussman},
+  title     = {Structure and Interpretation of {Computer} Programs},
+  publisher = mit # { Cambridge},
+  year      = 1996,
+  isbn      = {0-262-51087-1},
+  file      = {:books/sicp.pdf:PDF;Scan:C\:\\Scans\\sicp.pdf:PDF},
+}
+
+@InProceedings{goedel,
+  author    = "G{\"o}del, Kurt and others",
+  title     = "{\"U}ber formal unentscheidbare S{\"a}tze",
+  booktitle = {Monatshefte f\"ur Mathematik},
+  pages     = {173--198},
+}
+
+@Comment{jabref-meta: databaseType:bibtex;}
+"#;
+
+    #[test]
+    fn parse_jabref_file() {
+        let entries = BibEntry::parse_all(JABREF).expect("Valid BibTeX");
+        assert_eq!(entries.len(), 2);
+
+        let sicp = &entries[0];
+        assert_eq!(sicp.entry_type, "book");
+        assert_eq!(sicp.key, "sicp");
+        assert_eq!(sicp.field("publisher"), Some("MIT Press Cambridge"));
+        assert_eq!(sicp.field("year"), Some("1996"));
+        assert_eq!(
+            sicp.file_links(),
+            [
+                FileLink {
+                    description: String::new(),
+                    path: "books/sicp.pdf".to_owned(),
+                    file_type: "PDF".to_owned(),
+                },
+                FileLink {
+                    description: "Scan".to_owned(),
+                    path: r"C:\Scans\sicp.pdf".to_owned(),
+                    file_type: "PDF".to_owned(),
+                },
+            ]
+        );
+
+        let metadata = sicp.metadata(FileFormat::Pdf).expect("Valid metadata");
+        assert_eq!(
+            metadata.title,
+            "Structure and Interpretation of Computer Programs"
+        );
+        assert_eq!(metadata.authors, ["Harold Abelson", "Gerald Jay Sussman"]);
+        assert_eq!(metadata.isbns[0].to_string(), "9780262510875");
+        assert_eq!(metadata.doc_type, Some(DocType::Book));
+
+        let goedel = entries[1]
+            .metadata(FileFormat::Pdf)
+            .expect("Valid metadata");
+        assert_eq!(goedel.title, "Über formal unentscheidbare Sätze");
+        assert_eq!(goedel.authors, ["Kurt Gödel"]);
+        assert_eq!(
+            goedel.journal.as_deref(),
+            Some("Monatshefte für Mathematik")
+        );
+        assert_eq!(goedel.pages.as_deref(), Some("173-198"));
+        assert_eq!(goedel.doc_type, Some(DocType::Article));
+    }
+
+    #[test]
+    fn invalid_bibtex() {
+        assert!(BibEntry::parse_all("@book{key, title = {Unbalanced}").is_err());
+        assert!(BibEntry::parse_all("@book{key, title {Moby Dick}}").is_err());
+        assert!(BibEntry::parse_all("Contact: someone@example.com")
+            .expect("Text outside of entries is ignored")
+            .is_empty());
+    }
+
+    #[test]
+    fn authors() {
+        assert_eq!(
+            split_authors("{Barnes and Noble} and Melville, Herman"),
+            ["Barnes and Noble", "Herman Melville"]
+        );
+        assert_eq!(
+            split_authors("King, Jr., Martin Luther"),
+            ["Martin Luther King Jr."]
+        );
+    }
+
+    #[test]
+    fn latex() {
+        assert_eq!(latex_to_text(r"Tom \& Jerry's 100\%"), "Tom & Jerry's 100%");
+        assert_eq!(latex_to_text(r"\textit{Moby}  Dick"), "Moby Dick");
+        assert_eq!(latex_to_text(r"Stra\ss{}e"), "Straße");
+    }
+
+    #[test]
+    fn isbn10() {
+        let isbn = parse_isbn("0-262-51087-1").expect("Valid ISBN-10");
+        assert_eq!(isbn.to_string(), "9780262510875");
+        assert!(parse_isbn("978-0-262-51087-5").is_ok());
+    }
+}
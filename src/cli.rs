@@ -1,19 +1,43 @@
 //! Command line interface for the application.
 
 use {
-    crate::{DocMetadata, FileFormat, Library},
+    crate::{
+        library::check_expected_hash, sha256, AddOptions, AddOutcome, AnnotationKind,
+        BatchOperation, BibEntry, Config, DigestAlgorithm, DocMetadata, DocType, DocumentSummary,
+        DocumentUri, FileFormat, Filter, IndexEntry, Isbn13, Library, LinkStyle, Operation,
+        OpfPackage, OutputConflict, OutputTemplate, ReadingData, ReadingImportResults,
+        RetrieveOptions, ValidateOptions, WantedEntry, WantedMetadata,
+    },
     anyhow::{bail, Context},
     clap::{Parser, Subcommand},
+    serde::Deserialize,
     std::{
+        cmp::Reverse,
+        collections::HashMap,
+        env,
         fmt::{self, Display, Formatter},
         fs,
-        io::{self, Write},
-        path::PathBuf,
+        io::{self, IsTerminal, Write},
+        num::NonZeroUsize,
+        ops::Deref,
+        path::{self, Path, PathBuf},
         process::ExitCode,
         str::FromStr,
+        thread,
     },
 };
 
+mod exit_status;
+use exit_status::ExitStatus;
+
+mod uri_handler;
+
+/// Prompt used to ask for the type of a document.
+const DOC_TYPE_PROMPT: &str = "Type (book, article, thesis, report or standard)";
+
+/// Number of documents shown in each list of `burette stats`.
+const STATS_LIST_LENGTH: usize = 5;
+
 /// Run the command line application.
 ///
 /// This function is the entry point for the command line application. It parses the command line
@@ -22,19 +46,343 @@ use {
 /// # Errors
 ///
 /// If an error occurs, an error message is printed to standard error and the process exits with a
-/// non-zero exit code.
+/// non-zero exit code. The exit codes are listed by `burette exit-codes`.
 #[must_use]
 pub fn run() -> ExitCode {
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) => {
+            // Ignore errors when printing, there is nothing left to report them to.
+            let _ = error.print();
+            // Help and version information are printed to standard output.
+            return if error.use_stderr() {
+                ExitStatus::Usage.into()
+            } else {
+                ExitCode::SUCCESS
+            };
+        }
+    };
     match cli.run() {
         Ok(exit_code) => exit_code,
         Err(error) => {
             eprintln!("Error: {error:#}");
-            ExitCode::FAILURE
+            ExitStatus::from_error(&error).into()
+        }
+    }
+}
+
+/// Ask the user for the authors of a document.
+fn stdin_read_authors() -> anyhow::Result<Vec<String>> {
+    let mut authors = Vec::new();
+    loop {
+        let another_author = stdin_confirm("Add another author?")?;
+        if !another_author {
+            break;
+        }
+        let author = stdin_read_input("Author")?;
+        authors.push(author);
+    }
+    Ok(authors)
+}
+
+/// Ask the user for the ISBNs of a document.
+fn stdin_read_isbns() -> anyhow::Result<Vec<Isbn13>> {
+    let mut isbns = Vec::new();
+    loop {
+        let another_isbn = stdin_confirm("Add another ISBN?")?;
+        if !another_isbn {
+            break;
+        }
+        let isbn = stdin_read_input("ISBN")?;
+        isbns.push(isbn);
+    }
+    Ok(isbns)
+}
+
+/// Ask the user whether to add a document although documents with the same title and authors
+/// already exist.
+///
+/// If `allow_duplicate_title` is true, the user is not asked and true is returned.
+fn confirm_similar(allow_duplicate_title: bool, similar: &[&IndexEntry]) -> anyhow::Result<bool> {
+    if allow_duplicate_title {
+        return Ok(true);
+    }
+    // Warn about documents that look like the same document in a different file.
+    eprintln!("Documents with the same title and authors already exist:");
+    for doc in similar {
+        eprintln!("{}: {}", doc.hash().to_short_string(), doc.title());
+    }
+    stdin_confirm("Add anyway?")
+}
+
+/// Return the fields of `entry` that `burette edit` can change, with their values formatted for
+/// display.
+fn editable_fields(entry: &IndexEntry) -> [(&'static str, String); 11] {
+    fn optional(value: Option<impl Display>) -> String {
+        value.map_or_else(|| "(none)".to_owned(), |value| value.to_string())
+    }
+    fn list<T: Display>(values: impl Iterator<Item = T>) -> String {
+        let values: Vec<_> = values.map(|value| value.to_string()).collect();
+        if values.is_empty() {
+            "(none)".to_owned()
+        } else {
+            values.join(", ")
+        }
+    }
+    [
+        ("title", entry.title().to_owned()),
+        ("authors", list(entry.authors())),
+        ("ISBNs", list(entry.isbns())),
+        ("DOI", optional(entry.doi())),
+        ("type", optional(entry.doc_type())),
+        ("rights", optional(entry.rights())),
+        ("journal", optional(entry.journal())),
+        ("volume", optional(entry.volume())),
+        ("issue", optional(entry.issue())),
+        ("page range", optional(entry.pages())),
+        ("MD5 hash", optional(entry.md5())),
+    ]
+}
+
+/// Show how an edit changes the metadata of a document and ask whether to save it.
+///
+/// Nothing is asked if `yes` is true or the metadata is unchanged. Removed values are shown in
+/// red and added values in green if standard output is a terminal.
+fn confirm_edit(yes: bool, before: &IndexEntry, after: &IndexEntry) -> anyhow::Result<bool> {
+    let changes: Vec<_> = editable_fields(before)
+        .into_iter()
+        .zip(editable_fields(after))
+        .filter(|((_, old), (_, new))| old != new)
+        .collect();
+    if yes || changes.is_empty() {
+        return Ok(true);
+    }
+    let (red, green, reset) = if io::stdout().is_terminal() {
+        ("\x1b[31m", "\x1b[32m", "\x1b[0m")
+    } else {
+        ("", "", "")
+    };
+    println!("Changes to {}:", before.hash().to_short_string());
+    for ((field, old), (_, new)) in changes {
+        println!("{red}- {field}: {old}{reset}");
+        println!("{green}+ {field}: {new}{reset}");
+    }
+    stdin_confirm("Save changes?")
+}
+
+/// Write a document in the format used by `burette list` to `out`.
+fn write_list_line<W: Write>(
+    out: &mut W,
+    doc: &DocumentSummary,
+    hash_length: usize,
+) -> io::Result<()> {
+    write!(
+        out,
+        "{}: {}",
+        doc.hash().to_prefix_string(hash_length),
+        doc.title()
+    )?;
+    let mut authors = doc.authors();
+    if let Some(author) = authors.next() {
+        write!(out, " - {author}")?;
+        for author in authors {
+            write!(out, ", {author}")?;
+        }
+    }
+    if doc.is_archived() {
+        write!(out, " [archived]")?;
+    }
+    writeln!(out)
+}
+
+/// Return the SHA-256 hash of the file at `path` listed in `checksums`, a file in the format of
+/// `sha256sum`.
+///
+/// Files are looked up by their file name, since checksum files usually list files relative to
+/// the directory they were published in.
+fn read_expected_hash(checksums: &Path, path: &Path) -> anyhow::Result<sha256::Hash> {
+    let contents = fs::read_to_string(checksums)
+        .with_context(|| format!("Failed to read checksums from {}", checksums.display()))?;
+    for line in contents.lines() {
+        // `sha256sum` separates the hash from the file name by a space and a space (text mode) or
+        // an asterisk (binary mode).
+        let Some((hash, file_name)) = line.trim().split_once(' ') else {
+            continue;
+        };
+        let file_name = file_name.strip_prefix(['*', ' ']).unwrap_or(file_name);
+        if Path::new(file_name).file_name() == path.file_name() {
+            return hash.parse().with_context(|| {
+                format!(
+                    "Invalid SHA-256 hash for {file_name} in {}",
+                    checksums.display()
+                )
+            });
+        }
+    }
+    bail!(
+        "{} is not listed in {}",
+        path.display(),
+        checksums.display()
+    )
+}
+
+/// The contents of a file given to `burette add --metadata`.
+#[derive(Debug, Deserialize)]
+struct MetadataFile {
+    /// Hash the document must have. Sidecar files written by `burette get --with-sidecar`
+    /// contain it.
+    #[serde(default)]
+    hash: Option<sha256::Hash>,
+    #[serde(flatten)]
+    metadata: DocMetadata,
+}
+
+/// Read the metadata of the document at `doc_path` from the file at `path`, as given to `burette
+/// add --metadata`.
+///
+/// Files with the extension `opf` are read as OPF package documents, e.g. the `metadata.opf` files
+/// of a Calibre library. Any other file is read as JSON.
+fn read_metadata_file(path: &Path, doc_path: &Path) -> anyhow::Result<MetadataFile> {
+    if path.extension().is_some_and(|extension| extension == "opf") {
+        let package = OpfPackage::read_file(path)?;
+        let metadata = package
+            .metadata(FileFormat::from_path(doc_path)?)
+            .with_context(|| format!("Invalid document metadata in {}", path.display()))?;
+        let hash = package
+            .identifiers("sha256")
+            .next()
+            .map(str::parse)
+            .transpose()
+            .with_context(|| format!("Invalid SHA-256 hash in {}", path.display()))?;
+        return Ok(MetadataFile { hash, metadata });
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read metadata from {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid document metadata in {}", path.display()))
+}
+
+/// Record that the documents with hashes `hashes` were accessed, see [`Library::record_access()`].
+///
+/// The documents were retrieved already, so failing to record it, e.g. because the library is
+/// read-only, only results in a warning.
+fn record_access(library: &Library, hashes: &[sha256::Hash]) {
+    if let Err(error) = library.record_access(hashes) {
+        eprintln!("Warning: Failed to record the access to the documents: {error:#}");
+    }
+}
+
+/// A library opened by a command.
+///
+/// The warnings of the operations performed on the library (see [`Library::take_warnings()`]) are
+/// printed once the command is done with it.
+struct OpenLibrary(Library);
+
+impl OpenLibrary {
+    /// Open the library at `path`, see [`Library::open()`].
+    fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Library::open(path).map(Self)
+    }
+}
+
+impl Deref for OpenLibrary {
+    type Target = Library;
+
+    fn deref(&self) -> &Library {
+        &self.0
+    }
+}
+
+impl Drop for OpenLibrary {
+    fn drop(&mut self) {
+        for warning in self.0.take_warnings() {
+            eprintln!("Warning: {warning:#}");
+        }
+    }
+}
+
+/// Print a wanted document in the format used by `burette want list`.
+fn print_wanted(entry: &WantedEntry) {
+    let metadata = entry.metadata();
+    print!("{}: {}", entry.id(), metadata.title);
+    let mut authors = metadata.authors.iter();
+    if let Some(author) = authors.next() {
+        print!(" - {author}");
+        for author in authors {
+            print!(", {author}");
+        }
+    }
+    println!();
+}
+
+/// Print the results of `burette reading import`.
+fn print_reading_import_results(results: &ReadingImportResults) {
+    if results.imported().is_empty() {
+        println!("No reading data imported.");
+    } else {
+        println!("Imported reading data:");
+        for imported in results.imported() {
+            print!(
+                "{}: {} ({} new annotations",
+                imported.hash.to_short_string(),
+                imported.title,
+                imported.new_annotations
+            );
+            if let Some(progress) = imported.progress {
+                print!(", {progress}% read");
+            }
+            if let Some(status) = imported.status {
+                print!(", {status}");
+            }
+            if let Some(rating) = imported.rating {
+                print!(", rated {rating}/5");
+            }
+            println!(")");
+        }
+    }
+    if !results.not_found().is_empty() {
+        println!();
+        println!("No document found for:");
+        for title in results.not_found() {
+            println!("{title}");
+        }
+    }
+    if !results.ambiguous().is_empty() {
+        println!();
+        println!("Multiple documents found for:");
+        for title in results.ambiguous() {
+            println!("{title}");
         }
     }
 }
 
+/// Show the current value of an optional field and ask the user for its new value.
+///
+/// `name` is the name of the field in lowercase. `None` is returned if the user chooses to leave
+/// the field unset.
+fn stdin_read_optional(name: &str, current: Option<&str>) -> anyhow::Result<Option<String>> {
+    match current {
+        Some(value) => println!("Current {name}:\n{value}"),
+        None => println!("No {name} currently set."),
+    }
+    stdin_ask_optional(name)
+}
+
+/// Ask the user whether to set an optional field and, if so, for its value.
+///
+/// `name` is the name of the field in lowercase. `None` is returned if the user chooses to leave
+/// the field unset.
+fn stdin_ask_optional(name: &str) -> anyhow::Result<Option<String>> {
+    if !stdin_confirm(&format!("Set the {name}?"))? {
+        return Ok(None);
+    }
+    let mut capitalized = name.to_owned();
+    if let Some(first) = capitalized.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+    stdin_read_input(&capitalized).map(Some)
+}
+
 fn stdin_read_input<T>(prompt: &str) -> anyhow::Result<T>
 where
     T: FromStr,
@@ -110,14 +458,42 @@ impl Cli {
     /// Run the command.
     fn run(&self) -> anyhow::Result<ExitCode> {
         match &self.command {
-            Command::Add { path } => {
+            Command::Add {
+                path,
+                doc_type,
+                allow_duplicate_title,
+                move_file,
+                md5,
+                expect_sha256,
+                checksums,
+                file_format,
+                metadata_file,
+            } => {
                 // Validate the path
 
                 let exists = fs::exists(path).context("IO error while checking if file exists")?;
                 if !exists {
                     bail!("File does not exist: {}", path.display());
                 }
-                let file_format = FileFormat::from_path(path)?;
+
+                let metadata_file = metadata_file
+                    .as_deref()
+                    .map(|metadata_file| read_metadata_file(metadata_file, path))
+                    .transpose()?;
+
+                // Refuse corrupted files before the user enters any metadata. The hash is checked
+                // again when the document is added, in case the file changes in the meantime.
+                let expected_sha256 = match (expect_sha256, checksums) {
+                    (Some(hash), _) => Some(*hash),
+                    (None, Some(checksums)) => Some(read_expected_hash(checksums, path)?),
+                    (None, None) => metadata_file.as_ref().and_then(|file| file.hash),
+                };
+                if expected_sha256.is_some() {
+                    let file = fs::File::open(path)
+                        .with_context(|| format!("Failed to open file at {}", path.display()))?;
+                    let hash = sha256::hash_reader(file)?;
+                    check_expected_hash(path, hash, expected_sha256)?;
+                }
 
                 //--------------------------------------------------------------------------------//
 
@@ -126,69 +502,91 @@ impl Cli {
                 // user is not asked for metadata that cannot be saved.
 
                 let library_path = self.library_path()?;
-                let library = Library::open(library_path)?;
+                let library = OpenLibrary::open(library_path)?;
 
                 //--------------------------------------------------------------------------------//
 
-                // Ask the user for metadata about the document
+                // Ask the user for metadata about the document, unless it was given in a file
 
-                let title = stdin_read_input("Title")?;
+                let metadata = if let Some(file) = metadata_file {
+                    file.metadata
+                } else {
+                    let file_format = match file_format {
+                        Some(file_format) => *file_format,
+                        None => FileFormat::from_path(path)?,
+                    };
 
-                let mut authors = Vec::new();
-                loop {
-                    let another_author = stdin_confirm("Add another author?")?;
-                    if !another_author {
-                        break;
-                    }
-                    let author = stdin_read_input("Author")?;
-                    authors.push(author);
-                }
+                    let title = stdin_read_input("Title")?;
+                    let authors = stdin_read_authors()?;
 
-                let mut isbns = Vec::new();
-                loop {
-                    let another_isbn = stdin_confirm("Add another ISBN?")?;
-                    if !another_isbn {
-                        break;
-                    }
-                    let isbn = stdin_read_input("ISBN")?;
-                    isbns.push(isbn);
-                }
+                    // Articles do not have ISBNs, but are published in a journal.
+                    let is_article = *doc_type == Some(DocType::Article);
+                    let isbns = if is_article {
+                        Vec::new()
+                    } else {
+                        stdin_read_isbns()?
+                    };
 
-                let mut doi = None;
-                let read_doi = stdin_confirm("Add a DOI?")?;
-                if read_doi {
-                    doi = Some(stdin_read_input("DOI")?);
-                }
+                    let mut doi = None;
+                    let read_doi = stdin_confirm("Add a DOI?")?;
+                    if read_doi {
+                        doi = Some(stdin_read_input("DOI")?);
+                    }
 
-                let metadata = DocMetadata {
-                    title,
-                    authors,
-                    isbns,
-                    file_format,
-                    doi,
+                    let mut metadata = DocMetadata {
+                        title,
+                        authors,
+                        isbns,
+                        file_format,
+                        doi,
+                        doc_type: *doc_type,
+                        rights: None,
+                        journal: None,
+                        volume: None,
+                        issue: None,
+                        pages: None,
+                    };
+                    if is_article {
+                        metadata.journal = stdin_ask_optional("journal")?;
+                        metadata.volume = stdin_ask_optional("volume")?;
+                        metadata.issue = stdin_ask_optional("issue")?;
+                        metadata.pages = stdin_ask_optional("page range")?;
+                    }
+                    metadata
                 };
 
                 //--------------------------------------------------------------------------------//
 
-                library.add_document(path, metadata)?;
+                let options = AddOptions {
+                    move_file: *move_file,
+                    compute_md5: *md5,
+                    expected_sha256,
+                };
+                let added = library.add_document_checked(path, metadata, &options, |similar| {
+                    confirm_similar(*allow_duplicate_title, similar)
+                })?;
+                if !added {
+                    return Ok(ExitStatus::Aborted.into());
+                }
 
                 Ok(ExitCode::SUCCESS)
             }
-            Command::Edit { hash_prefix, field } => {
+            Command::Edit {
+                hash_prefix,
+                field,
+                yes,
+            } => {
                 let library_path = self.library_path()?;
-                let library = Library::open(library_path)?;
+                let library = OpenLibrary::open(library_path)?;
 
-                match field {
-                    EditField::Title => {
-                        library.edit_metadata(hash_prefix, |index_entry| {
+                let edit = |index_entry: &mut IndexEntry| -> anyhow::Result<()> {
+                    match field {
+                        EditField::Title => {
                             println!("Current title:\n{}", index_entry.title());
                             let title = stdin_read_input("New title")?;
                             index_entry.set_title(title);
-                            Ok(())
-                        })?;
-                    }
-                    EditField::Authors => {
-                        library.edit_metadata(hash_prefix, |index_entry| {
+                        }
+                        EditField::Authors => {
                             println!("Current authors:");
                             for author in index_entry.authors() {
                                 println!("{author}");
@@ -203,11 +601,8 @@ impl Cli {
                                 authors.push(author);
                             }
                             index_entry.set_authors(authors);
-                            Ok(())
-                        })?;
-                    }
-                    EditField::Isbns => {
-                        library.edit_metadata(hash_prefix, |index_entry| {
+                        }
+                        EditField::Isbns => {
                             println!("Current ISBNs:");
                             for isbn in index_entry.isbns() {
                                 println!("{isbn}");
@@ -222,11 +617,8 @@ impl Cli {
                                 isbns.push(isbn);
                             }
                             index_entry.set_isbns(isbns);
-                            Ok(())
-                        })?;
-                    }
-                    EditField::Doi => {
-                        library.edit_metadata(hash_prefix, |index_entry| {
+                        }
+                        EditField::Doi => {
                             match index_entry.doi() {
                                 Some(doi) => println!("Current DOI:\n{doi}"),
                                 None => println!("No DOI currently set."),
@@ -238,32 +630,537 @@ impl Cli {
                                 None
                             };
                             index_entry.set_doi(doi);
-                            Ok(())
-                        })?;
+                        }
+                        EditField::Type => {
+                            match index_entry.doc_type() {
+                                Some(doc_type) => println!("Current type:\n{doc_type}"),
+                                None => println!("No type currently set."),
+                            }
+                            let doc_type = if stdin_confirm("Set the type?")? {
+                                Some(stdin_read_input(DOC_TYPE_PROMPT)?)
+                            } else {
+                                None
+                            };
+                            index_entry.set_doc_type(doc_type);
+                        }
+                        EditField::Rights => {
+                            let rights = stdin_read_optional("rights", index_entry.rights())?;
+                            index_entry.set_rights(rights);
+                        }
+                        EditField::Journal => {
+                            let journal = stdin_read_optional("journal", index_entry.journal())?;
+                            index_entry.set_journal(journal);
+                        }
+                        EditField::Volume => {
+                            let volume = stdin_read_optional("volume", index_entry.volume())?;
+                            index_entry.set_volume(volume);
+                        }
+                        EditField::Issue => {
+                            let issue = stdin_read_optional("issue", index_entry.issue())?;
+                            index_entry.set_issue(issue);
+                        }
+                        EditField::Pages => {
+                            let pages = stdin_read_optional("page range", index_entry.pages())?;
+                            index_entry.set_pages(pages);
+                        }
+                        EditField::Md5 => {
+                            match index_entry.md5() {
+                                Some(md5) => println!("Current MD5 hash:\n{md5}"),
+                                None => println!("No MD5 hash currently set."),
+                            }
+                            let md5 = if stdin_confirm("Set the MD5 hash?")? {
+                                Some(stdin_read_input("MD5 hash")?)
+                            } else {
+                                None
+                            };
+                            index_entry.set_md5(md5);
+                        }
                     }
+                    Ok(())
+                };
+                let confirmed =
+                    library.edit_metadata_checked(hash_prefix, edit, |before, after| {
+                        confirm_edit(*yes, before, after)
+                    })?;
+                if !confirmed {
+                    return Ok(ExitStatus::Aborted.into());
                 }
 
                 Ok(ExitCode::SUCCESS)
             }
-            Command::Get { identifier, output } => {
+            Command::Alias { command } => {
                 let library_path = self.library_path()?;
-                let library = Library::open(library_path)?;
-                library.retrieve_document(identifier, output.as_ref())?;
+                let library = OpenLibrary::open(library_path)?;
+                match command {
+                    AliasCommand::Set { alias, identifier } => {
+                        library.set_alias(alias, identifier)?;
+                    }
+                    AliasCommand::Remove { alias } => {
+                        library.remove_alias(alias)?;
+                    }
+                    AliasCommand::List => {
+                        let docs: Vec<_> = library.documents()?.collect();
+                        let mut aliases: Vec<_> = docs
+                            .iter()
+                            .flat_map(|doc| doc.aliases().map(move |alias| (alias, doc)))
+                            .collect();
+                        aliases.sort_by_key(|(alias, _)| *alias);
+                        for (alias, doc) in aliases {
+                            println!("{alias}: {}: {}", doc.hash().to_short_string(), doc.title());
+                        }
+                    }
+                }
                 Ok(ExitCode::SUCCESS)
             }
-            Command::List => {
+            Command::Apply { file, dry_run } => {
                 let library_path = self.library_path()?;
-                let library = Library::open(library_path)?;
-                for doc in library.documents()? {
-                    print!("{}: {}", doc.hash().to_short_string(), doc.title());
-                    let mut authors = doc.authors();
-                    if let Some(author) = authors.next() {
-                        print!(" - {author}");
-                        for author in authors {
-                            print!(", {author}");
+                let library = OpenLibrary::open(library_path)?;
+                let json = fs::read_to_string(file).with_context(|| {
+                    format!("Failed to read operations from {}", file.display())
+                })?;
+                let mut operations: Vec<BatchOperation> = serde_json::from_str(&json)
+                    .with_context(|| format!("Invalid operations in {}", file.display()))?;
+                let base_dir = file.parent().unwrap_or(Path::new(""));
+                for operation in &mut operations {
+                    if let BatchOperation::Add { path, .. } = operation {
+                        *path = base_dir.join(&*path);
+                    }
+                }
+                for (operation, hash) in library.apply_operations(operations, *dry_run)? {
+                    let verb = match (operation, *dry_run) {
+                        (Operation::Add, false) => "Added",
+                        (Operation::Add, true) => "Would add",
+                        (Operation::Edit, false) => "Edited",
+                        (Operation::Edit, true) => "Would edit",
+                        (_, false) => "Removed",
+                        (_, true) => "Would remove",
+                    };
+                    println!("{verb} {}", hash.to_short_string());
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Archive { hash_prefix } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                library.edit_metadata(hash_prefix, |index_entry| {
+                    index_entry.set_archived(true);
+                    Ok(())
+                })?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Bibtex { command } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                match command {
+                    BibtexCommand::Import {
+                        path,
+                        allow_duplicate_title,
+                        jobs,
+                    } => {
+                        let entries = BibEntry::read_file(path)?;
+                        let bib_dir = path.parent().unwrap_or(Path::new("."));
+
+                        // Outcomes by position of the entry. Entries without an outcome have no
+                        // linked file.
+                        let mut outcomes = HashMap::new();
+                        let mut positions = Vec::new();
+                        let mut documents = Vec::new();
+                        for (position, entry) in entries.iter().enumerate() {
+                            let Some(link) = entry.file_links().into_iter().next() else {
+                                continue;
+                            };
+                            let file = bib_dir.join(&link.path);
+                            match FileFormat::from_path(&file)
+                                .and_then(|file_format| entry.metadata(file_format))
+                            {
+                                Ok(metadata) => {
+                                    positions.push(position);
+                                    documents.push((file, metadata));
+                                }
+                                Err(error) => {
+                                    outcomes.insert(position, AddOutcome::Failed(error));
+                                }
+                            }
+                        }
+                        let jobs = match jobs {
+                            Some(jobs) => *jobs,
+                            None => thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+                        };
+                        let added = library.add_documents(
+                            &documents,
+                            &AddOptions::default(),
+                            jobs,
+                            |_, similar| confirm_similar(*allow_duplicate_title, similar),
+                        )?;
+                        outcomes.extend(positions.into_iter().zip(added));
+
+                        let mut failed = false;
+                        for (position, entry) in entries.iter().enumerate() {
+                            match outcomes.remove(&position) {
+                                None => println!("Skipped {}: no linked file", entry.key),
+                                Some(AddOutcome::Added(_)) => println!("Added {}", entry.key),
+                                Some(AddOutcome::AlreadyInLibrary(hash)) => println!(
+                                    "Skipped {}: already in the library ({})",
+                                    entry.key,
+                                    hash.to_short_string()
+                                ),
+                                Some(AddOutcome::NotConfirmed) => {
+                                    println!("Skipped {}: not confirmed", entry.key);
+                                }
+                                Some(AddOutcome::Failed(error)) => {
+                                    eprintln!("Failed to import {}: {:#}", entry.key, error);
+                                    failed = true;
+                                }
+                            }
                         }
+                        Ok(if failed {
+                            ExitStatus::PartialFailure.into()
+                        } else {
+                            ExitCode::SUCCESS
+                        })
+                    }
+                    BibtexCommand::Export { output, files, all } => {
+                        let documents: Vec<_> = library
+                            .documents()?
+                            .filter(|entry| *all || !entry.is_archived())
+                            .collect();
+
+                        // Links are relative to the directory of the BibTeX file, like JabRef
+                        // writes them.
+                        let bib_dir = match output {
+                            Some(output) => path::absolute(output)
+                                .context("Failed to determine output directory")?
+                                .parent()
+                                .map(Path::to_owned)
+                                .unwrap_or_default(),
+                            None => env::current_dir()
+                                .context("Failed to determine current directory")?,
+                        };
+                        let mut links = HashMap::new();
+                        let mut failed = false;
+                        if let Some(files) = files {
+                            let config = Config::load()?;
+                            let options = RetrieveOptions {
+                                include_archived: *all,
+                                file_name_template: library
+                                    .settings()
+                                    .file_name_template
+                                    .clone()
+                                    .or(config.file_name_template),
+                                // The files are a view of the library, so they are replaced when
+                                // exporting again.
+                                on_conflict: OutputConflict::Overwrite,
+                                ..RetrieveOptions::default()
+                            };
+                            let hashes: Vec<_> =
+                                documents.iter().map(|doc| doc.hash().to_string()).collect();
+                            let results = library.retrieve_documents(
+                                hashes.iter().map(String::as_str),
+                                None,
+                                files,
+                                &options,
+                            )?;
+                            let retrieved: Vec<_> = results
+                                .retrieved()
+                                .iter()
+                                .map(|doc| *doc.entry().hash())
+                                .collect();
+                            record_access(&library, &retrieved);
+                            for doc in results.retrieved() {
+                                let path = path::absolute(doc.path())
+                                    .context("Failed to determine path of exported file")?;
+                                let link = path.strip_prefix(&bib_dir).unwrap_or(&path);
+                                links.insert(
+                                    *doc.entry().hash(),
+                                    link.to_string_lossy().into_owned(),
+                                );
+                            }
+                            for error in results.errors() {
+                                eprintln!("{}: {:#}", error.identifier(), error.error());
+                                failed = true;
+                            }
+                        }
+
+                        let bibtex: Vec<_> = documents
+                            .iter()
+                            .map(|doc| {
+                                let link = links.get(doc.hash()).map(String::as_str);
+                                BibEntry::from_document(doc, link).to_string()
+                            })
+                            .collect();
+                        let bibtex = bibtex.join("\n");
+                        match output {
+                            Some(output) => fs::write(output, bibtex).with_context(|| {
+                                format!("Failed to write BibTeX file to {}", output.display())
+                            })?,
+                            None => print!("{bibtex}"),
+                        }
+                        Ok(if failed {
+                            ExitStatus::PartialFailure.into()
+                        } else {
+                            ExitCode::SUCCESS
+                        })
+                    }
+                }
+            }
+            Command::Authors { command } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                match command {
+                    AuthorsCommand::List {
+                        all,
+                        documents_of: None,
+                    } => {
+                        for (author, count) in library.author_counts(*all)? {
+                            println!("{count}: {author}");
+                        }
+                    }
+                    AuthorsCommand::List {
+                        all,
+                        documents_of: Some(author),
+                    } => {
+                        let hash_length = library.short_hash_length()?;
+                        let mut stdout = io::BufWriter::new(io::stdout().lock());
+                        for doc in library.documents_by_author(author, *all)? {
+                            write_list_line(&mut stdout, &doc, hash_length)
+                                .context("Failed to write to standard output")?;
+                        }
+                        stdout
+                            .flush()
+                            .context("Failed to write to standard output")?;
+                    }
+                    AuthorsCommand::Merge { names } => {
+                        let (canonical, variants) =
+                            names.split_last().context("No author names given")?;
+                        let variants: Vec<_> = variants.iter().map(String::as_str).collect();
+                        let changed = library.merge_authors(&variants, canonical)?;
+                        println!("Updated {} documents", changed.len());
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Digests { command } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                match command {
+                    DigestsCommand::Update => {
+                        if library.settings().digests.is_empty() {
+                            eprintln!(
+                                "No digests are configured. Set \"digests\" in settings.json \
+                                in the library directory, e.g. to [\"md5\", \"sha1\"]."
+                            );
+                        }
+                        let updated = library.update_digests()?;
+                        println!("Computed the digests of {updated} documents");
+                    }
+                    DigestsCommand::Manifest { algorithm } => {
+                        print!("{}", library.digest_manifest(*algorithm)?);
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::ExportCalibre { dir, filter, all } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                // The directories are printed so that scripts can process the exported documents.
+                for book_dir in library.export_calibre(dir, filter.as_ref(), *all)? {
+                    println!("{}", book_dir.display());
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Get {
+                identifiers,
+                filter,
+                output,
+                directory,
+                all,
+                original_name,
+                force,
+                number,
+                preserve_mtime,
+                write_metadata,
+                with_sidecar,
+            } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let config = Config::load()?;
+                let metadata_command = if *write_metadata {
+                    Some(config.metadata_command.context(
+                        "--write-metadata requires a metadata_command in the configuration file",
+                    )?)
+                } else {
+                    None
+                };
+                let options = RetrieveOptions {
+                    include_archived: *all,
+                    original_name: *original_name,
+                    file_name_template: library
+                        .settings()
+                        .file_name_template
+                        .clone()
+                        .or(config.file_name_template),
+                    on_conflict: if *force {
+                        OutputConflict::Overwrite
+                    } else if *number {
+                        OutputConflict::Number
+                    } else {
+                        OutputConflict::Error
+                    },
+                    preserve_mtime: *preserve_mtime,
+                    metadata_command,
+                    write_sidecar: *with_sidecar,
+                };
+
+                if let [identifier] = identifiers.as_slice() {
+                    if filter.is_none() && directory.is_none() {
+                        let retrieved =
+                            library.retrieve_document(identifier, output.as_ref(), &options)?;
+                        record_access(&library, &[*retrieved.entry().hash()]);
+                        return Ok(ExitCode::SUCCESS);
                     }
-                    println!();
+                }
+                if output.is_some() {
+                    bail!("--output can only be used to retrieve a single document, use --directory instead");
+                }
+
+                let out_dir = directory.clone().unwrap_or_else(|| PathBuf::from("."));
+                let identifiers = identifiers.iter().map(String::as_str);
+                let results =
+                    library.retrieve_documents(identifiers, filter.as_ref(), &out_dir, &options)?;
+                let retrieved: Vec<_> = results
+                    .retrieved()
+                    .iter()
+                    .map(|doc| *doc.entry().hash())
+                    .collect();
+                record_access(&library, &retrieved);
+
+                if !results.retrieved().is_empty() {
+                    println!("Retrieved documents:");
+                    for doc in results.retrieved() {
+                        let entry = doc.entry();
+                        println!(
+                            "{}: {} -> {}",
+                            entry.hash().to_short_string(),
+                            entry.title(),
+                            doc.path().display()
+                        );
+                    }
+                } else if results.success() {
+                    println!("No documents found.");
+                }
+
+                if !results.errors().is_empty() {
+                    if !results.retrieved().is_empty() {
+                        eprintln!();
+                    }
+                    eprintln!("Errors:");
+                    for error in results.errors() {
+                        eprintln!("{}: {:#}", error.identifier(), error.error());
+                    }
+                }
+
+                Ok(if results.success() {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitStatus::PartialFailure.into()
+                })
+            }
+            Command::LinkFor { identifier, style } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let doc = library.find_document(identifier, true)?;
+                let uri = DocumentUri::from_hash(doc.hash());
+                println!("{}", uri.link(doc.title(), *style));
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::LintMetadata { json, all } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let issues = library.lint_metadata(*all)?;
+                if *json {
+                    let json = serde_json::to_string_pretty(&issues)
+                        .context("Failed to serialize metadata problems")?;
+                    println!("{json}");
+                } else if issues.is_empty() {
+                    println!("No metadata problems found.");
+                } else {
+                    for issue in &issues {
+                        println!("{issue}");
+                    }
+                }
+                Ok(if issues.is_empty() {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitStatus::ValidationFailed.into()
+                })
+            }
+            Command::List {
+                all,
+                sort,
+                doc_type,
+                format,
+            } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                // Only the fields needed for filtering and sorting are read, which is much faster
+                // for large libraries. Full entries are only read if a template needs them.
+                let mut docs: Vec<_> = library
+                    .document_summaries()?
+                    .filter(|doc| *all || !doc.is_archived())
+                    .filter(|doc| doc_type.is_none() || doc.doc_type() == *doc_type)
+                    .collect();
+                match sort {
+                    None => (),
+                    Some(ListSort::Title) => docs.sort_by(|a, b| a.title().cmp(b.title())),
+                    // Most recently retrieved first, never retrieved documents last.
+                    Some(ListSort::LastOpened) => {
+                        docs.sort_by_key(|doc| Reverse(doc.last_accessed()));
+                    }
+                    Some(ListSort::AccessCount) => {
+                        docs.sort_by_key(|doc| Reverse(doc.access_count()));
+                    }
+                }
+
+                let mut stdout = io::BufWriter::new(io::stdout().lock());
+                if let Some(format) = format {
+                    let entries: HashMap<_, _> = library
+                        .documents()?
+                        .map(|entry| (*entry.hash(), entry))
+                        .collect();
+                    for doc in docs {
+                        if let Some(entry) = entries.get(doc.hash()) {
+                            writeln!(stdout, "{}", format.render(entry))
+                                .context("Failed to write to standard output")?;
+                        }
+                    }
+                } else {
+                    let hash_length = library.short_hash_length()?;
+                    for doc in docs {
+                        write_list_line(&mut stdout, &doc, hash_length)
+                            .context("Failed to write to standard output")?;
+                    }
+                }
+                stdout
+                    .flush()
+                    .context("Failed to write to standard output")?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Log {
+                operation,
+                document,
+                user,
+            } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let events = library.events()?.into_iter().filter(|event| {
+                    operation.is_none_or(|operation| event.operation() == operation)
+                        && document.as_ref().is_none_or(|prefix| {
+                            event.hashes().any(|hash| hash.starts_with(prefix))
+                        })
+                        && user.as_ref().is_none_or(|user| event.user() == Some(user))
+                });
+                for event in events {
+                    println!("{event}");
                 }
                 Ok(ExitCode::SUCCESS)
             }
@@ -272,11 +1169,73 @@ impl Cli {
                 Library::new(library_path)?;
                 Ok(ExitCode::SUCCESS)
             }
-            Command::Remove { hash_prefixes } => {
+            Command::OpenUri { uri } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let config = Config::load()?;
+                let viewed =
+                    library.view_document(uri.identifier(), config.open_command.as_deref())?;
+                record_access(&library, &[*viewed.entry().hash()]);
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::RegisterUriHandler => {
+                let exe = env::current_exe().context("Failed to determine path to burette")?;
+                // Only pass on the library if it was given explicitly, so that the handler keeps
+                // following the default library otherwise.
+                let library = match &self.library {
+                    Some(library) => Some(path::absolute(library).with_context(|| {
+                        format!("Failed to resolve library path {}", library.display())
+                    })?),
+                    None => None,
+                };
+                let message = uri_handler::register(&exe, library.as_deref())?;
+                println!("{message}");
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Remove {
+                identifiers,
+                filter,
+                all,
+                yes,
+                json,
+            } => {
                 let library_path = self.library_path()?;
-                let library = Library::open(library_path)?;
-                let hash_prefixes = hash_prefixes.iter().map(String::as_str);
-                let results = library.remove_all(hash_prefixes)?;
+                let library = OpenLibrary::open(library_path)?;
+                if *json && filter.is_some() && !*yes {
+                    bail!("--json can only be used together with --filter if --yes is given");
+                }
+                // Computed before the removal, so that the hashes of removed documents are as
+                // long as they were in `burette list`.
+                let hash_length = library.short_hash_length()?;
+                let identifiers = identifiers.iter().map(String::as_str);
+                let results =
+                    library.remove_all_checked(identifiers, filter.as_ref(), *all, |docs| {
+                        // Documents selected by a filter are only removed after confirmation, since
+                        // the filter may match more documents than expected.
+                        if filter.is_none() || *yes {
+                            return Ok(true);
+                        }
+                        println!("The following documents will be removed:");
+                        for doc in docs {
+                            let hash = doc.hash().to_prefix_string(hash_length);
+                            println!("{hash}: {}", doc.title());
+                        }
+                        stdin_confirm("Remove these documents?")
+                    })?;
+                let Some(results) = results else {
+                    return Ok(ExitStatus::Aborted.into());
+                };
+
+                if *json {
+                    let json = serde_json::to_string_pretty(&results)
+                        .context("Failed to serialize removal results")?;
+                    println!("{json}");
+                    return Ok(if results.success() {
+                        ExitCode::SUCCESS
+                    } else {
+                        ExitStatus::PartialFailure.into()
+                    });
+                }
 
                 let mut printed = false;
 
@@ -285,7 +1244,8 @@ impl Cli {
                 if !removed.is_empty() {
                     println!("Removed documents:");
                     for doc in removed {
-                        println!("{}: {}", doc.hash().to_short_string(), doc.title());
+                        let hash = doc.hash().to_prefix_string(hash_length);
+                        println!("{hash}: {}", doc.title());
                     }
                     printed = true;
                 }
@@ -322,75 +1282,557 @@ impl Cli {
                     }
                     eprintln!("Errors:");
                     for error in results.errors() {
-                        eprintln!("{}: {}", error.hash().to_short_string(), error.error());
+                        let entry = error.entry();
+                        eprintln!(
+                            "{}: {}: {:#}",
+                            entry.hash().to_short_string(),
+                            entry.title(),
+                            error.error()
+                        );
                     }
                 }
 
                 Ok(if results.success() {
                     ExitCode::SUCCESS
                 } else {
-                    ExitCode::FAILURE
+                    ExitStatus::PartialFailure.into()
                 })
             }
-            Command::Validate => {
+            Command::Replace { hash_prefix, path } => {
                 let library_path = self.library_path()?;
-                let library = Library::open(library_path)?;
-                let results = library.validate()?;
-                if results.is_valid() {
-                    println!("Library is valid.");
-                    Ok(ExitCode::SUCCESS)
+                let library = OpenLibrary::open(library_path)?;
+                let new_hash = library.replace_blob(hash_prefix, path)?;
+                println!(
+                    "Replaced document, new hash: {}",
+                    new_hash.to_short_string()
+                );
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Restore { backup_dir, only } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let only: Vec<_> = only.iter().map(String::as_str).collect();
+                let results = library.restore_from_backup(backup_dir, &only)?;
+                if results.is_empty() {
+                    println!("Nothing to restore.");
+                }
+                for hash in results.restored_files() {
+                    println!("Restored file of {}", hash.to_short_string());
+                }
+                for hash in results.restored_index_entries() {
+                    println!("Restored index entry of {}", hash.to_short_string());
+                }
+                let mut failed = false;
+                for failure in results.failures() {
+                    eprintln!("Could not restore {failure}");
+                    failed = true;
+                }
+                Ok(if failed {
+                    ExitStatus::PartialFailure.into()
                 } else {
-                    let mut printed = false;
-
-                    let mut missing_files = results.missing_files();
-                    if let Some(missing_file) = missing_files.next() {
-                        eprintln!("Files present in the index but not in the document store:");
-                        eprintln!("{missing_file}");
-                        for missing_file in missing_files {
-                            eprintln!("{missing_file}");
-                        }
-                        printed = true;
+                    ExitCode::SUCCESS
+                })
+            }
+            Command::Send {
+                identifier,
+                device,
+                all,
+                force,
+                number,
+                write_metadata,
+            } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let mut config = Config::load()?;
+                let metadata_command = if *write_metadata {
+                    Some(config.metadata_command.take().context(
+                        "--write-metadata requires a metadata_command in the configuration file",
+                    )?)
+                } else {
+                    None
+                };
+                let profile = if let Some(device) = device {
+                    config
+                        .devices
+                        .remove(device)
+                        .with_context(|| format!("No device named {device} is configured"))?
+                } else {
+                    if config.devices.len() > 1 {
+                        bail!("Multiple devices are configured, use --device to choose one");
                     }
+                    config
+                        .devices
+                        .pop_first()
+                        .map(|(_, profile)| profile)
+                        .context("No devices are configured")?
+                };
+                let options = RetrieveOptions {
+                    include_archived: *all,
+                    original_name: false,
+                    file_name_template: library
+                        .settings()
+                        .file_name_template
+                        .clone()
+                        .or(config.file_name_template),
+                    on_conflict: if *force {
+                        OutputConflict::Overwrite
+                    } else if *number {
+                        OutputConflict::Number
+                    } else {
+                        OutputConflict::Error
+                    },
+                    preserve_mtime: false,
+                    metadata_command,
+                    write_sidecar: false,
+                };
+                let sent = library.send_document(identifier, &profile, &options)?;
+                record_access(&library, &[*sent.entry().hash()]);
+                println!("Sent document to {}", sent.path().display());
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Show { identifier, format } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let doc = library.find_document(identifier, true)?;
 
-                    let mut missing_index_entries = results.missing_index_entries();
-                    if let Some(missing_index_entry) = missing_index_entries.next() {
-                        if printed {
-                            eprintln!();
-                        }
-                        eprintln!("Files present in the document store but not in the index:");
-                        eprintln!("{missing_index_entry}");
-                        for missing_index_entry in missing_index_entries {
-                            eprintln!("{missing_index_entry}");
-                        }
-                        printed = true;
+                if let Some(format) = format {
+                    println!("{}", format.render(&doc));
+                    return Ok(ExitCode::SUCCESS);
+                }
+                println!("Hash: {}", doc.hash());
+                println!("Title: {}", doc.title());
+                let authors: Vec<_> = doc.authors().collect();
+                if !authors.is_empty() {
+                    println!("Authors: {}", authors.join(", "));
+                }
+                let isbns: Vec<_> = doc.isbns().map(ToString::to_string).collect();
+                if !isbns.is_empty() {
+                    println!("ISBNs: {}", isbns.join(", "));
+                }
+                let aliases: Vec<_> = doc.aliases().collect();
+                if !aliases.is_empty() {
+                    println!("Aliases: {}", aliases.join(", "));
+                }
+                if let Some(doi) = doc.doi() {
+                    println!("DOI: {doi}");
+                }
+                if let Some(doc_type) = doc.doc_type() {
+                    println!("Type: {doc_type}");
+                }
+                if let Some(journal) = doc.journal() {
+                    println!("Journal: {journal}");
+                }
+                if let Some(volume) = doc.volume() {
+                    println!("Volume: {volume}");
+                }
+                if let Some(issue) = doc.issue() {
+                    println!("Issue: {issue}");
+                }
+                if let Some(pages) = doc.pages() {
+                    println!("Pages: {pages}");
+                }
+                if let Some(rights) = doc.rights() {
+                    println!("Rights: {rights}");
+                }
+                println!("File format: {}", doc.file_format());
+                if let Some(size_bytes) = doc.size_bytes() {
+                    println!("Size: {size_bytes} bytes");
+                }
+                if let Some(source_filename) = doc.source_filename() {
+                    println!("Original file name: {source_filename}");
+                }
+                if let Some(source_path) = doc.source_path() {
+                    println!("Original path: {source_path}");
+                }
+                if doc.is_archived() {
+                    println!("Archived: yes");
+                }
+                print!("Retrievals: {}", doc.access_count());
+                match doc.last_accessed() {
+                    Some(timestamp) => println!(" (last on {timestamp})"),
+                    None => println!(),
+                }
+                if let Some(progress) = doc.reading_progress() {
+                    println!("Reading progress: {progress}%");
+                }
+                if let Some(status) = doc.reading_status() {
+                    match doc.finished_reading() {
+                        Some(date) => println!("Reading status: {status} (finished on {date})"),
+                        None => println!("Reading status: {status}"),
                     }
+                }
+                if let Some(rating) = doc.rating() {
+                    println!("Rating: {rating}/5");
+                }
+                let annotations = doc.annotations().count();
+                if annotations > 0 {
+                    println!("Annotations: {annotations}");
+                }
+                if let Some(status) = doc.preservation() {
+                    println!("PDF/A: {status}");
+                }
+                let volumes: Vec<_> = doc.volumes().collect();
+                if !volumes.is_empty() {
+                    let titles: HashMap<_, _> = library
+                        .document_summaries()?
+                        .map(|summary| (*summary.hash(), summary.title().to_owned()))
+                        .collect();
+                    println!("Volume 1: {} {}", doc.hash().to_short_string(), doc.title());
+                    for (number, hash) in volumes.into_iter().enumerate() {
+                        let title = titles.get(hash).map_or("", String::as_str);
+                        println!(
+                            "Volume {}: {} {}",
+                            number + 2,
+                            hash.to_short_string(),
+                            title
+                        );
+                    }
+                }
+                for former_hash in doc.former_hashes() {
+                    println!("Former hash: {former_hash}");
+                }
+                if let Some(md5) = doc.md5() {
+                    println!("MD5: {md5}");
+                }
+                for (algorithm, digest) in library.digests(doc.hash())? {
+                    println!("Digest ({algorithm}): {digest}");
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Preserve {
+                identifier,
+                convert,
+            } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let config = Config::load()?;
+                let validator = config.pdfa_validator.as_deref();
+                let convert_command = if *convert {
+                    Some(config.pdfa_convert.as_deref().context(
+                        "--convert requires a pdfa_convert command in the configuration file",
+                    )?)
+                } else {
+                    None
+                };
+
+                let doc = library.find_document(identifier, true)?;
+                let status = library.check_preservation(identifier, validator)?;
+                println!("{}: {}", doc.hash().to_short_string(), doc.title());
+                println!("PDF/A: {status}");
 
-                    let mut hash_mismatches = results.hash_mismatches();
-                    if let Some(hash_mismatch) = hash_mismatches.next() {
-                        if printed {
-                            eprintln!();
+                if let Some(command) = convert_command.filter(|_| !status.is_conformant()) {
+                    let new_hash = library.convert_to_pdfa(&doc.hash().to_string(), command)?;
+                    println!(
+                        "Converted document, new hash: {}",
+                        new_hash.to_short_string()
+                    );
+                    let status = library.check_preservation(&new_hash.to_string(), validator)?;
+                    println!("PDF/A: {status}");
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Reading { command } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                match command {
+                    ReadingCommand::Import { paths, document } => {
+                        let mut data = Vec::new();
+                        for path in paths {
+                            data.extend(ReadingData::from_path(path)?);
+                        }
+                        let results = library.import_reading_data(&data, document.as_deref())?;
+                        print_reading_import_results(&results);
+                    }
+                    ReadingCommand::Show { identifier } => {
+                        let doc = library.find_document(identifier, true)?;
+                        let mut empty = true;
+                        if let Some(progress) = doc.reading_progress() {
+                            println!("Progress: {progress}%");
+                            empty = false;
+                        }
+                        if let Some(status) = doc.reading_status() {
+                            println!("Status: {status}");
+                            empty = false;
                         }
-                        eprintln!("Files with names that do not match their hashes:");
-                        eprintln!("{hash_mismatch}");
-                        for hash_mismatch in hash_mismatches {
-                            eprintln!("{hash_mismatch}");
+                        if let Some(date) = doc.finished_reading() {
+                            println!("Finished: {date}");
+                            empty = false;
+                        }
+                        if let Some(rating) = doc.rating() {
+                            println!("Rating: {rating}/5");
+                            empty = false;
+                        }
+                        for annotation in doc.annotations() {
+                            let kind = match annotation.kind {
+                                AnnotationKind::Highlight => "Highlight",
+                                AnnotationKind::Note => "Note",
+                            };
+                            match &annotation.location {
+                                Some(location) => {
+                                    println!("{kind} ({location}): {}", annotation.text);
+                                }
+                                None => println!("{kind}: {}", annotation.text),
+                            }
+                            empty = false;
+                        }
+                        if empty {
+                            println!("No reading data.");
+                        }
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Snapshot { command } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                match command {
+                    SnapshotCommand::Create { name } => {
+                        let snapshot = library.create_snapshot(name.as_deref())?;
+                        println!(
+                            "Created snapshot {} ({} documents)",
+                            snapshot.name(),
+                            snapshot.document_count()
+                        );
+                    }
+                    SnapshotCommand::List => {
+                        for snapshot in library.snapshots()? {
+                            println!(
+                                "{}: {} documents",
+                                snapshot.name(),
+                                snapshot.document_count()
+                            );
                         }
-                        printed = true;
                     }
+                    SnapshotCommand::Restore { name } => {
+                        library.restore_snapshot(name)?;
+                        println!("Restored snapshot {name}");
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Stats => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let mut docs: Vec<_> = library.documents()?.collect();
 
-                    let mut invalid_file_types = results.invalid_file_types();
-                    if let Some(invalid_file_type) = invalid_file_types.next() {
-                        if printed {
-                            eprintln!();
+                let archived = docs.iter().filter(|doc| doc.is_archived()).count();
+                let retrievals: u64 = docs.iter().map(IndexEntry::access_count).sum();
+                println!("Documents: {}", docs.len());
+                println!("Archived documents: {archived}");
+                println!("Total retrievals: {retrievals}");
+
+                // Stable sort, so documents with the same count stay in index order.
+                docs.sort_by_key(|doc| Reverse(doc.access_count()));
+                let most_used: Vec<_> = docs
+                    .iter()
+                    .take(STATS_LIST_LENGTH)
+                    .filter(|doc| doc.access_count() > 0)
+                    .collect();
+                if !most_used.is_empty() {
+                    println!();
+                    println!("Most used documents:");
+                    for doc in most_used {
+                        println!(
+                            "{}: {} ({} retrievals)",
+                            doc.hash().to_short_string(),
+                            doc.title(),
+                            doc.access_count()
+                        );
+                    }
+                }
+
+                let least_used: Vec<_> = docs.iter().rev().take(STATS_LIST_LENGTH).collect();
+                if !least_used.is_empty() {
+                    println!();
+                    println!("Least used documents:");
+                    for doc in least_used {
+                        print!(
+                            "{}: {} ({} retrievals",
+                            doc.hash().to_short_string(),
+                            doc.title(),
+                            doc.access_count()
+                        );
+                        match doc.last_accessed() {
+                            Some(timestamp) => println!(", last on {timestamp})"),
+                            None => println!(")"),
                         }
-                        eprintln!("Files with invalid file types:");
-                        eprintln!("{invalid_file_type}");
-                        for invalid_file_type in invalid_file_types {
-                            eprintln!("{invalid_file_type}");
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Unarchive { hash_prefix } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                library.edit_metadata(hash_prefix, |index_entry| {
+                    index_entry.set_archived(false);
+                    Ok(())
+                })?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Undo { list } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                if *list {
+                    for record in library.undo_history()? {
+                        println!("{record}");
+                    }
+                } else {
+                    let record = library.undo()?;
+                    print!("Undid {}", record.operation());
+                    for hash in record.hashes() {
+                        print!(" {}", hash.to_short_string());
+                    }
+                    println!();
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Fulfill {
+                id,
+                path,
+                allow_duplicate_title,
+                move_file,
+            } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let options = AddOptions {
+                    move_file: *move_file,
+                    ..AddOptions::default()
+                };
+                let added = library.fulfill_wanted(*id, path, &options, |similar| {
+                    confirm_similar(*allow_duplicate_title, similar)
+                })?;
+                Ok(if added {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitStatus::Aborted.into()
+                })
+            }
+            Command::ExitCodes => {
+                for status in ExitStatus::ALL {
+                    println!(
+                        "{:>2}  {:<17}  {}",
+                        status.code(),
+                        status,
+                        status.description()
+                    );
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::LsHashes {
+                all,
+                null_terminated,
+            } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let terminator = if *null_terminated { '\0' } else { '\n' };
+                let mut stdout = io::stdout().lock();
+                for doc in library.documents()? {
+                    if *all || !doc.is_archived() {
+                        write!(stdout, "{}{terminator}", doc.hash())
+                            .context("Failed to write to standard output")?;
+                    }
+                }
+                stdout
+                    .flush()
+                    .context("Failed to write to standard output")?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::CatBlob { hash } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let mut file = library.open_document(hash)?;
+                let mut stdout = io::stdout().lock();
+                io::copy(&mut file, &mut stdout)
+                    .and_then(|_| stdout.flush())
+                    .context("Failed to write document to standard output")?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Volumes {
+                identifier,
+                volumes,
+                clear: _,
+            } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                let volumes: Vec<_> = volumes.iter().map(String::as_str).collect();
+                let hash = library.set_volumes(identifier, &volumes)?;
+                if volumes.is_empty() {
+                    println!("{} has a single volume", hash.to_short_string());
+                } else {
+                    println!(
+                        "{} has {} volumes",
+                        hash.to_short_string(),
+                        volumes.len() + 1
+                    );
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Want { command } => {
+                let library_path = self.library_path()?;
+                let library = OpenLibrary::open(library_path)?;
+                match command {
+                    WantCommand::Add { doc_type } => {
+                        let title = stdin_read_input("Title")?;
+                        let authors = stdin_read_authors()?;
+                        let isbns = if *doc_type == Some(DocType::Article) {
+                            Vec::new()
+                        } else {
+                            stdin_read_isbns()?
+                        };
+                        let mut doi = None;
+                        if stdin_confirm("Add a DOI?")? {
+                            doi = Some(stdin_read_input("DOI")?);
                         }
+                        let entry = library.add_wanted(WantedMetadata {
+                            title,
+                            authors,
+                            isbns,
+                            doi,
+                            doc_type: *doc_type,
+                        })?;
+                        println!();
+                        println!("Added wanted document {}", entry.id());
+                    }
+                    WantCommand::List => {
+                        for entry in library.wanted()? {
+                            print_wanted(&entry);
+                        }
+                    }
+                    WantCommand::Remove { id } => {
+                        let entry = library.remove_wanted(*id)?;
+                        println!("Removed wanted document:");
+                        print_wanted(&entry);
                     }
-                    Ok(ExitCode::FAILURE)
                 }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Validate {
+                json,
+                all_digests,
+                strict,
+            } => {
+                let library_path = self.library_path()?;
+                // Validation only inspects the library, so it also works on libraries created by
+                // other versions of burette.
+                let library = Library::open_unchecked(library_path)?;
+                let results = library.validate_with_options(&ValidateOptions {
+                    all_digests: *all_digests,
+                    strict: *strict,
+                })?;
+                if *json {
+                    let json = serde_json::to_string_pretty(&results)
+                        .context("Failed to serialize validation results")?;
+                    println!("{json}");
+                } else if results.is_valid() {
+                    println!("{results}");
+                } else {
+                    eprintln!("{results}");
+                }
+                Ok(if results.is_valid() {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitStatus::ValidationFailed.into()
+                })
             }
         }
     }
@@ -402,36 +1844,462 @@ enum Command {
     Add {
         /// The path to the document to add
         path: PathBuf,
+        /// Type of the document (book, article, thesis, report or standard)
+        ///
+        /// For articles, the journal, volume, issue and page range are asked for instead of
+        /// ISBNs.
+        #[clap(long = "type", short = 't', value_name = "TYPE")]
+        doc_type: Option<DocType>,
+        /// Do not warn if a document with the same title and authors already exists
+        #[clap(long)]
+        allow_duplicate_title: bool,
+        /// Move the document into the library instead of copying it
+        #[clap(long = "move")]
+        move_file: bool,
+        /// Record the MD5 hash of the document
+        ///
+        /// Library Genesis and Anna's Archive identify files by their MD5 hash. Recording it
+        /// makes it possible to check whether a file found there is already in the library, using
+        /// `md5:<HASH>` as the identifier.
+        #[clap(long)]
+        md5: bool,
+        /// SHA-256 hash the document must have
+        ///
+        /// The document is not added if its hash differs, e.g. because the download was
+        /// corrupted. The hash is checked before asking for metadata.
+        #[clap(long, value_name = "HASH", conflicts_with = "checksums")]
+        expect_sha256: Option<sha256::Hash>,
+        /// File listing the expected SHA-256 hashes of documents, in the format of `sha256sum`
+        ///
+        /// Download sites often publish such files, e.g. as SHA256SUMS. The document must be
+        /// listed in it under its file name, and is only added if its hash matches.
+        #[clap(long, value_name = "FILE")]
+        checksums: Option<PathBuf>,
+        /// File format of the document (epub or pdf)
+        ///
+        /// By default, the file format is detected from the contents of the file. Use this option
+        /// to add files that are not recognized, e.g. because they are slightly malformed.
+        #[clap(long = "format", value_name = "FORMAT", value_parser = FileFormat::from_extension)]
+        file_format: Option<FileFormat>,
+        /// JSON file with the metadata of the document, instead of asking for it
+        ///
+        /// The file contains an object with the same fields as the entries of the index file:
+        /// `title`, `authors`, `isbns`, `file_format` (as a MIME type) and `doi`, and optionally
+        /// `doc_type`, `rights`, `journal`, `volume`, `issue` and `pages`. This way, documents can
+        /// be added by scripts, and their metadata can be reviewed before adding them. If the file
+        /// has a `hash`, as the files written by `burette get --with-sidecar` do, the document
+        /// must have that SHA-256 hash, unless another hash is given.
+        ///
+        /// Files with the extension `.opf` are read as OPF package documents instead, like the
+        /// `metadata.opf` files of Calibre and those written by `burette export-calibre`. The
+        /// title, authors, ISBNs, DOI and rights are taken from the package, and the file format
+        /// is detected from the document.
+        #[clap(
+            long = "metadata",
+            value_name = "FILE",
+            conflicts_with_all = ["doc_type", "file_format"]
+        )]
+        metadata_file: Option<PathBuf>,
+    },
+    /// Manage aliases, short names that can be used to refer to documents
+    ///
+    /// Aliases can be used wherever a document identifier is expected, e.g. `burette get sicp`.
+    Alias {
+        /// Alias operation to perform
+        #[command(subcommand)]
+        command: AliasCommand,
+    },
+    /// Apply a list of operations to the library, either all of them or none
+    ///
+    /// The file contains a JSON array of operations, which are applied in order. Each operation
+    /// is an object whose `op` field is `add`, `edit` or `remove`. Operations that add a document
+    /// give the `path` of the file, relative to the directory of the operations file, and its
+    /// metadata in the same fields as for `burette add --metadata`. Operations that edit or
+    /// remove a document identify it by `document`, e.g. a hash prefix or an ISBN. Edits change
+    /// the metadata fields given in the object, and optional fields are cleared by setting them to
+    /// `null`.
+    ///
+    /// If any operation fails, e.g. because a document cannot be found, the library is left
+    /// unchanged.
+    Apply {
+        /// JSON file with the operations to apply
+        file: PathBuf,
+        /// Only check the operations, without changing the library
+        #[clap(long, short = 'n')]
+        dry_run: bool,
+    },
+    /// Archive a document
+    ///
+    /// Archived documents stay in the library, but are hidden from `list` and `get` unless
+    /// `--all` is given.
+    Archive {
+        /// Hash prefix of the document to archive
+        hash_prefix: String,
+    },
+    /// List authors and merge variant spellings of their names
+    Authors {
+        /// Author operation to perform
+        #[command(subcommand)]
+        command: AuthorsCommand,
+    },
+    /// Import and export BibTeX files with links to documents, as used by `JabRef`
+    Bibtex {
+        /// BibTeX operation to perform
+        #[command(subcommand)]
+        command: BibtexCommand,
+    },
+    /// Write the contents of a document to standard output (plumbing)
+    ///
+    /// The document is not recorded as retrieved. Archived documents can be read as well.
+    CatBlob {
+        /// Full SHA-256 hash of the document
+        hash: sha256::Hash,
+    },
+    /// Compute and list additional digests (MD5, SHA-1) of the documents
+    ///
+    /// The digests to compute are configured with "digests" in settings.json in the library
+    /// directory. They are computed whenever a document is added and can be checked with
+    /// `burette validate --all-digests`.
+    Digests {
+        /// Digest operation to perform
+        #[command(subcommand)]
+        command: DigestsCommand,
     },
-    /// List all documents in the library
-    List,
     /// Edit the metadata of a document in the library
     Edit {
         /// Hash prefix of the document to edit
         hash_prefix: String,
         /// Field of the document to edit
         field: EditField,
+        /// Do not ask for confirmation
+        ///
+        /// By default, the old and new values of the field are shown and the change is only saved
+        /// after confirmation.
+        #[clap(long, short)]
+        yes: bool,
     },
-    /// Retrieve a document from the library
+    /// List the exit codes of burette and their meaning
+    ///
+    /// Scripts can use the exit code to tell why a command failed. The exit codes do not change
+    /// between versions of burette.
+    ExitCodes,
+    /// Export documents to a directory that Calibre can import
+    ///
+    /// Each document is copied to `<author>/<title> (<id>)/` within the directory, together with
+    /// a `metadata.opf` file with its metadata, as in a Calibre library. In Calibre, add the
+    /// directory with "Add books from directories, including sub-directories (One book per
+    /// directory)".
+    ExportCalibre {
+        /// Directory to export to, which must not exist or be empty
+        dir: PathBuf,
+        /// Only export the documents matching this filter
+        ///
+        /// See `burette get --help` for the filter syntax.
+        #[clap(long)]
+        filter: Option<Filter>,
+        /// Also export archived documents
+        #[clap(long, short)]
+        all: bool,
+    },
+    /// Add the file of a wanted document to the library
+    ///
+    /// The document is added with the metadata of the wanted document, which is then removed
+    /// from the wanted documents.
+    Fulfill {
+        /// Number of the wanted document
+        id: u64,
+        /// The path to the document to add
+        path: PathBuf,
+        /// Do not warn if a document with the same title and authors already exists
+        #[clap(long)]
+        allow_duplicate_title: bool,
+        /// Move the document into the library instead of copying it
+        #[clap(long = "move")]
+        move_file: bool,
+    },
+    /// Retrieve documents from the library
     Get {
-        /// Identifier of the document to retrieve (hash prefix, DOI or ISBN)
-        identifier: String,
+        /// Identifiers of the documents to retrieve (hash prefix, alias, DOI or ISBN)
+        #[arg(required_unless_present = "filter")]
+        identifiers: Vec<String>,
+        /// Also retrieve all documents matching this filter
+        ///
+        /// A filter consists of terms separated by whitespace, all of which must match. Supported
+        /// terms are `title:<text>`, `author:<text>`, `isbn:<isbn>`, `doi:<doi>`, `type:<type>`,
+        /// `rights:<rights>`, `format:<extension>`, `hash:<prefix>` and `<text>`, which matches
+        /// the title or the authors. Terms can be combined with `AND`, `OR` and `NOT` and grouped
+        /// with parentheses, e.g. `title:"moby dick" AND NOT (format:pdf OR author:anonymous)`.
+        #[clap(long)]
+        filter: Option<Filter>,
         /// Path to save the document to
-        #[clap(long, short)]
+        ///
+        /// Can only be used when retrieving a single document.
+        #[clap(long, short, conflicts_with = "directory")]
         output: Option<PathBuf>,
+        /// Directory to save the documents to
+        ///
+        /// Defaults to the current directory. The directory is created if it does not exist.
+        #[clap(long, short)]
+        directory: Option<PathBuf>,
+        /// Also consider archived documents
+        #[clap(long, short)]
+        all: bool,
+        /// Save the document under the name of the file it was added from
+        ///
+        /// This only has an effect if no output path is given. If the original file name is not
+        /// known, the default name is used.
+        #[clap(long)]
+        original_name: bool,
+        /// Overwrite the output file if it already exists
+        #[clap(long, short, conflicts_with = "number")]
+        force: bool,
+        /// Append a number to the file name if the output file already exists
+        #[clap(long, short)]
+        number: bool,
+        /// Set the modification time of the retrieved files to that of the files they were added
+        /// from
+        #[clap(long)]
+        preserve_mtime: bool,
+        /// Write the metadata of the documents into the retrieved files
+        ///
+        /// The files are passed to the `metadata_command` from the configuration file, e.g. to
+        /// update the title and authors stored in the file.
+        #[clap(long)]
+        write_metadata: bool,
+        /// Write the hash and metadata of each document to a JSON file next to it
+        ///
+        /// The file is named like the retrieved file with `.burette.json` appended. It can be
+        /// passed to `burette add --metadata` to add the document to another library.
+        #[clap(long)]
+        with_sidecar: bool,
+    },
+    /// Print a link to a document for pasting into notes
+    ///
+    /// The link points to a `burette://` URI containing the full hash of the document, which
+    /// `burette open-uri` opens.
+    LinkFor {
+        /// Identifier of the document to link to (hash prefix, alias, DOI or ISBN)
+        identifier: String,
+        /// Syntax of the link (markdown, org or obsidian)
+        #[clap(long, short, default_value_t = LinkStyle::Markdown)]
+        style: LinkStyle,
+    },
+    /// Check the metadata of the documents for problems
+    ///
+    /// Documents without authors, with placeholder titles or titles that look like file names,
+    /// without ISBN and DOI, with DOIs that are malformed or contain an ISBN the document does not
+    /// have, and with authors listed more than once are reported. The exit status is 4 if problems
+    /// are found.
+    LintMetadata {
+        /// Print the problems as JSON to standard output
+        #[clap(long)]
+        json: bool,
+        /// Check archived documents as well
+        #[clap(long, short = 'a')]
+        all: bool,
+    },
+    /// List all documents in the library
+    List {
+        /// Also list archived documents
+        #[clap(long, short)]
+        all: bool,
+        /// Sort the documents (title, last-opened or access-count)
+        #[clap(long, short)]
+        sort: Option<ListSort>,
+        /// Only list documents of this type (book, article, thesis, report or standard)
+        #[clap(long = "type", short = 't', value_name = "TYPE")]
+        doc_type: Option<DocType>,
+        /// Print each document according to this template
+        ///
+        /// See `burette show --help` for the template syntax.
+        #[clap(long, value_name = "TEMPLATE")]
+        format: Option<OutputTemplate>,
+    },
+    /// Show the log of all operations that modified the library
+    Log {
+        /// Only show events of this operation (add, edit, remove, replace, restore or undo)
+        #[clap(long, short)]
+        operation: Option<Operation>,
+        /// Only show events involving a document whose hash starts with this prefix
+        #[clap(long, short)]
+        document: Option<String>,
+        /// Only show events performed by this user
+        #[clap(long, short)]
+        user: Option<String>,
+    },
+    /// Print the hashes of all documents (plumbing)
+    ///
+    /// Each document is printed as its full hash followed by a newline, in the order of the
+    /// index. Unlike the output of the other commands, this format is stable and meant for
+    /// scripts.
+    LsHashes {
+        /// Also print the hashes of archived documents
+        #[clap(long, short)]
+        all: bool,
+        /// Terminate each hash with a NUL byte instead of a newline
+        #[clap(short = 'z')]
+        null_terminated: bool,
     },
     /// Create a new library
     New,
+    /// Open the document a `burette://` URI refers to
+    ///
+    /// The document is copied to a temporary directory and opened with the `open_command` from
+    /// the configuration file, or with the default application of the system. Archived documents
+    /// can be opened as well.
+    OpenUri {
+        /// URI of the document, e.g. `burette://<hash>` as printed by `burette link-for`
+        uri: DocumentUri,
+    },
+    /// Check whether a document conforms to PDF/A, for long-term preservation
+    ///
+    /// The document is checked with the `pdfa_validator` from the configuration file, e.g.
+    /// veraPDF, or by a heuristic if none is configured. The heuristic only checks that the
+    /// document identifies itself as PDF/A and is not encrypted. The result is recorded and shown
+    /// by `burette show`.
+    Preserve {
+        /// Identifier of the document to check (hash prefix, alias, DOI or ISBN)
+        identifier: String,
+        /// Convert the document to PDF/A if it does not conform
+        ///
+        /// The document is converted with the `pdfa_convert` command from the configuration file
+        /// and its file is replaced by the converted one, like by `burette replace`.
+        #[clap(long)]
+        convert: bool,
+    },
+    /// Import and show reading progress and annotations
+    ///
+    /// Reading progress, highlights and notes can be imported from `KOReader` metadata sidecars
+    /// and Kindle `My Clippings.txt` files, and the reading status, rating and date finished
+    /// from Goodreads and The `StoryGraph` CSV exports.
+    Reading {
+        /// Reading data operation to perform
+        #[command(subcommand)]
+        command: ReadingCommand,
+    },
+    /// Make `burette open-uri` the handler for `burette://` URIs
+    ///
+    /// Afterwards, clicking a link created by `burette link-for` in a browser or note-taking
+    /// application opens the document. On Linux, a desktop entry is installed and made the
+    /// default handler with `xdg-mime`. On Windows, the handler is registered for the current
+    /// user. If `--library` is given, the handler opens documents from that library.
+    RegisterUriHandler,
     /// Remove documents from the library
     Remove {
-        /// Hash prefixes of the documents to remove
+        /// Identifiers of the documents to remove (hash prefix, alias, DOI or ISBN)
+        ///
+        /// All documents matching one of the given identifiers will be removed. If an identifier
+        /// matches multiple documents, none of them will be removed and instead a message will be
+        /// printed.
+        // This ensures that the user must provide at least one identifier or a filter.
+        #[arg(required_unless_present = "filter")]
+        identifiers: Vec<String>,
+        /// Also remove all documents matching this filter
+        ///
+        /// See `burette get --help` for the filter syntax. The documents matching the filter are
+        /// shown and only removed after confirmation.
+        #[clap(long)]
+        filter: Option<Filter>,
+        /// Also remove archived documents matching the filter
+        ///
+        /// Archived documents can always be removed by their identifiers.
+        #[clap(long, short)]
+        all: bool,
+        /// Do not ask for confirmation
+        #[clap(long, short)]
+        yes: bool,
+        /// Print the results as JSON to standard output
+        #[clap(long)]
+        json: bool,
+    },
+    /// Replace the file of a document, keeping its metadata
+    ///
+    /// The old file is moved to the trash directory of the library.
+    Replace {
+        /// Hash prefix of the document to replace
+        hash_prefix: String,
+        /// Path to the new file
+        path: PathBuf,
+    },
+    /// Restore missing or damaged documents and index entries from a backup
+    ///
+    /// The backup is a copy of the library directory or a snapshot in the `snapshots` directory
+    /// of the library. Files that `burette validate` reports as missing or damaged are replaced
+    /// by their copies in the backup, and files without index entries get their entries from the
+    /// backup. Changes made since the backup was taken are kept.
+    Restore {
+        /// Directory of the backup
+        backup_dir: PathBuf,
+        /// Only restore the documents with these hash prefixes
+        #[clap(long, num_args = 1.., value_name = "HASH_PREFIX")]
+        only: Vec<String>,
+    },
+    /// Send a document to a device such as an e-reader
+    ///
+    /// Devices are configured in the `devices` section of the configuration file. Each device has
+    /// the directory documents are copied to and optionally a preferred format, a file name
+    /// template and a command to convert documents to the preferred format.
+    Send {
+        /// Identifier of the document to send (hash prefix, alias, DOI or ISBN)
+        identifier: String,
+        /// Name of the device to send the document to
+        ///
+        /// Can be omitted if only one device is configured.
+        #[clap(long, short)]
+        device: Option<String>,
+        /// Also consider archived documents
+        #[clap(long, short)]
+        all: bool,
+        /// Overwrite the file on the device if it already exists
+        #[clap(long, short, conflicts_with = "number")]
+        force: bool,
+        /// Append a number to the file name if the file already exists on the device
+        #[clap(long, short)]
+        number: bool,
+        /// Write the metadata of the document into the file on the device
         ///
-        /// All documents with a hash that starts with one of the given prefixes will be removed.
-        /// If a document matches multiple prefixes, it will not be removed and instead a message
-        /// will be printed to standard error.
-        // This ensures that the user must provide at least one hash prefix.
-        #[arg(required = true, num_args = 1..)]
-        hash_prefixes: Vec<String>,
+        /// See `burette get --help`.
+        #[clap(long)]
+        write_metadata: bool,
+    },
+    /// Show all information about a document
+    Show {
+        /// Identifier of the document to show (hash prefix, alias, DOI or ISBN)
+        identifier: String,
+        /// Print the document according to this template
+        ///
+        /// Placeholders in curly braces are replaced by fields of the document: `hash`,
+        /// `hash.short`, `title`, `authors`, `aliases`, `isbns`, `doi`, `type`, `rights`,
+        /// `journal`, `volume`, `issue`, `pages`, `format`, `mime`, `size`, `filename`, `path`,
+        /// `modified`, `archived`, `last_accessed`, `access_count` and `progress`. Filters can be
+        /// appended with `|`: `join:<separator>`, `first`, `default:<text>`, `upper` and `lower`.
+        /// For example, `{hash.short}\t{title}\t{authors|join:", "}`. `\t`, `\n`, `\\`, `\{`
+        /// and `\}` are replaced by a tab, a newline, a backslash and curly braces.
+        #[clap(long, value_name = "TEMPLATE")]
+        format: Option<OutputTemplate>,
+    },
+    /// Create, list and restore snapshots of the library
+    Snapshot {
+        /// Snapshot operation to perform
+        #[command(subcommand)]
+        command: SnapshotCommand,
+    },
+    /// Show statistics about the library and its usage
+    Stats,
+    /// Unarchive a document
+    Unarchive {
+        /// Hash prefix of the document to unarchive
+        hash_prefix: String,
+    },
+    /// Undo the most recent operation that changed the library
+    ///
+    /// Edits are reverted, added documents are moved to the trash, and removed or replaced
+    /// documents are moved back from the trash. Running `undo` again undoes the operation before,
+    /// up to the last 20 operations.
+    Undo {
+        /// List the operations that can be undone, most recent first, instead of undoing one
+        #[clap(long, short)]
+        list: bool,
     },
     /// Validate the library
     ///
@@ -439,7 +2307,243 @@ enum Command {
     /// If the library is in a valid state, the command prints "Library is valid." and exits with a
     /// status code of 0. If the library is not valid, the command prints the errors found and
     /// exits with a non-zero status code.
-    Validate,
+    Validate {
+        /// Print the results as JSON to standard output
+        #[clap(long)]
+        json: bool,
+        /// Also check the documents against the digests recorded by `burette digests update`
+        #[clap(long)]
+        all_digests: bool,
+        /// Also report fields of the index that burette does not know
+        ///
+        /// burette ignores such fields, and they are lost the next time the index is written. They
+        /// are usually typos in a hand-edited index.
+        #[clap(long)]
+        strict: bool,
+    },
+    /// Combine documents into a document with multiple volumes
+    ///
+    /// The given volumes become the second, third, etc. volume of the document, which is the
+    /// first volume. Each volume stays a document of its own, but `burette get` retrieves all
+    /// volumes of a document into a directory, and `burette show` lists them.
+    Volumes {
+        /// Identifier of the first volume (hash prefix, alias, DOI or ISBN)
+        identifier: String,
+        /// Identifiers of the further volumes, in order
+        #[arg(required_unless_present = "clear", conflicts_with = "clear")]
+        volumes: Vec<String>,
+        /// Turn the document back into a single-volume document
+        #[clap(long)]
+        clear: bool,
+    },
+    /// Add, list and remove wanted documents
+    ///
+    /// Wanted documents are documents that are not in the library yet, e.g. books on a wishlist.
+    /// Only their metadata is stored. Use `fulfill` to add the file of a wanted document to the
+    /// library once it is available.
+    Want {
+        /// Operation on wanted documents to perform
+        #[command(subcommand)]
+        command: WantCommand,
+    },
+}
+
+/// Sort order of `burette list`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ListSort {
+    /// Sort by title
+    Title,
+    /// Sort by the time of the last retrieval, most recent first
+    LastOpened,
+    /// Sort by the number of retrievals, most retrieved first
+    AccessCount,
+}
+
+impl Display for ListSort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ListSort::Title => write!(f, "title"),
+            ListSort::LastOpened => write!(f, "last-opened"),
+            ListSort::AccessCount => write!(f, "access-count"),
+        }
+    }
+}
+
+impl FromStr for ListSort {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "title" => Ok(ListSort::Title),
+            "last-opened" => Ok(ListSort::LastOpened),
+            "access-count" => Ok(ListSort::AccessCount),
+            _ => bail!("Invalid sort order: {}", s),
+        }
+    }
+}
+
+/// Operations on aliases.
+#[derive(Debug, Subcommand)]
+enum AliasCommand {
+    /// Assign an alias to a document
+    ///
+    /// Aliases may contain ASCII letters, digits, `-`, `_` and `.`. They must contain at least
+    /// one character that is not a hexadecimal digit, so they cannot be confused with hash
+    /// prefixes. A document can have multiple aliases.
+    Set {
+        /// The alias to assign
+        alias: String,
+        /// Identifier of the document (hash prefix, alias, DOI or ISBN)
+        identifier: String,
+    },
+    /// Remove an alias
+    Remove {
+        /// The alias to remove
+        alias: String,
+    },
+    /// List all aliases
+    List,
+}
+
+/// Operations on the authors of the documents.
+#[derive(Debug, Subcommand)]
+enum AuthorsCommand {
+    /// List all authors with the number of their documents, most frequent first
+    List {
+        /// Count archived documents as well
+        #[clap(long, short)]
+        all: bool,
+        /// List the documents by this author instead
+        #[clap(long, value_name = "AUTHOR")]
+        documents_of: Option<String>,
+    },
+    /// Replace variant spellings of an author name in all documents
+    ///
+    /// The last name given is the canonical spelling, all other names are replaced by it, e.g.
+    /// `burette authors merge "J. Smith" "Smith, John" "John Smith"`. Names must match exactly.
+    Merge {
+        /// Variant spellings followed by the canonical spelling
+        #[clap(required = true, num_args = 2.., value_name = "NAME")]
+        names: Vec<String>,
+    },
+}
+
+/// Operations on snapshots of the library.
+#[derive(Debug, Subcommand)]
+enum SnapshotCommand {
+    /// Create a snapshot of the current state of the library
+    ///
+    /// Documents are hard-linked into the snapshot where possible, so snapshots are cheap.
+    Create {
+        /// Name of the snapshot (defaults to the current Unix timestamp)
+        name: Option<String>,
+    },
+    /// List all snapshots of the library
+    List,
+    /// Restore the library to the state recorded in a snapshot
+    ///
+    /// Documents added after the snapshot was taken are removed from the library.
+    Restore {
+        /// Name of the snapshot to restore
+        name: String,
+    },
+}
+
+/// Operations on wanted documents.
+#[derive(Debug, Subcommand)]
+enum WantCommand {
+    /// Add a wanted document
+    ///
+    /// You will be prompted to enter the metadata of the document.
+    Add {
+        /// Type of the document (book, article, thesis, report or standard)
+        #[clap(long = "type", short = 't', value_name = "TYPE")]
+        doc_type: Option<DocType>,
+    },
+    /// List all wanted documents
+    List,
+    /// Remove a wanted document
+    Remove {
+        /// Number of the wanted document to remove
+        id: u64,
+    },
+}
+
+/// Operations on the additional digests of the documents.
+#[derive(Debug, Subcommand)]
+enum DigestsCommand {
+    /// Compute the configured digests of all documents that do not have them yet
+    Update,
+    /// Print a manifest of all documents in the format of `md5sum` and `sha1sum`
+    ///
+    /// File names are relative to the library directory, so the manifest can be checked with
+    /// e.g. `md5sum -c` from within the library directory.
+    Manifest {
+        /// Digest to list (md5 or sha1, defaults to the SHA-256 hash)
+        #[clap(long)]
+        algorithm: Option<DigestAlgorithm>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum BibtexCommand {
+    /// Add the files linked from a BibTeX file to the library
+    ///
+    /// The first file linked from the `JabRef` `file` field of each entry is added, with the
+    /// metadata of the entry. Relative links are relative to the directory of the BibTeX file.
+    /// Entries without linked files and files that are already in the library are skipped.
+    Import {
+        /// The BibTeX file to import
+        path: PathBuf,
+        /// Do not ask before adding documents with the same title and authors as existing ones
+        #[clap(long)]
+        allow_duplicate_title: bool,
+        /// Number of files to hash and copy in parallel (defaults to the number of CPUs)
+        #[clap(long, short = 'j', value_name = "N")]
+        jobs: Option<NonZeroUsize>,
+    },
+    /// Write a BibTeX entry for each document in the library
+    ///
+    /// The citation key of each entry is the first alias of the document, or its short hash.
+    Export {
+        /// Write the BibTeX file to this path instead of standard output
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+        /// Copy the documents to this directory and link them from the entries
+        ///
+        /// Existing files are replaced, so exporting again updates the directory. Links are
+        /// relative to the directory of the BibTeX file if the documents are inside it.
+        #[clap(long, value_name = "DIRECTORY")]
+        files: Option<PathBuf>,
+        /// Include archived documents
+        #[clap(long, short)]
+        all: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ReadingCommand {
+    /// Import reading progress and annotations exported by an e-reader or a book tracking site
+    ///
+    /// Files ending in `.lua` and directories (such as `KOReader` `.sdr` directories) are read as
+    /// `KOReader` metadata sidecars, files ending in `.csv` as Goodreads or The `StoryGraph`
+    /// exports, all other files as Kindle clippings. The reading data is attached to the document
+    /// with the same ISBN or, failing that, the same title. If multiple documents have the same
+    /// title, the authors are used to tell them apart. Annotations that were imported before are
+    /// not added again.
+    Import {
+        /// Files or directories to import
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Attach all reading data to this document instead of matching by title (hash prefix,
+        /// alias, DOI or ISBN)
+        #[clap(long, short, value_name = "IDENTIFIER")]
+        document: Option<String>,
+    },
+    /// Show the reading progress and annotations of a document
+    Show {
+        /// Identifier of the document (hash prefix, alias, DOI or ISBN)
+        identifier: String,
+    },
 }
 
 /// Field of a document to edit.
@@ -453,6 +2557,20 @@ enum EditField {
     Isbns,
     /// Edit the DOI of the document
     Doi,
+    /// Edit the type of the document
+    Type,
+    /// Edit the license or other usage rights of the document
+    Rights,
+    /// Edit the journal or conference the document was published in
+    Journal,
+    /// Edit the volume of the journal or proceedings
+    Volume,
+    /// Edit the issue of the journal
+    Issue,
+    /// Edit the page range of the document
+    Pages,
+    /// Edit the MD5 hash of the document, as used by Library Genesis and Anna's Archive
+    Md5,
 }
 
 impl Display for EditField {
@@ -462,6 +2580,13 @@ impl Display for EditField {
             EditField::Authors => write!(f, "authors"),
             EditField::Isbns => write!(f, "isbns"),
             EditField::Doi => write!(f, "doi"),
+            EditField::Type => write!(f, "type"),
+            EditField::Rights => write!(f, "rights"),
+            EditField::Journal => write!(f, "journal"),
+            EditField::Volume => write!(f, "volume"),
+            EditField::Issue => write!(f, "issue"),
+            EditField::Pages => write!(f, "pages"),
+            EditField::Md5 => write!(f, "md5"),
         }
     }
 }
@@ -474,6 +2599,13 @@ impl FromStr for EditField {
             "authors" => Ok(EditField::Authors),
             "isbns" => Ok(EditField::Isbns),
             "doi" => Ok(EditField::Doi),
+            "type" => Ok(EditField::Type),
+            "rights" => Ok(EditField::Rights),
+            "journal" => Ok(EditField::Journal),
+            "volume" => Ok(EditField::Volume),
+            "issue" => Ok(EditField::Issue),
+            "pages" => Ok(EditField::Pages),
+            "md5" => Ok(EditField::Md5),
             _ => bail!("Invalid field: {}", s),
         }
     }
@@ -0,0 +1,143 @@
+//! Exit codes of the command line application.
+
+use {
+    crate::LookupError,
+    std::{
+        fmt::{self, Display, Formatter},
+        process::ExitCode,
+    },
+};
+
+/// Reason why the command line application exits, determining its exit code.
+///
+/// The exit codes are part of the interface of burette and must not change, so that scripts can
+/// rely on them. They are listed by `burette exit-codes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ExitStatus {
+    /// The command succeeded.
+    Success,
+    /// The command failed for a reason not covered by the other statuses.
+    Failure,
+    /// A document or other item was not found.
+    NotFound,
+    /// An identifier matched multiple documents.
+    Ambiguous,
+    /// `burette validate` or `burette lint-metadata` found problems in the library.
+    ValidationFailed,
+    /// Some, but not necessarily all, of the documents a command operated on failed.
+    PartialFailure,
+    /// The operation was not confirmed and thus not performed.
+    Aborted,
+    /// The command line arguments are invalid.
+    Usage,
+}
+
+impl ExitStatus {
+    /// All exit statuses, ordered by exit code.
+    pub(super) const ALL: [ExitStatus; 8] = [
+        ExitStatus::Success,
+        ExitStatus::Failure,
+        ExitStatus::NotFound,
+        ExitStatus::Ambiguous,
+        ExitStatus::ValidationFailed,
+        ExitStatus::PartialFailure,
+        ExitStatus::Aborted,
+        ExitStatus::Usage,
+    ];
+
+    /// Return the exit code of the status.
+    pub(super) fn code(self) -> u8 {
+        match self {
+            ExitStatus::Success => 0,
+            ExitStatus::Failure => 1,
+            ExitStatus::NotFound => 2,
+            ExitStatus::Ambiguous => 3,
+            ExitStatus::ValidationFailed => 4,
+            ExitStatus::PartialFailure => 5,
+            ExitStatus::Aborted => 6,
+            // Same as `EX_USAGE` from sysexits.h.
+            ExitStatus::Usage => 64,
+        }
+    }
+
+    /// Return a short description of the status.
+    pub(super) fn description(self) -> &'static str {
+        match self {
+            ExitStatus::Success => "The command succeeded",
+            ExitStatus::Failure => "The command failed for another reason",
+            ExitStatus::NotFound => "A document or other item was not found",
+            ExitStatus::Ambiguous => "An identifier matched multiple documents",
+            ExitStatus::ValidationFailed => "The library is not valid",
+            ExitStatus::PartialFailure => "Some of the selected documents could not be processed",
+            ExitStatus::Aborted => "The operation was not confirmed",
+            ExitStatus::Usage => "The command line arguments are invalid",
+        }
+    }
+
+    /// Return the status for a command that failed with `error`.
+    pub(super) fn from_error(error: &anyhow::Error) -> Self {
+        match error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<LookupError>())
+        {
+            Some(LookupError::NotFound(_)) => ExitStatus::NotFound,
+            Some(LookupError::Ambiguous(_)) => ExitStatus::Ambiguous,
+            None => ExitStatus::Failure,
+        }
+    }
+}
+
+impl Display for ExitStatus {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            ExitStatus::Success => "success",
+            ExitStatus::Failure => "failure",
+            ExitStatus::NotFound => "not-found",
+            ExitStatus::Ambiguous => "ambiguous",
+            ExitStatus::ValidationFailed => "validation-failed",
+            ExitStatus::PartialFailure => "partial-failure",
+            ExitStatus::Aborted => "aborted",
+            ExitStatus::Usage => "usage",
+        };
+        f.pad(name)
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::ExitStatus,
+        crate::LookupError,
+        anyhow::{anyhow, Context},
+    };
+
+    #[test]
+    fn exit_codes_are_sorted_and_unique() {
+        let codes: Vec<_> = ExitStatus::ALL.iter().map(|status| status.code()).collect();
+        assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn status_from_error() {
+        let not_found: anyhow::Result<()> =
+            Err(LookupError::NotFound("No document found".to_owned()).into());
+        let not_found = not_found
+            .context("Failed to retrieve document")
+            .expect_err("Error");
+        assert_eq!(ExitStatus::from_error(&not_found), ExitStatus::NotFound);
+
+        let ambiguous = LookupError::Ambiguous("Multiple documents found".to_owned()).into();
+        assert_eq!(ExitStatus::from_error(&ambiguous), ExitStatus::Ambiguous);
+
+        assert_eq!(
+            ExitStatus::from_error(&anyhow!("I/O error")),
+            ExitStatus::Failure
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Registration of burette as the handler of `burette://` URIs.
+
+use {
+    crate::DocumentUri,
+    anyhow::{bail, Context},
+    std::{
+        env,
+        ffi::OsString,
+        fs, io,
+        path::{Path, PathBuf},
+        process,
+    },
+};
+
+/// Name of the desktop entry that handles `burette://` URIs on Linux and other Unix systems.
+const DESKTOP_ENTRY_NAME: &str = "burette-uri-handler.desktop";
+
+/// Register `burette open-uri` as the handler of `burette://` URIs for the current user.
+///
+/// `exe` is the path to the burette executable. If `library` is given, the handler opens
+/// documents from that library instead of the default one. A description of what was registered
+/// is returned.
+pub(super) fn register(exe: &Path, library: Option<&Path>) -> anyhow::Result<String> {
+    if cfg!(windows) {
+        register_windows(exe, library)
+    } else if cfg!(target_os = "macos") {
+        bail!(
+            "URI handlers can only be registered by application bundles on macOS, configure a \
+            tool such as duti to run `burette open-uri` instead"
+        );
+    } else {
+        register_xdg(exe, library)
+    }
+}
+
+/// Install a desktop entry for `burette://` URIs and make it the default handler with
+/// `xdg-mime`.
+fn register_xdg(exe: &Path, library: Option<&Path>) -> anyhow::Result<String> {
+    let data_dir = match env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => crate::home_dir()
+            .context("Failed to determine data directory")?
+            .join(".local")
+            .join("share"),
+    };
+    let applications_dir = data_dir.join("applications");
+    fs::create_dir_all(&applications_dir).with_context(|| {
+        format!(
+            "Failed to create directory at {}",
+            applications_dir.display()
+        )
+    })?;
+    let entry_path = applications_dir.join(DESKTOP_ENTRY_NAME);
+    fs::write(&entry_path, desktop_entry(exe, library))
+        .with_context(|| format!("Failed to write desktop entry to {}", entry_path.display()))?;
+
+    let mime_type = format!("x-scheme-handler/{}", DocumentUri::SCHEME);
+    let status = match process::Command::new("xdg-mime")
+        .args(["default", DESKTOP_ENTRY_NAME, &mime_type])
+        .status()
+    {
+        Ok(status) => status,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => bail!(
+            "Installed desktop entry at {}, but xdg-mime was not found to make it the default \
+            handler for {}",
+            entry_path.display(),
+            mime_type
+        ),
+        Err(error) => return Err(error).context("Failed to run xdg-mime"),
+    };
+    if !status.success() {
+        bail!("xdg-mime failed ({})", status);
+    }
+
+    Ok(format!(
+        "Installed desktop entry at {} as the handler for {}",
+        entry_path.display(),
+        mime_type
+    ))
+}
+
+/// Return the contents of the desktop entry that handles `burette://` URIs.
+fn desktop_entry(exe: &Path, library: Option<&Path>) -> String {
+    let mut command = vec![quote_exec_arg(&exe.to_string_lossy())];
+    if let Some(library) = library {
+        command.push("--library".to_owned());
+        command.push(quote_exec_arg(&library.to_string_lossy()));
+    }
+    command.push("open-uri".to_owned());
+    command.push("%u".to_owned());
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=burette\n\
+         Comment=Open documents from a burette library\n\
+         Exec={}\n\
+         MimeType=x-scheme-handler/{};\n\
+         NoDisplay=true\n\
+         Terminal=false\n",
+        command.join(" "),
+        DocumentUri::SCHEME
+    )
+}
+
+/// Quote an argument of the `Exec` key of a desktop entry.
+///
+/// See <https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html>.
+fn quote_exec_arg(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    if !arg.contains(|c: char| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c)) {
+        return arg;
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    // Backslashes are unescaped once when the desktop entry is read, before the quoting is
+    // interpreted.
+    quoted.replace('\\', "\\\\")
+}
+
+/// Register the handler in the registry of the current user.
+fn register_windows(exe: &Path, library: Option<&Path>) -> anyhow::Result<String> {
+    let key = format!("HKCU\\Software\\Classes\\{}", DocumentUri::SCHEME);
+    let mut command = OsString::from("\"");
+    command.push(exe);
+    command.push("\"");
+    if let Some(library) = library {
+        command.push(" --library \"");
+        command.push(library);
+        command.push("\"");
+    }
+    command.push(" open-uri \"%1\"");
+
+    let values: [(String, &[&str], OsString); 3] = [
+        (
+            key.clone(),
+            &["/ve"],
+            OsString::from("URL:burette document"),
+        ),
+        (key.clone(), &["/v", "URL Protocol"], OsString::new()),
+        (format!("{key}\\shell\\open\\command"), &["/ve"], command),
+    ];
+    for (key, name, data) in values {
+        let status = process::Command::new("reg")
+            .args(["add", &key])
+            .args(name)
+            .arg("/d")
+            .arg(data)
+            .arg("/f")
+            .status()
+            .context("Failed to run reg")?;
+        if !status.success() {
+            bail!("Failed to write registry key {} ({})", key, status);
+        }
+    }
+
+    Ok(format!(
+        "Registered {} as the handler for {}:// URIs",
+        key,
+        DocumentUri::SCHEME
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{desktop_entry, quote_exec_arg},
+        std::path::Path,
+    };
+
+    #[test]
+    fn quote_exec_args() {
+        assert_eq!(quote_exec_arg("/usr/bin/burette"), "/usr/bin/burette");
+        assert_eq!(quote_exec_arg("100%"), "100%%");
+        assert_eq!(quote_exec_arg("/home/me/My Books"), "\"/home/me/My Books\"");
+        assert_eq!(quote_exec_arg("a \"b\""), "\"a \\\\\"b\\\\\"\"");
+    }
+
+    #[test]
+    fn desktop_entry_exec() {
+        let entry = desktop_entry(
+            Path::new("/usr/bin/burette"),
+            Some(Path::new("/home/me/My Books")),
+        );
+        assert!(entry.lines().any(
+            |line| line == "Exec=/usr/bin/burette --library \"/home/me/My Books\" open-uri %u"
+        ));
+        assert!(entry
+            .lines()
+            .any(|line| line == "MimeType=x-scheme-handler/burette;"));
+    }
+}
@@ -0,0 +1,123 @@
+//! User configuration.
+//!
+//! The configuration is stored as JSON in `$XDG_CONFIG_HOME/burette/config.json`, or in
+//! `$HOME/.config/burette/config.json` (`%APPDATA%\burette\config.json` on Windows) if
+//! `XDG_CONFIG_HOME` is not set. All settings are optional, and a missing configuration file is
+//! equivalent to an empty one.
+
+use {
+    anyhow::Context,
+    serde::{Deserialize, Serialize},
+    std::{collections::BTreeMap, env, fs, io, path::PathBuf},
+};
+
+/// User configuration of burette.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Template for the names of retrieved documents.
+    ///
+    /// See [`crate::IndexEntry::file_name_from_template()`] for the syntax.
+    #[serde(default)]
+    pub file_name_template: Option<String>,
+    /// Devices documents can be sent to, by name.
+    #[serde(default)]
+    pub devices: BTreeMap<String, DeviceProfile>,
+    /// Command used to open documents, as a program followed by its arguments.
+    ///
+    /// The placeholder `{path}` in the arguments is replaced by the path of the document. If this
+    /// is `None`, the default application of the system is used.
+    #[serde(default)]
+    pub open_command: Option<Vec<String>>,
+    /// Command used to write the metadata of documents into retrieved files.
+    ///
+    /// See [`crate::RetrieveOptions::metadata_command`] for the syntax.
+    #[serde(default)]
+    pub metadata_command: Option<Vec<String>>,
+    /// Command used to check whether documents conform to PDF/A, e.g. veraPDF.
+    ///
+    /// See [`crate::Library::check_preservation()`]. If this is `None`, a heuristic is used.
+    #[serde(default)]
+    pub pdfa_validator: Option<Vec<String>>,
+    /// Command used to convert documents to PDF/A, e.g. Ghostscript.
+    ///
+    /// See [`crate::Library::convert_to_pdfa()`].
+    #[serde(default)]
+    pub pdfa_convert: Option<Vec<String>>,
+}
+
+/// Settings for sending documents to a device, e.g. an e-reader.
+///
+/// See [`crate::Library::send_document()`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceProfile {
+    /// Directory documents are copied to, e.g. the directory the device is mounted at.
+    pub path: PathBuf,
+    /// File extension of the format the device prefers, e.g. `epub` or `mobi`.
+    ///
+    /// Documents in other formats are converted with [`DeviceProfile::convert`] if it is set, and
+    /// sent unchanged otherwise.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Template for the names of documents on the device.
+    ///
+    /// See [`crate::IndexEntry::file_name_from_template()`] for the syntax. If this is `None`,
+    /// [`Config::file_name_template`] is used.
+    #[serde(default)]
+    pub file_name_template: Option<String>,
+    /// Command used to convert documents to the preferred format, as a program followed by its
+    /// arguments.
+    ///
+    /// The placeholders `{input}` and `{output}` in the arguments are replaced by the path of the
+    /// document to convert and the path the converted document must be written to.
+    #[serde(default)]
+    pub convert: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Load the configuration from the default location.
+    ///
+    /// If there is no configuration file, the default configuration is returned.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the location of the configuration file cannot be determined or if
+    /// the configuration file cannot be read or contains invalid data.
+    pub fn load() -> anyhow::Result<Self> {
+        let path = config_path()?;
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => {
+                return Err(error).context(format!(
+                    "Failed to read configuration file at {}",
+                    path.display()
+                ))
+            }
+        };
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid configuration file at {}", path.display()))
+    }
+}
+
+/// Return the location of the configuration file.
+///
+/// The configuration directory is `$XDG_CONFIG_HOME` if it is set. Otherwise, it is `%APPDATA%`
+/// on Windows and `~/.config` on all other systems.
+fn config_path() -> anyhow::Result<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None if cfg!(windows) => match env::var_os("APPDATA").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => crate::home_dir()
+                .context("Failed to determine configuration directory")?
+                .join("AppData")
+                .join("Roaming"),
+        },
+        None => crate::home_dir()
+            .context("Failed to determine configuration directory")?
+            .join(".config"),
+    };
+    Ok(config_dir.join("burette").join("config.json"))
+}
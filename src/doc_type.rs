@@ -0,0 +1,96 @@
+use {
+    anyhow::bail,
+    serde::{de::Error, Deserialize, Serialize},
+    std::{
+        fmt::{self, Display, Formatter},
+        str::FromStr,
+    },
+};
+
+/// Types of documents.
+///
+/// The type of a document determines which metadata is relevant for it, e.g. only articles are
+/// published in a journal.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DocType {
+    /// A book.
+    Book,
+    /// An article published in a journal or conference proceedings.
+    Article,
+    /// A thesis, e.g. a doctoral or master's thesis.
+    Thesis,
+    /// A technical report.
+    Report,
+    /// A standard or specification.
+    Standard,
+}
+
+impl DocType {
+    /// All document types.
+    pub const ALL: [Self; 5] = [
+        Self::Book,
+        Self::Article,
+        Self::Thesis,
+        Self::Report,
+        Self::Standard,
+    ];
+
+    /// Get the name of this document type.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Book => "book",
+            Self::Article => "article",
+            Self::Thesis => "thesis",
+            Self::Report => "report",
+            Self::Standard => "standard",
+        }
+    }
+}
+
+impl Display for DocType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(self.name(), f)
+    }
+}
+
+impl FromStr for DocType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL
+            .into_iter()
+            .find(|doc_type| doc_type.name().eq_ignore_ascii_case(s))
+        {
+            Some(doc_type) => Ok(doc_type),
+            None => bail!("Unknown document type: {}", s),
+        }
+    }
+}
+
+impl Serialize for DocType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for DocType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DocType;
+
+    #[test]
+    fn parse_display() {
+        for doc_type in DocType::ALL {
+            assert_eq!(doc_type.to_string().parse::<DocType>().ok(), Some(doc_type));
+        }
+        assert_eq!("Article".parse::<DocType>().ok(), Some(DocType::Article));
+        assert!("paper".parse::<DocType>().is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! C interface for embedding burette in applications written in other languages.
+//!
+//! The interface is declared in `include/burette.h`. To build a shared library, run
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+//!
+//! All strings are UTF-8 and NUL-terminated. Strings returned by burette must be freed with
+//! [`burette_string_free()`]. Functions that fail return `NULL` or `-1`, and
+//! [`burette_last_error()`] describes what went wrong.
+
+use {
+    crate::{DocMetadata, Library, RetrieveOptions},
+    anyhow::{bail, Context},
+    std::{
+        cell::RefCell,
+        ffi::{c_char, c_int, CStr, CString},
+        path::Path,
+        ptr,
+    },
+};
+
+thread_local! {
+    /// The error of the last call on this thread that failed.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Run `f` and return its result, or record its error and return `on_error`.
+fn run<T>(on_error: T, f: impl FnOnce() -> anyhow::Result<T>) -> T {
+    f().unwrap_or_else(|error| {
+        // NUL bytes cannot be part of a C string, so they are dropped from the message.
+        let message = format!("{error:#}").replace('\0', "");
+        let message = CString::new(message).unwrap_or_default();
+        LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+        on_error
+    })
+}
+
+/// Return the string at `s`, where `name` is the name of the argument for error messages.
+///
+/// # Safety
+///
+/// `s` must be `NULL` or point to a NUL-terminated string that stays valid for `'a`.
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> anyhow::Result<&'a str> {
+    if s.is_null() {
+        bail!("{name} is NULL");
+    }
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .with_context(|| format!("{name} is not valid UTF-8"))
+}
+
+/// Return the library at `library`.
+///
+/// # Safety
+///
+/// `library` must be `NULL` or a pointer returned by [`burette_library_open()`] that has not
+/// been freed.
+unsafe fn library_arg<'a>(library: *const Library) -> anyhow::Result<&'a Library> {
+    unsafe { library.as_ref() }.context("library is NULL")
+}
+
+/// Return `s` as a C string owned by the caller.
+fn into_c_string(s: String) -> anyhow::Result<*mut c_char> {
+    Ok(CString::new(s)
+        .context("String contains a NUL byte")?
+        .into_raw())
+}
+
+/// Return a description of the last error on the calling thread, or `NULL` if no call has
+/// failed yet.
+///
+/// The string is owned by burette. It stays valid until the next call that fails on the same
+/// thread.
+#[no_mangle]
+pub extern "C" fn burette_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Free a string returned by burette. Does nothing if `s` is `NULL`.
+///
+/// # Safety
+///
+/// `s` must be `NULL` or a string returned by burette that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn burette_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Open the library at `path`.
+///
+/// Returns `NULL` on error. The library must be freed with [`burette_library_free()`].
+///
+/// # Safety
+///
+/// `path` must be `NULL` or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn burette_library_open(path: *const c_char) -> *mut Library {
+    run(ptr::null_mut(), || {
+        let path = unsafe { str_arg(path, "path") }?;
+        let library = Library::open(path)?;
+        Ok(Box::into_raw(Box::new(library)))
+    })
+}
+
+/// Free a library opened with [`burette_library_open()`]. Does nothing if `library` is `NULL`.
+///
+/// # Safety
+///
+/// `library` must be `NULL` or a pointer returned by [`burette_library_open()`] that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn burette_library_free(library: *mut Library) {
+    if !library.is_null() {
+        drop(unsafe { Box::from_raw(library) });
+    }
+}
+
+/// Return all documents in the library as a JSON array, in the format of the entries of the index
+/// file.
+///
+/// Returns `NULL` on error. The string must be freed with [`burette_string_free()`].
+///
+/// # Safety
+///
+/// `library` must be `NULL` or a pointer returned by [`burette_library_open()`] that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn burette_library_list(library: *const Library) -> *mut c_char {
+    run(ptr::null_mut(), || {
+        let library = unsafe { library_arg(library) }?;
+        let documents: Vec<_> = library.documents()?.collect();
+        let json = serde_json::to_string(&documents).context("Failed to serialize documents")?;
+        into_c_string(json)
+    })
+}
+
+/// Add the file at `path` to the library.
+///
+/// `metadata` is a JSON object with the metadata of the document, with the same fields as the
+/// entries returned by [`burette_library_list()`], for example:
+///
+/// ```json
+/// {"title": "Faust", "authors": ["Goethe"], "isbns": [], "file_format": "application/epub+zip",
+///  "doi": null}
+/// ```
+///
+/// Returns `0` on success and `-1` on error.
+///
+/// # Safety
+///
+/// `library` must be `NULL` or a pointer returned by [`burette_library_open()`] that has not
+/// been freed. `path` and `metadata` must be `NULL` or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn burette_library_add(
+    library: *const Library,
+    path: *const c_char,
+    metadata: *const c_char,
+) -> c_int {
+    run(-1, || {
+        let library = unsafe { library_arg(library) }?;
+        let path = unsafe { str_arg(path, "path") }?;
+        let metadata = unsafe { str_arg(metadata, "metadata") }?;
+        let metadata: DocMetadata =
+            serde_json::from_str(metadata).context("Invalid document metadata")?;
+        library.add_document(path, metadata)?;
+        Ok(0)
+    })
+}
+
+/// Copy the document identified by `identifier` out of the library to `out_path`, and return
+/// the path of the copy.
+///
+/// `identifier` can be anything `burette get` accepts, e.g. a hash prefix or an ISBN. If
+/// `out_path` is `NULL`, the document is copied to the current directory under its default file
+/// name. Returns `NULL` on error. The string must be freed with [`burette_string_free()`].
+///
+/// # Safety
+///
+/// `library` must be `NULL` or a pointer returned by [`burette_library_open()`] that has not
+/// been freed. `identifier` and `out_path` must be `NULL` or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn burette_library_get(
+    library: *const Library,
+    identifier: *const c_char,
+    out_path: *const c_char,
+) -> *mut c_char {
+    run(ptr::null_mut(), || {
+        let library = unsafe { library_arg(library) }?;
+        let identifier = unsafe { str_arg(identifier, "identifier") }?;
+        let out_path = if out_path.is_null() {
+            None
+        } else {
+            Some(Path::new(unsafe { str_arg(out_path, "out_path") }?))
+        };
+        let retrieved =
+            library.retrieve_document(identifier, out_path, &RetrieveOptions::default())?;
+        // Recording the access is best-effort, e.g. the library may be read-only.
+        let _ = library.record_access(&[*retrieved.entry().hash()]);
+        let path = retrieved
+            .path()
+            .to_owned()
+            .into_os_string()
+            .into_string()
+            .ok()
+            .context("Path of the retrieved document is not valid UTF-8")?;
+        into_c_string(path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            burette_last_error, burette_library_add, burette_library_free, burette_library_get,
+            burette_library_list, burette_library_open, burette_string_free,
+        },
+        crate::{
+            testing::{self, TempLibrary},
+            FileFormat,
+        },
+        std::{
+            ffi::{CStr, CString},
+            fs, ptr,
+        },
+    };
+
+    #[test]
+    fn open_add_list_get() {
+        let temp = TempLibrary::new().expect("Failed to create library");
+        let path = CString::new(temp.path().to_str().expect("UTF-8 path")).expect("No NUL");
+        let document = temp.path().join("faust.epub");
+        fs::write(
+            &document,
+            testing::synthetic_document("Faust", FileFormat::Epub),
+        )
+        .expect("Failed to write document");
+        let document = CString::new(document.to_str().expect("UTF-8 path")).expect("No NUL");
+        let metadata = CString::new(
+            r#"{"title": "Faust", "authors": ["Goethe"], "isbns": [], "file_format": "application/epub+zip", "doi": null}"#,
+        )
+        .expect("No NUL");
+        let out_path = temp.path().join("out.epub");
+        let out_path = CString::new(out_path.to_str().expect("UTF-8 path")).expect("No NUL");
+        let identifier = CString::new("Faust").expect("No NUL");
+
+        unsafe {
+            let library = burette_library_open(path.as_ptr());
+            assert!(!library.is_null());
+            assert_eq!(
+                burette_library_add(library, document.as_ptr(), metadata.as_ptr()),
+                0
+            );
+            assert_eq!(
+                burette_library_add(library, document.as_ptr(), c"{}".as_ptr()),
+                -1
+            );
+            let error = CStr::from_ptr(burette_last_error());
+            assert!(error
+                .to_str()
+                .expect("UTF-8 error")
+                .starts_with("Invalid document metadata"));
+
+            let list = burette_library_list(library);
+            let json = CStr::from_ptr(list).to_str().expect("UTF-8 JSON");
+            assert!(json.contains(r#""title":"Faust""#));
+            burette_string_free(list);
+
+            assert!(burette_library_get(library, identifier.as_ptr(), out_path.as_ptr()).is_null());
+            let hash = temp
+                .documents()
+                .expect("Failed to read index")
+                .next()
+                .expect("Document was added")
+                .hash()
+                .to_string();
+            let hash = CString::new(hash).expect("No NUL");
+            let retrieved = burette_library_get(library, hash.as_ptr(), out_path.as_ptr());
+            assert!(!retrieved.is_null());
+            assert_eq!(CStr::from_ptr(retrieved), out_path.as_c_str());
+            burette_string_free(retrieved);
+
+            burette_library_free(library);
+            assert!(burette_library_open(ptr::null()).is_null());
+        }
+    }
+}
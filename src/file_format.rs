@@ -3,6 +3,7 @@ use {
     serde::{de::Error, Deserialize, Serialize},
     std::{
         fmt::{self, Display, Formatter},
+        fs,
         path::Path,
         str::FromStr,
     },
@@ -49,30 +50,80 @@ impl FileFormat {
         }
     }
 
+    /// Parse a file extension (e.g. `pdf`, case-insensitive) into a `FileFormat`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the extension is not recognized.
+    pub fn from_extension(extension: &str) -> anyhow::Result<Self> {
+        [Self::Epub, Self::Pdf]
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+            .ok_or_else(|| anyhow!("Unknown file format: {} (expected epub or pdf)", extension))
+    }
+
     /// Determines the file format from a file.
     ///
+    /// Files that are not recognized by their signature are sniffed further, to accept slightly
+    /// malformed files: PDF files may have up to 1024 bytes of garbage before the `%PDF-` header,
+    /// as most PDF readers accept, and ZIP files are recognized as EPUB files if they contain the
+    /// EPUB MIME type or an OCF container (`META-INF/container.xml`) anywhere, not only in a
+    /// `mimetype` entry at the start of the archive.
+    ///
     /// # Errors
     ///
     /// Returns an error if the file format cannot be determined due to IO errors or if the file
     /// format is not recognized.
     pub fn from_path<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
-        let format = file_format::FileFormat::from_file(&path).with_context(|| {
-            format!(
-                "Failed to determine file format for {}",
-                path.as_ref().display()
-            )
-        })?;
+        let path = path.as_ref();
+        let format = file_format::FileFormat::from_file(path)
+            .with_context(|| format!("Failed to determine file format for {}", path.display()))?;
         Ok(match format {
             file_format::FileFormat::ElectronicPublication => Self::Epub,
             file_format::FileFormat::PortableDocumentFormat => Self::Pdf,
-            _ => bail!(
-                "Unsupported file format: {}",
-                format.short_name().unwrap_or(format.name())
-            ),
+            _ => {
+                let contents = fs::read(path).with_context(|| {
+                    format!("Failed to determine file format for {}", path.display())
+                })?;
+                match sniff(&contents) {
+                    Some(format) => format,
+                    None => bail!(
+                        "Unsupported file format: {}",
+                        format.short_name().unwrap_or(format.name())
+                    ),
+                }
+            }
         })
     }
 }
 
+/// Maximum offset of the `%PDF-` header in PDF files.
+const PDF_HEADER_MAX_OFFSET: usize = 1024;
+
+/// Return true if `haystack` contains `needle`.
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+/// Determine the file format of `contents` for files that are not recognized by their signature.
+///
+/// See [`FileFormat::from_path()`].
+fn sniff(contents: &[u8]) -> Option<FileFormat> {
+    let head = &contents[..contents.len().min(PDF_HEADER_MAX_OFFSET + b"%PDF-".len())];
+    if contains(head, b"%PDF-") {
+        return Some(FileFormat::Pdf);
+    }
+    if contents.starts_with(b"PK\x03\x04")
+        && (contains(contents, b"application/epub+zip")
+            || contains(contents, b"META-INF/container.xml"))
+    {
+        return Some(FileFormat::Epub);
+    }
+    None
+}
+
 impl Display for FileFormat {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         Display::fmt(self.mime_type(), f)
@@ -99,3 +150,39 @@ impl<'de> Deserialize<'de> for FileFormat {
         Self::from_mime_type(&s).map_err(Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{sniff, FileFormat};
+
+    #[test]
+    fn parse_extension() {
+        assert_eq!(
+            FileFormat::from_extension("pdf").ok(),
+            Some(FileFormat::Pdf)
+        );
+        assert_eq!(
+            FileFormat::from_extension("EPUB").ok(),
+            Some(FileFormat::Epub)
+        );
+        assert!(FileFormat::from_extension("mobi").is_err());
+    }
+
+    #[test]
+    fn sniff_pdf_with_leading_garbage() {
+        let mut contents = vec![b' '; 1000];
+        contents.extend_from_slice(b"%PDF-1.4\n%%EOF\n");
+        assert_eq!(sniff(&contents), Some(FileFormat::Pdf));
+
+        let mut contents = vec![b' '; 2000];
+        contents.extend_from_slice(b"%PDF-1.4\n%%EOF\n");
+        assert_eq!(sniff(&contents), None);
+    }
+
+    #[test]
+    fn sniff_epub_without_leading_mimetype() {
+        let epub = b"PK\x03\x04 ... META-INF/container.xml ... PK\x05\x06";
+        assert_eq!(sniff(epub), Some(FileFormat::Epub));
+        assert_eq!(sniff(b"PK\x03\x04 ... word/document.xml ..."), None);
+    }
+}
@@ -0,0 +1,417 @@
+//! Filters for selecting documents by their metadata.
+
+use {
+    crate::{normalize_title, DocType, IndexEntry, Isbn13},
+    anyhow::bail,
+    std::{
+        fmt::{self, Display, Formatter},
+        mem,
+        str::FromStr,
+    },
+};
+
+/// A filter that selects documents by their metadata.
+///
+/// A filter consists of terms separated by whitespace. A document matches the filter if it
+/// matches all of its terms. The following terms are supported:
+/// - `title:<text>`: the title contains `<text>`
+/// - `author:<text>`: one of the authors contains `<text>`
+/// - `isbn:<isbn>`: the document has the ISBN `<isbn>`
+/// - `doi:<doi>`: the document has the DOI `<doi>`
+/// - `type:<type>`: the document is of type `<type>` (see [`DocType`])
+/// - `rights:<rights>`: the rights of the document are `<rights>`, ignoring case
+/// - `format:<extension>`: the file format of the document has the extension `<extension>`
+/// - `hash:<prefix>`: the hash of the document starts with `<prefix>`
+/// - `<text>`: the title or one of the authors contains `<text>`
+///
+/// Text is compared after normalization (see [`crate::normalize_title()`]), so case and
+/// punctuation do not matter. Values containing whitespace can be enclosed in double quotes, e.g.
+/// `author:"herman melville"`.
+///
+/// Terms can be combined with the operators `AND`, `OR` and `NOT` and grouped with parentheses,
+/// e.g. `title:"moby dick" AND NOT (format:pdf OR author:anonymous)`. `NOT` binds more tightly
+/// than `AND`, which binds more tightly than `OR`. Terms without an operator between them must
+/// all match, as if they were separated by `AND`. Operators must be written in uppercase, quote
+/// them (e.g. `"OR"`) to search for the words instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+/// A boolean combination of [`Term`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Term(Term),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+/// A single term of a [`Filter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Title(String),
+    Author(String),
+    Isbn(Isbn13),
+    Doi(String),
+    Type(DocType),
+    Rights(String),
+    Format(String),
+    Hash(String),
+    Any(String),
+}
+
+impl Filter {
+    /// Return true if the document matches the filter.
+    #[must_use]
+    pub fn matches(&self, entry: &IndexEntry) -> bool {
+        self.expr.matches(entry)
+    }
+}
+
+impl Expr {
+    /// Return true if the document matches the expression.
+    fn matches(&self, entry: &IndexEntry) -> bool {
+        match self {
+            Expr::Term(term) => term.matches(entry),
+            Expr::Not(expr) => !expr.matches(entry),
+            Expr::And(exprs) => exprs.iter().all(|expr| expr.matches(entry)),
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr.matches(entry)),
+        }
+    }
+}
+
+impl Term {
+    /// Return true if the document matches the term.
+    fn matches(&self, entry: &IndexEntry) -> bool {
+        let title_contains = |text: &str| normalize_title(entry.title()).contains(text);
+        let author_contains = |text: &str| {
+            entry
+                .authors()
+                .any(|author| normalize_title(author).contains(text))
+        };
+        match self {
+            Term::Title(text) => title_contains(text),
+            Term::Author(text) => author_contains(text),
+            Term::Isbn(isbn) => entry.isbns().any(|entry_isbn| entry_isbn == isbn),
+            Term::Doi(doi) => entry.doi() == Some(doi.as_str()),
+            Term::Type(doc_type) => entry.doc_type() == Some(*doc_type),
+            Term::Rights(rights) => entry
+                .rights()
+                .is_some_and(|entry_rights| entry_rights.to_lowercase() == *rights),
+            Term::Format(extension) => entry.file_format().extension() == extension,
+            Term::Hash(prefix) => entry.hash().starts_with(prefix),
+            Term::Any(text) => title_contains(text) || author_contains(text),
+        }
+    }
+}
+
+impl FromStr for Term {
+    type Err = anyhow::Error;
+    fn from_str(term: &str) -> anyhow::Result<Self> {
+        let Some((key, value)) = term.split_once(':') else {
+            return Ok(Term::Any(normalize_title(term)));
+        };
+        if value.is_empty() {
+            bail!("Missing value in filter term: {}", term);
+        }
+        Ok(match key {
+            "title" => Term::Title(normalize_title(value)),
+            "author" => Term::Author(normalize_title(value)),
+            "isbn" => Term::Isbn(value.parse()?),
+            "doi" => Term::Doi(value.to_owned()),
+            "type" => Term::Type(value.parse()?),
+            "rights" => Term::Rights(value.to_lowercase()),
+            "format" => Term::Format(value.to_lowercase()),
+            "hash" => Term::Hash(value.to_owned()),
+            _ => bail!("Unknown filter key: {}", key),
+        })
+    }
+}
+
+/// A token of a filter, see [`tokenize()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Term(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+/// Split a filter into tokens.
+///
+/// Terms are separated by whitespace or parentheses outside of double quotes. Operators are only
+/// recognized if they are not quoted.
+fn tokenize(s: &str) -> anyhow::Result<Vec<Token>> {
+    fn finish(tokens: &mut Vec<Token>, term: &mut String, quoted: &mut bool) {
+        if !term.is_empty() || *quoted {
+            let term = mem::take(term);
+            tokens.push(match term.as_str() {
+                "AND" if !*quoted => Token::And,
+                "OR" if !*quoted => Token::Or,
+                "NOT" if !*quoted => Token::Not,
+                _ => Token::Term(term),
+            });
+        }
+        *quoted = false;
+    }
+
+    let mut tokens = Vec::new();
+    let mut term = String::new();
+    let mut in_quotes = false;
+    // Whether the current term contains quotes
+    let mut quoted = false;
+    for c in s.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            c if in_quotes => term.push(c),
+            '(' | ')' => {
+                finish(&mut tokens, &mut term, &mut quoted);
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            c if c.is_whitespace() => finish(&mut tokens, &mut term, &mut quoted),
+            c => term.push(c),
+        }
+    }
+    if in_quotes {
+        bail!("Unterminated quote in filter");
+    }
+    finish(&mut tokens, &mut term, &mut quoted);
+    Ok(tokens)
+}
+
+/// Parser for the tokens of a filter.
+///
+/// The grammar is as follows, with terms separated by nothing but whitespace being combined as if
+/// they were separated by `AND`:
+/// ```text
+/// or   = and ("OR" and)*
+/// and  = not ("AND"? not)*
+/// not  = "NOT" not | "(" or ")" | term
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    /// Return the next token without consuming it.
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Consume the next token if it is `token` and return whether it was.
+    fn eat(&mut self, token: &Token) -> bool {
+        let matches = self.peek() == Some(token);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    /// Parse terms combined with `OR`.
+    fn parse_or(&mut self) -> anyhow::Result<Expr> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.eat(&Token::Or) {
+            exprs.push(self.parse_and()?);
+        }
+        Ok(combine(exprs, Expr::Or))
+    }
+
+    /// Parse terms combined with `AND`.
+    fn parse_and(&mut self) -> anyhow::Result<Expr> {
+        let mut exprs = vec![self.parse_not()?];
+        // `AND` is optional between terms.
+        while self.eat(&Token::And)
+            || matches!(self.peek(), Some(Token::Term(_) | Token::Not | Token::Open))
+        {
+            exprs.push(self.parse_not()?);
+        }
+        Ok(combine(exprs, Expr::And))
+    }
+
+    /// Parse a single term, a negated term or a parenthesized expression.
+    fn parse_not(&mut self) -> anyhow::Result<Expr> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        match token {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.parse_not()?))),
+            Some(Token::Open) => {
+                let expr = self.parse_or()?;
+                if !self.eat(&Token::Close) {
+                    bail!("Missing closing parenthesis in filter");
+                }
+                Ok(expr)
+            }
+            Some(Token::Term(term)) => Ok(Expr::Term(term.parse()?)),
+            Some(Token::Close) => bail!("Unexpected closing parenthesis in filter"),
+            Some(Token::And | Token::Or) => bail!("Missing term before operator in filter"),
+            None => bail!("Missing term at the end of filter"),
+        }
+    }
+}
+
+/// Combine `exprs` with `combinator`, unless there is only a single expression.
+fn combine(mut exprs: Vec<Expr>, combinator: fn(Vec<Expr>) -> Expr) -> Expr {
+    if exprs.len() == 1 {
+        exprs.remove(0)
+    } else {
+        combinator(exprs)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let tokens = tokenize(s)?;
+        if tokens.is_empty() {
+            bail!("Filter cannot be empty");
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let expr = parser.parse_or()?;
+        if parser.peek().is_some() {
+            bail!("Unexpected closing parenthesis in filter");
+        }
+        Ok(Self {
+            source: s.to_owned(),
+            expr,
+        })
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{tokenize, Expr, Filter, Term, Token},
+        proptest::{prop_assert_eq, proptest},
+    };
+
+    proptest! {
+        #[test]
+        fn parse_arbitrary_string(s in "\\PC*") {
+            let _ = s.parse::<Filter>();
+        }
+
+        #[test]
+        fn parse_display(s in "((title|author|rights|format|hash):)?[a-zA-Z0-9\"-]{1,10}( [a-z]{1,10})*") {
+            if let Ok(filter) = s.parse::<Filter>() {
+                prop_assert_eq!(filter.to_string().parse::<Filter>().ok(), Some(filter));
+            }
+        }
+
+        #[test]
+        fn parse_arbitrary_operators(s in "((NOT |\\(|\\))*[a-z]{1,5}\\)* ?(AND |OR )?){1,5}") {
+            let _ = s.parse::<Filter>();
+        }
+    }
+
+    #[test]
+    fn split_quoted() {
+        assert_eq!(
+            tokenize(r#"author:"herman melville"  whale"#).expect("Valid filter"),
+            vec![
+                Token::Term("author:herman melville".to_owned()),
+                Token::Term("whale".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn split_unterminated_quote() {
+        assert!(tokenize(r#"author:"herman"#).is_err());
+    }
+
+    #[test]
+    fn split_operators() {
+        assert_eq!(
+            tokenize(r#"NOT (a OR "OR")AND b"#).expect("Valid filter"),
+            vec![
+                Token::Not,
+                Token::Open,
+                Token::Term("a".to_owned()),
+                Token::Or,
+                Token::Term("OR".to_owned()),
+                Token::Close,
+                Token::And,
+                Token::Term("b".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_terms() {
+        let filter: Filter = "title:Moby-Dick format:PDF rights:CC-BY whale"
+            .parse()
+            .expect("Valid filter");
+        assert_eq!(
+            filter.expr,
+            Expr::And(vec![
+                Expr::Term(Term::Title("moby dick".to_owned())),
+                Expr::Term(Term::Format("pdf".to_owned())),
+                Expr::Term(Term::Rights("cc-by".to_owned())),
+                Expr::Term(Term::Any("whale".to_owned())),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_precedence() {
+        let filter: Filter = "whale OR NOT format:pdf title:dick"
+            .parse()
+            .expect("Valid filter");
+        assert_eq!(
+            filter.expr,
+            Expr::Or(vec![
+                Expr::Term(Term::Any("whale".to_owned())),
+                Expr::And(vec![
+                    Expr::Not(Box::new(Expr::Term(Term::Format("pdf".to_owned())))),
+                    Expr::Term(Term::Title("dick".to_owned())),
+                ]),
+            ])
+        );
+
+        let filter: Filter = "(whale OR fish) AND author:melville"
+            .parse()
+            .expect("Valid filter");
+        assert_eq!(
+            filter.expr,
+            Expr::And(vec![
+                Expr::Or(vec![
+                    Expr::Term(Term::Any("whale".to_owned())),
+                    Expr::Term(Term::Any("fish".to_owned())),
+                ]),
+                Expr::Term(Term::Author("melville".to_owned())),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("".parse::<Filter>().is_err());
+        assert!("year:1851".parse::<Filter>().is_err());
+        assert!("title:".parse::<Filter>().is_err());
+        assert!("isbn:123".parse::<Filter>().is_err());
+        assert!("type:paper".parse::<Filter>().is_err());
+        assert!("whale OR".parse::<Filter>().is_err());
+        assert!("AND whale".parse::<Filter>().is_err());
+        assert!("NOT".parse::<Filter>().is_err());
+        assert!("(whale".parse::<Filter>().is_err());
+        assert!("whale)".parse::<Filter>().is_err());
+        assert!("()".parse::<Filter>().is_err());
+    }
+}
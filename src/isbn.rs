@@ -59,9 +59,7 @@ impl FromStr for Isbn13 {
                 }
                 checksum += if count % 2 == 0 { d } else { d * 3 };
                 count += 1;
-            } else if c == '-' {
-                continue;
-            } else {
+            } else if c != '-' {
                 bail!("Invalid character in ISBN-13: '{}'", c);
             }
         }
@@ -89,7 +87,43 @@ impl Serialize for Isbn13 {
 
 #[cfg(test)]
 mod tests {
-    use {super::Isbn13, std::str::FromStr};
+    use {
+        super::Isbn13,
+        proptest::{collection::vec, prop_assert_eq, proptest},
+        std::str::FromStr,
+    };
+
+    proptest! {
+        #[test]
+        fn parse_arbitrary_string(s in "\\PC*") {
+            let _ = Isbn13::from_str(&s);
+        }
+
+        #[test]
+        fn parse_valid_isbn(digits in vec(0_u32..10, 12)) {
+            let checksum: u32 = digits
+                .iter()
+                .enumerate()
+                .map(|(i, digit)| if i % 2 == 0 { *digit } else { digit * 3 })
+                .sum();
+            let check_digit = (10 - checksum % 10) % 10;
+            let isbn: String = digits
+                .iter()
+                .chain([check_digit].iter())
+                .map(ToString::to_string)
+                .collect();
+            prop_assert_eq!(
+                Isbn13::from_str(&isbn).map(|isbn| isbn.to_string()).ok(),
+                Some(isbn.clone())
+            );
+
+            let hyphenated: String = isbn.chars().flat_map(|c| ['-', c]).collect();
+            prop_assert_eq!(
+                Isbn13::from_str(&hyphenated).map(|isbn| isbn.to_string()).ok(),
+                Some(isbn)
+            );
+        }
+    }
 
     #[test]
     fn valid_isbn13_hyphens_correct() {
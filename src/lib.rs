@@ -19,22 +19,56 @@
 #![allow(clippy::too_many_lines, reason = "This is not bad in my opinion")]
 
 use {
-    anyhow::{anyhow, Context},
+    anyhow::{anyhow, bail, Context},
     std::{env, path::PathBuf},
 };
 
 pub mod cli;
+pub mod md5;
 pub mod sha256;
 
+mod bibtex;
+pub use bibtex::{BibEntry, FileLink};
+
+mod config;
+pub use config::{Config, DeviceProfile};
+
 mod library;
 pub use library::*;
 
+mod doc_type;
+pub use doc_type::DocType;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 mod file_format;
 pub use file_format::FileFormat;
 
+mod filter;
+pub use filter::Filter;
+
 mod isbn;
 pub use isbn::Isbn13;
 
+mod opf;
+pub use opf::OpfPackage;
+
+mod output_template;
+pub use output_template::OutputTemplate;
+
+mod reading_data;
+pub use reading_data::{Annotation, AnnotationKind, ReadingData, ReadingStatus};
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+mod timestamp;
+pub use timestamp::Timestamp;
+
+mod uri;
+pub use uri::{DocumentUri, LinkStyle};
+
 /// Format a string into a format suitable for use as a file name.
 #[must_use]
 pub fn format_as_file_name(s: &str) -> String {
@@ -58,8 +92,42 @@ pub fn format_as_file_name(s: &str) -> String {
     result
 }
 
+/// Normalize a title (or author name) for comparison.
+///
+/// The result is lowercase and consists of the alphanumeric words of `s` separated by single
+/// spaces. Two titles that only differ in case, punctuation or whitespace normalize to the same
+/// string.
+#[must_use]
+pub fn normalize_title(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for word in s
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        result.extend(word.chars().flat_map(char::to_lowercase));
+    }
+    result
+}
+
 /// Return the home directory of the current user.
+///
+/// This is `$HOME` on Unix. On Windows, it is `%USERPROFILE%`, or `%HOMEDRIVE%%HOMEPATH%` if
+/// `USERPROFILE` is not set.
 fn home_dir() -> anyhow::Result<PathBuf> {
+    if cfg!(windows) {
+        if let Some(profile) = env::var_os("USERPROFILE").filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(profile));
+        }
+        if let (Some(mut drive), Some(path)) = (env::var_os("HOMEDRIVE"), env::var_os("HOMEPATH")) {
+            drive.push(path);
+            return Ok(PathBuf::from(drive));
+        }
+        bail!("Failed to read USERPROFILE environment variable");
+    }
+
     let home_dir =
         env::var_os("HOME").ok_or_else(|| anyhow!("Failed to read HOME environment variable"))?;
     Ok(PathBuf::from(home_dir))
@@ -77,3 +145,29 @@ pub fn default_library_dir() -> anyhow::Result<PathBuf> {
         .context("Failed to determine library directory")?
         .join(".book-store"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_title;
+
+    #[test]
+    fn normalize_title_punctuation_and_case() {
+        assert_eq!(
+            normalize_title("Moby Dick; Or, The Whale"),
+            normalize_title("moby dick or the whale"),
+        );
+    }
+
+    #[test]
+    fn normalize_title_whitespace() {
+        assert_eq!(
+            normalize_title("  Faust:\tEine  Tragödie "),
+            "faust eine tragödie"
+        );
+    }
+
+    #[test]
+    fn normalize_title_different() {
+        assert_ne!(normalize_title("Faust I"), normalize_title("Faust II"));
+    }
+}
@@ -1,24 +1,116 @@
 use {
-    crate::{sha256, FileFormat, Isbn13},
+    crate::{
+        md5, sha256, Annotation, DocType, FileFormat, Filter, Isbn13, ReadingStatus, Timestamp,
+    },
     anyhow::{anyhow, bail, Context},
-    serde::{Deserialize, Serialize},
+    serde::{
+        de::{self, value::SeqAccessDeserializer, IgnoredAny, MapAccess, SeqAccess, Visitor},
+        ser::SerializeStruct,
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+    serde_json::Value,
     std::{
-        collections::HashSet,
+        collections::{HashMap, HashSet},
         env,
         ffi::{OsStr, OsString},
         fmt::{self, Display, Formatter},
         fs::{self, File, FileType},
-        io,
-        path::{Path, PathBuf},
+        io::{self, Write},
+        marker::PhantomData,
+        path::{self, Path, PathBuf},
+        sync::{mpsc::Sender, Mutex},
     },
 };
 
+mod alias;
+
+mod authors;
+
+mod batch;
+pub use batch::{BatchOperation, MetadataChanges};
+
+mod bulk_add;
+pub use bulk_add::AddOutcome;
+
+mod calibre;
+
+mod checksums;
+pub use checksums::{DigestAlgorithm, DigestMismatch};
+
+mod device;
+
+mod disk_space;
+
+mod event_log;
+pub use event_log::{ChangeEvent, Event, Operation};
+
+mod lint;
+pub use lint::{MetadataIssue, MetadataProblem};
+
+mod lookup_error;
+pub use lookup_error::LookupError;
+
+mod reading;
+pub use reading::{ImportedReadingData, ReadingImportResults};
+
+mod preservation;
+pub use preservation::{CheckMethod, PreservationStatus};
+
+mod restore;
+pub use restore::{RestoreFailure, RestoreResults};
+
+mod settings;
+pub use settings::{DuplicatePolicy, LibrarySettings};
+
+mod snapshot;
+pub use snapshot::SnapshotInfo;
+
+mod summary;
+pub use summary::DocumentSummary;
+
+mod undo;
+pub use undo::UndoRecord;
+
+mod viewer;
+
+mod volumes;
+
+mod wal;
+
+mod wanted;
+pub use wanted::{WantedEntry, WantedMetadata};
+
+mod watch;
+
+mod write_back;
+
 /// The location of the document store directory within the library directory.
 const DOCUMENT_STORE_DIR: &str = "documents";
 
 /// The location of the index file within the library directory.
 const INDEX_FILE: &str = "index.json";
 
+/// The version of the format of the index file written by this version of burette.
+///
+/// The version is increased when the format changes in a way that older versions of burette
+/// cannot read. Fields that are added to the index do not require a new version, since fields that
+/// are unknown to burette are ignored when reading the index.
+const INDEX_SCHEMA_VERSION: u32 = 1;
+
+/// The location of the index checksum file within the library directory.
+///
+/// The file contains the SHA-256 hash of the index file as written by burette. It is used to
+/// detect modifications of the index file by other programs, as well as truncated index files.
+const INDEX_CHECKSUM_FILE: &str = "index.json.sha256";
+
+/// The location of the optional settings file within the library directory.
+const SETTINGS_FILE: &str = "settings.json";
+
+/// The location of the trash directory within the library directory.
+///
+/// Documents that are replaced by a different file are moved here instead of being deleted.
+const TRASH_DIR: &str = "trash";
+
 /// The location of the version file within the library directory.
 const VERSION_FILE: &str = "burette_version";
 
@@ -32,14 +124,36 @@ const VERSION_FILE: &str = "burette_version";
 #[derive(Debug)]
 pub struct Library {
     path: PathBuf,
-    #[allow(
-        dead_code,
-        reason = "
-        For now, we don't use the version field.
-        However, it may be useful in the future if we want to implement backwards compatibility or
-        other features that depend on the version of the library."
-    )]
     version: String,
+    settings: LibrarySettings,
+    /// Senders for the receivers returned by [`Library::subscribe()`].
+    subscribers: Mutex<Vec<Sender<ChangeEvent>>>,
+    /// Changes of the last index update, pushed onto the undo log with the next event.
+    pending_changes: Mutex<Option<undo::IndexChanges>>,
+    /// Lock held while an index update is in progress, see [`Library::begin_index_update()`].
+    update_lock: Mutex<Option<File>>,
+    /// Problems that did not prevent operations from completing, see [`Library::take_warnings()`].
+    warnings: Mutex<Vec<anyhow::Error>>,
+}
+
+/// Return true if a library created by burette version `library_version` can be used by burette
+/// version `software_version`.
+///
+/// Versions are compatible if their major and minor versions are the same. Patch releases do not
+/// change the format of the library.
+fn is_compatible_version(library_version: &str, software_version: &str) -> bool {
+    /// Return the major and minor version of a version string such as `1.2.3`.
+    fn major_minor(version: &str) -> Option<(u64, u64)> {
+        let mut parts = version.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        Some((major, minor))
+    }
+
+    match (major_minor(library_version), major_minor(software_version)) {
+        (Some(library), Some(software)) => library == software,
+        _ => library_version == software_version,
+    }
 }
 
 impl Library {
@@ -53,6 +167,19 @@ impl Library {
         self.path.join(INDEX_FILE)
     }
 
+    /// Return the version of burette that created the library.
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Return the settings of the library, read from `settings.json` in the library directory
+    /// when the library was opened.
+    #[must_use]
+    pub fn settings(&self) -> &LibrarySettings {
+        &self.settings
+    }
+
     /// Create a new library at the specified path.
     ///
     /// # Errors
@@ -104,14 +231,21 @@ impl Library {
         Ok(Self {
             path: path.to_owned(),
             version: version.to_owned(),
+            settings: LibrarySettings::default(),
+            subscribers: Mutex::new(Vec::new()),
+            pending_changes: Mutex::new(None),
+            update_lock: Mutex::new(None),
+            warnings: Mutex::new(Vec::new()),
         })
     }
 
     /// Open an existing library at the specified path.
     ///
     /// This function validates that the version of the library is compatible with the software
-    /// version and that the index file is correctly formatted. If either of these checks fail, an
-    /// error is returned.
+    /// version, that the index file is correctly formatted and that the index file matches its
+    /// checksum, i.e. that it was not modified outside of burette. If any of these checks fail,
+    /// an error is returned. Versions are compatible if their major and minor versions are the
+    /// same, i.e. libraries can be shared between patch releases.
     ///
     /// Note that this function does fully validate that the library directory is intact. If the
     /// library directory is corrupted, this function may still succeed and subsequent operations
@@ -124,16 +258,38 @@ impl Library {
     /// - The library directory does not exist.
     /// - The version file is missing or cannot be read.
     /// - The index file is missing, cannot be read or contains invalid data.
+    /// - The index file does not match its checksum.
     /// - The version of the library is incompatible with the software version.
     pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
         // This is only a small wrapper around `open_impl` to provide a better error message.
-        Self::open_impl(path.as_ref()).context("Failed to open library")
+        Self::open_impl(path.as_ref(), true).context("Failed to open library")
+    }
+
+    /// Open an existing library at the specified path without checking its version.
+    ///
+    /// This works like [`Library::open()`], except that libraries created by incompatible
+    /// versions of burette and libraries with an index file that does not match its checksum can
+    /// be opened as well. This is meant for tools that only inspect the library, such as
+    /// [`Library::validate()`]. Use [`Library::version()`] to find out which version created the
+    /// library.
+    ///
+    /// Interrupted index updates are not completed, so that opening the library does not write to
+    /// it. They are reported by [`ValidationResults::pending_update()`] instead.
+    ///
+    /// Note that the index file must still be readable by this version of burette.
+    ///
+    /// # Errors
+    ///
+    /// See [`Library::open()`], except that incompatible versions and checksum mismatches are not
+    /// an error.
+    pub fn open_unchecked<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Self::open_impl(path.as_ref(), false).context("Failed to open library")
     }
 
-    /// Internal implementation of [`Library::open()`].
+    /// Internal implementation of [`Library::open()`] and [`Library::open_unchecked()`].
     ///
     /// See [`Library::open()`] for details.
-    fn open_impl(path: &Path) -> anyhow::Result<Self> {
+    fn open_impl(path: &Path, checked: bool) -> anyhow::Result<Self> {
         let exists = path.try_exists().with_context(|| {
             format!(
                 "Could not determine if library directory exists at {}",
@@ -155,7 +311,7 @@ impl Library {
             library_version.pop();
         }
         let software_version = env!("CARGO_PKG_VERSION");
-        if library_version != software_version {
+        if checked && !is_compatible_version(&library_version, software_version) {
             bail!(
                 "Document library version ({}) is incompatible with software version ({})",
                 library_version,
@@ -163,14 +319,42 @@ impl Library {
             );
         }
 
+        // Complete any index update that was interrupted before opening the index. Unchecked
+        // libraries are only inspected, and may have been written by a newer version of burette
+        // whose index fields would be lost when saving the index.
+        if checked {
+            wal::replay(path)?;
+        }
+
         let index_path = path.join(INDEX_FILE);
+        let checksum_status = index_checksum_status(&index_path)?;
         // We open the index file here to validate that it is correctly formatted.
-        // No need to keep the index around, as we only need to validate it once.
-        LibraryIndex::open(&index_path)?;
+        // No need to keep the index around, as we only need to validate it once. An index that
+        // matches its checksum was written by burette, so it does not need to be validated again.
+        // This matters for large libraries, where parsing the index takes a while.
+        if checksum_status != Some(true) {
+            LibraryIndex::open(&index_path)?;
+        }
+        if checked && checksum_status == Some(false) {
+            bail!(
+                "Library index at {} was modified outside of burette or is damaged. Run `burette \
+                validate` for details. If the modification was intentional, remove {} to accept \
+                it.",
+                index_path.display(),
+                index_checksum_path(&index_path).display()
+            );
+        }
+
+        let settings = LibrarySettings::load(&path.join(SETTINGS_FILE))?;
 
         Ok(Self {
             path: path.to_owned(),
             version: library_version,
+            settings,
+            subscribers: Mutex::new(Vec::new()),
+            pending_changes: Mutex::new(None),
+            update_lock: Mutex::new(None),
+            warnings: Mutex::new(Vec::new()),
         })
     }
 
@@ -191,44 +375,63 @@ impl Library {
         path: P,
         metadata: DocMetadata,
     ) -> anyhow::Result<()> {
+        self.add_document_checked(path, metadata, &AddOptions::default(), |_| Ok(true))
+            .map(|_| ())
+    }
+
+    /// Add a document to the library, asking for confirmation if similar documents exist.
+    ///
+    /// This works like [`Library::add_document()`], except that `confirm_similar` is called with
+    /// the likely duplicates of the document (see [`Library::similar_documents()`]) if there are
+    /// any. If `confirm_similar` returns false, the document is not added and `false` is returned.
+    ///
+    /// `confirm_similar` is only called after the document has been checked for exact duplicates,
+    /// so documents that cannot be added result in an error without asking for confirmation. If
+    /// [`LibrarySettings::duplicate_titles`] is not [`DuplicatePolicy::Ask`], it is not called at
+    /// all, and similar documents are either added or rejected.
+    ///
+    /// If [`AddOptions::move_file`] is set, the file at `path` is removed once the document has
+    /// been added. If [`AddOptions::compute_md5`] is set or MD5 is one of the configured
+    /// [`LibrarySettings::digests`], the MD5 hash of the file is recorded (see
+    /// [`IndexEntry::md5()`]).
+    ///
+    /// # Errors
+    ///
+    /// See [`Library::add_document()`]. In addition, an error is returned if the file does not
+    /// have the hash given by [`AddOptions::expected_sha256`], if `confirm_similar` returns an
+    /// error, if similar documents exist and the library settings reject them, or if the file at
+    /// `path` should be moved but cannot be removed.
+    pub fn add_document_checked<P, F>(
+        &self,
+        path: P,
+        metadata: DocMetadata,
+        options: &AddOptions,
+        confirm_similar: F,
+    ) -> anyhow::Result<bool>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&[&IndexEntry]) -> anyhow::Result<bool>,
+    {
         let doc_file = File::open(&path)
             .with_context(|| format!("Failed to open file at {}", path.as_ref().display()))?;
+        let file_size = file_size(&doc_file, path.as_ref())?;
+        let source_modified = modification_time(&doc_file);
         let hash = sha256::hash_reader(doc_file)?;
+        check_expected_hash(path.as_ref(), hash, options.expected_sha256)?;
+        let md5 = if options.compute_md5 || self.records_md5() {
+            let doc_file = File::open(&path)
+                .with_context(|| format!("Failed to open file at {}", path.as_ref().display()))?;
+            Some(md5::hash_reader(doc_file)?)
+        } else {
+            None
+        };
 
         let index_path = self.index_path();
         let mut index = LibraryIndex::open(&index_path)?;
 
-        // Check if the document is already in the library.
-        for doc in &index.documents {
-            if let Some(isbn) = doc
-                .metadata
-                .isbns
-                .iter()
-                .find(|isbn| metadata.isbns.contains(isbn))
-            {
-                bail!(
-                    "Document with ISBN {} already exists ({})",
-                    isbn,
-                    doc.hash.to_short_string()
-                );
-            }
-            if doc.hash == hash {
-                bail!(
-                    "Document is already in the library ({})",
-                    doc.hash.to_short_string()
-                );
-            }
-            if let (Some(doi), Some(new_doi)) =
-                (doc.metadata.doi.as_deref(), metadata.doi.as_deref())
-            {
-                if doi == new_doi {
-                    bail!(
-                        "Document with DOI {} already exists ({})",
-                        doi,
-                        doc.hash.to_short_string()
-                    );
-                }
-            }
+        index.check_duplicates(hash, &metadata)?;
+        if !self.check_similar(&index, &metadata, confirm_similar)? {
+            return Ok(false);
         }
 
         // Add the document to the library.
@@ -240,19 +443,20 @@ impl Library {
             )
         })?;
         let store_path = document_store_dir.join(hash.to_string());
-        fs::copy(&path, &store_path).with_context(|| {
-            format!(
-                "Failed to copy file from {} to document store at {}",
-                path.as_ref().display(),
-                store_path.display()
-            )
-        })?;
-        let index_entry = IndexEntry { hash, metadata };
+        let size_bytes = if options.move_file {
+            move_to_store(path.as_ref(), &store_path, hash, file_size)?
+        } else {
+            disk_space::check_available_space(&document_store_dir, file_size)?;
+            copy_to_store(path.as_ref(), &store_path)?
+        };
+        let mut index_entry = IndexEntry::added(hash, metadata, path.as_ref(), source_modified);
+        index_entry.size_bytes = Some(size_bytes);
+        index_entry.md5 = md5;
         index.documents.push(index_entry);
 
         // Save the updated index.
-        if let Err(error) = index.save(&index_path) {
-            // If we can't save the index, remove the document we just added.
+        if let Err(error) = self.begin_index_update(&index, &[], &[]) {
+            // If we can't record the update, remove the document we just added.
             // This is a best-effort approach to avoid having a document in the library without an
             // index entry.
             // We ignore any errors that occur when removing the document, as we want to propagate the
@@ -260,8 +464,51 @@ impl Library {
             let _ = fs::remove_file(&store_path);
             return Err(error);
         }
+        self.finish_index_update(&index)?;
 
-        Ok(())
+        self.log_event(Operation::Add, vec![hash]);
+        self.record_digests(&[hash])
+            .context("Document was added, but failed to record its digests")?;
+
+        // The original file is only removed once the document is safely in the library.
+        if options.move_file {
+            fs::remove_file(&path).with_context(|| {
+                format!(
+                    "Document was added, but failed to remove {}",
+                    path.as_ref().display()
+                )
+            })?;
+        }
+
+        Ok(true)
+    }
+
+    /// Check whether a document with `metadata` may be added although similar documents exist in
+    /// `index`, according to [`LibrarySettings::duplicate_titles`].
+    ///
+    /// Return false if `confirm_similar` was asked and declined.
+    fn check_similar<F>(
+        &self,
+        index: &LibraryIndex,
+        metadata: &DocMetadata,
+        confirm_similar: F,
+    ) -> anyhow::Result<bool>
+    where
+        F: FnOnce(&[&IndexEntry]) -> anyhow::Result<bool>,
+    {
+        let similar = index.similar_documents(metadata);
+        if similar.is_empty() {
+            return Ok(true);
+        }
+        match self.settings.duplicate_titles {
+            DuplicatePolicy::Ask => confirm_similar(&similar),
+            DuplicatePolicy::Allow => Ok(true),
+            DuplicatePolicy::Reject => bail!(
+                "Document is similar to {}: {}, and the library settings reject similar documents",
+                similar[0].hash.to_short_string(),
+                similar[0].title()
+            ),
+        }
     }
 
     /// Edit the metadata of a document in the library.
@@ -279,170 +526,626 @@ impl Library {
     pub fn edit_metadata<F>(&self, hash_prefix: &str, edit: F) -> anyhow::Result<()>
     where
         F: FnOnce(&mut IndexEntry) -> anyhow::Result<()>,
+    {
+        self.edit_metadata_checked(hash_prefix, edit, |_, _| Ok(true))
+            .map(|_| ())
+    }
+
+    /// Edit the metadata of a document in the library, asking for confirmation first.
+    ///
+    /// This works like [`Library::edit_metadata()`], except that `confirm` is called with the
+    /// entry of the document before and after the edit. If `confirm` returns false, the edit is
+    /// discarded and `false` is returned.
+    ///
+    /// # Errors
+    ///
+    /// See [`Library::edit_metadata()`]. In addition, an error is returned if `confirm` returns an
+    /// error.
+    pub fn edit_metadata_checked<F, C>(
+        &self,
+        hash_prefix: &str,
+        edit: F,
+        confirm: C,
+    ) -> anyhow::Result<bool>
+    where
+        F: FnOnce(&mut IndexEntry) -> anyhow::Result<()>,
+        C: FnOnce(&IndexEntry, &IndexEntry) -> anyhow::Result<bool>,
     {
         let index_path = self.index_path();
         let mut index = LibraryIndex::open(&index_path)?;
 
         let entry = index.find_hash_mut(hash_prefix)?.found_or_error()?;
+        let hash = *entry.hash();
+        let before = entry.clone();
         edit(entry)?;
+        if !confirm(&before, entry)? {
+            return Ok(false);
+        }
+
+        self.update_index(&index)?;
+
+        self.log_event(Operation::Edit, vec![hash]);
+        Ok(true)
+    }
+
+    /// Replace the file of a document in the library, keeping its metadata.
+    ///
+    /// The document matching the given hash prefix is replaced by the file at `path`. The hash
+    /// and file format of the document are updated, everything else about the document is kept.
+    /// The old hash is remembered, so it can still be used to refer to the document (see
+    /// [`IndexEntry::former_hashes()`]). The old file is moved to the trash directory of the
+    /// library.
+    ///
+    /// Returns the hash of the new file.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - No document or multiple documents match the hash prefix.
+    /// - The new file cannot be read or its file format is not supported.
+    /// - The new file is already in the library.
+    /// - The new file cannot be copied to the document store.
+    /// - The index file cannot be read or written.
+    pub fn replace_blob<P: AsRef<Path>>(
+        &self,
+        hash_prefix: &str,
+        path: P,
+    ) -> anyhow::Result<sha256::Hash> {
+        self.replace_blob_impl(hash_prefix, path.as_ref(), true)
+    }
+
+    /// Implementation of [`Library::replace_blob()`].
+    ///
+    /// If `update_source` is false, the information about the file the document was added from
+    /// is kept, e.g. because the new file was derived from the old one.
+    fn replace_blob_impl(
+        &self,
+        hash_prefix: &str,
+        path: &Path,
+        update_source: bool,
+    ) -> anyhow::Result<sha256::Hash> {
+        let file_format = FileFormat::from_path(path)?;
+        let doc_file = File::open(path)
+            .with_context(|| format!("Failed to open file at {}", path.display()))?;
+        let file_size = file_size(&doc_file, path)?;
+        let new_hash = sha256::hash_reader(doc_file)?;
+
+        let index_path = self.index_path();
+        let mut index = LibraryIndex::open(&index_path)?;
 
-        index.save(&index_path)
+        if let Some(doc) = index.documents.iter().find(|doc| doc.hash == new_hash) {
+            bail!(
+                "Document is already in the library ({})",
+                doc.hash.to_short_string()
+            );
+        }
+
+        let entry = index.find_hash_mut(hash_prefix)?.found_or_error()?;
+        let old_hash = entry.hash;
+
+        let document_store_dir = self.document_store_dir();
+        disk_space::check_available_space(&document_store_dir, file_size)?;
+        let store_path = document_store_dir.join(new_hash.to_string());
+        let size_bytes = copy_to_store(path, &store_path)?;
+
+        entry.hash = new_hash;
+        entry.metadata.file_format = file_format;
+        entry.size_bytes = Some(size_bytes);
+        if update_source {
+            (entry.source_filename, entry.source_path) = source_of(path);
+        }
+        // The preservation status was determined for the old file.
+        entry.preservation = None;
+        // Keep the old hash as an alias, unless the document goes back to a former file.
+        entry.former_hashes.retain(|hash| *hash != new_hash);
+        entry.former_hashes.push(old_hash);
+        for entry in &mut index.documents {
+            for volume in &mut entry.volumes {
+                if *volume == old_hash {
+                    *volume = new_hash;
+                }
+            }
+        }
+
+        if let Err(error) = self.begin_index_update(&index, &[], &[old_hash]) {
+            // Same as in `add_document`: best-effort removal of the file we just added.
+            let _ = fs::remove_file(&store_path);
+            return Err(error);
+        }
+        wal::trash_documents(&self.path, &[old_hash])?;
+        self.finish_index_update(&index)?;
+
+        self.log_event(Operation::Replace, vec![old_hash, new_hash]);
+
+        Ok(new_hash)
     }
 
     /// Retrieve a document from the library.
     ///
-    /// `identifier` can be an ISBN, DOI or a hash prefix.
+    /// `identifier` can be an ISBN, DOI, alias or a hash prefix.
     ///
     /// The matching document is copied to the specified output path.
     /// If no output path is provided, the document is copied to the current working directory with
-    /// a file name based on the document's title.
+    /// a file name based on the document's title, or on [`RetrieveOptions::file_name_template`] if
+    /// it is set (see [`IndexEntry::file_name_from_template()`]). If
+    /// [`RetrieveOptions::original_name`] is set, the name of the file the document was added from
+    /// is used instead, if it is known.
     /// If multiple or no documents match, an error is returned.
     ///
+    /// Archived documents are only considered if [`RetrieveOptions::include_archived`] is set.
+    ///
+    /// If the output file already exists, [`RetrieveOptions::on_conflict`] decides what happens.
+    /// The returned [`RetrievedDocument`] holds the path the document was actually copied to.
+    ///
+    /// Documents with multiple volumes (see [`Library::set_volumes()`]) are copied to a directory
+    /// named like the output path without its extension, which contains all volumes in order.
+    /// The path of the directory is returned in that case.
+    ///
+    /// The retrieval is not recorded in the index, use [`Library::record_access()`] for that.
+    ///
     /// # Errors
     ///
     /// An error will be returned in any of the following cases:
-    /// - The file at the output path already exists.
+    /// - The file at the output path already exists and [`RetrieveOptions::on_conflict`] is
+    ///   [`OutputConflict::Error`].
     /// - Multiple documents match the hash prefix.
     /// - No documents match the hash prefix.
     /// - The index file cannot be read.
     /// - The document cannot be copied to the output path.
+    /// - [`RetrieveOptions::metadata_command`] fails.
     pub fn retrieve_document<P: AsRef<Path>>(
         &self,
         identifier: &str,
         out_path: Option<P>,
-    ) -> anyhow::Result<()> {
+        options: &RetrieveOptions,
+    ) -> anyhow::Result<RetrievedDocument> {
         let index_path = self.index_path();
         let index = LibraryIndex::open(&index_path)?;
 
-        let entry = index.find_document(identifier)?;
+        let entry = index.find_document(identifier, options.include_archived)?;
 
         let out_path = match out_path {
             Some(p) => p.as_ref().to_owned(),
-            None => PathBuf::from(entry.default_file_name()),
+            None => output_file_name(entry, options)?,
         };
-        let exists = out_path.try_exists().with_context(|| {
-            format!(
-                "Could not determine if output file exists at {}",
-                out_path.display()
-            )
-        })?;
-        if exists {
-            bail!("Output file {} already exists", out_path.display());
-        }
-        let store_path = self.document_store_dir().join(entry.hash().to_string());
-        fs::copy(&store_path, &out_path).with_context(|| {
-            format!(
-                "Failed to copy document from {} to {}",
-                store_path.display(),
-                out_path.display()
-            )
-        })?;
+        let path = self.copy_out_volumes(&index, entry, out_path, options)?;
 
-        Ok(())
+        Ok(RetrievedDocument {
+            entry: entry.clone(),
+            path,
+        })
     }
 
-    /// Iterate over the metadata of all documents in the library.
+    /// Record that the documents with hashes `hashes` were accessed, e.g. retrieved with
+    /// [`Library::retrieve_document()`].
+    ///
+    /// The time of the access is recorded in the index (see [`IndexEntry::last_accessed()`] and
+    /// [`IndexEntry::access_count()`]). Unlike changes to the library, this is neither logged nor
+    /// can it be undone. Hashes of documents that are not in the library are ignored.
     ///
     /// # Errors
     ///
-    /// This function returns an error if the index file cannot be read.
-    pub fn documents(&self) -> anyhow::Result<impl Iterator<Item = IndexEntry>> {
-        let index_path = self.index_path();
-        Ok(LibraryIndex::open(&index_path)?.documents.into_iter())
+    /// An error is returned if the index cannot be read or written, e.g. because the library is
+    /// read-only.
+    pub fn record_access(&self, hashes: &[sha256::Hash]) -> anyhow::Result<()> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let old = LibraryIndex::open(&self.index_path())?;
+        let mut index = old.clone();
+        let timestamp = Timestamp::now()?;
+        for entry in &mut index.documents {
+            if hashes.contains(&entry.hash) {
+                entry.record_access(timestamp);
+            }
+        }
+        self.update_index_untracked(&old, &index)
     }
 
-    /// Remove all documents that match the specified hash prefixes.
+    /// Retrieve multiple documents from the library into a directory.
     ///
-    /// Documents are removed from the library if their hash starts with one of the specified hash
-    /// prefixes. If a hash prefix matches multiple documents, none of the matched documents are
-    /// removed.
+    /// The documents matching the `identifiers` (see [`Library::retrieve_document()`]) and, if
+    /// given, all documents matching `filter` are copied to `out_dir`, each under the name it
+    /// would get from [`Library::retrieve_document()`] without an output path. A document that
+    /// is selected multiple times is only retrieved once. `out_dir` is created if it does not
+    /// exist.
     ///
-    /// The returned [`RemovalResults`] object provides information about which documents were
-    /// - successfully removed,
-    /// - not found in the library,
-    /// - ambiguous (i.e. multiple documents matched the hash prefix), and
-    /// - could not be removed due to an error.
+    /// The returned [`RetrievalResults`] object provides information about which documents were
+    /// retrieved and which identifiers or documents could not be retrieved due to an error.
     ///
     /// # Errors
     ///
-    /// This function returns an error if the index file cannot be read or written. If an error
-    /// occurs when trying to remove a document, the error is included in the [`RemovalResults`]
-    /// object.
-    pub fn remove_all<'a, H>(&self, hash_prefixes: H) -> anyhow::Result<RemovalResults<'a>>
+    /// This function returns an error if the index file cannot be read, or if the output
+    /// directory cannot be created. If an error occurs when trying to retrieve a single
+    /// document, the error is included in the [`RetrievalResults`] object.
+    pub fn retrieve_documents<'a, I>(
+        &self,
+        identifiers: I,
+        filter: Option<&Filter>,
+        out_dir: &Path,
+        options: &RetrieveOptions,
+    ) -> anyhow::Result<RetrievalResults>
     where
-        H: Iterator<Item = &'a str>,
+        I: Iterator<Item = &'a str>,
     {
         let index_path = self.index_path();
         let index = LibraryIndex::open(&index_path)?;
 
-        let matches = index.find_all_hashes(hash_prefixes)?;
-
-        let not_found = matches.not_found;
-        let ambiguous = matches.ambiguous;
+        fs::create_dir_all(out_dir).with_context(|| {
+            format!("Failed to create output directory at {}", out_dir.display())
+        })?;
 
-        // This could be a HashSet, but we expect the number of documents to be small, so a Vec is
-        // fine.
-        let mut to_be_removed = Vec::new();
         let mut errors = Vec::new();
-
-        let document_store_dir = self.document_store_dir();
-        for hash in matches.found {
-            let path = document_store_dir.join(hash.to_string());
-            match fs::remove_file(&path) {
-                Ok(()) => to_be_removed.push(hash),
-                Err(error) => {
-                    let error = anyhow::Error::from(error)
-                        .context(format!("Failed to remove document at {}", path.display()));
-                    errors.push(RemovalError { hash, error });
-                }
+        // Pairs of the hash of a document and the identifier it was selected by.
+        let mut selected: Vec<(sha256::Hash, String)> = Vec::new();
+        for identifier in identifiers {
+            match index.find_document(identifier, options.include_archived) {
+                Ok(entry) => selected.push((*entry.hash(), identifier.to_owned())),
+                Err(error) => errors.push(RetrievalError {
+                    identifier: identifier.to_owned(),
+                    error,
+                }),
             }
         }
+        if let Some(filter) = filter {
+            selected.extend(
+                index
+                    .documents
+                    .iter()
+                    .filter(|entry| options.include_archived || !entry.is_archived())
+                    .filter(|entry| filter.matches(entry))
+                    .map(|entry| (*entry.hash(), entry.hash().to_string())),
+            );
+        }
+        let mut seen = HashSet::new();
+        selected.retain(|(hash, _)| seen.insert(*hash));
 
-        let mut documents = Vec::with_capacity(index.documents.len() - to_be_removed.len());
-        let mut removed = Vec::with_capacity(to_be_removed.len());
-
-        for entry in index.documents {
-            if to_be_removed.contains(entry.hash()) {
-                removed.push(entry);
-            } else {
-                documents.push(entry);
+        let mut retrieved = Vec::new();
+        for (hash, identifier) in selected {
+            let Some(entry) = index.documents.iter().find(|entry| entry.hash == hash) else {
+                continue;
+            };
+            let result = output_file_name(entry, options).and_then(|file_name| {
+                self.copy_out_volumes(&index, entry, out_dir.join(file_name), options)
+            });
+            match result {
+                Ok(path) => retrieved.push(RetrievedDocument {
+                    entry: entry.clone(),
+                    path,
+                }),
+                Err(error) => errors.push(RetrievalError { identifier, error }),
             }
         }
 
-        // If this fails, the library is in an inconsistent state.
-        LibraryIndex { documents }.save(&index_path)?;
-
-        Ok(RemovalResults {
-            ambiguous,
-            errors,
-            not_found,
-            removed,
-        })
+        Ok(RetrievalResults { retrieved, errors })
     }
 
-    /// Check if the library is in a consistent state.
-    ///
-    /// This function performs the following checks:
-    /// - The document store contains only files and no directories or other types of files.
-    /// - The names of all files in the document store match their SHA-256 hash.
-    /// - All entries in the index file have a corresponding file in the document store.
-    /// - All files in the document store have an entry in the index file.
-    ///
-    /// # Errors
+    /// Copy the document of `entry` to `out_path` and return the path it was copied to.
     ///
-    /// If there is an IO error when validating the library, an error is returned.
+    /// If `out_path` already exists, the conflict is resolved according to
+    /// [`RetrieveOptions::on_conflict`].
+    fn copy_out(
+        &self,
+        entry: &IndexEntry,
+        out_path: PathBuf,
+        options: &RetrieveOptions,
+    ) -> anyhow::Result<PathBuf> {
+        let out_path = resolve_output_conflict(out_path, options.on_conflict)?;
+        let store_path = self.document_store_dir().join(entry.hash().to_string());
+        fs::copy(&store_path, &out_path).with_context(|| {
+            format!(
+                "Failed to copy document from {} to {}",
+                store_path.display(),
+                out_path.display()
+            )
+        })?;
+        if let Some(command) = &options.metadata_command {
+            write_back::write_metadata(command, entry, &out_path)?;
+        }
+        if options.write_sidecar {
+            write_back::write_sidecar(entry, &out_path)?;
+        }
+        if let Some(modified) = entry.source_modified().filter(|_| options.preserve_mtime) {
+            set_modification_time(&out_path, modified)?;
+        }
+        Ok(out_path)
+    }
+
+    /// Find a document in the library.
+    ///
+    /// `identifier` can be an ISBN, DOI, alias (see [`Library::set_alias()`]), MD5 hash prefixed
+    /// with `md5:` (see [`IndexEntry::md5()`]) or a hash prefix. Archived documents are only
+    /// considered if `include_archived` is true.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - Multiple documents match the identifier.
+    /// - No documents match the identifier.
+    /// - The index file cannot be read.
+    pub fn find_document(
+        &self,
+        identifier: &str,
+        include_archived: bool,
+    ) -> anyhow::Result<IndexEntry> {
+        let index = LibraryIndex::open(&self.index_path())?;
+        index.find_document(identifier, include_archived).cloned()
+    }
+
+    /// Open the file of the document with the given hash for reading.
+    ///
+    /// Unlike [`Library::find_document()`], this requires the full hash and also finds archived
+    /// documents. The access is not recorded in the index.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if no document has the hash, or if the index or the document
+    /// cannot be read.
+    pub fn open_document(&self, hash: &sha256::Hash) -> anyhow::Result<File> {
+        let index = LibraryIndex::open(&self.index_path())?;
+        if !index.documents.iter().any(|entry| entry.hash == *hash) {
+            return Err(LookupError::NotFound(format!("No document with hash {hash}")).into());
+        }
+        let store_path = self.document_store_dir().join(hash.to_string());
+        File::open(&store_path)
+            .with_context(|| format!("Failed to open document at {}", store_path.display()))
+    }
+
+    /// Return all documents that are likely duplicates of a document with the given metadata.
+    ///
+    /// A document is considered a likely duplicate if its normalized title (see
+    /// [`crate::normalize_title()`]) is the same and the authors overlap. If either document has
+    /// no authors, the authors are considered to overlap.
+    ///
+    /// This is useful to catch documents that are added again from a different file, in which
+    /// case the hashes differ.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the index file cannot be read.
+    pub fn similar_documents(&self, metadata: &DocMetadata) -> anyhow::Result<Vec<IndexEntry>> {
+        let index = LibraryIndex::open(&self.index_path())?;
+        Ok(index
+            .similar_documents(metadata)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+
+    /// Iterate over the metadata of all documents in the library.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the index file cannot be read.
+    pub fn documents(&self) -> anyhow::Result<impl Iterator<Item = IndexEntry>> {
+        let index_path = self.index_path();
+        Ok(LibraryIndex::open(&index_path)?.documents.into_iter())
+    }
+
+    /// Read the index once and run `f` on it.
+    ///
+    /// Unlike [`Library::documents()`], the entries are borrowed instead of moved out of the
+    /// index, so several queries can be run against the same index without reading the index
+    /// file again. Changes made to the library while `f` runs are not visible to it.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the index file cannot be read.
+    pub fn with_index<R>(&self, f: impl FnOnce(&LibraryIndex) -> R) -> anyhow::Result<R> {
+        let index = LibraryIndex::open(&self.index_path())?;
+        Ok(f(&index))
+    }
+
+    /// Remove all documents that match the specified identifiers.
+    ///
+    /// Each identifier is interpreted like in [`Library::find_document()`], i.e. it can be an ISBN,
+    /// DOI, alias, MD5 hash or a prefix of a current or former hash. Documents are removed from
+    /// the library if they match one of the specified identifiers. If an identifier matches
+    /// multiple documents, none of the documents it matched are removed. The files of removed documents are moved to the trash
+    /// directory of the library, so that the removal can be undone (see [`Library::undo()`]).
+    ///
+    /// The returned [`RemovalResults`] object provides information about which documents were
+    /// - successfully removed,
+    /// - not found in the library,
+    /// - ambiguous (i.e. multiple documents matched the identifier), and
+    /// - could not be removed due to an error.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the index file cannot be read or written. If an error
+    /// occurs when trying to remove a document, the error is included in the [`RemovalResults`]
+    /// object.
+    pub fn remove_all<'a, I>(&self, identifiers: I) -> anyhow::Result<RemovalResults<'a>>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        self.remove_all_checked(identifiers, None, false, |_| Ok(true))?
+            .context("Removal was not confirmed")
+    }
+
+    /// Remove documents from the library, asking for confirmation first.
+    ///
+    /// This works like [`Library::remove_all()`], except that in addition to the documents
+    /// matching the `identifiers`, all documents matching `filter` are removed. Archived documents
+    /// only match `filter` if `include_archived` is true, like in
+    /// [`Library::retrieve_documents()`]. Before anything is
+    /// removed, `confirm` is called with the documents that are about to be removed. If `confirm`
+    /// returns false, nothing is removed and `None` is returned. If no documents are about to be
+    /// removed, `confirm` is not called.
+    ///
+    /// # Errors
+    ///
+    /// See [`Library::remove_all()`]. In addition, an error is returned if `confirm` returns an
+    /// error.
+    pub fn remove_all_checked<'a, I, F>(
+        &self,
+        identifiers: I,
+        filter: Option<&Filter>,
+        include_archived: bool,
+        confirm: F,
+    ) -> anyhow::Result<Option<RemovalResults<'a>>>
+    where
+        I: Iterator<Item = &'a str>,
+        F: FnOnce(&[&IndexEntry]) -> anyhow::Result<bool>,
+    {
+        let index_path = self.index_path();
+        let index = LibraryIndex::open(&index_path)?;
+
+        let mut matches = index.find_all(identifiers, true)?;
+        if let Some(filter) = filter {
+            matches.found.extend(
+                index
+                    .documents
+                    .iter()
+                    .filter(|entry| include_archived || !entry.is_archived())
+                    .filter(|entry| filter.matches(entry))
+                    .map(|entry| *entry.hash()),
+            );
+        }
+
+        let not_found = matches.not_found;
+        let ambiguous = matches.ambiguous;
+
+        // This could be a HashSet, but we expect the number of documents to be small, so a Vec is
+        // fine.
+        let found: Vec<_> = matches.found.into_iter().collect();
+
+        let to_be_confirmed: Vec<_> = index
+            .documents
+            .iter()
+            .filter(|entry| found.contains(entry.hash()))
+            .collect();
+        if !to_be_confirmed.is_empty() && !confirm(&to_be_confirmed)? {
+            return Ok(None);
+        }
+
+        // The intended index is recorded in the write-ahead log before any document is removed.
+        // If the removal is interrupted, it is completed the next time the library is opened.
+        let mut intended = LibraryIndex {
+            documents: index
+                .documents
+                .iter()
+                .filter(|entry| !found.contains(entry.hash()))
+                .cloned()
+                .collect(),
+        };
+        for entry in &mut intended.documents {
+            entry.volumes.retain(|hash| !found.contains(hash));
+        }
+        // The files are moved to the trash, so that the removal can be undone.
+        self.begin_index_update(&intended, &[], &found)?;
+
+        let mut to_be_removed = Vec::new();
+        let mut errors = Vec::new();
+
+        let document_store_dir = self.document_store_dir();
+        let trash_dir = self.path.join(TRASH_DIR);
+        fs::create_dir_all(&trash_dir).with_context(|| {
+            format!(
+                "Failed to create trash directory at {}",
+                trash_dir.display()
+            )
+        })?;
+        for entry in index
+            .documents
+            .iter()
+            .filter(|entry| found.contains(entry.hash()))
+        {
+            let path = document_store_dir.join(entry.hash().to_string());
+            match fs::rename(&path, trash_dir.join(entry.hash().to_string())) {
+                Ok(()) => to_be_removed.push(*entry.hash()),
+                Err(error) => {
+                    let error = anyhow::Error::from(error)
+                        .context(format!("Failed to remove document at {}", path.display()));
+                    errors.push(RemovalError {
+                        entry: entry.clone(),
+                        error,
+                    });
+                }
+            }
+        }
+
+        let mut documents = Vec::with_capacity(index.documents.len() - to_be_removed.len());
+        let mut removed = Vec::with_capacity(to_be_removed.len());
+
+        for mut entry in index.documents {
+            if to_be_removed.contains(entry.hash()) {
+                removed.push(entry);
+            } else {
+                entry.volumes.retain(|hash| !to_be_removed.contains(hash));
+                documents.push(entry);
+            }
+        }
+
+        // Documents that could not be removed are kept in the index.
+        self.finish_index_update(&LibraryIndex { documents })?;
+
+        if !removed.is_empty() {
+            self.log_event(
+                Operation::Remove,
+                removed.iter().map(|entry| *entry.hash()).collect(),
+            );
+        }
+
+        Ok(Some(RemovalResults {
+            ambiguous,
+            errors,
+            not_found,
+            removed,
+        }))
+    }
+
+    /// Check if the library is in a consistent state.
+    ///
+    /// This function performs the following checks:
+    /// - The document store contains only files and no directories or other types of files.
+    /// - All files in the document store can be read.
+    /// - The names of all files in the document store match their SHA-256 hash.
+    /// - No file in the document store is empty.
+    /// - The sizes of the files in the document store match the sizes recorded in the index.
+    /// - All entries in the index file have a corresponding file in the document store.
+    /// - All files in the document store have an entry in the index file.
+    /// - The index file matches its checksum, i.e. it was not modified outside of burette.
+    /// - No interrupted index update is pending, which can only be the case if the library was
+    ///   opened with [`Library::open_unchecked()`].
+    ///
+    /// # Errors
+    ///
+    /// If there is an IO error when validating the library, an error is returned. Files that
+    /// cannot be opened are not treated as an error, they are reported in
+    /// [`ValidationResults::unreadable_files()`] instead.
+    pub fn validate(&self) -> anyhow::Result<ValidationResults> {
+        self.validate_with_options(&ValidateOptions::default())
+    }
+
+    /// Check if the library is in a consistent state, with additional checks selected by
+    /// `options`.
+    ///
+    /// See [`Library::validate()`] and [`ValidateOptions`] for the checks that are performed.
+    ///
+    /// # Errors
+    ///
+    /// See [`Library::validate()`]. In addition, an error is returned if one of the additional
+    /// checks cannot be performed, e.g. because the checksums file cannot be read.
     //
     // Note that we don't need to check
     // - existence of the document store directory
     // - validity of the index file
     // - validity of the version file
     // as these are checked when opening the library.
-    pub fn validate(&self) -> anyhow::Result<ValidationResults> {
+    pub fn validate_with_options(
+        &self,
+        options: &ValidateOptions,
+    ) -> anyhow::Result<ValidationResults> {
         let document_store_dir = self.document_store_dir();
 
         let mut hash_mismatches = Vec::new();
         let mut invalid_file_types = Vec::new();
+        let mut unreadable_files = Vec::new();
+        let mut empty_files = Vec::new();
         let mut existing_files = HashSet::new();
+        // Sizes of the files in the document store, keyed by file name.
+        let mut file_sizes = HashMap::new();
 
         let dir = match fs::read_dir(&document_store_dir) {
             Ok(dir) => Some(dir),
@@ -477,8 +1180,28 @@ impl Library {
             }
 
             let path = entry.path();
-            let file = File::open(&path)
-                .with_context(|| format!("Failed to open file {}", path.display()))?;
+            let size = entry
+                .metadata()
+                .with_context(|| format!("Failed to read metadata of {}", path.display()))?
+                .len();
+            if size == 0 {
+                empty_files.push(file_name.clone());
+            }
+            file_sizes.insert(file_name.clone(), size);
+
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(error) => {
+                    // The file exists, even if we cannot read it. If its name is a valid hash, we
+                    // assume that it matches its content so that it is not also reported as
+                    // missing.
+                    if let Some(hash) = file_name.to_str().and_then(|name| name.parse().ok()) {
+                        existing_files.insert(hash);
+                    }
+                    unreadable_files.push(UnreadableFile { file_name, error });
+                    continue;
+                }
+            };
             let hash = sha256::hash_reader(file)
                 .with_context(|| format!("Failed to hash file {}", path.display()))?;
             let hash_str = hash.to_string();
@@ -490,6 +1213,7 @@ impl Library {
             }
             existing_files.insert(hash);
         }
+        empty_files.sort_unstable();
 
         let index_path = self.index_path();
         let index = LibraryIndex::open(&index_path)?;
@@ -511,42 +1235,420 @@ impl Library {
             .collect();
         missing_index_entries.sort_unstable();
 
+        let mut size_mismatches: Vec<_> = index
+            .documents
+            .iter()
+            .filter_map(|entry| {
+                let expected = entry.size_bytes()?;
+                let actual = *file_sizes.get(OsStr::new(&entry.hash().to_string()))?;
+                (expected != actual).then_some(SizeMismatch {
+                    hash: *entry.hash(),
+                    expected,
+                    actual,
+                })
+            })
+            .collect();
+        size_mismatches.sort_unstable_by_key(|mismatch| mismatch.hash);
+
+        let index_modified = !index_checksum_matches(&index_path)?;
+        let pending_update = wal::has_pending_update(&self.path)?;
+        let digest_mismatches = if options.all_digests {
+            self.digest_mismatches()?
+        } else {
+            Vec::new()
+        };
+        let unknown_fields = if options.strict {
+            unknown_index_fields(&index_path)?
+        } else {
+            Vec::new()
+        };
+
         Ok(ValidationResults {
+            index_modified,
+            pending_update,
             missing_files,
             missing_index_entries,
             hash_mismatches,
             invalid_file_types,
+            unreadable_files,
+            empty_files,
+            size_mismatches,
+            digest_mismatches,
+            unknown_fields,
+        })
+    }
+
+    /// Check if the library is in a consistent state, including the digests recorded in the
+    /// checksums file.
+    ///
+    /// In addition to the checks of [`Library::validate()`], the files in the document store are
+    /// checked against all digests recorded for them (see [`LibrarySettings::digests`]).
+    ///
+    /// # Errors
+    ///
+    /// See [`Library::validate()`]. In addition, an error is returned if the checksums file cannot
+    /// be read.
+    pub fn validate_all_digests(&self) -> anyhow::Result<ValidationResults> {
+        self.validate_with_options(&ValidateOptions {
+            all_digests: true,
+            ..ValidateOptions::default()
         })
     }
 }
 
+/// Return the size of the opened file `file` at `path` in bytes.
+fn file_size(file: &File, path: &Path) -> anyhow::Result<u64> {
+    Ok(file
+        .metadata()
+        .with_context(|| format!("Failed to read metadata of {}", path.display()))?
+        .len())
+}
+
+/// Return the modification time of `file`, or `None` if it cannot be determined.
+fn modification_time(file: &File) -> Option<Timestamp> {
+    let modified = file
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    Timestamp::from_system_time(modified)
+}
+
+/// Set the modification time of the file at `path` to `timestamp`.
+fn set_modification_time(path: &Path, timestamp: Timestamp) -> anyhow::Result<()> {
+    File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(timestamp.to_system_time()))
+        .with_context(|| format!("Failed to set modification time of {}", path.display()))
+}
+
+/// Copy the file at `path` to `store_path` in the document store and return its size in bytes.
+///
+/// If the copy fails (e.g. because the disk is full), the partially written file is removed from
+/// the document store.
+fn copy_to_store(path: &Path, store_path: &Path) -> anyhow::Result<u64> {
+    fs::copy(path, store_path).or_else(|error| {
+        // Ignore errors when cleaning up, as we want to propagate the original error.
+        let _ = fs::remove_file(store_path);
+        Err(error).with_context(|| {
+            format!(
+                "Failed to copy file from {} to document store at {}",
+                path.display(),
+                store_path.display()
+            )
+        })
+    })
+}
+
+/// Place the file at `path` in the document store at `store_path` without copying it if possible
+/// and return its size in bytes.
+///
+/// The file is hard linked into the document store, so no additional disk space is needed. If
+/// that is not possible, e.g. because the document store is on a different file system, the file
+/// is copied instead and the copy is verified against `hash`. In both cases, the original file is
+/// left in place; it is up to the caller to remove it once the document has been added.
+fn move_to_store(
+    path: &Path,
+    store_path: &Path,
+    hash: sha256::Hash,
+    file_size: u64,
+) -> anyhow::Result<u64> {
+    if fs::hard_link(path, store_path).is_ok() {
+        return Ok(file_size);
+    }
+
+    if let Some(document_store_dir) = store_path.parent() {
+        disk_space::check_available_space(document_store_dir, file_size)?;
+    }
+    let size_bytes = copy_to_store(path, store_path)?;
+
+    let copied_hash = File::open(store_path)
+        .and_then(sha256::hash_reader)
+        .with_context(|| format!("Failed to verify copied file at {}", store_path.display()));
+    match copied_hash {
+        Ok(copied_hash) if copied_hash == hash => Ok(size_bytes),
+        Ok(_) => {
+            let _ = fs::remove_file(store_path);
+            bail!(
+                "Copy of {} in the document store does not match the original",
+                path.display()
+            );
+        }
+        Err(error) => {
+            let _ = fs::remove_file(store_path);
+            Err(error)
+        }
+    }
+}
+
+/// Options for [`Library::add_document_checked()`].
+#[derive(Debug, Clone, Default)]
+pub struct AddOptions {
+    /// Whether the original file is removed after it has been added to the library.
+    pub move_file: bool,
+    /// Whether the MD5 hash of the file is computed and recorded in the index.
+    pub compute_md5: bool,
+    /// The SHA-256 hash the file must have, e.g. as published by the site it was downloaded
+    /// from. Files with a different hash are not added.
+    pub expected_sha256: Option<sha256::Hash>,
+}
+
+/// Check that the file at `path` with hash `hash` has the `expected` hash, if any.
+pub(crate) fn check_expected_hash(
+    path: &Path,
+    hash: sha256::Hash,
+    expected: Option<sha256::Hash>,
+) -> anyhow::Result<()> {
+    match expected {
+        Some(expected) if expected != hash => bail!(
+            "{} has the SHA-256 hash {hash} instead of the expected {expected}",
+            path.display()
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Return whether `c` must not appear in a file name.
+///
+/// Path separators and control characters are never allowed. If `windows` is true, the
+/// characters that Windows reserves are not allowed either.
+fn is_reserved_in_file_name(c: char, windows: bool) -> bool {
+    path::is_separator(c)
+        || c.is_control()
+        || (windows && matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*'))
+}
+
+/// Remove the `\\?\` prefix that [`Path::canonicalize()`] adds to absolute paths on Windows.
+///
+/// Paths with the prefix are hard to read and not understood by many programs. The prefix is
+/// only removed from paths with a drive letter, other verbatim paths are returned unchanged.
+fn strip_verbatim_prefix(path: &str) -> &str {
+    match path.strip_prefix(r"\\?\") {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest,
+        _ => path,
+    }
+}
+
+/// Return the file name and the absolute path of the file at `path` for
+/// [`IndexEntry::source_filename()`] and [`IndexEntry::source_path()`].
+///
+/// Parts that cannot be determined are `None`.
+fn source_of(path: &Path) -> (Option<String>, Option<String>) {
+    let source_filename = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned());
+    let source_path = path.canonicalize().ok().map(|path| {
+        let path = path.to_string_lossy();
+        if cfg!(windows) {
+            strip_verbatim_prefix(&path).to_owned()
+        } else {
+            path.into_owned()
+        }
+    });
+    (source_filename, source_path)
+}
+
+/// Return the file name under which the document of `entry` is retrieved if no output path is
+/// given.
+///
+/// See [`Library::retrieve_document()`] for details.
+fn output_file_name(entry: &IndexEntry, options: &RetrieveOptions) -> anyhow::Result<PathBuf> {
+    Ok(
+        match entry.source_filename().filter(|_| options.original_name) {
+            // Only use the file name, in case the index was edited to contain a path.
+            Some(source_filename) => Path::new(source_filename)
+                .file_name()
+                .map_or_else(|| PathBuf::from(entry.default_file_name()), PathBuf::from),
+            None => match &options.file_name_template {
+                Some(template) => PathBuf::from(entry.file_name_from_template(template)?),
+                None => PathBuf::from(entry.default_file_name()),
+            },
+        },
+    )
+}
+
+/// Return the path a document should be written to if it is to be written to `out_path`.
+///
+/// If `out_path` already exists, the conflict is resolved according to `on_conflict`.
+fn resolve_output_conflict(
+    out_path: PathBuf,
+    on_conflict: OutputConflict,
+) -> anyhow::Result<PathBuf> {
+    let exists = |path: &Path| {
+        path.try_exists().with_context(|| {
+            format!(
+                "Could not determine if output file exists at {}",
+                path.display()
+            )
+        })
+    };
+
+    if !exists(&out_path)? {
+        return Ok(out_path);
+    }
+    match on_conflict {
+        OutputConflict::Error => bail!("Output file {} already exists", out_path.display()),
+        OutputConflict::Overwrite => Ok(out_path),
+        OutputConflict::Number => {
+            let stem = out_path.file_stem().unwrap_or_default().to_owned();
+            let extension = out_path.extension().map(OsStr::to_owned);
+            for number in 1.. {
+                let mut file_name = stem.clone();
+                file_name.push(format!("_{number}"));
+                if let Some(extension) = &extension {
+                    file_name.push(".");
+                    file_name.push(extension);
+                }
+                let numbered_path = out_path.with_file_name(file_name);
+                if !exists(&numbered_path)? {
+                    return Ok(numbered_path);
+                }
+            }
+            unreachable!("There is always a free number");
+        }
+    }
+}
+
+/// What to do if the output file of [`Library::retrieve_document()`] already exists.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum OutputConflict {
+    /// Return an error.
+    #[default]
+    Error,
+    /// Overwrite the existing file.
+    Overwrite,
+    /// Append the first free number to the file name, e.g. `title_1.pdf`.
+    Number,
+}
+
+/// Options for [`Library::retrieve_document()`].
+#[derive(Debug, Clone, Default)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "The options are independent of each other"
+)]
+pub struct RetrieveOptions {
+    /// Whether archived documents can be retrieved.
+    pub include_archived: bool,
+    /// Whether the document is saved under the name of the file it was added from, if no output
+    /// path is given.
+    pub original_name: bool,
+    /// Template for the file name of the document, if no output path is given.
+    ///
+    /// See [`IndexEntry::file_name_from_template()`] for the syntax. If this is `None`,
+    /// [`IndexEntry::default_file_name()`] is used.
+    pub file_name_template: Option<String>,
+    /// What to do if the output file already exists.
+    pub on_conflict: OutputConflict,
+    /// Whether the modification time of the retrieved file is set to that of the file the
+    /// document was added from (see [`IndexEntry::source_modified()`]).
+    ///
+    /// If the original modification time is not known, the retrieved file keeps the time it was
+    /// written at.
+    pub preserve_mtime: bool,
+    /// Command run on each retrieved file to write the metadata of the document into it, as a
+    /// program followed by its arguments.
+    ///
+    /// `{file}` in the arguments is replaced by the path of the retrieved file, and the rest of
+    /// each argument is an [`crate::OutputTemplate`] for the document, e.g.
+    /// `["ebook-meta", "{file}", "--title", "{title}", "--authors", "{authors|join:&}"]`. This
+    /// way, files leaving the library carry the metadata of the library with them.
+    pub metadata_command: Option<Vec<String>>,
+    /// Whether a sidecar file with the metadata and hash of the document is written next to each
+    /// retrieved file.
+    ///
+    /// The sidecar is named like the retrieved file with `.burette.json` appended. It contains the
+    /// hash of the document and its [`DocMetadata`] as a JSON object, so files passed on to
+    /// others keep their provenance, and can be added to another library with `burette add
+    /// --metadata`.
+    pub write_sidecar: bool,
+}
+
+/// Options for [`Library::validate_with_options()`].
+#[derive(Debug, Clone, Default)]
+pub struct ValidateOptions {
+    /// Whether the files in the document store are also checked against all digests recorded
+    /// for them (see [`LibrarySettings::digests`]).
+    pub all_digests: bool,
+    /// Whether fields of the index that burette does not know are reported (see
+    /// [`ValidationResults::unknown_fields()`]).
+    ///
+    /// Such fields are ignored when reading the index and are lost the next time the index is
+    /// written. They are usually typos in a hand-edited index, or were written by a newer version
+    /// of burette.
+    pub strict: bool,
+}
+
 /// Results from [`Library::validate()`].
 ///
 /// See [`Library::validate()`] for details.
-#[derive(Debug)]
+///
+/// The [`Display`] implementation lists all problems found, grouped by kind. If the library is
+/// valid, it is displayed as `Library is valid.`.
+#[derive(Debug, Serialize)]
 pub struct ValidationResults {
+    index_modified: bool,
+    pending_update: bool,
     missing_files: Vec<sha256::Hash>,
     missing_index_entries: Vec<sha256::Hash>,
     hash_mismatches: Vec<HashMismatch>,
     invalid_file_types: Vec<NotAFile>,
+    unreadable_files: Vec<UnreadableFile>,
+    #[serde(serialize_with = "serialize_os_strings")]
+    empty_files: Vec<OsString>,
+    size_mismatches: Vec<SizeMismatch>,
+    digest_mismatches: Vec<DigestMismatch>,
+    unknown_fields: Vec<UnknownField>,
 }
 
 impl ValidationResults {
     /// Return true if the library is in a consistent state.
     ///
     /// If this returns true, then
+    /// - [`Self::index_modified()`] is false,
+    /// - [`Self::pending_update()`] is false,
     /// - [`Self::missing_files()`] is empty,
     /// - [`Self::missing_index_entries()`] is empty,
-    /// - [`Self::hash_mismatches()`] is empty, and
-    /// - [`Self::invalid_file_types()`] is empty.
+    /// - [`Self::hash_mismatches()`] is empty,
+    /// - [`Self::invalid_file_types()`] is empty,
+    /// - [`Self::unreadable_files()`] is empty,
+    /// - [`Self::empty_files()`] is empty,
+    /// - [`Self::size_mismatches()`] is empty,
+    /// - [`Self::digest_mismatches()`] is empty, and
+    /// - [`Self::unknown_fields()`] is empty.
     ///
     /// If this returns false, then at least one of the above conditions is not met.
     #[must_use]
     pub fn is_valid(&self) -> bool {
-        self.missing_files.is_empty()
+        !self.index_modified
+            && !self.pending_update
+            && self.missing_files.is_empty()
             && self.missing_index_entries.is_empty()
             && self.hash_mismatches.is_empty()
             && self.invalid_file_types.is_empty()
+            && self.unreadable_files.is_empty()
+            && self.empty_files.is_empty()
+            && self.size_mismatches.is_empty()
+            && self.digest_mismatches.is_empty()
+            && self.unknown_fields.is_empty()
+    }
+
+    /// Return true if the index file does not match its checksum.
+    ///
+    /// This means that the index file was modified outside of burette, or that it is damaged,
+    /// e.g. truncated.
+    #[must_use]
+    pub fn index_modified(&self) -> bool {
+        self.index_modified
+    }
+
+    /// Return true if an index update was interrupted and has not been completed yet.
+    ///
+    /// The update is completed the next time the library is opened with [`Library::open()`].
+    /// Until then, the other results describe the library as it was before the update.
+    #[must_use]
+    pub fn pending_update(&self) -> bool {
+        self.pending_update
     }
 
     /// Return the SHA-256 hashes of files that are in the document store but not in the index.
@@ -559,93 +1661,449 @@ impl ValidationResults {
         self.missing_index_entries.iter()
     }
 
-    /// Return information about files with names that do not match their SHA-256 hash.
-    pub fn hash_mismatches(&self) -> impl Iterator<Item = &HashMismatch> {
-        self.hash_mismatches.iter()
+    /// Return information about files with names that do not match their SHA-256 hash.
+    pub fn hash_mismatches(&self) -> impl Iterator<Item = &HashMismatch> {
+        self.hash_mismatches.iter()
+    }
+
+    /// Return information about files with invalid file types.
+    pub fn invalid_file_types(&self) -> impl Iterator<Item = &NotAFile> {
+        self.invalid_file_types.iter()
+    }
+
+    /// Return information about files in the document store that could not be read.
+    pub fn unreadable_files(&self) -> impl Iterator<Item = &UnreadableFile> {
+        self.unreadable_files.iter()
+    }
+
+    /// Return the names of files in the document store that are empty.
+    pub fn empty_files(&self) -> impl Iterator<Item = &OsStr> {
+        self.empty_files.iter().map(OsString::as_os_str)
+    }
+
+    /// Return information about files with a size that differs from the size in the index.
+    pub fn size_mismatches(&self) -> impl Iterator<Item = &SizeMismatch> {
+        self.size_mismatches.iter()
+    }
+
+    /// Return information about files that do not match the digests in the checksums file.
+    ///
+    /// This is always empty unless the results are from [`Library::validate_all_digests()`].
+    pub fn digest_mismatches(&self) -> impl Iterator<Item = &DigestMismatch> {
+        self.digest_mismatches.iter()
+    }
+
+    /// Return the fields of the index that burette does not know, and whose values would be lost
+    /// the next time the index is written.
+    ///
+    /// This is always empty unless the results are from a validation with
+    /// [`ValidateOptions::strict`] set.
+    pub fn unknown_fields(&self) -> impl Iterator<Item = &UnknownField> {
+        self.unknown_fields.iter()
+    }
+}
+
+impl Display for ValidationResults {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "Library is valid.");
+        }
+
+        let mut printed = false;
+        if self.index_modified {
+            write!(
+                f,
+                "Index file was modified outside of burette (checksum mismatch)."
+            )?;
+            printed = true;
+        }
+        if self.pending_update {
+            if printed {
+                write!(f, "\n\n")?;
+            }
+            write!(
+                f,
+                "An interrupted index update is pending. It is completed the next time the \
+                library is opened by a command other than `burette validate`."
+            )?;
+            printed = true;
+        }
+        write_section(
+            f,
+            &mut printed,
+            "Files present in the index but not in the document store:",
+            self.missing_files(),
+        )?;
+        write_section(
+            f,
+            &mut printed,
+            "Files present in the document store but not in the index:",
+            self.missing_index_entries(),
+        )?;
+        write_section(
+            f,
+            &mut printed,
+            "Files with names that do not match their hashes:",
+            self.hash_mismatches(),
+        )?;
+        write_section(
+            f,
+            &mut printed,
+            "Files with invalid file types:",
+            self.invalid_file_types(),
+        )?;
+        write_section(
+            f,
+            &mut printed,
+            "Files that cannot be read:",
+            self.unreadable_files(),
+        )?;
+        write_section(
+            f,
+            &mut printed,
+            "Empty files:",
+            self.empty_files().map(OsStr::to_string_lossy),
+        )?;
+        write_section(
+            f,
+            &mut printed,
+            "Files with sizes that do not match the index:",
+            self.size_mismatches(),
+        )?;
+        write_section(
+            f,
+            &mut printed,
+            "Files that do not match their recorded digests:",
+            self.digest_mismatches(),
+        )?;
+        write_section(
+            f,
+            &mut printed,
+            "Unknown fields in the index:",
+            self.unknown_fields(),
+        )
+    }
+}
+
+/// Write a heading followed by one item per line, unless there are no items.
+///
+/// Sections are separated by an empty line. `printed` keeps track of whether a section has been
+/// written before.
+fn write_section<I>(f: &mut Formatter, printed: &mut bool, heading: &str, items: I) -> fmt::Result
+where
+    I: Iterator,
+    I::Item: Display,
+{
+    let mut items = items.peekable();
+    if items.peek().is_none() {
+        return Ok(());
+    }
+    if *printed {
+        write!(f, "\n\n")?;
+    }
+    write!(f, "{heading}")?;
+    for item in items {
+        write!(f, "\n{item}")?;
+    }
+    *printed = true;
+    Ok(())
+}
+
+/// Helper for `#[serde(serialize_with)]` that serializes a file name as a (lossy) string.
+fn serialize_os_string<S: Serializer>(s: &OsStr, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&s.to_string_lossy())
+}
+
+/// Helper for `#[serde(serialize_with)]` that serializes file names as (lossy) strings.
+fn serialize_os_strings<S: Serializer>(
+    strings: &[OsString],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(strings.iter().map(|s| s.to_string_lossy()))
+}
+
+/// Helper for `#[serde(serialize_with)]` that serializes an error as its message.
+fn serialize_io_error<S: Serializer>(error: &io::Error, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(error)
+}
+
+/// Indicates that the name of a file does not match its SHA-256 hash.
+#[derive(Debug, Serialize)]
+pub struct HashMismatch {
+    expected: sha256::Hash,
+    #[serde(serialize_with = "serialize_os_string")]
+    actual: OsString,
+}
+
+impl HashMismatch {
+    /// The SHA-256 hash of the file. This is the expected name of the file.
+    #[must_use]
+    pub fn expected(&self) -> &sha256::Hash {
+        &self.expected
+    }
+
+    /// The actual name of the file.
+    #[must_use]
+    pub fn actual(&self) -> &OsStr {
+        &self.actual
+    }
+}
+
+impl Display for HashMismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} has name {}",
+            self.expected.to_short_string(),
+            self.actual.to_string_lossy(),
+        )
+    }
+}
+
+/// Indicates that a file has an invalid file type.
+#[derive(Debug, Serialize)]
+pub struct NotAFile {
+    #[serde(serialize_with = "serialize_os_string")]
+    file_name: OsString,
+    #[serde(serialize_with = "serialize_file_type")]
+    file_type: FileType,
+}
+
+/// Helper for `#[serde(serialize_with)]` that serializes the type of a [`NotAFile`].
+#[expect(clippy::trivially_copy_pass_by_ref, reason = "Required by serde")]
+fn serialize_file_type<S: Serializer>(
+    file_type: &FileType,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(not_a_file_type_name(*file_type))
+}
+
+/// Return a human readable name of the type of a [`NotAFile`].
+fn not_a_file_type_name(file_type: FileType) -> &'static str {
+    if file_type.is_dir() {
+        "directory"
+    } else if file_type.is_symlink() {
+        "symlink"
+    } else if file_type.is_file() {
+        unreachable!("NotAFile should only be used for non-file types");
+    } else {
+        "unknown"
+    }
+}
+
+impl NotAFile {
+    /// The name of the file.
+    #[must_use]
+    pub fn file_name(&self) -> &OsStr {
+        &self.file_name
+    }
+
+    /// The type of the file.
+    #[must_use]
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+}
+
+impl Display for NotAFile {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} is not a regular file (type: {})",
+            self.file_name.to_string_lossy(),
+            not_a_file_type_name(self.file_type),
+        )
+    }
+}
+
+/// Indicates that a file in the document store could not be read.
+#[derive(Debug, Serialize)]
+pub struct UnreadableFile {
+    #[serde(serialize_with = "serialize_os_string")]
+    file_name: OsString,
+    #[serde(serialize_with = "serialize_io_error")]
+    error: io::Error,
+}
+
+impl UnreadableFile {
+    /// The name of the file.
+    #[must_use]
+    pub fn file_name(&self) -> &OsStr {
+        &self.file_name
+    }
+
+    /// The error that occurred when trying to read the file.
+    #[must_use]
+    pub fn error(&self) -> &io::Error {
+        &self.error
     }
+}
 
-    /// Return information about files with invalid file types.
-    pub fn invalid_file_types(&self) -> impl Iterator<Item = &NotAFile> {
-        self.invalid_file_types.iter()
+impl Display for UnreadableFile {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} cannot be read: {}",
+            self.file_name.to_string_lossy(),
+            self.error,
+        )
     }
 }
 
-/// Indicates that the name of a file does not match its SHA-256 hash.
-#[derive(Debug)]
-pub struct HashMismatch {
-    expected: sha256::Hash,
-    actual: OsString,
+/// Indicates that the size of a file does not match the size recorded in the index.
+#[derive(Debug, Serialize)]
+pub struct SizeMismatch {
+    hash: sha256::Hash,
+    expected: u64,
+    actual: u64,
 }
 
-impl HashMismatch {
-    /// The SHA-256 hash of the file. This is the expected name of the file.
+impl SizeMismatch {
+    /// The SHA-256 hash of the document.
     #[must_use]
-    pub fn expected(&self) -> &sha256::Hash {
-        &self.expected
+    pub fn hash(&self) -> &sha256::Hash {
+        &self.hash
     }
 
-    /// The actual name of the file.
+    /// The size of the document in bytes as recorded in the index.
     #[must_use]
-    pub fn actual(&self) -> &OsStr {
-        &self.actual
+    pub fn expected(&self) -> u64 {
+        self.expected
+    }
+
+    /// The actual size of the file in the document store in bytes.
+    #[must_use]
+    pub fn actual(&self) -> u64 {
+        self.actual
     }
 }
 
-impl Display for HashMismatch {
+impl Display for SizeMismatch {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(
             f,
-            "{} has name {}",
-            self.expected.to_short_string(),
-            self.actual.to_string_lossy(),
+            "{} has {} bytes, expected {} bytes",
+            self.hash.to_short_string(),
+            self.actual,
+            self.expected,
         )
     }
 }
 
-/// Indicates that a file has an invalid file type.
-#[derive(Debug)]
-pub struct NotAFile {
-    file_name: OsString,
-    file_type: FileType,
+/// Indicates that the index contains a field that burette does not know.
+#[derive(Debug, Serialize)]
+pub struct UnknownField {
+    hash: Option<sha256::Hash>,
+    name: String,
 }
 
-impl NotAFile {
-    /// The name of the file.
+impl UnknownField {
+    /// The SHA-256 hash of the document whose index entry contains the field, or `None` if the
+    /// field is not part of an index entry.
     #[must_use]
-    pub fn file_name(&self) -> &OsStr {
-        &self.file_name
+    pub fn hash(&self) -> Option<&sha256::Hash> {
+        self.hash.as_ref()
     }
 
-    /// The type of the file.
+    /// The name of the field.
     #[must_use]
-    pub fn file_type(&self) -> FileType {
-        self.file_type
+    pub fn name(&self) -> &str {
+        &self.name
     }
 }
 
-impl Display for NotAFile {
+impl Display for UnknownField {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let file_type = if self.file_type.is_dir() {
-            "directory"
-        } else if self.file_type.is_symlink() {
-            "symlink"
-        } else if self.file_type.is_file() {
-            unreachable!("NotAFile should only be used for non-file types");
-        } else {
-            "unknown"
-        };
-        write!(
-            f,
-            "{} is not a regular file (type: {file_type})",
-            self.file_name.to_string_lossy(),
-        )
+        match &self.hash {
+            Some(hash) => write!(
+                f,
+                "{} has unknown field {}",
+                hash.to_short_string(),
+                self.name
+            ),
+            None => write!(f, "Index has unknown field {}", self.name),
+        }
+    }
+}
+
+/// Results from [`Library::retrieve_documents()`].
+///
+/// See [`Library::retrieve_documents()`] for details.
+#[derive(Debug, Serialize)]
+pub struct RetrievalResults {
+    retrieved: Vec<RetrievedDocument>,
+    errors: Vec<RetrievalError>,
+}
+
+impl RetrievalResults {
+    /// Return true if all documents were successfully retrieved.
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Documents that were successfully retrieved.
+    #[must_use]
+    pub fn retrieved(&self) -> &[RetrievedDocument] {
+        &self.retrieved
+    }
+
+    /// Identifiers or documents that could not be retrieved due to an error.
+    #[must_use]
+    pub fn errors(&self) -> &[RetrievalError] {
+        &self.errors
+    }
+}
+
+/// A document that was retrieved by [`Library::retrieve_documents()`].
+#[derive(Debug, Serialize)]
+pub struct RetrievedDocument {
+    entry: IndexEntry,
+    path: PathBuf,
+}
+
+impl RetrievedDocument {
+    /// Get the index entry of the document.
+    #[must_use]
+    pub fn entry(&self) -> &IndexEntry {
+        &self.entry
+    }
+
+    /// Get the path the document was copied to.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Error that occurred when trying to retrieve a document from the library.
+#[derive(Debug, Serialize)]
+pub struct RetrievalError {
+    identifier: String,
+    #[serde(serialize_with = "serialize_anyhow_error")]
+    error: anyhow::Error,
+}
+
+impl RetrievalError {
+    /// Get the identifier of the document that could not be retrieved.
+    ///
+    /// For documents selected by a filter, this is the hash of the document.
+    #[must_use]
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Get the error that occurred when trying to retrieve the document.
+    #[must_use]
+    pub fn error(&self) -> &anyhow::Error {
+        &self.error
     }
 }
 
 /// Results from [`Library::remove_all()`].
 ///
 /// See [`Library::remove_all()`] for details.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct RemovalResults<'a> {
     ambiguous: Vec<AmbiguousHashMatch<'a>>,
     errors: Vec<RemovalError>,
@@ -660,7 +2118,7 @@ impl<'a> RemovalResults<'a> {
         self.ambiguous.is_empty() && self.errors.is_empty() && self.not_found.is_empty()
     }
 
-    /// Entries that could not be removed because multiple documents matched the hash prefix.
+    /// Entries that could not be removed because multiple documents matched the identifier.
     #[must_use]
     pub fn ambiguous(&self) -> &[AmbiguousHashMatch<'a>] {
         &self.ambiguous
@@ -672,7 +2130,7 @@ impl<'a> RemovalResults<'a> {
         &self.errors
     }
 
-    /// Hashes that could not be found in the library.
+    /// Identifiers that could not be found in the library.
     #[must_use]
     pub fn not_found(&self) -> &[&'a str] {
         &self.not_found
@@ -686,17 +2144,26 @@ impl<'a> RemovalResults<'a> {
 }
 
 /// Error that occurred when trying to remove a document from the library.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct RemovalError {
-    hash: sha256::Hash,
+    entry: IndexEntry,
+    #[serde(serialize_with = "serialize_anyhow_error")]
     error: anyhow::Error,
 }
 
+/// Helper for `#[serde(serialize_with)]` that serializes an error with all of its context.
+fn serialize_anyhow_error<S: Serializer>(
+    error: &anyhow::Error,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{error:#}"))
+}
+
 impl RemovalError {
-    /// Get the hash of the document that could not be removed.
+    /// Get the index entry of the document that could not be removed.
     #[must_use]
-    pub fn hash(&self) -> &sha256::Hash {
-        &self.hash
+    pub fn entry(&self) -> &IndexEntry {
+        &self.entry
     }
 
     /// Get the error that occurred when trying to remove the entry.
@@ -709,21 +2176,178 @@ impl RemovalError {
 /// The index of the document library.
 ///
 /// The index is a list of all documents in the library along with metadata about each document.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(transparent)]
-struct LibraryIndex {
+/// Use [`Library::with_index()`] to query the index without reading it once per query.
+///
+/// On disk, the index is an object with the version of its format in `schema_version` and the
+/// entries in `documents`. Indexes written by older versions of burette are a plain list of
+/// entries, which is read as schema version 0.
+#[derive(Debug, Clone)]
+pub struct LibraryIndex {
     documents: Vec<IndexEntry>,
 }
 
+impl Serialize for LibraryIndex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut index = serializer.serialize_struct("LibraryIndex", 2)?;
+        index.serialize_field("schema_version", &INDEX_SCHEMA_VERSION)?;
+        index.serialize_field("documents", &self.documents)?;
+        index.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for LibraryIndex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let documents = deserialize_index_documents(deserializer)?;
+        Ok(Self { documents })
+    }
+}
+
+/// Read the documents of an index in the current or the unversioned format of the index file.
+///
+/// `T` is usually [`IndexEntry`], but can be any type that is deserialized from index entries,
+/// such as [`DocumentSummary`].
+///
+/// # Errors
+///
+/// An error is returned if the index is malformed or has a newer schema version than
+/// [`INDEX_SCHEMA_VERSION`].
+fn deserialize_index_documents<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    /// Reads both formats of the index.
+    struct IndexVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for IndexVisitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+            f.write_str("a library index")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Vec::deserialize(SeqAccessDeserializer::new(seq))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut schema_version = None;
+            let mut documents = None;
+            while let Some(key) = map.next_key::<String>()? {
+                match key.as_str() {
+                    "schema_version" => {
+                        let version: u32 = map.next_value()?;
+                        // Newer formats may store the documents differently, so the version is
+                        // checked before reading them.
+                        if version > INDEX_SCHEMA_VERSION {
+                            return Err(de::Error::custom(format_args!(
+                                "index has schema version {version}, but this version of burette \
+                                only supports up to {INDEX_SCHEMA_VERSION}"
+                            )));
+                        }
+                        schema_version = Some(version);
+                    }
+                    "documents" => documents = Some(map.next_value()?),
+                    // Fields added by newer versions of burette are ignored.
+                    _ => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+            schema_version.ok_or_else(|| de::Error::missing_field("schema_version"))?;
+            documents.ok_or_else(|| de::Error::missing_field("documents"))
+        }
+    }
+
+    deserializer.deserialize_any(IndexVisitor(PhantomData))
+}
+
 /// An entry in the index of the document library.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct IndexEntry {
     hash: sha256::Hash,
     #[serde(flatten)]
     metadata: DocMetadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_filename: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_modified: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    volumes: Vec<sha256::Hash>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    archived: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_accessed: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    access_count: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    former_hashes: Vec<sha256::Hash>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    md5: Option<md5::Hash>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reading_progress: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reading_status: Option<ReadingStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rating: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    finished_reading: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<Annotation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preservation: Option<PreservationStatus>,
+}
+
+/// Helper for `#[serde(skip_serializing_if)]`.
+#[expect(clippy::trivially_copy_pass_by_ref, reason = "Required by serde")]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Helper for `#[serde(skip_serializing_if)]`.
+#[expect(clippy::trivially_copy_pass_by_ref, reason = "Required by serde")]
+fn is_zero(value: &u64) -> bool {
+    *value == 0
 }
 
 impl IndexEntry {
+    /// Create the entry of a document that is added to the library from the file at `path`.
+    fn added(
+        hash: sha256::Hash,
+        metadata: DocMetadata,
+        path: &Path,
+        source_modified: Option<Timestamp>,
+    ) -> Self {
+        let (source_filename, source_path) = source_of(path);
+        Self {
+            hash,
+            metadata,
+            size_bytes: None,
+            source_filename,
+            source_path,
+            source_modified,
+            aliases: Vec::new(),
+            volumes: Vec::new(),
+            archived: false,
+            last_accessed: None,
+            access_count: 0,
+            former_hashes: Vec::new(),
+            md5: None,
+            reading_progress: None,
+            reading_status: None,
+            rating: None,
+            finished_reading: None,
+            annotations: Vec::new(),
+            preservation: None,
+        }
+    }
+
     /// Return the default file name for the document.
     #[must_use]
     pub fn default_file_name(&self) -> String {
@@ -733,12 +2357,96 @@ impl IndexEntry {
         file_name
     }
 
+    /// Return a file name for the document built from a template.
+    ///
+    /// The template is copied to the file name, except for placeholders in curly braces, which are
+    /// replaced as follows:
+    /// - `{title}`: the title of the document
+    /// - `{authors}`: the authors of the document, separated by `, `
+    /// - `{hash}`: the short form of the hash of the document
+    /// - `{ext}`: the file extension of the document, without the leading `.`
+    ///
+    /// For example, `{authors} - {title}.{ext}` results in `Herman Melville - Moby Dick.epub`.
+    /// Path separators in the replaced values are replaced by `_`, as are characters that are not
+    /// allowed in file names on the current platform (e.g. `:` and `?` on Windows).
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the template contains unknown or unterminated placeholders, if it
+    /// contains a path separator, or if the resulting file name is empty.
+    pub fn file_name_from_template(&self, template: &str) -> anyhow::Result<String> {
+        if template.contains(path::is_separator) {
+            bail!("File name template must not contain path separators");
+        }
+
+        let mut file_name = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            file_name.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!("Unterminated placeholder in file name template"))?
+                + start;
+            let value = match &rest[start + 1..end] {
+                "title" => self.title().to_owned(),
+                "authors" => self.authors().collect::<Vec<_>>().join(", "),
+                "hash" => self.hash().to_short_string(),
+                "ext" => self.file_format().extension().to_owned(),
+                placeholder => bail!(
+                    "Unknown placeholder {{{}}} in file name template",
+                    placeholder
+                ),
+            };
+            file_name.extend(value.chars().map(|c| {
+                if is_reserved_in_file_name(c, cfg!(windows)) {
+                    '_'
+                } else {
+                    c
+                }
+            }));
+            rest = &rest[end + 1..];
+        }
+        file_name.push_str(rest);
+
+        if file_name.trim().is_empty() || file_name == "." || file_name == ".." {
+            bail!("File name template results in an invalid file name");
+        }
+        Ok(file_name)
+    }
+
     /// Return the hash of the document.
     #[must_use]
     pub fn hash(&self) -> &sha256::Hash {
         &self.hash
     }
 
+    /// Return the hashes the document had before its file was replaced, oldest first.
+    ///
+    /// Hash prefixes of former hashes still resolve to the document.
+    pub fn former_hashes(&self) -> impl Iterator<Item = &sha256::Hash> {
+        self.former_hashes.iter()
+    }
+
+    /// Return the MD5 hash of the document, if it is known.
+    ///
+    /// Shadow libraries such as Library Genesis and Anna's Archive identify files by their MD5
+    /// hash, so it can be used to check whether a file found there is already in the library. The
+    /// hash is only known if it was computed when the document was added (see
+    /// [`AddOptions::compute_md5`]) or by [`Library::update_digests()`], or set with
+    /// [`IndexEntry::set_md5()`].
+    #[must_use]
+    pub fn md5(&self) -> Option<&md5::Hash> {
+        self.md5.as_ref()
+    }
+
+    /// Set the MD5 hash of the document.
+    ///
+    /// The hash is not checked against the file, so that the hash of a file seen elsewhere can be
+    /// recorded as well, e.g. of a different release of the same document.
+    pub fn set_md5(&mut self, md5: Option<md5::Hash>) {
+        self.md5 = md5;
+    }
+
     /// Return the title of the document.
     #[must_use]
     pub fn title(&self) -> &str {
@@ -755,36 +2463,217 @@ impl IndexEntry {
         self.metadata.authors.iter().map(String::as_str)
     }
 
-    /// Set the authors of the document.
-    pub fn set_authors(&mut self, authors: Vec<String>) {
-        self.metadata.authors = authors;
+    /// Set the authors of the document.
+    pub fn set_authors(&mut self, authors: Vec<String>) {
+        self.metadata.authors = authors;
+    }
+
+    /// Return the ISBNs of the document.
+    pub fn isbns(&self) -> impl Iterator<Item = &Isbn13> {
+        self.metadata.isbns.iter()
+    }
+
+    /// Set the ISBNs of the document.
+    pub fn set_isbns(&mut self, isbns: Vec<Isbn13>) {
+        self.metadata.isbns = isbns;
+    }
+
+    /// Return the DOI of the document.
+    #[must_use]
+    pub fn doi(&self) -> Option<&str> {
+        self.metadata.doi.as_deref()
+    }
+
+    /// Set the DOI of the document.
+    pub fn set_doi(&mut self, doi: Option<String>) {
+        self.metadata.doi = doi;
+    }
+
+    /// Return the type of the document, if known.
+    #[must_use]
+    pub fn doc_type(&self) -> Option<DocType> {
+        self.metadata.doc_type
+    }
+
+    /// Set the type of the document.
+    pub fn set_doc_type(&mut self, doc_type: Option<DocType>) {
+        self.metadata.doc_type = doc_type;
+    }
+
+    /// Return the license or other usage rights of the document.
+    #[must_use]
+    pub fn rights(&self) -> Option<&str> {
+        self.metadata.rights.as_deref()
+    }
+
+    /// Set the license or other usage rights of the document.
+    pub fn set_rights(&mut self, rights: Option<String>) {
+        self.metadata.rights = rights;
+    }
+
+    /// Return the journal or conference of the document.
+    #[must_use]
+    pub fn journal(&self) -> Option<&str> {
+        self.metadata.journal.as_deref()
+    }
+
+    /// Set the journal or conference of the document.
+    pub fn set_journal(&mut self, journal: Option<String>) {
+        self.metadata.journal = journal;
+    }
+
+    /// Return the volume of the document.
+    #[must_use]
+    pub fn volume(&self) -> Option<&str> {
+        self.metadata.volume.as_deref()
+    }
+
+    /// Set the volume of the document.
+    pub fn set_volume(&mut self, volume: Option<String>) {
+        self.metadata.volume = volume;
+    }
+
+    /// Return the issue of the document.
+    #[must_use]
+    pub fn issue(&self) -> Option<&str> {
+        self.metadata.issue.as_deref()
+    }
+
+    /// Set the issue of the document.
+    pub fn set_issue(&mut self, issue: Option<String>) {
+        self.metadata.issue = issue;
+    }
+
+    /// Return the page range of the document.
+    #[must_use]
+    pub fn pages(&self) -> Option<&str> {
+        self.metadata.pages.as_deref()
+    }
+
+    /// Set the page range of the document.
+    pub fn set_pages(&mut self, pages: Option<String>) {
+        self.metadata.pages = pages;
+    }
+
+    /// Return the file format of the document.
+    #[must_use]
+    pub fn file_format(&self) -> FileFormat {
+        self.metadata.file_format
+    }
+
+    /// Return the size of the document file in bytes.
+    ///
+    /// This is `None` for documents that were added before burette recorded file sizes.
+    #[must_use]
+    pub fn size_bytes(&self) -> Option<u64> {
+        self.size_bytes
+    }
+
+    /// Return the name of the file the document was added from, if known.
+    #[must_use]
+    pub fn source_filename(&self) -> Option<&str> {
+        self.source_filename.as_deref()
+    }
+
+    /// Return the absolute path of the file the document was added from, if known.
+    #[must_use]
+    pub fn source_path(&self) -> Option<&str> {
+        self.source_path.as_deref()
+    }
+
+    /// Return the aliases of the document (see [`Library::set_alias()`]).
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.aliases.iter().map(String::as_str)
+    }
+
+    /// Return the modification time of the file the document was added from, if known.
+    ///
+    /// Documents added by older versions of burette have no modification time.
+    #[must_use]
+    pub fn source_modified(&self) -> Option<Timestamp> {
+        self.source_modified
+    }
+
+    /// Return true if the document is archived.
+    ///
+    /// Archived documents are kept in the library, but are hidden from listings and retrieval
+    /// unless explicitly requested.
+    #[must_use]
+    pub fn is_archived(&self) -> bool {
+        self.archived
+    }
+
+    /// Set whether the document is archived.
+    pub fn set_archived(&mut self, archived: bool) {
+        self.archived = archived;
+    }
+
+    /// Return the time at which the document was last retrieved, if ever.
+    #[must_use]
+    pub fn last_accessed(&self) -> Option<Timestamp> {
+        self.last_accessed
+    }
+
+    /// Return the number of times the document has been retrieved.
+    #[must_use]
+    pub fn access_count(&self) -> u64 {
+        self.access_count
+    }
+
+    /// Return how much of the document has been read, in percent, if known.
+    ///
+    /// The reading progress is imported from e-readers with [`Library::import_reading_data()`].
+    #[must_use]
+    pub fn reading_progress(&self) -> Option<u8> {
+        self.reading_progress
+    }
+
+    /// Return whether the document was read, if known.
+    ///
+    /// The reading status is imported from Goodreads and The `StoryGraph` with
+    /// [`Library::import_reading_data()`], as are the rating and the date finished.
+    #[must_use]
+    pub fn reading_status(&self) -> Option<ReadingStatus> {
+        self.reading_status
     }
 
-    /// Return the ISBNs of the document.
-    pub fn isbns(&self) -> impl Iterator<Item = &Isbn13> {
-        self.metadata.isbns.iter()
+    /// Return the rating of the document, from 1 to 5 stars, if it was rated.
+    #[must_use]
+    pub fn rating(&self) -> Option<u8> {
+        self.rating
     }
 
-    /// Set the ISBNs of the document.
-    pub fn set_isbns(&mut self, isbns: Vec<Isbn13>) {
-        self.metadata.isbns = isbns;
+    /// Return the date the document was finished, e.g. `2024-01-31`, if known.
+    #[must_use]
+    pub fn finished_reading(&self) -> Option<&str> {
+        self.finished_reading.as_deref()
     }
 
-    /// Return the DOI of the document.
-    #[must_use]
-    pub fn doi(&self) -> Option<&str> {
-        self.metadata.doi.as_deref()
+    /// Return the highlights and notes made while reading the document.
+    pub fn annotations(&self) -> impl Iterator<Item = &Annotation> {
+        self.annotations.iter()
     }
 
-    /// Set the DOI of the document.
-    pub fn set_doi(&mut self, doi: Option<String>) {
-        self.metadata.doi = doi;
+    /// Return the hashes of the further volumes of the document, in order.
+    ///
+    /// The document itself is the first volume. See [`Library::set_volumes()`].
+    pub fn volumes(&self) -> impl Iterator<Item = &sha256::Hash> {
+        self.volumes.iter()
     }
 
-    /// Return the file format of the document.
+    /// Return the result of the last PDF/A conformance check of the document, if any.
+    ///
+    /// See [`Library::check_preservation()`]. The result is discarded when the file of the
+    /// document is replaced.
     #[must_use]
-    pub fn file_format(&self) -> FileFormat {
-        self.metadata.file_format
+    pub fn preservation(&self) -> Option<&PreservationStatus> {
+        self.preservation.as_ref()
+    }
+
+    /// Record that the document was retrieved at the given time.
+    fn record_access(&mut self, timestamp: Timestamp) {
+        self.last_accessed = Some(timestamp);
+        self.access_count += 1;
     }
 }
 
@@ -799,10 +2688,99 @@ impl LibraryIndex {
         }
     }
 
+    /// Iterate over the entries of all documents in the index, in index order.
+    pub fn documents(&self) -> impl Iterator<Item = &IndexEntry> {
+        self.documents.iter()
+    }
+
+    /// Return the number of documents in the index.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Return true if the index contains no documents.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Return the entry of the document with the hash `hash`, if there is one.
+    #[must_use]
+    pub fn get(&self, hash: &sha256::Hash) -> Option<&IndexEntry> {
+        self.documents.iter().find(|entry| entry.hash == *hash)
+    }
+
+    /// Check that no document in the index has the hash `hash` or shares an ISBN or the DOI with
+    /// `metadata`.
+    ///
+    /// # Errors
+    ///
+    /// An error describing the duplicate is returned if such a document exists.
+    fn check_duplicates(&self, hash: sha256::Hash, metadata: &DocMetadata) -> anyhow::Result<()> {
+        for doc in &self.documents {
+            if let Some(isbn) = doc
+                .metadata
+                .isbns
+                .iter()
+                .find(|isbn| metadata.isbns.contains(isbn))
+            {
+                bail!(
+                    "Document with ISBN {} already exists ({})",
+                    isbn,
+                    doc.hash.to_short_string()
+                );
+            }
+            if doc.hash == hash {
+                bail!(
+                    "Document is already in the library ({})",
+                    doc.hash.to_short_string()
+                );
+            }
+            if let (Some(doi), Some(new_doi)) =
+                (doc.metadata.doi.as_deref(), metadata.doi.as_deref())
+            {
+                if doi == new_doi {
+                    bail!(
+                        "Document with DOI {} already exists ({})",
+                        doi,
+                        doc.hash.to_short_string()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find all documents that are likely duplicates of a document with the given metadata.
+    ///
+    /// See [`Library::similar_documents()`] for details.
+    fn similar_documents(&self, metadata: &DocMetadata) -> Vec<&IndexEntry> {
+        let title = crate::normalize_title(&metadata.title);
+        let authors: HashSet<_> = metadata
+            .authors
+            .iter()
+            .map(|author| crate::normalize_title(author))
+            .collect();
+
+        self.documents
+            .iter()
+            .filter(|entry| {
+                crate::normalize_title(entry.title()) == title
+                    && (authors.is_empty()
+                        || entry.metadata.authors.is_empty()
+                        || entry
+                            .authors()
+                            .any(|author| authors.contains(&crate::normalize_title(author))))
+            })
+            .collect()
+    }
+
     /// Find a document in the index.
     ///
-    /// `identifier` can be an ISBN, DOI or a hash prefix.
-    /// The document matching the identifier is returned.
+    /// `identifier` can be an ISBN, MD5 hash prefixed with `md5:`, DOI, alias or a hash prefix,
+    /// which are tried in this order. The document matching the identifier is returned.
+    /// Archived documents are ignored unless `include_archived` is true.
     ///
     /// # Errors
     ///
@@ -810,71 +2788,141 @@ impl LibraryIndex {
     /// - `identifier` matches multiple documents
     /// - `identifier` does not match any document
     ///
-    fn find_document(&self, identifier: &str) -> anyhow::Result<&IndexEntry> {
-        // If the identifier is an ISBN, search for a document with that ISBN.
+    pub fn find_document(
+        &self,
+        identifier: &str,
+        include_archived: bool,
+    ) -> anyhow::Result<&IndexEntry> {
+        self.find_matches(identifier, include_archived)?
+            .found_or_error()
+    }
+
+    /// Find all documents in the index that match `identifier`.
+    ///
+    /// `identifier` is interpreted as the first of the following that applies:
+    /// - an ISBN, if it is a valid ISBN
+    /// - an MD5 hash, if it is prefixed with `md5:`
+    /// - a DOI, if a document has it as its DOI
+    /// - an alias, if a document has it as an alias
+    /// - a hash prefix otherwise, see [`LibraryIndex::find_hash_matches()`]
+    ///
+    /// Archived documents are ignored unless `include_archived` is true.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if `identifier` is the empty string or an invalid MD5 hash.
+    fn find_matches<'a>(
+        &'a self,
+        identifier: &str,
+        include_archived: bool,
+    ) -> anyhow::Result<IdentifierMatches<'a>> {
+        if identifier.trim().is_empty() {
+            bail!("Identifier cannot be an empty string");
+        }
+
+        let candidates = || {
+            self.documents
+                .iter()
+                .filter(move |entry| include_archived || !entry.is_archived())
+        };
+
         if let Ok(isbn) = identifier.parse::<Isbn13>() {
-            for entry in &self.documents {
-                if entry.isbns().any(|entry_isbn| *entry_isbn == isbn) {
-                    return Ok(entry);
-                }
-            }
-            return Err(anyhow!("No document found with ISBN {}", isbn));
+            return Ok(IdentifierMatches {
+                description: format!("ISBN {isbn}"),
+                entries: candidates()
+                    .filter(|entry| entry.isbns().any(|entry_isbn| *entry_isbn == isbn))
+                    .collect(),
+            });
         }
 
-        // The identifier might be a DOI. Search for a document with that DOI.
-        for entry in &self.documents {
-            if entry.doi() == Some(identifier) {
-                return Ok(entry);
-            }
+        // The identifier might be an MD5 hash, e.g. copied from Anna's Archive.
+        if let Some(md5) = identifier.strip_prefix("md5:") {
+            let md5 = md5.parse::<md5::Hash>()?;
+            return Ok(IdentifierMatches {
+                description: format!("MD5 hash {md5}"),
+                entries: candidates()
+                    .filter(|entry| entry.md5() == Some(&md5))
+                    .collect(),
+            });
+        }
+
+        let entries: Vec<_> = candidates()
+            .filter(|entry| entry.doi() == Some(identifier))
+            .collect();
+        if !entries.is_empty() {
+            return Ok(IdentifierMatches {
+                description: format!("DOI {identifier}"),
+                entries,
+            });
+        }
+
+        // Aliases cannot look like hash prefixes, so they are unambiguous.
+        let entries: Vec<_> = candidates()
+            .filter(|entry| entry.aliases().any(|alias| alias == identifier))
+            .collect();
+        if !entries.is_empty() {
+            return Ok(IdentifierMatches {
+                description: format!("alias {identifier}"),
+                entries,
+            });
         }
 
-        // If the identifier is not an ISBN or DOI, it is assumed to be a hash prefix.
-        self.find_hash(identifier)?.found_or_error()
+        Ok(IdentifierMatches {
+            description: "hash prefix".to_owned(),
+            entries: self
+                .find_hash_matches(identifier)?
+                .into_iter()
+                .map(|i| &self.documents[i])
+                .filter(|entry| include_archived || !entry.is_archived())
+                .collect(),
+        })
     }
 
     /// Find all documents in the index that match the specified hash prefix.
     ///
+    /// If no document's current hash matches the prefix, the former hashes of the documents (see
+    /// [`IndexEntry::former_hashes()`]) are searched instead. This way, references to a document
+    /// keep working after its file has been replaced.
+    ///
     /// # Errors
     ///
     /// If `hash_prefix` is the empty string, an error is returned.
     fn find_hash_matches(&self, hash_prefix: &str) -> anyhow::Result<Vec<usize>> {
-        if hash_prefix.is_empty() {
+        if hash_prefix.trim().is_empty() {
             bail!("Hash prefix cannot be an empty string");
         }
 
-        Ok(self
+        let matches: Vec<_> = self
             .documents
             .iter()
             .enumerate()
             .filter_map(|(i, entry)| {
-                if entry.hash().to_string().starts_with(hash_prefix) {
+                if entry.hash().starts_with(hash_prefix) {
                     Some(i)
                 } else {
                     None
                 }
             })
-            .collect())
-    }
+            .collect();
+        if !matches.is_empty() {
+            return Ok(matches);
+        }
 
-    /// Find a document in the index that matches the specified hash prefix.
-    ///
-    /// - If no document matches the hash prefix, [`FindHash::NotFound`] is returned.
-    /// - If exactly one document matches the hash prefix, [`FindHash::Found`] is returned with a
-    ///   reference to the document.
-    /// - If multiple documents match the hash prefix, [`FindHash::Ambiguous`] is returned.
-    ///
-    /// See also [`LibraryIndex::find_hash_mut()`].
-    ///
-    /// # Errors
-    ///
-    /// If `hash_prefix` is the empty string, an error is returned.
-    fn find_hash<'a>(&'a self, hash_prefix: &str) -> anyhow::Result<FindHash<&'a IndexEntry>> {
-        self.find_hash_matches(hash_prefix)
-            .map(|matches| match matches.len() {
-                0 => FindHash::NotFound,
-                1 => FindHash::Found(&self.documents[matches[0]]),
-                _ => FindHash::Ambiguous,
+        Ok(self
+            .documents
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                if entry
+                    .former_hashes()
+                    .any(|hash| hash.starts_with(hash_prefix))
+                {
+                    Some(i)
+                } else {
+                    None
+                }
             })
+            .collect())
     }
 
     /// Find a document in the index that matches the specified hash prefix.
@@ -884,8 +2932,6 @@ impl LibraryIndex {
     ///   to the document.
     /// - If multiple documents match the hash prefix, [`FindHash::Ambiguous`] is returned.
     ///
-    /// See also [`LibraryIndex::find_hash()`].
-    ///
     /// # Errors
     ///
     /// If `hash_prefix` is the empty string, an error is returned.
@@ -901,55 +2947,44 @@ impl LibraryIndex {
             })
     }
 
-    /// Find all documents in the index that match the specified hash prefixes.
+    /// Find all documents in the index that match the specified identifiers.
+    ///
+    /// Each identifier is interpreted like in [`LibraryIndex::find_document()`]. Archived
+    /// documents are ignored unless `include_archived` is true.
     ///
-    /// If a document is matched by multiple hash prefixes, it will only be included in the results
+    /// If a document is matched by multiple identifiers, it will only be included in the results
     /// once.
     /// This is the case if a hash prefix is a prefix of another hash prefix.
     ///
     /// # Errors
     ///
-    /// If any of the hash prefixes are the empty string, an error is returned.
-    fn find_all_hashes<'a, H>(&self, hash_prefixes: H) -> anyhow::Result<HashMatches<'a>>
+    /// If any of the identifiers are the empty string or an invalid MD5 hash, an error is
+    /// returned.
+    fn find_all<'a, I>(
+        &self,
+        identifiers: I,
+        include_archived: bool,
+    ) -> anyhow::Result<HashMatches<'a>>
     where
-        H: Iterator<Item = &'a str>,
+        I: Iterator<Item = &'a str>,
     {
-        // Collect the hashes into a HashSet to remove duplicates.
-        let hash_prefixes: Vec<_> = hash_prefixes
-            .map(|hash_prefix| {
-                if hash_prefix.is_empty() {
-                    Err(anyhow!("Hash prefix cannot be an empty string"))
-                } else {
-                    Ok(hash_prefix)
-                }
-            })
-            .collect::<anyhow::Result<HashSet<_>>>()?
-            .into_iter()
-            .collect();
-        let mut matches = vec![Vec::new(); hash_prefixes.len()];
-
-        for hash in self.documents.iter().map(|entry| *entry.hash()) {
-            let hash_str = hash.to_string();
-            for (i, hash_prefix) in hash_prefixes.iter().enumerate() {
-                if hash_str.starts_with(hash_prefix) {
-                    matches[i].push(hash);
-                }
-            }
-        }
+        // Collect the identifiers into a HashSet to remove duplicates.
+        let identifiers: HashSet<_> = identifiers.collect();
 
         let mut ambiguous = Vec::new();
         let mut not_found = Vec::new();
         let mut found = HashSet::new();
 
-        for (hash_prefix, matches) in hash_prefixes.iter().zip(matches) {
-            match matches.len() {
-                0 => not_found.push(*hash_prefix),
-                1 => {
-                    found.insert(matches[0]);
+        for identifier in identifiers {
+            let matches = self.find_matches(identifier, include_archived)?;
+            match matches.entries.as_slice() {
+                [] => not_found.push(identifier),
+                [entry] => {
+                    found.insert(*entry.hash());
                 }
-                _ => ambiguous.push(AmbiguousHashMatch {
-                    hash_prefix,
-                    matches,
+                entries => ambiguous.push(AmbiguousHashMatch {
+                    hash_prefix: identifier,
+                    matches: entries.iter().map(|entry| *entry.hash()).collect(),
                 }),
             }
         }
@@ -963,36 +2998,194 @@ impl LibraryIndex {
 
     /// Read the index from disk.
     fn open(index_path: &Path) -> anyhow::Result<Self> {
-        let file = File::open(index_path).with_context(|| {
-            format!(
-                "Failed to open library index file at {}",
-                index_path.display()
-            )
-        })?;
-        let documents: Vec<IndexEntry> = serde_json::from_reader(file).with_context(|| {
-            format!("Failed to read library index from {}", index_path.display())
-        })?;
-        Ok(Self { documents })
+        let contents = read_index_file(index_path)?;
+        serde_json::from_slice(&contents)
+            .with_context(|| format!("Failed to read library index from {}", index_path.display()))
     }
 
     /// Save the index to disk.
+    ///
+    /// The index is first written to a temporary file which then replaces the index file, so the
+    /// index file is never left in a partially written state. Afterwards, the checksum of the
+    /// index file is updated (see [`index_checksum_matches()`]).
     fn save(&self, index_path: &Path) -> anyhow::Result<()> {
-        let writer = File::create(index_path).with_context(|| {
+        let mut tmp_path = index_path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let contents = serde_json::to_vec_pretty(&self).with_context(|| {
+            format!(
+                "Failed to serialize library index to {}",
+                tmp_path.display()
+            )
+        })?;
+        let mut writer = File::create(&tmp_path).with_context(|| {
             format!(
                 "Failed to write library index file at {}",
-                index_path.display()
+                tmp_path.display()
             )
         })?;
-        serde_json::to_writer_pretty(writer, &self).with_context(|| {
+        writer.write_all(&contents).with_context(|| {
             format!(
-                "Failed to serialize library index to {}",
+                "Failed to write library index file at {}",
+                tmp_path.display()
+            )
+        })?;
+        writer.sync_all().with_context(|| {
+            format!(
+                "Failed to write library index file at {}",
+                tmp_path.display()
+            )
+        })?;
+        fs::rename(&tmp_path, index_path).with_context(|| {
+            format!(
+                "Failed to replace library index file at {}",
                 index_path.display()
             )
+        })?;
+
+        // If we are interrupted before the checksum is written, the index is saved again when
+        // the write-ahead log is replayed.
+        let checksum = sha256::hash_reader(contents.as_slice())
+            .context("Failed to compute checksum of library index")?;
+        let checksum_path = index_checksum_path(index_path);
+        fs::write(&checksum_path, checksum.to_string()).with_context(|| {
+            format!(
+                "Failed to write library index checksum to {}",
+                checksum_path.display()
+            )
         })
     }
 }
 
-/// Results from [`LibraryIndex::find_hash()`] and [`LibraryIndex::find_hash_mut()`].
+/// Read the contents of the index file at `index_path`.
+///
+/// The whole file is read at once, since parsing from memory is much faster than parsing from a
+/// reader.
+fn read_index_file(index_path: &Path) -> anyhow::Result<Vec<u8>> {
+    fs::read(index_path).with_context(|| {
+        format!(
+            "Failed to open library index file at {}",
+            index_path.display()
+        )
+    })
+}
+
+/// Return the fields of the index file at `index_path` whose values are dropped when burette reads
+/// the index.
+///
+/// Fields of index entries are found by reading each entry and writing it again. Fields that are
+/// missing afterwards and had a value other than `null`, `false`, zero or an empty string, list or
+/// object are reported. Fields with such values are not, since they are the defaults of optional
+/// fields, and ignoring them loses no data.
+///
+/// # Errors
+///
+/// An error is returned if the index file cannot be read.
+fn unknown_index_fields(index_path: &Path) -> anyhow::Result<Vec<UnknownField>> {
+    /// Return true if `value` carries no data.
+    fn is_empty(value: &Value) -> bool {
+        match value {
+            Value::Null | Value::Bool(false) => true,
+            Value::Number(number) => number.as_f64() == Some(0.0),
+            Value::String(s) => s.is_empty(),
+            Value::Array(array) => array.is_empty(),
+            Value::Object(object) => object.is_empty(),
+            Value::Bool(true) => false,
+        }
+    }
+
+    let contents = read_index_file(index_path)?;
+    let index: Value = serde_json::from_slice(&contents)
+        .with_context(|| format!("Failed to read library index from {}", index_path.display()))?;
+
+    let mut unknown = Vec::new();
+    let entries = match index {
+        Value::Array(entries) => entries,
+        Value::Object(mut index) => {
+            let entries = match index.remove("documents") {
+                Some(Value::Array(entries)) => entries,
+                _ => Vec::new(),
+            };
+            unknown.extend(
+                index
+                    .into_iter()
+                    .filter(|(name, value)| name != "schema_version" && !is_empty(value))
+                    .map(|(name, _)| UnknownField { hash: None, name }),
+            );
+            entries
+        }
+        _ => Vec::new(),
+    };
+    for entry in entries {
+        let Ok(parsed) = serde_json::from_value::<IndexEntry>(entry.clone()) else {
+            continue;
+        };
+        let Ok(Value::Object(known)) = serde_json::to_value(&parsed) else {
+            continue;
+        };
+        let Value::Object(entry) = entry else {
+            continue;
+        };
+        unknown.extend(
+            entry
+                .into_iter()
+                .filter(|(name, value)| !known.contains_key(name) && !is_empty(value))
+                .map(|(name, _)| UnknownField {
+                    hash: Some(parsed.hash),
+                    name,
+                }),
+        );
+    }
+    Ok(unknown)
+}
+
+/// Return the path of the checksum file of the index file at `index_path`.
+fn index_checksum_path(index_path: &Path) -> PathBuf {
+    index_path.with_file_name(INDEX_CHECKSUM_FILE)
+}
+
+/// Return true if the index file at `index_path` matches its checksum.
+///
+/// Libraries created by older versions of burette have no checksum file. In this case, the index
+/// is assumed to be unmodified, and the checksum file is created the next time the index is
+/// saved.
+fn index_checksum_matches(index_path: &Path) -> anyhow::Result<bool> {
+    Ok(index_checksum_status(index_path)? != Some(false))
+}
+
+/// Return whether the index file at `index_path` matches its checksum, or `None` if there is no
+/// checksum file.
+fn index_checksum_status(index_path: &Path) -> anyhow::Result<Option<bool>> {
+    let checksum_path = index_checksum_path(index_path);
+    let checksum = match fs::read_to_string(&checksum_path) {
+        Ok(checksum) => checksum,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(error).context(format!(
+                "Failed to read library index checksum from {}",
+                checksum_path.display()
+            ))
+        }
+    };
+    let file = File::open(index_path).with_context(|| {
+        format!(
+            "Failed to open library index file at {}",
+            index_path.display()
+        )
+    })?;
+    let actual = sha256::hash_reader(file).with_context(|| {
+        format!(
+            "Failed to compute checksum of library index at {}",
+            index_path.display()
+        )
+    })?;
+    Ok(Some(
+        checksum.trim().parse::<sha256::Hash>().ok() == Some(actual),
+    ))
+}
+
+/// Results from [`LibraryIndex::find_hash_mut()`].
 #[derive(Debug)]
 enum FindHash<E> {
     /// No document matched the hash prefix.
@@ -1006,14 +3199,46 @@ enum FindHash<E> {
 impl<E> FindHash<E> {
     pub fn found_or_error(self) -> anyhow::Result<E> {
         match self {
-            FindHash::NotFound => Err(anyhow!("No document found with hash prefix")),
+            FindHash::NotFound => {
+                Err(LookupError::NotFound("No document found with hash prefix".to_owned()).into())
+            }
             FindHash::Found(entry) => Ok(entry),
-            FindHash::Ambiguous => Err(anyhow!("Multiple documents found matching hash prefix")),
+            FindHash::Ambiguous => Err(LookupError::Ambiguous(
+                "Multiple documents found matching hash prefix".to_owned(),
+            )
+            .into()),
+        }
+    }
+}
+
+/// The documents matching an identifier, see [`LibraryIndex::find_matches()`].
+#[derive(Debug)]
+struct IdentifierMatches<'a> {
+    /// Description of the identifier for error messages, e.g. "ISBN 9780141439570".
+    description: String,
+    entries: Vec<&'a IndexEntry>,
+}
+
+impl<'a> IdentifierMatches<'a> {
+    /// Return the matching document, or an error if no or multiple documents match.
+    fn found_or_error(self) -> anyhow::Result<&'a IndexEntry> {
+        match self.entries.as_slice() {
+            [] => Err(LookupError::NotFound(format!(
+                "No document found with {}",
+                self.description
+            ))
+            .into()),
+            [entry] => Ok(entry),
+            _ => Err(LookupError::Ambiguous(format!(
+                "Multiple documents found matching {}",
+                self.description
+            ))
+            .into()),
         }
     }
 }
 
-/// Results from [`LibraryIndex::find_all_hashes()`].
+/// Results from [`LibraryIndex::find_all()`].
 #[derive(Debug)]
 struct HashMatches<'a> {
     ambiguous: Vec<AmbiguousHashMatch<'a>>,
@@ -1022,7 +3247,7 @@ struct HashMatches<'a> {
 }
 
 /// Information about a hash prefix that matched multiple documents in the index.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct AmbiguousHashMatch<'a> {
     hash_prefix: &'a str,
     matches: Vec<sha256::Hash>,
@@ -1054,4 +3279,271 @@ pub struct DocMetadata {
     pub file_format: FileFormat,
     /// DOI of the document.
     pub doi: Option<String>,
+    /// Type of the document, e.g. book or article. Unknown for documents added by older
+    /// versions of burette.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_type: Option<DocType>,
+    /// License or other usage rights of the document, e.g. `CC-BY-4.0`, `public-domain` or
+    /// `proprietary`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rights: Option<String>,
+    /// Journal or conference proceedings the document was published in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal: Option<String>,
+    /// Volume of the journal or proceedings the document was published in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<String>,
+    /// Issue of the journal the document was published in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+    /// Page range of the document within the journal or proceedings, e.g. `123-145`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pages: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            is_compatible_version, is_reserved_in_file_name, strip_verbatim_prefix,
+            unknown_index_fields, DocMetadata, IndexEntry, LibraryIndex, INDEX_SCHEMA_VERSION,
+        },
+        crate::{
+            sha256,
+            testing::{self, TempLibrary},
+            FileFormat,
+        },
+        proptest::{prelude::any, prop_assert_eq, proptest, sample, test_runner::TestCaseError},
+        std::fs,
+    };
+
+    pub(super) fn moby_dick() -> IndexEntry {
+        IndexEntry {
+            hash: sha256::hash_reader(&b"Call me Ishmael."[..]).expect("Reading cannot fail"),
+            metadata: DocMetadata {
+                title: "Moby Dick; Or, The Whale".to_owned(),
+                authors: vec!["Herman Melville".to_owned()],
+                isbns: Vec::new(),
+                file_format: FileFormat::Epub,
+                doi: None,
+                doc_type: None,
+                rights: None,
+                journal: None,
+                volume: None,
+                issue: None,
+                pages: None,
+            },
+            size_bytes: None,
+            source_filename: None,
+            source_path: None,
+            source_modified: None,
+            aliases: Vec::new(),
+            volumes: Vec::new(),
+            archived: false,
+            last_accessed: None,
+            access_count: 0,
+            former_hashes: Vec::new(),
+            md5: None,
+            reading_progress: None,
+            reading_status: None,
+            rating: None,
+            finished_reading: None,
+            annotations: Vec::new(),
+            preservation: None,
+        }
+    }
+
+    #[test]
+    fn file_name_template() {
+        let entry = moby_dick();
+        assert_eq!(
+            entry
+                .file_name_from_template("{authors} - {title}.{ext}")
+                .expect("Valid template"),
+            "Herman Melville - Moby Dick; Or, The Whale.epub"
+        );
+    }
+
+    #[test]
+    fn file_name_template_separator_in_value() {
+        let mut entry = moby_dick();
+        entry.set_title("Either/Or".to_owned());
+        assert_eq!(
+            entry
+                .file_name_from_template("{title}.{ext}")
+                .expect("Valid template"),
+            "Either_Or.epub"
+        );
+    }
+
+    #[test]
+    fn file_name_template_invalid() {
+        let entry = moby_dick();
+        assert!(entry.file_name_from_template("{year}.{ext}").is_err());
+        assert!(entry.file_name_from_template("{title.{ext}").is_err());
+        assert!(entry.file_name_from_template("books/{title}").is_err());
+        assert!(entry.file_name_from_template("").is_err());
+    }
+
+    #[test]
+    fn query_borrowed_index() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let hash = library
+            .add(testing::metadata("Borrowed", FileFormat::Pdf))
+            .expect("Failed to add document");
+        let hash_string = hash.to_string();
+        let (len, title, found) = library
+            .with_index(|index| {
+                let title = index.get(&hash).map(|entry| entry.title().to_owned());
+                let found = index
+                    .find_document(&hash_string[..10], false)
+                    .map(IndexEntry::hash)
+                    .copied()
+                    .ok();
+                (index.len(), title, found)
+            })
+            .expect("Failed to read index");
+        assert_eq!(len, 3);
+        assert_eq!(title.as_deref(), Some("Borrowed"));
+        assert_eq!(found, Some(hash));
+    }
+
+    #[test]
+    fn unknown_fields_in_index() {
+        let library = TempLibrary::with_documents(1).expect("Failed to create library");
+        let index_path = library.index_path();
+        let index = fs::read_to_string(&index_path).expect("Failed to read index");
+        let index = index
+            .replacen(
+                r#""schema_version": 1,"#,
+                r#""schema_version": 1, "comment": "mine", "empty": null,"#,
+                1,
+            )
+            .replacen(
+                r#""title":"#,
+                r#""titel": "Typo", "archived": false, "title":"#,
+                1,
+            );
+        fs::write(&index_path, index).expect("Failed to write index");
+
+        let unknown = unknown_index_fields(&index_path).expect("Failed to read index");
+        let unknown: Vec<_> = unknown.iter().map(ToString::to_string).collect();
+        let hash = library
+            .documents()
+            .expect("Failed to read index")
+            .next()
+            .expect("Library has a document")
+            .hash()
+            .to_short_string();
+        assert_eq!(
+            unknown,
+            [
+                "Index has unknown field comment".to_owned(),
+                format!("{hash} has unknown field titel"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reserved_file_name_characters() {
+        assert!(is_reserved_in_file_name('/', false));
+        assert!(is_reserved_in_file_name('\n', false));
+        assert!(!is_reserved_in_file_name(':', false));
+        assert!(is_reserved_in_file_name(':', true));
+        assert!(is_reserved_in_file_name('\\', true));
+        assert!(!is_reserved_in_file_name(';', true));
+    }
+
+    #[test]
+    fn verbatim_prefix() {
+        assert_eq!(
+            strip_verbatim_prefix(r"\\?\C:\Users\ishmael\moby.epub"),
+            r"C:\Users\ishmael\moby.epub"
+        );
+        assert_eq!(
+            strip_verbatim_prefix(r"\\?\UNC\server\share\moby.epub"),
+            r"\\?\UNC\server\share\moby.epub"
+        );
+        assert_eq!(
+            strip_verbatim_prefix("/home/ishmael/moby.epub"),
+            "/home/ishmael/moby.epub"
+        );
+    }
+
+    #[test]
+    fn compatible_version_same() {
+        assert!(is_compatible_version("0.1.1", "0.1.1"));
+    }
+
+    #[test]
+    fn compatible_version_patch() {
+        assert!(is_compatible_version("0.1.0", "0.1.1"));
+        assert!(is_compatible_version("1.2.9", "1.2.0"));
+    }
+
+    #[test]
+    fn incompatible_version_minor() {
+        assert!(!is_compatible_version("0.0.0", "0.1.1"));
+        assert!(!is_compatible_version("1.3.0", "1.2.0"));
+    }
+
+    #[test]
+    fn incompatible_version_major() {
+        assert!(!is_compatible_version("2.1.0", "1.1.0"));
+    }
+
+    #[test]
+    fn index_schema_versions() {
+        let entry = serde_json::to_string(&moby_dick()).expect("Failed to serialize entry");
+        let unversioned = format!("[{entry}]");
+        let current = format!(
+            r#"{{"schema_version": {INDEX_SCHEMA_VERSION}, "documents": [{entry}], "later": 1}}"#
+        );
+        for json in [unversioned, current] {
+            let index: LibraryIndex = serde_json::from_str(&json).expect("Failed to read index");
+            assert_eq!(index.len(), 1);
+        }
+
+        let newer = format!(
+            r#"{{"schema_version": {}, "documents": {{}}}}"#,
+            INDEX_SCHEMA_VERSION + 1
+        );
+        let error = serde_json::from_str::<LibraryIndex>(&newer).expect_err("Newer schema");
+        assert!(error.to_string().contains("only supports up to"));
+        assert!(serde_json::from_str::<LibraryIndex>(r#"{"documents": []}"#).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn deserialize_arbitrary_index(s in "\\PC*") {
+            let _ = serde_json::from_str::<LibraryIndex>(&s);
+        }
+
+        #[test]
+        fn deserialize_modified_index(position in any::<sample::Index>(), c in any::<char>()) {
+            let to_json = |index: &LibraryIndex| {
+                serde_json::to_string(index).map_err(|error| TestCaseError::fail(error.to_string()))
+            };
+            let json = to_json(&LibraryIndex {
+                documents: vec![moby_dick()],
+            })?;
+            let mut chars: Vec<char> = json.chars().collect();
+            let position = position.index(chars.len());
+            chars[position] = c;
+            let modified: String = chars.into_iter().collect();
+
+            // Whatever is accepted must survive another round trip unchanged.
+            if let Ok(index) = serde_json::from_str::<LibraryIndex>(&modified) {
+                let json = to_json(&index)?;
+                let reparsed = serde_json::from_str::<LibraryIndex>(&json)
+                    .map_err(|error| TestCaseError::fail(error.to_string()))?;
+                prop_assert_eq!(to_json(&reparsed)?, json);
+            }
+        }
+    }
+
+    #[test]
+    fn incompatible_version_malformed() {
+        assert!(!is_compatible_version("not a version", "0.1.1"));
+    }
 }
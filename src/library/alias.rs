@@ -0,0 +1,162 @@
+//! Aliases, short names that can be used to refer to documents.
+
+use {
+    super::{LibraryIndex, LookupError, Operation},
+    crate::{sha256, Isbn13, Library},
+    anyhow::bail,
+};
+
+/// Check that `alias` can be used as an alias.
+///
+/// Aliases may only contain ASCII letters, digits, `-`, `_` and `.`. They must not look like an
+/// ISBN or a hash prefix, since those identifiers would be ambiguous otherwise.
+fn validate_alias(alias: &str) -> anyhow::Result<()> {
+    if alias.is_empty() {
+        bail!("Alias cannot be empty");
+    }
+    if let Some(c) = alias
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '_' | '.'))
+    {
+        bail!("Invalid character {:?} in alias {}", c, alias);
+    }
+    if alias.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!(
+            "Alias {} could be confused with a hash prefix, use at least one character that is \
+            not a hexadecimal digit",
+            alias
+        );
+    }
+    if alias.parse::<Isbn13>().is_ok() {
+        bail!("Alias {} could be confused with an ISBN", alias);
+    }
+    Ok(())
+}
+
+impl Library {
+    /// Assign the alias `alias` to the document matching `identifier` and return the hash of the
+    /// document.
+    ///
+    /// Afterwards, the alias can be used to refer to the document wherever an identifier is
+    /// accepted (see [`Library::find_document()`]). A document can have multiple aliases, but
+    /// each alias belongs to a single document. Archived documents are considered as well.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - The alias is invalid. Aliases may only contain ASCII letters, digits, `-`, `_` and `.`,
+    ///   and must not look like a hash prefix or an ISBN.
+    /// - The alias already belongs to another document.
+    /// - `identifier` does not match exactly one document.
+    /// - The index file cannot be read or written.
+    pub fn set_alias(&self, alias: &str, identifier: &str) -> anyhow::Result<sha256::Hash> {
+        validate_alias(alias)?;
+
+        let index_path = self.index_path();
+        let mut index = LibraryIndex::open(&index_path)?;
+
+        let hash = *index.find_document(identifier, true)?.hash();
+        if let Some(other) = index
+            .documents
+            .iter()
+            .find(|entry| entry.aliases.iter().any(|other| other == alias))
+        {
+            if other.hash == hash {
+                return Ok(hash);
+            }
+            bail!(
+                "Alias {} already belongs to {}: {}",
+                alias,
+                other.hash.to_short_string(),
+                other.title()
+            );
+        }
+
+        if let Some(entry) = index.documents.iter_mut().find(|entry| entry.hash == hash) {
+            entry.aliases.push(alias.to_owned());
+        }
+        self.update_index(&index)?;
+        self.log_event(Operation::Edit, vec![hash]);
+
+        Ok(hash)
+    }
+
+    /// Remove the alias `alias` and return the hash of the document it belonged to.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if no document has the alias or if the index file cannot be read
+    /// or written.
+    pub fn remove_alias(&self, alias: &str) -> anyhow::Result<sha256::Hash> {
+        let index_path = self.index_path();
+        let mut index = LibraryIndex::open(&index_path)?;
+
+        let Some(entry) = index
+            .documents
+            .iter_mut()
+            .find(|entry| entry.aliases.iter().any(|other| other == alias))
+        else {
+            return Err(LookupError::NotFound(format!("No document has the alias {alias}")).into());
+        };
+        entry.aliases.retain(|other| other != alias);
+        let hash = entry.hash;
+
+        self.update_index(&index)?;
+        self.log_event(Operation::Edit, vec![hash]);
+
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::validate_alias,
+        crate::{testing::TempLibrary, LookupError},
+    };
+
+    #[test]
+    fn valid_aliases() {
+        assert!(validate_alias("sicp").is_ok());
+        assert!(validate_alias("moby-dick_2.0").is_ok());
+        assert!(validate_alias("").is_err());
+        assert!(validate_alias("moby dick").is_err());
+        assert!(validate_alias("cafe").is_err());
+        assert!(validate_alias("1234").is_err());
+        assert!(validate_alias("978-0-262-51087-5").is_err());
+    }
+
+    #[test]
+    fn resolve_alias() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let hashes: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| *entry.hash())
+            .collect();
+
+        library
+            .set_alias("second", &hashes[1].to_string())
+            .expect("Failed to set alias");
+        let found = library
+            .find_document("second", false)
+            .expect("Alias not found");
+        assert_eq!(*found.hash(), hashes[1]);
+        assert_eq!(found.aliases().collect::<Vec<_>>(), ["second"]);
+
+        // Each alias belongs to a single document.
+        assert!(library.set_alias("second", &hashes[0].to_string()).is_err());
+
+        assert_eq!(
+            library.remove_alias("second").expect("Alias not found"),
+            hashes[1]
+        );
+        let error = library
+            .remove_alias("second")
+            .expect_err("Alias was removed");
+        assert!(matches!(
+            error.downcast_ref::<LookupError>(),
+            Some(LookupError::NotFound(_))
+        ));
+    }
+}
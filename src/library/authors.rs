@@ -0,0 +1,182 @@
+//! Listing and merging the authors of the documents in the library.
+
+use {
+    super::{LibraryIndex, Operation},
+    crate::{sha256, DocumentSummary, Library},
+    anyhow::bail,
+    std::{cmp::Reverse, collections::HashMap},
+};
+
+impl Library {
+    /// Return the distinct authors of the documents in the library, together with the number of
+    /// documents by each author.
+    ///
+    /// Authors are ordered by the number of documents, most frequent first, and by name if the
+    /// numbers are equal. Spellings that differ in any way are counted separately, so variant
+    /// spellings of the same name show up next to each other when sorted by name, and can be
+    /// merged with [`Library::merge_authors()`]. Archived documents are only counted if
+    /// `include_archived` is true.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the index cannot be read.
+    pub fn author_counts(&self, include_archived: bool) -> anyhow::Result<Vec<(String, usize)>> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for doc in self
+            .document_summaries()?
+            .filter(|doc| include_archived || !doc.is_archived())
+        {
+            for author in doc.authors() {
+                // Avoid allocating for authors that were counted before.
+                if let Some(count) = counts.get_mut(author) {
+                    *count += 1;
+                } else {
+                    counts.insert(author.to_owned(), 1);
+                }
+            }
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_unstable_by(|(a, a_count), (b, b_count)| {
+            (Reverse(a_count), a).cmp(&(Reverse(b_count), b))
+        });
+        Ok(counts)
+    }
+
+    /// Return the documents by `author`, in index order.
+    ///
+    /// The name is compared exactly, after removing leading and trailing whitespace, in the same
+    /// way as by [`Library::merge_authors()`]. Archived documents are only returned if
+    /// `include_archived` is true.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the index cannot be read.
+    pub fn documents_by_author(
+        &self,
+        author: &str,
+        include_archived: bool,
+    ) -> anyhow::Result<Vec<DocumentSummary>> {
+        let author = author.trim();
+        Ok(self
+            .document_summaries()?
+            .filter(|doc| include_archived || !doc.is_archived())
+            .filter(|doc| doc.authors().any(|name| name.trim() == author))
+            .collect())
+    }
+
+    /// Replace the author names `variants` by `canonical` in all documents, and return the hashes
+    /// of the documents that changed.
+    ///
+    /// Names are compared exactly, after removing leading and trailing whitespace. If a document
+    /// lists both a variant and the canonical name, or several variants, the canonical name is
+    /// kept once, at the position of its first occurrence. All documents are updated in a single
+    /// change of the index. Archived documents are updated as well.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if `canonical` is empty or if the index cannot be read or written.
+    pub fn merge_authors(
+        &self,
+        variants: &[&str],
+        canonical: &str,
+    ) -> anyhow::Result<Vec<sha256::Hash>> {
+        let canonical = canonical.trim();
+        if canonical.is_empty() {
+            bail!("Author name cannot be empty");
+        }
+
+        let index_path = self.index_path();
+        let mut index = LibraryIndex::open(&index_path)?;
+
+        let mut changed = Vec::new();
+        for entry in &mut index.documents {
+            let authors = &mut entry.metadata.authors;
+            if !authors
+                .iter()
+                .any(|author| variants.contains(&author.trim()))
+            {
+                continue;
+            }
+            let mut merged: Vec<String> = Vec::with_capacity(authors.len());
+            for author in authors.drain(..) {
+                let author = if variants.contains(&author.trim()) {
+                    canonical.to_owned()
+                } else {
+                    author
+                };
+                if author.trim() != canonical || !merged.iter().any(|a| a.trim() == canonical) {
+                    merged.push(author);
+                }
+            }
+            *authors = merged;
+            changed.push(entry.hash);
+        }
+
+        if !changed.is_empty() {
+            self.update_index(&index)?;
+            self.log_event(Operation::Edit, changed.clone());
+        }
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        testing::{self, TempLibrary},
+        FileFormat,
+    };
+
+    #[test]
+    fn count_and_merge_authors() {
+        let library = TempLibrary::new().expect("Failed to create library");
+        for (title, authors) in [
+            ("A", vec!["J. Smith"]),
+            ("B", vec!["John Smith", "Jane Doe"]),
+            ("C", vec!["J. Smith", "Smith, John", "John Smith"]),
+        ] {
+            let mut metadata = testing::metadata(title, FileFormat::Pdf);
+            metadata.authors = authors.into_iter().map(str::to_owned).collect();
+            library.add(metadata).expect("Failed to add document");
+        }
+
+        let counts = library.author_counts(false).expect("Failed to count");
+        assert_eq!(
+            counts,
+            [
+                ("J. Smith".to_owned(), 2),
+                ("John Smith".to_owned(), 2),
+                ("Jane Doe".to_owned(), 1),
+                ("Smith, John".to_owned(), 1),
+            ]
+        );
+        let titles = |author| -> Vec<String> {
+            library
+                .documents_by_author(author, false)
+                .expect("Failed to read index")
+                .iter()
+                .map(|doc| doc.title().to_owned())
+                .collect()
+        };
+        assert_eq!(titles(" J. Smith"), ["A", "C"]);
+        assert!(titles("Smith").is_empty());
+
+        let changed = library
+            .merge_authors(&["J. Smith", "Smith, John"], "John Smith")
+            .expect("Failed to merge");
+        assert_eq!(changed.len(), 2);
+        let counts = library.author_counts(false).expect("Failed to count");
+        assert_eq!(
+            counts,
+            [("John Smith".to_owned(), 3), ("Jane Doe".to_owned(), 1)]
+        );
+
+        let authors: Vec<Vec<String>> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| entry.authors().map(str::to_owned).collect())
+            .collect();
+        assert_eq!(authors[2], ["John Smith"]);
+        assert!(library.merge_authors(&["Jane Doe"], " ").is_err());
+    }
+}
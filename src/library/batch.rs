@@ -0,0 +1,390 @@
+//! Applying a list of operations to the library as a single change.
+//!
+//! All operations are first applied to the index in memory, so that an operation that fails
+//! leaves the library unchanged. Only then are the files of added documents placed in the
+//! document store, and the index is updated once for the whole list.
+
+use {
+    super::{
+        check_expected_hash, copy_to_store, disk_space, file_size, modification_time,
+        wal::{remove_documents, trash_documents},
+        DocMetadata, IndexEntry, LibraryIndex, Operation,
+    },
+    crate::{md5, sha256, DocType, Isbn13, Library},
+    anyhow::{anyhow, Context},
+    serde::{Deserialize, Deserializer},
+    std::{
+        collections::HashSet,
+        fs::{self, File},
+        path::PathBuf,
+    },
+};
+
+/// An operation of a batch applied by [`Library::apply_operations()`].
+///
+/// Operations are read from JSON objects whose `op` field names the operation, e.g.
+/// `{"op": "remove", "document": "e611fe8"}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    /// Add the file at `path` with the given metadata.
+    Add {
+        /// Path of the file to add.
+        path: PathBuf,
+        /// SHA-256 hash the file must have, if known.
+        #[serde(default)]
+        hash: Option<sha256::Hash>,
+        /// Metadata of the document.
+        #[serde(flatten)]
+        metadata: DocMetadata,
+    },
+    /// Change some of the metadata of a document.
+    Edit {
+        /// Identifier of the document (hash prefix, alias, DOI or ISBN).
+        document: String,
+        /// The fields to change.
+        #[serde(flatten)]
+        changes: MetadataChanges,
+    },
+    /// Remove a document from the library.
+    Remove {
+        /// Identifier of the document (hash prefix, alias, DOI or ISBN).
+        document: String,
+    },
+}
+
+/// Changes to the metadata of a document, as made by [`BatchOperation::Edit`].
+///
+/// Fields that are `None` are left unchanged. Optional fields of the metadata are cleared by
+/// setting them to `null` in JSON, which is read as `Some(None)`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetadataChanges {
+    /// New title of the document.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// New authors of the document.
+    #[serde(default)]
+    pub authors: Option<Vec<String>>,
+    /// New ISBNs of the document.
+    #[serde(default)]
+    pub isbns: Option<Vec<Isbn13>>,
+    /// New DOI of the document.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub doi: Option<Option<String>>,
+    /// New type of the document.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub doc_type: Option<Option<DocType>>,
+    /// New usage rights of the document.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub rights: Option<Option<String>>,
+    /// New journal of the document.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub journal: Option<Option<String>>,
+    /// New volume of the document.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub volume: Option<Option<String>>,
+    /// New issue of the document.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub issue: Option<Option<String>>,
+    /// New page range of the document.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub pages: Option<Option<String>>,
+}
+
+/// Helper for `#[serde(deserialize_with)]` that tells a field set to `null` apart from a missing
+/// field, which is `None` by `#[serde(default)]`.
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl MetadataChanges {
+    /// Apply the changes to `entry`.
+    fn apply(self, entry: &mut IndexEntry) {
+        let metadata = &mut entry.metadata;
+        if let Some(title) = self.title {
+            metadata.title = title;
+        }
+        if let Some(authors) = self.authors {
+            metadata.authors = authors;
+        }
+        if let Some(isbns) = self.isbns {
+            metadata.isbns = isbns;
+        }
+        if let Some(doi) = self.doi {
+            metadata.doi = doi;
+        }
+        if let Some(doc_type) = self.doc_type {
+            metadata.doc_type = doc_type;
+        }
+        if let Some(rights) = self.rights {
+            metadata.rights = rights;
+        }
+        if let Some(journal) = self.journal {
+            metadata.journal = journal;
+        }
+        if let Some(volume) = self.volume {
+            metadata.volume = volume;
+        }
+        if let Some(issue) = self.issue {
+            metadata.issue = issue;
+        }
+        if let Some(pages) = self.pages {
+            metadata.pages = pages;
+        }
+    }
+}
+
+/// A file to place in the document store once all operations have been checked.
+struct PendingFile {
+    path: PathBuf,
+    hash: sha256::Hash,
+}
+
+impl Library {
+    /// Apply `operations` to the library in order, as a single change.
+    ///
+    /// Either all operations are applied, or none of them. Documents are identified in the same
+    /// way as by [`Library::find_document()`], including archived documents, and later operations
+    /// see the changes of earlier ones, e.g. a document added by the batch can be edited by a
+    /// later operation. Documents are added without asking for confirmation if similar documents
+    /// exist, unless the library settings reject them (see [`LibrarySettings::duplicate_titles`]).
+    ///
+    /// [`LibrarySettings::duplicate_titles`]: crate::LibrarySettings::duplicate_titles
+    ///
+    /// If `dry_run` is true, the operations are only checked and the library is not changed.
+    /// Returns each operation that was applied, or would have been applied, with the hash of the
+    /// document it applies to.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any operation cannot be applied, e.g. because a file cannot be read
+    /// or is already in the library, or because a document cannot be found. The error names the
+    /// position of the operation in the list. An error is also returned if the index cannot be
+    /// read or written, or if the files cannot be placed in the document store.
+    pub fn apply_operations(
+        &self,
+        operations: Vec<BatchOperation>,
+        dry_run: bool,
+    ) -> anyhow::Result<Vec<(Operation, sha256::Hash)>> {
+        let mut index = LibraryIndex::open(&self.index_path())?;
+
+        let mut applied = Vec::with_capacity(operations.len());
+        let mut pending_files = Vec::new();
+        for (number, operation) in operations.into_iter().enumerate() {
+            let result =
+                self.apply_operation(&mut index, operation, &mut pending_files, &mut applied);
+            result.with_context(|| format!("Failed to apply operation {}", number + 1))?;
+        }
+        if dry_run {
+            return Ok(applied);
+        }
+
+        // Documents removed by the batch may have been added again by a later operation.
+        let present: HashSet<_> = index.documents.iter().map(|entry| entry.hash).collect();
+        let removed: Vec<_> = applied
+            .iter()
+            .filter(|(operation, hash)| *operation == Operation::Remove && !present.contains(hash))
+            .map(|&(_, hash)| hash)
+            .collect();
+        pending_files.retain(|file| present.contains(&file.hash));
+        for entry in &mut index.documents {
+            entry.volumes.retain(|hash| present.contains(hash));
+        }
+
+        let stored = self.store_pending_files(&index, &pending_files)?;
+        if let Err(error) = self.begin_index_update(&index, &[], &removed) {
+            // As in `add_document_checked`, remove the documents we just stored on a best-effort
+            // basis and propagate the original error.
+            let _ = remove_documents(&self.path, &stored);
+            return Err(error);
+        }
+        trash_documents(&self.path, &removed)?;
+        self.finish_index_update(&index)?;
+
+        for operation in [Operation::Add, Operation::Edit, Operation::Remove] {
+            let hashes: Vec<_> = applied
+                .iter()
+                .filter(|(applied, _)| *applied == operation)
+                .map(|&(_, hash)| hash)
+                .collect();
+            if !hashes.is_empty() {
+                self.log_event(operation, hashes);
+            }
+        }
+        self.record_digests(&stored)
+            .context("Documents were added, but failed to record their digests")?;
+        Ok(applied)
+    }
+
+    /// Apply a single operation of [`Library::apply_operations()`] to `index`.
+    ///
+    /// Files of added documents are appended to `pending_files` instead of being stored, and the
+    /// operation is appended to `applied`.
+    fn apply_operation(
+        &self,
+        index: &mut LibraryIndex,
+        operation: BatchOperation,
+        pending_files: &mut Vec<PendingFile>,
+        applied: &mut Vec<(Operation, sha256::Hash)>,
+    ) -> anyhow::Result<()> {
+        match operation {
+            BatchOperation::Add {
+                path,
+                hash: expected,
+                metadata,
+            } => {
+                let file = File::open(&path)
+                    .with_context(|| format!("Failed to open file at {}", path.display()))?;
+                let size = file_size(&file, &path)?;
+                let modified = modification_time(&file);
+                let hash = sha256::hash_reader(file)
+                    .with_context(|| format!("Failed to hash file {}", path.display()))?;
+                check_expected_hash(&path, hash, expected)?;
+                let md5 = if self.records_md5() {
+                    let file = File::open(&path)
+                        .with_context(|| format!("Failed to open file at {}", path.display()))?;
+                    Some(md5::hash_reader(file)?)
+                } else {
+                    None
+                };
+                index.check_duplicates(hash, &metadata)?;
+                self.check_similar(index, &metadata, |_| Ok(true))?;
+
+                let mut entry = IndexEntry::added(hash, metadata, &path, modified);
+                entry.size_bytes = Some(size);
+                entry.md5 = md5;
+                index.documents.push(entry);
+                pending_files.push(PendingFile { path, hash });
+                applied.push((Operation::Add, hash));
+            }
+            BatchOperation::Edit { document, changes } => {
+                let hash = *index.find_document(&document, true)?.hash();
+                let entry = index
+                    .documents
+                    .iter_mut()
+                    .find(|entry| entry.hash == hash)
+                    .ok_or_else(|| anyhow!("Document {hash} disappeared from the index"))?;
+                changes.apply(entry);
+                applied.push((Operation::Edit, hash));
+            }
+            BatchOperation::Remove { document } => {
+                let hash = *index.find_document(&document, true)?.hash();
+                index.documents.retain(|entry| entry.hash != hash);
+                applied.push((Operation::Remove, hash));
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy the files of the documents added by [`Library::apply_operations()`] to the document
+    /// store, and return their hashes.
+    ///
+    /// If a file cannot be copied, the files copied before are removed again.
+    fn store_pending_files(
+        &self,
+        index: &LibraryIndex,
+        pending_files: &[PendingFile],
+    ) -> anyhow::Result<Vec<sha256::Hash>> {
+        let document_store_dir = self.document_store_dir();
+        fs::create_dir_all(&document_store_dir).with_context(|| {
+            format!(
+                "Failed to create document store directory at {}",
+                document_store_dir.display()
+            )
+        })?;
+        let required = pending_files
+            .iter()
+            .filter_map(|file| index.get(&file.hash)?.size_bytes)
+            .sum();
+        disk_space::check_available_space(&document_store_dir, required)?;
+
+        let mut stored = Vec::with_capacity(pending_files.len());
+        for file in pending_files {
+            let store_path = document_store_dir.join(file.hash.to_string());
+            if let Err(error) = copy_to_store(&file.path, &store_path) {
+                let _ = remove_documents(&self.path, &stored);
+                return Err(error);
+            }
+            stored.push(file.hash);
+        }
+        Ok(stored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::BatchOperation,
+        crate::{
+            testing::{self, TempLibrary},
+            FileFormat, Operation,
+        },
+        std::fs,
+    };
+
+    /// Parse a list of operations from JSON.
+    fn operations(json: &str) -> Vec<BatchOperation> {
+        serde_json::from_str(json).expect("Valid operations")
+    }
+
+    #[test]
+    fn apply_all_or_nothing() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let hashes: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| entry.hash().to_string())
+            .collect();
+        let path = library.path().join("new.pdf");
+        fs::write(&path, testing::synthetic_document("New", FileFormat::Pdf))
+            .expect("Failed to write document");
+        let json = format!(
+            r#"[
+                {{"op": "add", "path": {path:?}, "title": "New", "authors": [], "isbns": [],
+                  "file_format": "application/pdf", "doi": null}},
+                {{"op": "edit", "document": "{first}", "title": "Edited", "rights": null}},
+                {{"op": "remove", "document": "{second}"}},
+                {{"op": "remove", "document": "{second}"}}
+            ]"#,
+            first = &hashes[0][..10],
+            second = &hashes[1][..10],
+        );
+
+        // The second removal fails, so nothing is changed.
+        let error = library
+            .apply_operations(operations(&json), false)
+            .expect_err("Document was already removed");
+        assert!(format!("{error:#}").starts_with("Failed to apply operation 4"));
+        assert_eq!(library.documents().expect("Failed to read").count(), 2);
+        assert!(library.validate().expect("Failed to validate").is_valid());
+
+        let json = json.replacen(
+            &format!(r#"{{"op": "remove", "document": "{}"}},"#, &hashes[1][..10]),
+            "",
+            1,
+        );
+        let applied = library
+            .apply_operations(operations(&json), true)
+            .expect("Failed to apply");
+        assert_eq!(applied.len(), 3);
+        assert_eq!(library.documents().expect("Failed to read").count(), 2);
+
+        let applied = library
+            .apply_operations(operations(&json), false)
+            .expect("Failed to apply");
+        let kinds: Vec<_> = applied.iter().map(|(operation, _)| *operation).collect();
+        assert_eq!(kinds, [Operation::Add, Operation::Edit, Operation::Remove]);
+        let titles: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| entry.title().to_owned())
+            .collect();
+        assert_eq!(titles, ["Edited", "New"]);
+        assert!(library.validate().expect("Failed to validate").is_valid());
+    }
+}
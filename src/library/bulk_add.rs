@@ -0,0 +1,382 @@
+//! Adding many documents at once.
+//!
+//! Hashing and copying dominate the time it takes to add a document, so for large imports they
+//! are done by a pool of worker threads, while duplicate checks, confirmations and the index
+//! update happen on the calling thread. The index is updated only once for the whole batch.
+
+use {
+    super::{
+        check_expected_hash, copy_to_store, disk_space, file_size, modification_time,
+        move_to_store, AddOptions, DocMetadata, IndexEntry, LibraryIndex, Operation,
+    },
+    crate::{md5, sha256, Library, Timestamp},
+    anyhow::Context,
+    std::{
+        fs::{self, File},
+        num::NonZeroUsize,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        thread,
+    },
+};
+
+/// What happened to a document passed to [`Library::add_documents()`].
+#[derive(Debug)]
+pub enum AddOutcome {
+    /// The document was added to the library with the given hash.
+    Added(sha256::Hash),
+    /// The file is already in the library (possibly archived) as the document with the given
+    /// hash, or it was given more than once.
+    AlreadyInLibrary(sha256::Hash),
+    /// Similar documents exist and adding the document was not confirmed.
+    NotConfirmed,
+    /// The document could not be added.
+    Failed(anyhow::Error),
+}
+
+/// A file that was hashed by a worker thread.
+struct HashedFile {
+    hash: sha256::Hash,
+    md5: Option<md5::Hash>,
+    size: u64,
+    modified: Option<Timestamp>,
+}
+
+/// Hash the file at `path`, and compute its MD5 hash as well if `compute_md5` is true.
+fn hash_file(path: &Path, compute_md5: bool) -> anyhow::Result<HashedFile> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open file at {}", path.display()))?;
+    let size = file_size(&file, path)?;
+    let modified = modification_time(&file);
+    let hash = sha256::hash_reader(file)
+        .with_context(|| format!("Failed to hash file {}", path.display()))?;
+    let md5 = if compute_md5 {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open file at {}", path.display()))?;
+        Some(md5::hash_reader(file)?)
+    } else {
+        None
+    };
+    Ok(HashedFile {
+        hash,
+        md5,
+        size,
+        modified,
+    })
+}
+
+/// Apply `f` to all `items` using up to `jobs` threads, and return the results in the order of
+/// the items.
+fn parallel_map<T, R, F>(items: &[T], jobs: NonZeroUsize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..jobs.get().min(items.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().expect("No worker panicked")[i] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .expect("No worker panicked")
+        .into_iter()
+        .map(|result| result.expect("Every item was processed"))
+        .collect()
+}
+
+impl Library {
+    /// Add several documents to the library at once, and return what happened to each of them,
+    /// in order.
+    ///
+    /// Each document is added like by [`Library::add_document_checked()`], with the following
+    /// differences:
+    /// - The files are hashed and copied into the document store by up to `jobs` threads in
+    ///   parallel.
+    /// - The index is updated once for all documents, so either all documents that could be
+    ///   added are added, or none of them.
+    /// - Problems with individual documents do not stop the other documents from being added.
+    ///   They are reported as [`AddOutcome::Failed`] instead.
+    /// - Files that are already in the library, including archived documents, are reported as
+    ///   [`AddOutcome::AlreadyInLibrary`] instead of as an error.
+    /// - If [`AddOptions::expected_sha256`] is set, the files with a different hash are reported
+    ///   as [`AddOutcome::Failed`].
+    /// - If there is not enough disk space for all documents, they are added in order while there
+    ///   is space left, and the others are reported as [`AddOutcome::Failed`].
+    ///
+    /// `confirm_similar` is called on the calling thread with the path of a document and its
+    /// likely duplicates, in the same way as by [`Library::add_document_checked()`]. Documents
+    /// added earlier in the same batch count as duplicates as well.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the index cannot be read or written, or if `confirm_similar`
+    /// returns an error. In these cases, no document is added.
+    pub fn add_documents<F>(
+        &self,
+        documents: &[(PathBuf, DocMetadata)],
+        options: &AddOptions,
+        jobs: NonZeroUsize,
+        mut confirm_similar: F,
+    ) -> anyhow::Result<Vec<AddOutcome>>
+    where
+        F: FnMut(&Path, &[&IndexEntry]) -> anyhow::Result<bool>,
+    {
+        let index_path = self.index_path();
+        let mut index = LibraryIndex::open(&index_path)?;
+
+        let hashed_files = parallel_map(documents, jobs, |(path, _)| {
+            hash_file(path, options.compute_md5 || self.records_md5())
+        });
+
+        // Check the documents in order, so that the outcome does not depend on the scheduling of
+        // the worker threads.
+        let mut outcomes = Vec::with_capacity(documents.len());
+        // Positions in `outcomes` and paths of the documents to add, in index order.
+        let mut accepted: Vec<(usize, &Path)> = Vec::new();
+        for ((path, metadata), hashed) in documents.iter().zip(hashed_files) {
+            let hashed = match hashed {
+                Ok(hashed) => hashed,
+                Err(error) => {
+                    outcomes.push(AddOutcome::Failed(error));
+                    continue;
+                }
+            };
+            if let Err(error) = check_expected_hash(path, hashed.hash, options.expected_sha256) {
+                outcomes.push(AddOutcome::Failed(error));
+                continue;
+            }
+            if index.documents.iter().any(|doc| doc.hash == hashed.hash) {
+                outcomes.push(AddOutcome::AlreadyInLibrary(hashed.hash));
+                continue;
+            }
+            if let Err(error) = index.check_duplicates(hashed.hash, metadata) {
+                outcomes.push(AddOutcome::Failed(error));
+                continue;
+            }
+            // Errors from `confirm_similar` abort the whole batch, while a rejection by the
+            // library settings only affects this document.
+            let mut confirm_error = None;
+            let confirmed = self.check_similar(&index, metadata, |similar| {
+                confirm_similar(path, similar).or_else(|error| {
+                    confirm_error = Some(error);
+                    Ok(false)
+                })
+            });
+            if let Some(error) = confirm_error {
+                return Err(error);
+            }
+            match confirmed {
+                Ok(true) => {}
+                Ok(false) => {
+                    outcomes.push(AddOutcome::NotConfirmed);
+                    continue;
+                }
+                Err(error) => {
+                    outcomes.push(AddOutcome::Failed(error));
+                    continue;
+                }
+            }
+
+            let mut entry = IndexEntry::added(hashed.hash, metadata.clone(), path, hashed.modified);
+            entry.size_bytes = Some(hashed.size);
+            entry.md5 = hashed.md5;
+            accepted.push((outcomes.len(), path));
+            outcomes.push(AddOutcome::Added(hashed.hash));
+            index.documents.push(entry);
+        }
+
+        // Place the files in the document store.
+        let document_store_dir = self.document_store_dir();
+        fs::create_dir_all(&document_store_dir).with_context(|| {
+            format!(
+                "Failed to create document store directory at {}",
+                document_store_dir.display()
+            )
+        })?;
+        // Moved files do not take up additional space. Copied files are added in order while
+        // there is space left for them, the others are skipped.
+        let available = (!options.move_file)
+            .then(|| disk_space::available_space(&document_store_dir))
+            .flatten();
+        if let Some(mut available) = available {
+            let first_new = index.documents.len() - accepted.len();
+            let mut no_space = Vec::new();
+            for (&(outcome, _), entry) in accepted.iter().zip(&index.documents[first_new..]) {
+                let size = entry.size_bytes.unwrap_or_default();
+                if let Some(left) = available.checked_sub(size) {
+                    available = left;
+                } else {
+                    outcomes[outcome] = AddOutcome::Failed(disk_space::not_enough_space(
+                        &document_store_dir,
+                        size,
+                        available,
+                    ));
+                    no_space.push(entry.hash);
+                }
+            }
+            accepted.retain(|&(outcome, _)| !matches!(outcomes[outcome], AddOutcome::Failed(_)));
+            index
+                .documents
+                .retain(|entry| !no_space.contains(&entry.hash));
+        }
+        let first_new = index.documents.len() - accepted.len();
+        let new_entries = &index.documents[first_new..];
+        let jobs_input: Vec<_> = accepted
+            .iter()
+            .zip(new_entries)
+            .map(|(&(_, path), entry)| (path, entry.hash, entry.size_bytes.unwrap_or_default()))
+            .collect();
+        let stored = parallel_map(&jobs_input, jobs, |&(path, hash, size)| {
+            let store_path = document_store_dir.join(hash.to_string());
+            if options.move_file {
+                move_to_store(path, &store_path, hash, size)
+            } else {
+                copy_to_store(path, &store_path)
+            }
+        });
+
+        // Drop the documents that could not be stored from the index.
+        let mut added = Vec::with_capacity(accepted.len());
+        let mut failed = Vec::new();
+        for ((outcome, path), (result, entry)) in accepted
+            .iter()
+            .zip(stored.into_iter().zip(&index.documents[first_new..]))
+        {
+            match result {
+                Ok(_) => added.push((*outcome, entry.hash, path)),
+                Err(error) => {
+                    failed.push(entry.hash);
+                    outcomes[*outcome] = AddOutcome::Failed(error);
+                }
+            }
+        }
+        index
+            .documents
+            .retain(|entry| !failed.contains(&entry.hash));
+        if added.is_empty() {
+            return Ok(outcomes);
+        }
+
+        let added_hashes: Vec<_> = added.iter().map(|&(_, hash, _)| hash).collect();
+        if let Err(error) = self.begin_index_update(&index, &[], &[]) {
+            // As in `add_document_checked`, remove the documents we just stored on a best-effort
+            // basis and propagate the original error.
+            for hash in &added_hashes {
+                let _ = fs::remove_file(document_store_dir.join(hash.to_string()));
+            }
+            return Err(error);
+        }
+        self.finish_index_update(&index)?;
+        self.log_event(Operation::Add, added_hashes.clone());
+        self.record_digests(&added_hashes)
+            .context("Documents were added, but failed to record their digests")?;
+
+        // The original files are only removed once the documents are safely in the library.
+        if options.move_file {
+            for (outcome, hash, path) in added {
+                if let Err(error) = fs::remove_file(path) {
+                    outcomes[outcome] =
+                        AddOutcome::Failed(anyhow::Error::new(error).context(format!(
+                            "Document was added ({}), but failed to remove {}",
+                            hash.to_short_string(),
+                            path.display()
+                        )));
+                }
+            }
+        }
+
+        Ok(outcomes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::AddOutcome,
+        crate::{
+            sha256,
+            testing::{self, TempLibrary},
+            AddOptions, FileFormat,
+        },
+        std::{fs, num::NonZeroUsize},
+    };
+
+    #[test]
+    fn add_documents_in_parallel() {
+        let library = TempLibrary::with_documents(1).expect("Failed to create library");
+        let dir = library.path().join("import");
+        fs::create_dir(&dir).expect("Failed to create directory");
+        let mut documents = Vec::new();
+        for (file_name, title, file_format) in [
+            ("a.pdf", "Document A", FileFormat::Pdf),
+            ("b.pdf", "Document B", FileFormat::Pdf),
+            ("copy_of_a.pdf", "Document A", FileFormat::Pdf),
+            ("existing.epub", "Synthetic Document 1", FileFormat::Epub),
+        ] {
+            let path = dir.join(file_name);
+            fs::write(&path, testing::synthetic_document(title, file_format))
+                .expect("Failed to write document");
+            documents.push((path, testing::metadata(title, file_format)));
+        }
+        documents.push((
+            dir.join("missing.pdf"),
+            testing::metadata("Missing", FileFormat::Pdf),
+        ));
+
+        let jobs = NonZeroUsize::new(3).expect("3 is not zero");
+        let outcomes = library
+            .add_documents(&documents, &AddOptions::default(), jobs, |_, _| Ok(true))
+            .expect("Failed to add documents");
+        assert!(matches!(outcomes[0], AddOutcome::Added(_)));
+        assert!(matches!(outcomes[1], AddOutcome::Added(_)));
+        assert!(matches!(outcomes[2], AddOutcome::AlreadyInLibrary(_)));
+        assert!(matches!(outcomes[3], AddOutcome::AlreadyInLibrary(_)));
+        assert!(matches!(outcomes[4], AddOutcome::Failed(_)));
+
+        assert_eq!(
+            library.documents().expect("Failed to read index").count(),
+            3
+        );
+        assert!(library.validate().expect("Failed to validate").is_valid());
+    }
+
+    #[test]
+    fn add_documents_with_expected_hash() {
+        let library = TempLibrary::new().expect("Failed to create library");
+        let mut documents = Vec::new();
+        for title in ["Document A", "Document B"] {
+            let path = library.path().join(format!("{title}.pdf"));
+            fs::write(&path, testing::synthetic_document(title, FileFormat::Pdf))
+                .expect("Failed to write document");
+            documents.push((path, testing::metadata(title, FileFormat::Pdf)));
+        }
+        let expected =
+            sha256::hash_reader(&testing::synthetic_document("Document B", FileFormat::Pdf)[..])
+                .expect("Reading cannot fail");
+
+        let options = AddOptions {
+            expected_sha256: Some(expected),
+            ..AddOptions::default()
+        };
+        let jobs = NonZeroUsize::new(2).expect("2 is not zero");
+        let outcomes = library
+            .add_documents(&documents, &options, jobs, |_, _| Ok(true))
+            .expect("Failed to add documents");
+        assert!(matches!(outcomes[0], AddOutcome::Failed(_)));
+        assert!(matches!(outcomes[1], AddOutcome::Added(hash) if hash == expected));
+    }
+}
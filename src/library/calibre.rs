@@ -0,0 +1,153 @@
+//! Exporting documents in the directory layout of a Calibre library.
+//!
+//! Calibre stores each book in a directory `Author/Title (id)/`, next to a `metadata.opf` file
+//! with its metadata. Calibre can add such a directory tree in one go with "Add books from
+//! directories, including sub-directories (One book per directory)".
+
+use {
+    super::{is_reserved_in_file_name, IndexEntry, LibraryIndex},
+    crate::{Filter, Library, OpfPackage},
+    anyhow::{bail, Context},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Name of the file with the metadata of a book in its directory.
+const OPF_FILE: &str = "metadata.opf";
+
+/// Maximum number of characters of a directory or file name built from metadata.
+///
+/// Calibre shortens names as well, to stay below the path length limit of Windows.
+const NAME_LIMIT: usize = 60;
+
+/// Return `s` as a file name that is valid on all platforms, shortened to [`NAME_LIMIT`]
+/// characters.
+fn calibre_name(s: &str) -> String {
+    let name: String = s
+        .chars()
+        .map(|c| {
+            if is_reserved_in_file_name(c, true) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .take(NAME_LIMIT)
+        .collect();
+    // Windows does not allow names that end with a dot or a space.
+    let name = name.trim().trim_end_matches('.');
+    if name.is_empty() {
+        "Unknown".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Return the contents of the `metadata.opf` file of the document of `entry`, which has the
+/// Calibre id `id`.
+fn opf(entry: &IndexEntry, id: usize) -> String {
+    let mut package = OpfPackage::from_document(entry);
+    package
+        .identifiers
+        .insert(0, ("calibre".to_owned(), id.to_string()));
+    package.to_string()
+}
+
+impl Library {
+    /// Copy documents to `dir` in the directory layout of a Calibre library.
+    ///
+    /// Each document is copied to `<author>/<title> (<id>)/<title> - <author>.<ext>` within `dir`,
+    /// where `<author>` is the first author of the document and `<id>` numbers the exported
+    /// documents. The metadata of the document is written next to it in `metadata.opf`, which
+    /// Calibre reads when adding the directory. If `filter` is given, only the documents matching
+    /// it are exported. Archived documents are only exported if `include_archived` is true.
+    ///
+    /// Returns the directories of the exported documents.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if `dir` exists and is not empty, if the index cannot be read, or if
+    /// a document or its metadata cannot be written.
+    pub fn export_calibre(
+        &self,
+        dir: &Path,
+        filter: Option<&Filter>,
+        include_archived: bool,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        if fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()) {
+            bail!("{} is not empty", dir.display());
+        }
+        let index = LibraryIndex::open(&self.index_path())?;
+
+        let entries = index.documents.iter().filter(|entry| {
+            (include_archived || !entry.is_archived())
+                && filter.is_none_or(|filter| filter.matches(entry))
+        });
+        let mut exported = Vec::new();
+        for (id, entry) in (1..).zip(entries) {
+            let author = calibre_name(entry.authors().next().unwrap_or_default());
+            let title = calibre_name(entry.title());
+            let book_dir = dir.join(&author).join(format!("{title} ({id})"));
+            fs::create_dir_all(&book_dir)
+                .with_context(|| format!("Failed to create directory {}", book_dir.display()))?;
+
+            let file_name = format!("{title} - {author}.{}", entry.file_format().extension());
+            let store_path = self.document_store_dir().join(entry.hash().to_string());
+            let out_path = book_dir.join(file_name);
+            fs::copy(&store_path, &out_path).with_context(|| {
+                format!(
+                    "Failed to copy document from {} to {}",
+                    store_path.display(),
+                    out_path.display()
+                )
+            })?;
+            let opf_path = book_dir.join(OPF_FILE);
+            fs::write(&opf_path, opf(entry, id))
+                .with_context(|| format!("Failed to write metadata to {}", opf_path.display()))?;
+            exported.push(book_dir);
+        }
+        Ok(exported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{calibre_name, OPF_FILE},
+        crate::{testing::TempLibrary, Filter},
+        std::fs,
+    };
+
+    #[test]
+    fn names_are_portable() {
+        assert_eq!(calibre_name("Faust: Eine Tragödie"), "Faust_ Eine Tragödie");
+        assert_eq!(calibre_name("What?. "), "What_");
+        assert_eq!(calibre_name(""), "Unknown");
+        assert_eq!(calibre_name(&"a".repeat(100)).len(), 60);
+    }
+
+    #[test]
+    fn export_calibre_layout() {
+        let library = TempLibrary::with_documents(3).expect("Failed to create library");
+        let dir = library.path().join("calibre");
+        let filter: Filter = "format:pdf".parse().expect("Valid filter");
+        let expected = library
+            .documents()
+            .expect("Failed to read index")
+            .filter(|entry| filter.matches(entry))
+            .count();
+
+        let exported = library
+            .export_calibre(&dir, Some(&filter), false)
+            .expect("Failed to export");
+        assert_eq!(exported.len(), expected);
+        for book_dir in &exported {
+            let opf = fs::read_to_string(book_dir.join(OPF_FILE)).expect("Failed to read OPF");
+            assert!(opf.contains(r#"<dc:identifier opf:scheme="sha256">"#));
+            assert_eq!(fs::read_dir(book_dir).expect("Failed to list").count(), 2);
+        }
+        assert!(library.export_calibre(&dir, None, false).is_err());
+    }
+}
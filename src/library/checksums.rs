@@ -0,0 +1,446 @@
+//! Additional digests of the documents in the library.
+//!
+//! Documents are identified by their SHA-256 hash, but external catalogs and manifests often use
+//! other digests, e.g. MD5 or SHA-1. The digest algorithms listed in
+//! [`LibrarySettings::digests`](super::LibrarySettings::digests) are computed for every document
+//! added to the library and stored in a separate checksums file, so the index stays independent
+//! of them. The exception is MD5, which the index records anyway (see
+//! [`IndexEntry::md5()`](crate::IndexEntry::md5)), so it is kept there and not duplicated in the
+//! checksums file.
+
+use {
+    super::{LibraryIndex, Operation, DOCUMENT_STORE_DIR},
+    crate::{md5, sha256, Library},
+    anyhow::{bail, Context},
+    serde::{Deserialize, Serialize},
+    sha1::{Digest, Sha1},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt::{self, Display, Formatter, Write as _},
+        fs::{self, File},
+        io::{self, Read, Write},
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+};
+
+/// The location of the checksums file within the library directory.
+const CHECKSUMS_FILE: &str = "checksums.json";
+
+/// The recorded digests of the documents, keyed by the SHA-256 hash of the documents.
+type Checksums = BTreeMap<sha256::Hash, BTreeMap<DigestAlgorithm, String>>;
+
+/// A digest algorithm that can be computed in addition to SHA-256.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestAlgorithm {
+    /// MD5, as used by Library Genesis and Anna's Archive. Unlike other digests, it is recorded
+    /// in the index.
+    Md5,
+    /// SHA-1.
+    Sha1,
+}
+
+impl DigestAlgorithm {
+    /// Hash the contents of `reader` and return the digest as a lowercase hex string.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if it cannot read from the reader.
+    pub fn hash_reader<R: Read>(self, mut reader: R) -> io::Result<String> {
+        match self {
+            DigestAlgorithm::Md5 => Ok(md5::hash_reader(reader)?.to_string()),
+            DigestAlgorithm::Sha1 => {
+                let mut hasher = Sha1::new();
+                io::copy(&mut reader, &mut hasher)?;
+                let mut hex = String::with_capacity(40);
+                for byte in hasher.finalize() {
+                    write!(hex, "{byte:02x}").expect("Writing to string cannot fail");
+                }
+                Ok(hex)
+            }
+        }
+    }
+}
+
+impl Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            DigestAlgorithm::Md5 => write!(f, "md5"),
+            DigestAlgorithm::Sha1 => write!(f, "sha1"),
+        }
+    }
+}
+
+impl FromStr for DigestAlgorithm {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "md5" => Ok(DigestAlgorithm::Md5),
+            "sha1" => Ok(DigestAlgorithm::Sha1),
+            _ => bail!("Invalid digest algorithm: {} (expected md5 or sha1)", s),
+        }
+    }
+}
+
+/// Indicates that a file does not match a digest recorded in the checksums file.
+#[derive(Debug, Serialize)]
+pub struct DigestMismatch {
+    hash: sha256::Hash,
+    algorithm: DigestAlgorithm,
+    expected: String,
+    actual: String,
+}
+
+impl DigestMismatch {
+    /// The SHA-256 hash of the document.
+    #[must_use]
+    pub fn hash(&self) -> &sha256::Hash {
+        &self.hash
+    }
+
+    /// The algorithm of the digest that does not match.
+    #[must_use]
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    /// The digest recorded in the checksums file.
+    #[must_use]
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// The digest of the file in the document store.
+    #[must_use]
+    pub fn actual(&self) -> &str {
+        &self.actual
+    }
+}
+
+impl Display for DigestMismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} has {} digest {}, expected {}",
+            self.hash.to_short_string(),
+            self.algorithm,
+            self.actual,
+            self.expected,
+        )
+    }
+}
+
+/// Read the checksums from the file at `path`.
+///
+/// A missing file is equivalent to an empty one.
+fn read_checksums(path: &Path) -> anyhow::Result<Checksums> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Checksums::new()),
+        Err(error) => {
+            return Err(error).context(format!(
+                "Failed to open checksums file at {}",
+                path.display()
+            ))
+        }
+    };
+    serde_json::from_reader(file)
+        .with_context(|| format!("Failed to read checksums from {}", path.display()))
+}
+
+/// Write the checksums to the file at `path`.
+///
+/// Like the index, the file is first written to a temporary file which then replaces the
+/// original, so it is never left in a partially written state.
+fn write_checksums(path: &Path, checksums: &Checksums) -> anyhow::Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    let context = || format!("Failed to write checksums file at {}", tmp_path.display());
+    let mut file = File::create(&tmp_path).with_context(context)?;
+    serde_json::to_writer_pretty(&mut file, checksums).with_context(context)?;
+    file.flush().with_context(context)?;
+    file.sync_all().with_context(context)?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace checksums file at {}", path.display()))
+}
+
+impl Library {
+    /// Return the path to the checksums file of the library.
+    fn checksums_path(&self) -> PathBuf {
+        self.path.join(CHECKSUMS_FILE)
+    }
+
+    /// Return true if the MD5 hash of every document is recorded in the index, because MD5 is
+    /// one of the configured digests.
+    pub(super) fn records_md5(&self) -> bool {
+        self.settings.digests.contains(&DigestAlgorithm::Md5)
+    }
+
+    /// Hash the file of the document with hash `hash` with `algorithm`.
+    fn hash_document(
+        &self,
+        hash: sha256::Hash,
+        algorithm: DigestAlgorithm,
+    ) -> anyhow::Result<String> {
+        let path = self.document_store_dir().join(hash.to_string());
+        let file = File::open(&path)
+            .with_context(|| format!("Failed to open file at {}", path.display()))?;
+        algorithm
+            .hash_reader(file)
+            .with_context(|| format!("Failed to hash file {}", path.display()))
+    }
+
+    /// Compute the digests of the document with hash `hash` that are missing from `checksums`.
+    ///
+    /// Only the algorithms in [`LibrarySettings::digests`](super::LibrarySettings::digests) are
+    /// computed, except for MD5, which is recorded in the index. Return true if a digest was
+    /// added.
+    fn compute_missing_digests(
+        &self,
+        checksums: &mut Checksums,
+        hash: sha256::Hash,
+    ) -> anyhow::Result<bool> {
+        let digests = checksums.entry(hash).or_default();
+        let mut added = false;
+        for &algorithm in &self.settings.digests {
+            if algorithm == DigestAlgorithm::Md5 || digests.contains_key(&algorithm) {
+                continue;
+            }
+            let digest = self.hash_document(hash, algorithm)?;
+            digests.insert(algorithm, digest);
+            added = true;
+        }
+        if digests.is_empty() {
+            checksums.remove(&hash);
+        }
+        Ok(added)
+    }
+
+    /// Record the digests of the newly added documents with hashes `hashes`.
+    ///
+    /// Nothing is done if no digest algorithms are configured.
+    pub(super) fn record_digests(&self, hashes: &[sha256::Hash]) -> anyhow::Result<()> {
+        if self.settings.digests.is_empty() {
+            return Ok(());
+        }
+        let path = self.checksums_path();
+        let mut checksums = read_checksums(&path)?;
+        let mut changed = false;
+        for &hash in hashes {
+            changed |= self.compute_missing_digests(&mut checksums, hash)?;
+        }
+        if changed {
+            write_checksums(&path, &checksums)?;
+        }
+        Ok(())
+    }
+
+    /// Return the digests recorded for the document with hash `hash` in the checksums file, i.e.
+    /// other than its SHA-256 and MD5 hashes.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the checksums file cannot be read.
+    pub fn digests(
+        &self,
+        hash: &sha256::Hash,
+    ) -> anyhow::Result<BTreeMap<DigestAlgorithm, String>> {
+        let mut checksums = read_checksums(&self.checksums_path())?;
+        Ok(checksums.remove(hash).unwrap_or_default())
+    }
+
+    /// Compute the configured digests of all documents that do not have them yet, and return the
+    /// number of documents whose digests were computed.
+    ///
+    /// The digests to compute are configured in
+    /// [`LibrarySettings::digests`](super::LibrarySettings::digests). MD5 hashes are recorded in
+    /// the index, and all other digests in the checksums file. Digests of documents that are no
+    /// longer in the library, and MD5 hashes recorded by earlier versions, are removed from the
+    /// checksums file.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the index or the checksums file cannot be read or written, or if a
+    /// document cannot be read.
+    pub fn update_digests(&self) -> anyhow::Result<usize> {
+        let mut index = LibraryIndex::open(&self.index_path())?;
+        let mut updated = BTreeSet::new();
+
+        if self.records_md5() {
+            for entry in index
+                .documents
+                .iter_mut()
+                .filter(|entry| entry.md5.is_none())
+            {
+                let md5 = self.hash_document(entry.hash, DigestAlgorithm::Md5)?;
+                entry.md5 = Some(md5.parse()?);
+                updated.insert(entry.hash);
+            }
+            if !updated.is_empty() {
+                self.begin_index_update(&index, &[], &[])?;
+                self.finish_index_update(&index)?;
+                self.log_event(Operation::Edit, updated.iter().copied().collect());
+            }
+        }
+
+        let path = self.checksums_path();
+        let mut checksums = read_checksums(&path)?;
+        let original = checksums.clone();
+        for digests in checksums.values_mut() {
+            digests.remove(&DigestAlgorithm::Md5);
+        }
+        checksums.retain(|hash, digests| {
+            !digests.is_empty() && index.documents.iter().any(|entry| entry.hash == *hash)
+        });
+        for entry in &index.documents {
+            if self.compute_missing_digests(&mut checksums, entry.hash)? {
+                updated.insert(entry.hash);
+            }
+        }
+        if checksums != original {
+            write_checksums(&path, &checksums)?;
+        }
+        Ok(updated.len())
+    }
+
+    /// Return a manifest of the documents in the library in the format of `md5sum` and
+    /// `sha1sum`, one line per document.
+    ///
+    /// The file names are relative to the library directory, so the manifest can be checked with
+    /// e.g. `md5sum -c` from within it. For [`DigestAlgorithm`]s, only documents with a recorded
+    /// digest are listed (see [`Library::update_digests()`]), where MD5 hashes are taken from the
+    /// index. If `algorithm` is `None`, the
+    /// SHA-256 hashes of all documents are listed.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the index or the checksums file cannot be read.
+    pub fn digest_manifest(&self, algorithm: Option<DigestAlgorithm>) -> anyhow::Result<String> {
+        let index = LibraryIndex::open(&self.index_path())?;
+        let checksums = read_checksums(&self.checksums_path())?;
+        let mut manifest = String::new();
+        for entry in &index.documents {
+            let digest = match algorithm {
+                None => entry.hash.to_string(),
+                Some(DigestAlgorithm::Md5) => match entry.md5 {
+                    Some(md5) => md5.to_string(),
+                    None => continue,
+                },
+                Some(algorithm) => {
+                    match checksums
+                        .get(&entry.hash)
+                        .and_then(|digests| digests.get(&algorithm))
+                    {
+                        Some(digest) => digest.clone(),
+                        None => continue,
+                    }
+                }
+            };
+            writeln!(manifest, "{digest}  {DOCUMENT_STORE_DIR}/{}", entry.hash)
+                .expect("Writing to string cannot fail");
+        }
+        Ok(manifest)
+    }
+
+    /// Check the files in the document store against the MD5 hashes recorded in the index and
+    /// all digests recorded in the checksums file, and return the digests that do not match.
+    ///
+    /// Files that are missing or cannot be read are skipped, since [`Library::validate()`]
+    /// reports them already.
+    pub(super) fn digest_mismatches(&self) -> anyhow::Result<Vec<DigestMismatch>> {
+        let index = LibraryIndex::open(&self.index_path())?;
+        let mut checksums = read_checksums(&self.checksums_path())?;
+        for entry in &index.documents {
+            if let Some(md5) = entry.md5 {
+                checksums
+                    .entry(entry.hash)
+                    .or_default()
+                    .insert(DigestAlgorithm::Md5, md5.to_string());
+            }
+        }
+        let mut mismatches = Vec::new();
+        for (hash, digests) in checksums {
+            let path = self.document_store_dir().join(hash.to_string());
+            for (algorithm, expected) in digests {
+                let Ok(file) = File::open(&path) else {
+                    break;
+                };
+                let actual = algorithm
+                    .hash_reader(file)
+                    .with_context(|| format!("Failed to hash file {}", path.display()))?;
+                if actual != expected.to_ascii_lowercase() {
+                    mismatches.push(DigestMismatch {
+                        hash,
+                        algorithm,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{DigestAlgorithm, CHECKSUMS_FILE},
+        crate::{testing::TempLibrary, Library},
+        std::fs,
+    };
+
+    #[test]
+    fn known_digests() {
+        let sha1 = DigestAlgorithm::Sha1
+            .hash_reader(&b"The quick brown fox jumps over the lazy dog"[..])
+            .expect("Reading from a slice cannot fail");
+        assert_eq!(sha1, "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12");
+        let md5 = DigestAlgorithm::Md5
+            .hash_reader(&b""[..])
+            .expect("Reading from a slice cannot fail");
+        assert_eq!(md5, "d41d8cd98f00b204e9800998ecf8427e");
+    }
+
+    #[test]
+    fn update_and_verify_digests() {
+        let temp_library = TempLibrary::with_documents(2).expect("Failed to create library");
+        fs::write(
+            temp_library.path().join("settings.json"),
+            r#"{"digests": ["md5", "sha1"]}"#,
+        )
+        .expect("Failed to write settings");
+        let library = Library::open(temp_library.path()).expect("Failed to open library");
+        assert_eq!(library.update_digests().expect("Failed to update"), 2);
+        assert_eq!(library.update_digests().expect("Failed to update"), 0);
+
+        let entry = library
+            .documents()
+            .expect("Failed to read index")
+            .next()
+            .expect("Library has documents");
+        let hash = *entry.hash();
+        let md5 = *entry.md5().expect("MD5 is recorded in the index");
+        let digests = library.digests(&hash).expect("Failed to read digests");
+        assert_eq!(digests.keys().collect::<Vec<_>>(), [&DigestAlgorithm::Sha1]);
+        let manifest = library
+            .digest_manifest(Some(DigestAlgorithm::Md5))
+            .expect("Failed to create manifest");
+        assert_eq!(manifest.lines().count(), 2);
+        assert!(manifest.contains(&format!("{md5}  documents/{hash}")));
+
+        let results = library.validate_all_digests().expect("Failed to validate");
+        assert!(results.is_valid());
+
+        // Tamper with a recorded digest.
+        let path = temp_library.path().join(CHECKSUMS_FILE);
+        let checksums = fs::read_to_string(&path).expect("Failed to read checksums");
+        let tampered = checksums.replace(&digests[&DigestAlgorithm::Sha1], &"0".repeat(40));
+        fs::write(&path, tampered).expect("Failed to write checksums");
+        let results = library.validate().expect("Failed to validate");
+        assert!(results.is_valid());
+        let results = library.validate_all_digests().expect("Failed to validate");
+        assert!(!results.is_valid());
+        assert_eq!(results.digest_mismatches().count(), 1);
+    }
+}
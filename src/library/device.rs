@@ -0,0 +1,169 @@
+//! Sending documents to devices such as e-readers.
+
+use {
+    super::{
+        output_file_name, resolve_output_conflict, write_back::write_metadata, IndexEntry,
+        LibraryIndex, RetrievedDocument,
+    },
+    crate::{DeviceProfile, Library, OutputConflict, RetrieveOptions},
+    anyhow::{bail, Context},
+    std::{env, fs, path::Path, process},
+};
+
+/// Replace the `{input}` and `{output}` placeholders in `arg`.
+fn expand_placeholders(arg: &str, input: &Path, output: &Path) -> String {
+    arg.replace("{input}", &input.to_string_lossy())
+        .replace("{output}", &output.to_string_lossy())
+}
+
+/// Run the conversion command `command` to convert the file at `input` to `output`.
+pub(super) fn run_conversion(
+    command: &[String],
+    input: &Path,
+    output: &Path,
+) -> anyhow::Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("Conversion command is empty");
+    };
+    let status = process::Command::new(program)
+        .args(
+            args.iter()
+                .map(|arg| expand_placeholders(arg, input, output)),
+        )
+        .status()
+        .with_context(|| format!("Failed to run conversion command {program}"))?;
+    if !status.success() {
+        bail!("Conversion command {} failed ({})", program, status);
+    }
+    if !output.is_file() {
+        bail!(
+            "Conversion command {} did not create {}",
+            program,
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+impl Library {
+    /// Send a document to a device.
+    ///
+    /// The document matching `identifier` (see [`Library::find_document()`]) is copied to the
+    /// directory of the device profile, under the name it would get from
+    /// [`Library::retrieve_document()`], or from [`DeviceProfile::file_name_template`] if it is
+    /// set. If the profile has a preferred format that differs from the format of the document
+    /// and a conversion command, the document is converted and only the converted file is written
+    /// to the device.
+    ///
+    /// Archived documents are only considered if [`RetrieveOptions::include_archived`] is set. If
+    /// the file already exists on the device, [`RetrieveOptions::on_conflict`] decides what
+    /// happens. The returned [`RetrievedDocument`] holds the path the document was written to.
+    ///
+    /// Like retrievals, sending a document is not recorded in the index, use
+    /// [`Library::record_access()`] for that.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - The directory of the device does not exist, e.g. because the device is not connected.
+    /// - Multiple or no documents match the identifier.
+    /// - The file already exists and [`RetrieveOptions::on_conflict`] is
+    ///   [`OutputConflict::Error`].
+    /// - The conversion command or [`RetrieveOptions::metadata_command`] fails.
+    /// - The index file cannot be read, or the document cannot be copied.
+    pub fn send_document(
+        &self,
+        identifier: &str,
+        profile: &DeviceProfile,
+        options: &RetrieveOptions,
+    ) -> anyhow::Result<RetrievedDocument> {
+        if !profile.path.is_dir() {
+            bail!(
+                "Device directory {} does not exist, is the device connected?",
+                profile.path.display()
+            );
+        }
+
+        let index_path = self.index_path();
+        let index = LibraryIndex::open(&index_path)?;
+
+        let entry = index.find_document(identifier, options.include_archived)?;
+
+        let mut options = options.clone();
+        if let Some(template) = &profile.file_name_template {
+            options.file_name_template = Some(template.clone());
+        }
+        let file_name = output_file_name(entry, &options)?;
+
+        let path = match (&profile.format, &profile.convert) {
+            (Some(format), Some(convert))
+                if !format.eq_ignore_ascii_case(entry.file_format().extension()) =>
+            {
+                let out_path = profile.path.join(&file_name).with_extension(format);
+                let out_path = resolve_output_conflict(out_path, options.on_conflict)?;
+                self.convert_document(entry, convert, &file_name, &out_path)?;
+                if let Some(command) = &options.metadata_command {
+                    write_metadata(command, entry, &out_path)?;
+                }
+                out_path
+            }
+            _ => self.copy_out(entry, profile.path.join(file_name), &options)?,
+        };
+
+        Ok(RetrievedDocument {
+            entry: entry.clone(),
+            path,
+        })
+    }
+
+    /// Convert a document with the conversion command `command` and write it to `out_path`.
+    ///
+    /// The document is copied to a temporary directory as `file_name` first, since conversion
+    /// tools usually determine the input format from the file extension.
+    fn convert_document(
+        &self,
+        entry: &IndexEntry,
+        command: &[String],
+        file_name: &Path,
+        out_path: &Path,
+    ) -> anyhow::Result<()> {
+        let work_dir = env::temp_dir().join(format!("burette-send-{}", process::id()));
+        fs::create_dir_all(&work_dir).with_context(|| {
+            format!(
+                "Failed to create temporary directory at {}",
+                work_dir.display()
+            )
+        })?;
+
+        let options = RetrieveOptions {
+            on_conflict: OutputConflict::Overwrite,
+            ..RetrieveOptions::default()
+        };
+        let result = self
+            .copy_out(entry, work_dir.join(file_name), &options)
+            .and_then(|input| run_conversion(command, &input, out_path));
+        // Ignore errors when cleaning up, the conversion result is what matters.
+        let _ = fs::remove_dir_all(&work_dir);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::expand_placeholders, std::path::Path};
+
+    #[test]
+    fn expand_conversion_placeholders() {
+        let input = Path::new("/tmp/in/Moby Dick.epub");
+        let output = Path::new("/media/kindle/Moby Dick.mobi");
+        assert_eq!(
+            expand_placeholders("{input}", input, output),
+            "/tmp/in/Moby Dick.epub"
+        );
+        assert_eq!(
+            expand_placeholders("--output={output}", input, output),
+            "--output=/media/kindle/Moby Dick.mobi"
+        );
+        assert_eq!(expand_placeholders("-v", input, output), "-v");
+    }
+}
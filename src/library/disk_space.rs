@@ -0,0 +1,95 @@
+//! Checks for available disk space.
+//!
+//! Before a document is copied into the document store, we check that the file system has enough
+//! space for it. This way, the user gets a clear error message instead of a failed copy halfway
+//! through the file.
+//!
+//! The available space is determined using the POSIX `df` utility. If it cannot be determined,
+//! e.g. on Windows, where `df` is usually not available, the check is skipped and the copy is
+//! attempted anyway.
+
+use {
+    anyhow::anyhow,
+    std::{path::Path, process::Command},
+};
+
+/// Return the number of bytes available to unprivileged users on the file system containing
+/// `path`, or `None` if it cannot be determined.
+pub(super) fn available_space(path: &Path) -> Option<u64> {
+    let output = Command::new("df").arg("-Pk").arg(path).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse the output of `df -Pk` and return the available space in bytes.
+///
+/// The output consists of a header line followed by a line of the form
+/// `<file system> <total> <used> <available> <capacity>% <mount point>`, where the sizes are
+/// given in units of 1024 bytes. Both the file system and the mount point may contain spaces, so
+/// the available space is located relative to the capacity column.
+fn parse_df_output(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    let columns: Vec<_> = line.split_whitespace().collect();
+    let capacity = columns.iter().position(|column| column.ends_with('%'))?;
+    let available: u64 = columns.get(capacity.checked_sub(1)?)?.parse().ok()?;
+    available.checked_mul(1024)
+}
+
+/// Check that the file system containing `dir` has room for `required` more bytes.
+///
+/// # Errors
+///
+/// An error is returned if the file system is known to have less than `required` bytes
+/// available. If the available space cannot be determined, no error is returned.
+pub(super) fn check_available_space(dir: &Path, required: u64) -> anyhow::Result<()> {
+    match available_space(dir) {
+        Some(available) if available < required => Err(not_enough_space(dir, required, available)),
+        _ => Ok(()),
+    }
+}
+
+/// Return the error for a file system containing `dir` that has only `available` bytes
+/// available, while `required` bytes are needed.
+pub(super) fn not_enough_space(dir: &Path, required: u64, available: u64) -> anyhow::Error {
+    anyhow!(
+        "Not enough disk space in {}: {} bytes required, but only {} bytes available",
+        dir.display(),
+        required,
+        available
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_df_output;
+
+    #[test]
+    fn parse_df() {
+        let output = "\
+Filesystem     1024-blocks     Used Available Capacity Mounted on
+/dev/vda         264212084 12679064  83378344      14% /
+";
+        assert_eq!(parse_df_output(output), Some(83_378_344 * 1024));
+    }
+
+    #[test]
+    fn parse_df_spaces() {
+        let output = "\
+Filesystem     1024-blocks     Used Available Capacity Mounted on
+my disk          1000          400       600      40% /mnt/my disk
+";
+        assert_eq!(parse_df_output(output), Some(600 * 1024));
+    }
+
+    #[test]
+    fn parse_df_invalid() {
+        assert_eq!(parse_df_output(""), None);
+        assert_eq!(parse_df_output("Filesystem\n"), None);
+        assert_eq!(
+            parse_df_output("Filesystem\n/dev/vda 10 5 five 50% /\n"),
+            None
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! Audit trail of all operations that modify the library.
+//!
+//! Every mutating operation appends a single JSON object on its own line to the event log file.
+//! The log is append-only; burette never rewrites or truncates it.
+//!
+//! The same operations are also sent to the subscribers of the library within the process, see
+//! [`Library::subscribe()`].
+//!
+//! Events are logged after the operation has been performed, so failing to log an event does not
+//! fail the operation. It is reported as a warning instead, see [`Library::take_warnings()`].
+
+use {
+    crate::{sha256, Library, Timestamp},
+    anyhow::{bail, Context},
+    serde::{Deserialize, Serialize},
+    std::{
+        env,
+        fmt::{self, Display, Formatter},
+        fs::{File, OpenOptions},
+        io::{self, BufRead, BufReader, Write},
+        mem,
+        path::PathBuf,
+        str::FromStr,
+        sync::{
+            mpsc::{self, Receiver},
+            PoisonError,
+        },
+    },
+};
+
+/// The location of the event log file within the library directory.
+const EVENT_LOG_FILE: &str = "events.log";
+
+/// Kind of operation recorded in the event log.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    /// A document was added to the library.
+    Add,
+    /// The metadata of a document was edited.
+    Edit,
+    /// Documents were removed from the library.
+    Remove,
+    /// The file of a document was replaced.
+    Replace,
+    /// The library was restored from a snapshot.
+    Restore,
+    /// An earlier operation was undone.
+    Undo,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Add => write!(f, "add"),
+            Operation::Edit => write!(f, "edit"),
+            Operation::Remove => write!(f, "remove"),
+            Operation::Replace => write!(f, "replace"),
+            Operation::Restore => write!(f, "restore"),
+            Operation::Undo => write!(f, "undo"),
+        }
+    }
+}
+
+impl FromStr for Operation {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "add" => Ok(Operation::Add),
+            "edit" => Ok(Operation::Edit),
+            "remove" => Ok(Operation::Remove),
+            "replace" => Ok(Operation::Replace),
+            "restore" => Ok(Operation::Restore),
+            "undo" => Ok(Operation::Undo),
+            _ => bail!("Invalid operation: {}", s),
+        }
+    }
+}
+
+/// A single entry in the event log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    timestamp: Timestamp,
+    user: Option<String>,
+    operation: Operation,
+    hashes: Vec<sha256::Hash>,
+}
+
+impl Event {
+    /// Return the time at which the operation was performed.
+    #[must_use]
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Return the name of the user that performed the operation, if known.
+    #[must_use]
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// Return the kind of operation that was performed.
+    #[must_use]
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Return the hashes of the documents affected by the operation.
+    pub fn hashes(&self) -> impl Iterator<Item = &sha256::Hash> {
+        self.hashes.iter()
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.timestamp,
+            self.user.as_deref().unwrap_or("-"),
+            self.operation
+        )?;
+        for hash in &self.hashes {
+            write!(f, " {}", hash.to_short_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// A change to the library, sent to the receivers returned by [`Library::subscribe()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeEvent {
+    /// The document with the given hash was added to the library.
+    DocumentAdded(sha256::Hash),
+    /// The metadata of the document with the given hash was edited.
+    MetadataEdited(sha256::Hash),
+    /// The document with the given hash was removed from the library.
+    DocumentRemoved(sha256::Hash),
+    /// The file of a document was replaced, which changed its hash from `old` to `new`.
+    DocumentReplaced {
+        /// The hash of the document before it was replaced.
+        old: sha256::Hash,
+        /// The hash of the document after it was replaced.
+        new: sha256::Hash,
+    },
+    /// The document with the given hash was added back, removed or repaired by restoring a
+    /// snapshot or backup, or by undoing an operation.
+    DocumentRestored(sha256::Hash),
+}
+
+impl ChangeEvent {
+    /// Return the change events for an operation on the documents with the given hashes.
+    pub(super) fn from_operation(operation: Operation, hashes: &[sha256::Hash]) -> Vec<Self> {
+        match (operation, hashes) {
+            (Operation::Replace, &[old, new]) => vec![ChangeEvent::DocumentReplaced { old, new }],
+            (Operation::Replace, _) => Vec::new(),
+            (operation, hashes) => {
+                let event = match operation {
+                    Operation::Add => ChangeEvent::DocumentAdded,
+                    Operation::Edit => ChangeEvent::MetadataEdited,
+                    Operation::Remove => ChangeEvent::DocumentRemoved,
+                    Operation::Restore | Operation::Replace | Operation::Undo => {
+                        ChangeEvent::DocumentRestored
+                    }
+                };
+                hashes.iter().copied().map(event).collect()
+            }
+        }
+    }
+}
+
+/// Return the name of the current user, if it can be determined.
+fn current_user() -> Option<String> {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+impl Library {
+    /// Return the path to the event log file of the library.
+    pub(super) fn event_log_path(&self) -> PathBuf {
+        self.path.join(EVENT_LOG_FILE)
+    }
+
+    /// Receive the changes made to the library through this [`Library`] from now on.
+    ///
+    /// An event is sent for every document affected by a mutating operation once the operation
+    /// has completed, so frontends can refresh their views without polling the index file.
+    /// Changes made by other processes, or through other [`Library`] values for the same
+    /// directory, are not received. Dropping the receiver ends the subscription.
+    pub fn subscribe(&self) -> Receiver<ChangeEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(sender);
+        receiver
+    }
+
+    /// Send the change events for an operation to all subscribers.
+    fn notify_subscribers(&self, operation: Operation, hashes: &[sha256::Hash]) {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if subscribers.is_empty() {
+            return;
+        }
+        let events = ChangeEvent::from_operation(operation, hashes);
+        // Sending only fails if the receiver was dropped, which ends the subscription.
+        subscribers.retain(|sender| events.iter().all(|event| sender.send(*event).is_ok()));
+    }
+
+    /// Return the problems that did not prevent operations from completing, and forget them.
+    ///
+    /// For example, an operation that was performed but could not be recorded in the event log or
+    /// the undo log succeeds, and the failure to record it is returned here.
+    pub fn take_warnings(&self) -> Vec<anyhow::Error> {
+        mem::take(&mut *self.warnings.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Append an event to the event log and the undo log, and notify the subscribers of the
+    /// library.
+    ///
+    /// The operation has been performed at this point, so failing to write the logs does not
+    /// fail it. The error is kept as a warning instead, see [`Library::take_warnings()`].
+    pub(super) fn log_event(&self, operation: Operation, hashes: Vec<sha256::Hash>) {
+        self.notify_subscribers(operation, &hashes);
+        if let Err(error) = self.write_event(operation, hashes) {
+            self.warnings
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(error);
+        }
+    }
+
+    /// Write an event to the undo log and the event log, see [`Library::log_event()`].
+    fn write_event(&self, operation: Operation, hashes: Vec<sha256::Hash>) -> anyhow::Result<()> {
+        let timestamp = Timestamp::now()?;
+        self.push_undo_record(operation, timestamp, &hashes)?;
+        let event = Event {
+            timestamp,
+            user: current_user(),
+            operation,
+            hashes,
+        };
+
+        let log_path = self.event_log_path();
+        let context = || format!("Failed to write to event log at {}", log_path.display());
+
+        let mut line = serde_json::to_string(&event).with_context(context)?;
+        line.push('\n');
+        // The whole line is written with a single call so that concurrent writers do not
+        // interleave their events.
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(context)
+    }
+
+    /// Read all events from the event log, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the event log cannot be read or contains invalid data.
+    pub fn events(&self) -> anyhow::Result<Vec<Event>> {
+        let log_path = self.event_log_path();
+        let file = match File::open(&log_path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(error).context(format!(
+                    "Failed to open event log at {}",
+                    log_path.display()
+                ))
+            }
+        };
+
+        let mut events = Vec::new();
+        for (line_number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line
+                .with_context(|| format!("Failed to read event log at {}", log_path.display()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(&line).with_context(|| {
+                format!(
+                    "Invalid event on line {} of {}",
+                    line_number + 1,
+                    log_path.display()
+                )
+            })?;
+            events.push(event);
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{ChangeEvent, EVENT_LOG_FILE},
+        crate::{
+            testing::{self, TempLibrary},
+            FileFormat,
+        },
+        std::fs,
+    };
+
+    #[test]
+    fn subscribe_to_changes() {
+        let library = TempLibrary::with_documents(1).expect("Failed to create library");
+        let receiver = library.subscribe();
+        let dropped = library.subscribe();
+        drop(dropped);
+
+        let hash = library
+            .add(testing::metadata("Subscribed", FileFormat::Pdf))
+            .expect("Failed to add document");
+        library
+            .set_alias("subscribed", &hash.to_string())
+            .expect("Failed to set alias");
+        let hash_string = hash.to_string();
+        library
+            .remove_all_checked([hash_string.as_str()].into_iter(), None, false, |_| {
+                Ok(true)
+            })
+            .expect("Failed to remove document");
+
+        let events: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(
+            events,
+            [
+                ChangeEvent::DocumentAdded(hash),
+                ChangeEvent::MetadataEdited(hash),
+                ChangeEvent::DocumentRemoved(hash),
+            ]
+        );
+    }
+
+    #[test]
+    fn warn_if_event_log_cannot_be_written() {
+        let library = TempLibrary::with_documents(1).expect("Failed to create library");
+        assert!(library.take_warnings().is_empty());
+        let log_path = library.path().join(EVENT_LOG_FILE);
+        fs::remove_file(&log_path).expect("Failed to remove event log");
+        fs::create_dir(&log_path).expect("Failed to create directory");
+
+        // The document is added, even though the event cannot be logged.
+        let hash = library
+            .add(testing::metadata("Unlogged", FileFormat::Pdf))
+            .expect("Failed to add document");
+        assert!(library
+            .documents()
+            .expect("Failed to read index")
+            .any(|entry| *entry.hash() == hash));
+        assert_eq!(library.take_warnings().len(), 1);
+        assert!(library.take_warnings().is_empty());
+    }
+}
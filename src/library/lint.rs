@@ -0,0 +1,275 @@
+//! Checks for incomplete or suspicious metadata.
+
+use {
+    super::IndexEntry,
+    crate::{normalize_title, sha256, Isbn13, Library},
+    serde::Serialize,
+    std::{
+        collections::HashSet,
+        fmt::{self, Display, Formatter},
+    },
+};
+
+/// Titles that are placeholders rather than actual titles, after normalization.
+const PLACEHOLDER_TITLES: [&str; 6] = [
+    "untitled",
+    "unknown",
+    "no title",
+    "title",
+    "document",
+    "microsoft word",
+];
+
+/// Kind of problem found by [`Library::lint_metadata()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetadataProblem {
+    /// The document has no authors.
+    EmptyAuthors,
+    /// The title is a placeholder or looks like a file name.
+    SuspiciousTitle,
+    /// The document has neither an ISBN nor a DOI.
+    MissingIdentifier,
+    /// The DOI is malformed or refers to an ISBN the document does not have.
+    InconsistentIdentifiers,
+    /// An author is listed more than once.
+    DuplicateAuthors,
+}
+
+impl Display for MetadataProblem {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MetadataProblem::EmptyAuthors => write!(f, "empty-authors"),
+            MetadataProblem::SuspiciousTitle => write!(f, "suspicious-title"),
+            MetadataProblem::MissingIdentifier => write!(f, "missing-identifier"),
+            MetadataProblem::InconsistentIdentifiers => write!(f, "inconsistent-identifiers"),
+            MetadataProblem::DuplicateAuthors => write!(f, "duplicate-authors"),
+        }
+    }
+}
+
+/// A problem with the metadata of a document, found by [`Library::lint_metadata()`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataIssue {
+    hash: sha256::Hash,
+    title: String,
+    problem: MetadataProblem,
+    message: String,
+}
+
+impl MetadataIssue {
+    /// The SHA-256 hash of the document.
+    #[must_use]
+    pub fn hash(&self) -> &sha256::Hash {
+        &self.hash
+    }
+
+    /// The title of the document.
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The kind of problem.
+    #[must_use]
+    pub fn problem(&self) -> MetadataProblem {
+        self.problem
+    }
+
+    /// A description of the problem.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for MetadataIssue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} [{}]: {}",
+            self.hash.to_short_string(),
+            self.title,
+            self.problem,
+            self.message
+        )
+    }
+}
+
+impl Library {
+    /// Check the metadata of the documents in the library for problems and return them, ordered
+    /// by document in index order.
+    ///
+    /// The following problems are found:
+    /// - Documents without authors.
+    /// - Titles that are placeholders, e.g. "Untitled", or look like file names.
+    /// - Documents with neither an ISBN nor a DOI.
+    /// - Malformed DOIs, and DOIs that contain an ISBN the document does not have, as the DOIs
+    ///   of many book publishers do.
+    /// - Authors that are listed more than once.
+    ///
+    /// Archived documents are only checked if `include_archived` is true.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the index cannot be read.
+    pub fn lint_metadata(&self, include_archived: bool) -> anyhow::Result<Vec<MetadataIssue>> {
+        Ok(self
+            .documents()?
+            .filter(|entry| include_archived || !entry.is_archived())
+            .flat_map(|entry| lint_entry(&entry))
+            .collect())
+    }
+}
+
+/// Return the problems with the metadata of `entry`.
+fn lint_entry(entry: &IndexEntry) -> Vec<MetadataIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |problem, message: String| {
+        issues.push(MetadataIssue {
+            hash: *entry.hash(),
+            title: entry.title().to_owned(),
+            problem,
+            message,
+        });
+    };
+
+    if entry.authors().all(|author| author.trim().is_empty()) {
+        issue(MetadataProblem::EmptyAuthors, "No authors".to_owned());
+    }
+
+    if let Some(reason) = suspicious_title(entry.title()) {
+        issue(MetadataProblem::SuspiciousTitle, reason.to_owned());
+    }
+
+    match entry.doi() {
+        None if entry.isbns().next().is_none() => {
+            issue(
+                MetadataProblem::MissingIdentifier,
+                "Neither an ISBN nor a DOI".to_owned(),
+            );
+        }
+        None => {}
+        Some(doi) => {
+            if !doi.starts_with("10.") || !doi.contains('/') {
+                issue(
+                    MetadataProblem::InconsistentIdentifiers,
+                    format!("Malformed DOI {doi}"),
+                );
+            } else if let Some(isbn) = isbn_in_doi(doi) {
+                if entry.isbns().next().is_some() && !entry.isbns().any(|own| *own == isbn) {
+                    issue(
+                        MetadataProblem::InconsistentIdentifiers,
+                        format!(
+                            "DOI {doi} refers to ISBN {isbn}, which the document does not have"
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for author in entry.authors() {
+        let normalized = normalize_title(author);
+        if !normalized.is_empty() && !seen.insert(normalized) {
+            issue(
+                MetadataProblem::DuplicateAuthors,
+                format!("{author} is listed more than once"),
+            );
+        }
+    }
+
+    issues
+}
+
+/// Return why `title` is suspicious, if it is.
+fn suspicious_title(title: &str) -> Option<&'static str> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Some("Empty title");
+    }
+    if PLACEHOLDER_TITLES.contains(&normalize_title(title).as_str()) {
+        return Some("Placeholder title");
+    }
+    let lowercase = title.to_ascii_lowercase();
+    let has_extension = [".pdf", ".epub", ".djvu", ".mobi", ".azw3", ".doc", ".docx"]
+        .iter()
+        .any(|extension| lowercase.ends_with(extension));
+    // Hashes and UUIDs are long, so that short titles such as "1984" are not affected.
+    let is_identifier = !title.contains(char::is_whitespace)
+        && (title.contains('_')
+            || (title.len() >= 16 && title.chars().all(|c| c.is_ascii_hexdigit() || c == '-')));
+    if has_extension || is_identifier {
+        return Some("Title looks like a file name");
+    }
+    None
+}
+
+/// Return the ISBN that is part of `doi`, e.g. `978-3-540-45117-4` in
+/// `10.1007/978-3-540-45117-4_1`.
+fn isbn_in_doi(doi: &str) -> Option<Isbn13> {
+    let (_, suffix) = doi.split_once('/')?;
+    let digits: String = suffix
+        .split(|c: char| !c.is_ascii_digit() && c != '-')
+        .map(|part| part.replace('-', ""))
+        .find(|part| part.len() >= 13 && (part.starts_with("978") || part.starts_with("979")))?;
+    digits[..13].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{isbn_in_doi, lint_entry, suspicious_title, MetadataProblem},
+        crate::{library::tests::moby_dick, IndexEntry},
+    };
+
+    #[test]
+    fn suspicious_titles() {
+        assert!(suspicious_title("Moby Dick; Or, The Whale").is_none());
+        assert!(suspicious_title("Untitled").is_some());
+        assert!(suspicious_title("  ").is_some());
+        assert!(suspicious_title("moby_dick_1").is_some());
+        assert!(suspicious_title("Scan 2024-01-01.pdf").is_some());
+        assert!(suspicious_title("2e511b1bdedd984f").is_some());
+        assert!(suspicious_title("Faust").is_none());
+        assert!(suspicious_title("1984").is_none());
+    }
+
+    #[test]
+    fn isbns_in_dois() {
+        let isbn = isbn_in_doi("10.1007/978-3-540-45117-4_1").expect("DOI contains an ISBN");
+        assert_eq!(isbn.to_string(), "9783540451174");
+        assert!(isbn_in_doi("10.5962/bhl.title.59991").is_none());
+    }
+
+    #[test]
+    fn lint_moby_dick() {
+        fn problems(entry: &IndexEntry) -> Vec<MetadataProblem> {
+            lint_entry(entry)
+                .into_iter()
+                .map(|issue| issue.problem())
+                .collect()
+        }
+
+        let mut entry = moby_dick();
+        assert_eq!(problems(&entry), [MetadataProblem::MissingIdentifier]);
+
+        entry.set_doi(Some("10.1007/978-3-540-45117-4".to_owned()));
+        entry.set_authors(vec![
+            "Herman Melville".to_owned(),
+            "herman  melville".to_owned(),
+        ]);
+        assert_eq!(problems(&entry), [MetadataProblem::DuplicateAuthors]);
+
+        entry.set_isbns(vec!["978-0198853695".parse().expect("Valid ISBN")]);
+        entry.set_authors(Vec::new());
+        assert_eq!(
+            problems(&entry),
+            [
+                MetadataProblem::EmptyAuthors,
+                MetadataProblem::InconsistentIdentifiers
+            ]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Errors for failed lookups in the library.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// Error returned if something cannot be found in the library, or is not unique.
+///
+/// Functions of [`crate::Library`] return this error wrapped in an [`anyhow::Error`]. It can be
+/// recovered with [`anyhow::Error::downcast_ref()`], e.g. to distinguish a missing document from
+/// an I/O error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupError {
+    /// Nothing matched. Contains a description of what was looked up.
+    NotFound(String),
+    /// Multiple documents matched. Contains a description of what was looked up.
+    Ambiguous(String),
+}
+
+impl Display for LookupError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            LookupError::NotFound(message) | LookupError::Ambiguous(message) => {
+                write!(f, "{message}")
+            }
+        }
+    }
+}
+
+impl Error for LookupError {}
@@ -0,0 +1,289 @@
+//! Checks for PDF/A conformance, for long-term preservation of documents.
+
+use {
+    super::{device::run_conversion, output_file_name, LibraryIndex, Operation},
+    crate::{sha256, FileFormat, Library, OutputConflict, RetrieveOptions, Timestamp},
+    anyhow::{bail, Context},
+    serde::{Deserialize, Serialize},
+    std::{
+        env,
+        fmt::{self, Display, Formatter},
+        fs,
+        path::Path,
+        process,
+    },
+};
+
+/// Result of a PDF/A conformance check, see [`Library::check_preservation()`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreservationStatus {
+    checked: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    level: Option<String>,
+    conformant: bool,
+    method: CheckMethod,
+}
+
+impl PreservationStatus {
+    /// Return the time of the check.
+    #[must_use]
+    pub fn checked(&self) -> Timestamp {
+        self.checked
+    }
+
+    /// Return the PDF/A level the document claims to conform to, e.g. `PDF/A-2B`.
+    #[must_use]
+    pub fn level(&self) -> Option<&str> {
+        self.level.as_deref()
+    }
+
+    /// Return true if the document was found to conform to PDF/A.
+    #[must_use]
+    pub fn is_conformant(&self) -> bool {
+        self.conformant
+    }
+
+    /// Return how the document was checked.
+    #[must_use]
+    pub fn method(&self) -> CheckMethod {
+        self.method
+    }
+}
+
+impl Display for PreservationStatus {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match (&self.level, self.conformant) {
+            (Some(level), true) => write!(f, "{level}")?,
+            (Some(level), false) => write!(f, "not conforming to the claimed {level}")?,
+            (None, true) => write!(f, "PDF/A")?,
+            (None, false) => write!(f, "not PDF/A")?,
+        }
+        write!(f, " (checked by {} on {})", self.method, self.checked)
+    }
+}
+
+/// How the PDF/A conformance of a document was checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckMethod {
+    /// The document was checked for a PDF/A identification in its XMP metadata, and for
+    /// encryption, which PDF/A forbids.
+    Heuristic,
+    /// The document was checked by an external validator such as veraPDF.
+    Validator,
+}
+
+impl Display for CheckMethod {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            CheckMethod::Heuristic => "heuristic",
+            CheckMethod::Validator => "validator",
+        };
+        f.pad(name)
+    }
+}
+
+/// Return the value of the XMP property `name` (e.g. `pdfaid:part`) in `contents`.
+///
+/// Both forms of XMP properties are supported, attributes (`pdfaid:part="2"`) and elements
+/// (`<pdfaid:part>2</pdfaid:part>`).
+fn xmp_property(contents: &[u8], name: &str) -> Option<String> {
+    let name = name.as_bytes();
+    let start = contents
+        .windows(name.len())
+        .position(|window| window == name)?
+        + name.len();
+    let rest = &contents[start..];
+    let rest = match rest.first()? {
+        b'=' => rest.get(2..)?,
+        b'>' => rest.get(1..)?,
+        _ => return None,
+    };
+    let value: Vec<u8> = rest
+        .iter()
+        .take_while(|&&byte| !matches!(byte, b'"' | b'\'' | b'<'))
+        .copied()
+        .collect();
+    let value = String::from_utf8(value).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+/// Return the PDF/A level claimed by the XMP metadata in `contents`, e.g. `PDF/A-2B`.
+///
+/// Only uncompressed metadata streams are found, which is how PDF/A tools write them.
+fn claimed_pdfa_level(contents: &[u8]) -> Option<String> {
+    let part = xmp_property(contents, "pdfaid:part")?;
+    let conformance = xmp_property(contents, "pdfaid:conformance").unwrap_or_default();
+    Some(format!("PDF/A-{part}{}", conformance.to_uppercase()))
+}
+
+/// Return true if `contents` contains an encryption dictionary.
+fn is_encrypted(contents: &[u8]) -> bool {
+    contents
+        .windows(b"/Encrypt".len())
+        .any(|window| window == b"/Encrypt")
+}
+
+/// Run the validator `command` on the file at `path` and return true if it succeeds.
+fn run_validator(command: &[String], path: &Path) -> anyhow::Result<bool> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("Validator command is empty");
+    };
+    let status = process::Command::new(program)
+        .args(
+            args.iter()
+                .map(|arg| arg.replace("{file}", &path.to_string_lossy())),
+        )
+        .stdout(process::Stdio::null())
+        .status()
+        .with_context(|| format!("Failed to run validator {program}"))?;
+    Ok(status.success())
+}
+
+impl Library {
+    /// Check whether a document conforms to PDF/A and record the result in the index.
+    ///
+    /// The document matching `identifier` (see [`Library::find_document()`]) must be a PDF file.
+    /// Archived documents are considered as well. If `validator` is given, it is run as a program
+    /// followed by its arguments, with `{file}` replaced by the path of the document, and the
+    /// document conforms if it exits successfully. The validator must not modify the file.
+    /// Otherwise, a heuristic is used: the document conforms if its XMP metadata claims a PDF/A
+    /// level and it is not encrypted. The result is returned and can later be read with
+    /// [`crate::IndexEntry::preservation()`].
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - Multiple or no documents match the identifier.
+    /// - The document is not a PDF file.
+    /// - The document cannot be read or the validator cannot be run.
+    /// - The index file cannot be read or written.
+    pub fn check_preservation(
+        &self,
+        identifier: &str,
+        validator: Option<&[String]>,
+    ) -> anyhow::Result<PreservationStatus> {
+        let index_path = self.index_path();
+        let mut index = LibraryIndex::open(&index_path)?;
+
+        let entry = index.find_document(identifier, true)?;
+        if entry.file_format() != FileFormat::Pdf {
+            bail!(
+                "Document {} is not a PDF file, only PDF documents can be checked for PDF/A conformance",
+                entry.hash().to_short_string()
+            );
+        }
+        let hash = *entry.hash();
+
+        let store_path = self.document_store_dir().join(hash.to_string());
+        let contents = fs::read(&store_path)
+            .with_context(|| format!("Failed to read document at {}", store_path.display()))?;
+        let level = claimed_pdfa_level(&contents);
+        let (conformant, method) = match validator {
+            Some(validator) => (
+                run_validator(validator, &store_path)?,
+                CheckMethod::Validator,
+            ),
+            None => (
+                level.is_some() && !is_encrypted(&contents),
+                CheckMethod::Heuristic,
+            ),
+        };
+        let status = PreservationStatus {
+            checked: Timestamp::now()?,
+            level,
+            conformant,
+            method,
+        };
+
+        if let Some(entry) = index.documents.iter_mut().find(|entry| entry.hash == hash) {
+            entry.preservation = Some(status.clone());
+        }
+        self.update_index(&index)?;
+        self.log_event(Operation::Edit, vec![hash]);
+
+        Ok(status)
+    }
+
+    /// Convert a document to PDF/A and replace its file by the converted one.
+    ///
+    /// `command` is run as a program followed by its arguments, with `{input}` and `{output}`
+    /// replaced by the path of the document and the path the converted document must be written
+    /// to, e.g. a Ghostscript invocation with `-dPDFA=2`. The file is then replaced like by
+    /// [`Library::replace_blob()`], but the information about the file the document was added
+    /// from is kept. The hash of the converted document is returned. The converted document is
+    /// not checked, use [`Library::check_preservation()`] for that.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - Multiple or no documents match the identifier.
+    /// - The document is not a PDF file.
+    /// - The conversion command fails or does not create a PDF file.
+    /// - The converted document is already in the library.
+    /// - The index file cannot be read or written.
+    pub fn convert_to_pdfa(
+        &self,
+        identifier: &str,
+        command: &[String],
+    ) -> anyhow::Result<sha256::Hash> {
+        let entry = self.find_document(identifier, true)?;
+        if entry.file_format() != FileFormat::Pdf {
+            bail!(
+                "Document {} is not a PDF file, only PDF documents can be converted to PDF/A",
+                entry.hash().to_short_string()
+            );
+        }
+
+        let work_dir = env::temp_dir().join(format!("burette-preserve-{}", process::id()));
+        fs::create_dir_all(&work_dir).with_context(|| {
+            format!(
+                "Failed to create temporary directory at {}",
+                work_dir.display()
+            )
+        })?;
+        let options = RetrieveOptions {
+            on_conflict: OutputConflict::Overwrite,
+            ..RetrieveOptions::default()
+        };
+        let result = output_file_name(&entry, &options)
+            .and_then(|file_name| self.copy_out(&entry, work_dir.join(file_name), &options))
+            .and_then(|input| {
+                let output = work_dir.join("pdfa.pdf");
+                run_conversion(command, &input, &output)?;
+                self.replace_blob_impl(&entry.hash().to_string(), &output, false)
+            });
+        // Ignore errors when cleaning up, the conversion result is what matters.
+        let _ = fs::remove_dir_all(&work_dir);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{claimed_pdfa_level, is_encrypted};
+
+    #[test]
+    fn pdfa_level_from_xmp() {
+        let attributes = br#"<rdf:Description pdfaid:part="2" pdfaid:conformance="b"/>"#;
+        assert_eq!(claimed_pdfa_level(attributes).as_deref(), Some("PDF/A-2B"));
+
+        let elements = b"<pdfaid:part>1</pdfaid:part><pdfaid:conformance>A</pdfaid:conformance>";
+        assert_eq!(claimed_pdfa_level(elements).as_deref(), Some("PDF/A-1A"));
+
+        let no_conformance = b"<pdfaid:part>4</pdfaid:part>";
+        assert_eq!(
+            claimed_pdfa_level(no_conformance).as_deref(),
+            Some("PDF/A-4")
+        );
+
+        assert_eq!(claimed_pdfa_level(b"%PDF-1.7\n<< /Type /Catalog >>"), None);
+    }
+
+    #[test]
+    fn encryption() {
+        assert!(is_encrypted(b"trailer\n<< /Root 1 0 R /Encrypt 5 0 R >>"));
+        assert!(!is_encrypted(b"trailer\n<< /Root 1 0 R >>"));
+    }
+}
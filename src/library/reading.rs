@@ -0,0 +1,281 @@
+//! Import of reading progress and annotations from e-readers, and of reading history from book
+//! tracking sites.
+//!
+//! See [`ReadingData`] for the supported formats.
+
+use {
+    super::{IndexEntry, LibraryIndex, Operation},
+    crate::{sha256, Library, ReadingData, ReadingStatus},
+    serde::Serialize,
+    std::collections::BTreeSet,
+};
+
+/// Results from [`Library::import_reading_data()`].
+///
+/// See [`Library::import_reading_data()`] for details.
+#[derive(Debug, Serialize)]
+pub struct ReadingImportResults {
+    imported: Vec<ImportedReadingData>,
+    not_found: Vec<String>,
+    ambiguous: Vec<String>,
+}
+
+impl ReadingImportResults {
+    /// Documents the reading data was attached to.
+    #[must_use]
+    pub fn imported(&self) -> &[ImportedReadingData] {
+        &self.imported
+    }
+
+    /// Titles of reading data that did not match any document.
+    #[must_use]
+    pub fn not_found(&self) -> &[String] {
+        &self.not_found
+    }
+
+    /// Titles of reading data that matched multiple documents and was therefore not imported.
+    #[must_use]
+    pub fn ambiguous(&self) -> &[String] {
+        &self.ambiguous
+    }
+}
+
+/// Reading data that was attached to a document, see [`ReadingImportResults::imported()`].
+#[derive(Debug, Serialize)]
+pub struct ImportedReadingData {
+    /// Hash of the document the reading data was attached to.
+    pub hash: sha256::Hash,
+    /// Title of the document in the library.
+    pub title: String,
+    /// Number of annotations that were not attached to the document before.
+    pub new_annotations: usize,
+    /// Reading progress of the document after the import.
+    pub progress: Option<u8>,
+    /// Reading status of the document after the import.
+    pub status: Option<ReadingStatus>,
+    /// Rating of the document after the import.
+    pub rating: Option<u8>,
+}
+
+/// Return the set of words in the names of `authors`.
+///
+/// Comparing word sets makes `Melville, Herman` (as used by Kindle) match `Herman Melville`.
+fn author_words<'a, I>(authors: I) -> BTreeSet<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    authors
+        .into_iter()
+        .flat_map(|author| {
+            crate::normalize_title(author)
+                .split(' ')
+                .filter(|word| !word.is_empty())
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Return the indices of the documents in `index` that `data` belongs to.
+///
+/// Documents are matched by ISBN if possible, and by title otherwise. If multiple documents have
+/// the title, only those whose authors match the authors of `data` are kept.
+fn match_documents(index: &LibraryIndex, data: &ReadingData) -> Vec<usize> {
+    let by_isbn: Vec<_> = index
+        .documents
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.isbns().any(|isbn| data.isbns.contains(isbn)))
+        .map(|(i, _)| i)
+        .collect();
+    if !by_isbn.is_empty() {
+        return by_isbn;
+    }
+
+    let title = crate::normalize_title(&data.title);
+    let matches: Vec<_> = index
+        .documents
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| crate::normalize_title(entry.title()) == title)
+        .collect();
+    if matches.len() <= 1 || data.authors.is_empty() {
+        return matches.into_iter().map(|(i, _)| i).collect();
+    }
+
+    let authors = author_words(data.authors.iter().map(String::as_str));
+    matches
+        .into_iter()
+        .filter(|(_, entry)| author_words(entry.authors()) == authors)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Attach `data` to `entry` and return the number of new annotations.
+fn attach(entry: &mut IndexEntry, data: &ReadingData) -> usize {
+    if data.progress.is_some() {
+        entry.reading_progress = data.progress;
+    }
+    if data.status.is_some() {
+        entry.reading_status = data.status;
+    }
+    if data.rating.is_some() {
+        entry.rating = data.rating;
+    }
+    if data.finished.is_some() {
+        entry.finished_reading.clone_from(&data.finished);
+    }
+    let mut new_annotations = 0;
+    for annotation in &data.annotations {
+        if !entry.annotations.contains(annotation) {
+            entry.annotations.push(annotation.clone());
+            new_annotations += 1;
+        }
+    }
+    new_annotations
+}
+
+impl Library {
+    /// Attach reading progress and annotations exported by an e-reader, or reading history
+    /// exported by a book tracking site, to documents in the library.
+    ///
+    /// If `identifier` is given (an ISBN, DOI, alias or hash prefix, see
+    /// [`Library::find_document()`]), all of `data` is attached to the document it identifies.
+    /// Otherwise, each [`ReadingData`] is attached to the document with one of its ISBNs, or else
+    /// to the document with the same normalized title (see [`crate::normalize_title()`]). If
+    /// multiple documents have that title, the authors are used to tell them apart. Archived
+    /// documents are considered as well.
+    ///
+    /// Annotations that are already attached to a document are not added again, so the same
+    /// export can be imported repeatedly. The reading progress, reading status, rating and date
+    /// finished are overwritten if `data` contains them.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - `identifier` does not match exactly one document.
+    /// - The index file cannot be read or written.
+    pub fn import_reading_data(
+        &self,
+        data: &[ReadingData],
+        identifier: Option<&str>,
+    ) -> anyhow::Result<ReadingImportResults> {
+        let index_path = self.index_path();
+        let mut index = LibraryIndex::open(&index_path)?;
+
+        let target = match identifier {
+            Some(identifier) => {
+                let hash = *index.find_document(identifier, true)?.hash();
+                index.documents.iter().position(|entry| entry.hash == hash)
+            }
+            None => None,
+        };
+
+        let mut results = ReadingImportResults {
+            imported: Vec::new(),
+            not_found: Vec::new(),
+            ambiguous: Vec::new(),
+        };
+        for data in data {
+            let position = match target {
+                Some(position) => position,
+                None => match match_documents(&index, data).as_slice() {
+                    [position] => *position,
+                    [] => {
+                        results.not_found.push(data.title.clone());
+                        continue;
+                    }
+                    _ => {
+                        results.ambiguous.push(data.title.clone());
+                        continue;
+                    }
+                },
+            };
+
+            let entry = &mut index.documents[position];
+            let new_annotations = attach(entry, data);
+            match results
+                .imported
+                .iter_mut()
+                .find(|imported| imported.hash == entry.hash)
+            {
+                Some(imported) => {
+                    imported.new_annotations += new_annotations;
+                    imported.progress = entry.reading_progress;
+                    imported.status = entry.reading_status;
+                    imported.rating = entry.rating;
+                }
+                None => results.imported.push(ImportedReadingData {
+                    hash: entry.hash,
+                    title: entry.title().to_owned(),
+                    new_annotations,
+                    progress: entry.reading_progress,
+                    status: entry.reading_status,
+                    rating: entry.rating,
+                }),
+            }
+        }
+
+        if results.imported.is_empty() {
+            return Ok(results);
+        }
+        self.update_index(&index)?;
+        let hashes = results
+            .imported
+            .iter()
+            .map(|imported| imported.hash)
+            .collect();
+        self.log_event(Operation::Edit, hashes);
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::author_words,
+        crate::{testing::TempLibrary, ReadingData},
+    };
+
+    #[test]
+    fn author_words_ignore_order_and_punctuation() {
+        assert_eq!(
+            author_words(["Melville, Herman"]),
+            author_words(["Herman Melville"])
+        );
+        assert_ne!(
+            author_words(["Herman Melville"]),
+            author_words(["Herman Hesse"])
+        );
+    }
+
+    #[test]
+    fn import_matches_documents_by_title() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let data = [
+            ReadingData {
+                title: "synthetic document 2".to_owned(),
+                authors: Vec::new(),
+                progress: Some(42),
+                annotations: Vec::new(),
+                ..ReadingData::default()
+            },
+            ReadingData {
+                title: "Synthetic Document 3".to_owned(),
+                authors: Vec::new(),
+                progress: Some(10),
+                annotations: Vec::new(),
+                ..ReadingData::default()
+            },
+        ];
+
+        let results = library
+            .import_reading_data(&data, None)
+            .expect("Import failed");
+        assert_eq!(results.imported().len(), 1);
+        assert_eq!(results.imported()[0].title, "Synthetic Document 2");
+        assert_eq!(results.imported()[0].progress, Some(42));
+        assert_eq!(results.not_found(), ["Synthetic Document 3"]);
+    }
+}
@@ -0,0 +1,279 @@
+//! Repairing the library from a backup.
+//!
+//! A backup is a copy of the library directory, or a snapshot of the library. Only the documents
+//! and index entries that [`Library::validate()`] finds to be missing or damaged are restored, so
+//! changes made to the library after the backup was taken are kept.
+
+use {
+    super::{LibraryIndex, Operation, DOCUMENT_STORE_DIR, INDEX_FILE},
+    crate::{sha256, Library},
+    anyhow::Context,
+    std::{
+        collections::BTreeSet,
+        ffi::OsStr,
+        fmt::{self, Display, Formatter},
+        fs::{self, File},
+        path::Path,
+    },
+};
+
+/// A document that could not be restored by [`Library::restore_from_backup()`].
+#[derive(Debug, Clone)]
+pub struct RestoreFailure {
+    hash: sha256::Hash,
+    reason: String,
+}
+
+impl RestoreFailure {
+    /// The SHA-256 hash of the document.
+    #[must_use]
+    pub fn hash(&self) -> &sha256::Hash {
+        &self.hash
+    }
+
+    /// Why the document could not be restored.
+    #[must_use]
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for RestoreFailure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.hash.to_short_string(), self.reason)
+    }
+}
+
+/// The results of [`Library::restore_from_backup()`].
+#[derive(Debug, Clone, Default)]
+pub struct RestoreResults {
+    restored_files: Vec<sha256::Hash>,
+    restored_index_entries: Vec<sha256::Hash>,
+    failures: Vec<RestoreFailure>,
+}
+
+impl RestoreResults {
+    /// Return true if nothing needed to be restored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.restored_files.is_empty()
+            && self.restored_index_entries.is_empty()
+            && self.failures.is_empty()
+    }
+
+    /// Return the hashes of the documents whose files were restored.
+    pub fn restored_files(&self) -> impl Iterator<Item = &sha256::Hash> {
+        self.restored_files.iter()
+    }
+
+    /// Return the hashes of the documents whose index entries were restored.
+    pub fn restored_index_entries(&self) -> impl Iterator<Item = &sha256::Hash> {
+        self.restored_index_entries.iter()
+    }
+
+    /// Return the documents that could not be restored.
+    pub fn failures(&self) -> impl Iterator<Item = &RestoreFailure> {
+        self.failures.iter()
+    }
+}
+
+/// Return true if the file at `path` exists and has the SHA-256 hash `hash`.
+fn has_hash(path: &Path, hash: &sha256::Hash) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|file| sha256::hash_reader(file).ok())
+        .is_some_and(|actual| actual == *hash)
+}
+
+impl Library {
+    /// Restore the documents and index entries that are missing or damaged from the backup at
+    /// `backup_path`.
+    ///
+    /// The backup is a copy of a library directory or a snapshot of the library. The library is
+    /// validated like by [`Library::validate()`], and the following problems are repaired:
+    /// - Files that are missing from the document store, empty, unreadable, or have the wrong
+    ///   hash or size are replaced by their copy in the backup, if the copy is intact.
+    /// - Files in the document store without an index entry get their index entry from the
+    ///   backup, if it has one.
+    ///
+    /// If `only` is not empty, only the documents whose hashes start with one of the given
+    /// prefixes are restored. Problems that cannot be repaired from the backup are reported in
+    /// [`RestoreResults::failures()`].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the index of the backup cannot be read, if the library cannot be
+    /// validated, or if the index cannot be written.
+    pub fn restore_from_backup(
+        &self,
+        backup_path: &Path,
+        only: &[&str],
+    ) -> anyhow::Result<RestoreResults> {
+        let backup_index = LibraryIndex::open(&backup_path.join(INDEX_FILE))
+            .with_context(|| format!("Failed to open backup at {}", backup_path.display()))?;
+        let backup_store = backup_path.join(DOCUMENT_STORE_DIR);
+
+        let validation = self.validate()?;
+        let parse_hash = |file_name: &OsStr| file_name.to_str()?.parse().ok();
+        let selected =
+            |hash: &sha256::Hash| only.is_empty() || only.iter().any(|p| hash.starts_with(p));
+        let damaged_files: BTreeSet<sha256::Hash> = validation
+            .missing_files
+            .iter()
+            .copied()
+            .chain(
+                validation
+                    .hash_mismatches
+                    .iter()
+                    .filter_map(|mismatch| parse_hash(&mismatch.actual)),
+            )
+            .chain(
+                validation
+                    .unreadable_files
+                    .iter()
+                    .filter_map(|unreadable| parse_hash(&unreadable.file_name)),
+            )
+            .chain(
+                validation
+                    .empty_files
+                    .iter()
+                    .filter_map(|name| parse_hash(name)),
+            )
+            .chain(
+                validation
+                    .size_mismatches
+                    .iter()
+                    .map(|mismatch| mismatch.hash),
+            )
+            .filter(selected)
+            .collect();
+
+        let mut results = RestoreResults::default();
+        let document_store_dir = self.document_store_dir();
+        fs::create_dir_all(&document_store_dir).with_context(|| {
+            format!(
+                "Failed to create document store directory at {}",
+                document_store_dir.display()
+            )
+        })?;
+        for hash in damaged_files {
+            let backup_file = backup_store.join(hash.to_string());
+            if !has_hash(&backup_file, &hash) {
+                results.failures.push(RestoreFailure {
+                    hash,
+                    reason: "No intact copy of the file in the backup".to_owned(),
+                });
+                continue;
+            }
+            // Copying onto a damaged file truncates it, so the file is replaced as a whole.
+            let store_path = document_store_dir.join(hash.to_string());
+            match fs::copy(&backup_file, &store_path) {
+                Ok(_) => results.restored_files.push(hash),
+                Err(error) => results.failures.push(RestoreFailure {
+                    hash,
+                    reason: format!("Failed to copy the file from the backup: {error}"),
+                }),
+            }
+        }
+
+        // Validation takes the hashes of the contents of damaged files to be files without index
+        // entries. They are not documents, and restoring their files removes them anyway.
+        let damaged_contents: BTreeSet<_> = validation
+            .hash_mismatches
+            .iter()
+            .map(|mismatch| mismatch.expected)
+            .collect();
+        let mut index = LibraryIndex::open(&self.index_path())?;
+        for hash in validation
+            .missing_index_entries
+            .iter()
+            .filter(|hash| selected(hash) && !damaged_contents.contains(hash))
+        {
+            match backup_index
+                .documents
+                .iter()
+                .find(|entry| entry.hash == *hash)
+            {
+                Some(entry) => {
+                    index.documents.push(entry.clone());
+                    results.restored_index_entries.push(*hash);
+                }
+                None => results.failures.push(RestoreFailure {
+                    hash: *hash,
+                    reason: "No index entry in the backup".to_owned(),
+                }),
+            }
+        }
+        if !results.restored_index_entries.is_empty() {
+            self.update_index(&index)?;
+        }
+
+        let changed: BTreeSet<_> = results
+            .restored_files
+            .iter()
+            .chain(&results.restored_index_entries)
+            .copied()
+            .collect();
+        if !changed.is_empty() {
+            self.log_event(Operation::Restore, changed.into_iter().collect());
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::super::{LibraryIndex, DOCUMENT_STORE_DIR},
+        crate::testing::TempLibrary,
+        std::fs,
+    };
+
+    #[test]
+    fn restore_damaged_documents() {
+        let library = TempLibrary::with_documents(3).expect("Failed to create library");
+        library
+            .create_snapshot(Some("backup"))
+            .expect("Failed to create snapshot");
+        let backup_path = library.path().join("snapshots").join("backup");
+        let hashes: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| *entry.hash())
+            .collect();
+        let store = library.path().join(DOCUMENT_STORE_DIR);
+
+        // The snapshot hard links the documents, so the damaged file is written anew.
+        fs::remove_file(store.join(hashes[0].to_string())).expect("Failed to remove");
+        fs::write(store.join(hashes[0].to_string()), b"garbage").expect("Failed to write");
+        fs::remove_file(store.join(hashes[1].to_string())).expect("Failed to remove");
+        let mut index = LibraryIndex::open(&library.index_path()).expect("Failed to open index");
+        index.documents.retain(|entry| entry.hash != hashes[2]);
+        library
+            .update_index(&index)
+            .expect("Failed to update index");
+        assert!(!library.validate().expect("Failed to validate").is_valid());
+
+        let prefix = hashes[1].to_string();
+        let results = library
+            .restore_from_backup(&backup_path, &[&prefix[..8]])
+            .expect("Failed to restore");
+        assert_eq!(results.restored_files().collect::<Vec<_>>(), [&hashes[1]]);
+        assert_eq!(results.restored_index_entries().count(), 0);
+
+        let results = library
+            .restore_from_backup(&backup_path, &[])
+            .expect("Failed to restore");
+        assert_eq!(results.restored_files().collect::<Vec<_>>(), [&hashes[0]]);
+        assert_eq!(
+            results.restored_index_entries().collect::<Vec<_>>(),
+            [&hashes[2]]
+        );
+        assert_eq!(results.failures().count(), 0);
+        assert!(library.validate().expect("Failed to validate").is_valid());
+        assert!(library
+            .restore_from_backup(&backup_path, &[])
+            .expect("Failed to restore")
+            .is_empty());
+    }
+}
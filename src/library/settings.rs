@@ -0,0 +1,118 @@
+//! Settings stored in the library directory, so they apply wherever the library is used.
+
+use {
+    super::DigestAlgorithm,
+    anyhow::Context,
+    serde::{Deserialize, Serialize},
+    std::{fs, io, path::Path},
+};
+
+/// Settings of a library, read from `settings.json` in the library directory.
+///
+/// All settings are optional, and a missing settings file is equivalent to an empty one. Unlike
+/// the user configuration ([`crate::Config`]), the settings are part of the library and therefore
+/// travel with it, e.g. when the library directory is synced to another computer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LibrarySettings {
+    /// Template for the names of retrieved documents.
+    ///
+    /// See [`crate::IndexEntry::file_name_from_template()`] for the syntax. This takes precedence
+    /// over [`crate::Config::file_name_template`].
+    #[serde(default)]
+    pub file_name_template: Option<String>,
+    /// What happens when a document is added that is similar to a document in the library.
+    ///
+    /// See [`crate::Library::add_document_checked()`].
+    #[serde(default)]
+    pub duplicate_titles: DuplicatePolicy,
+    /// Digests computed for each document in addition to its SHA-256 hash.
+    ///
+    /// See [`crate::Library::update_digests()`].
+    #[serde(default)]
+    pub digests: Vec<DigestAlgorithm>,
+}
+
+/// What happens when a document is added that is likely a duplicate of a document in the
+/// library, see [`crate::Library::similar_documents()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Ask for confirmation before adding the document.
+    #[default]
+    Ask,
+    /// Add the document without asking.
+    Allow,
+    /// Refuse to add the document.
+    Reject,
+}
+
+impl LibrarySettings {
+    /// Load the settings from the file at `path`.
+    ///
+    /// If there is no settings file, the default settings are returned.
+    pub(super) fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => {
+                return Err(error).context(format!(
+                    "Failed to read library settings at {}",
+                    path.display()
+                ))
+            }
+        };
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid library settings at {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{DuplicatePolicy, LibrarySettings},
+        crate::{
+            testing::{self, TempLibrary},
+            AddOptions, FileFormat, Library,
+        },
+        std::fs,
+    };
+
+    #[test]
+    fn parse_settings() {
+        let settings: LibrarySettings = serde_json::from_str(
+            r#"{"file_name_template": "{title}", "duplicate_titles": "reject"}"#,
+        )
+        .expect("Valid settings");
+        assert_eq!(settings.file_name_template.as_deref(), Some("{title}"));
+        assert_eq!(settings.duplicate_titles, DuplicatePolicy::Reject);
+
+        let settings: LibrarySettings = serde_json::from_str("{}").expect("Valid settings");
+        assert_eq!(settings, LibrarySettings::default());
+
+        assert!(serde_json::from_str::<LibrarySettings>(r#"{"compression": true}"#).is_err());
+    }
+
+    #[test]
+    fn reject_similar_documents() {
+        let library = TempLibrary::with_documents(1).expect("Failed to create library");
+        fs::write(
+            library.path().join("settings.json"),
+            r#"{"duplicate_titles": "reject"}"#,
+        )
+        .expect("Failed to write settings");
+        let reopened = Library::open(library.path()).expect("Failed to open library");
+        assert_eq!(
+            reopened.settings().duplicate_titles,
+            DuplicatePolicy::Reject
+        );
+
+        let file = library.path().join("similar.pdf");
+        fs::write(&file, "A different file").expect("Failed to write file");
+        let metadata = testing::metadata("Synthetic Document 1", FileFormat::Pdf);
+        // Similar documents are rejected even though adding them would be confirmed.
+        let added =
+            reopened.add_document_checked(&file, metadata, &AddOptions::default(), |_| Ok(true));
+        assert!(added.is_err());
+    }
+}
@@ -0,0 +1,296 @@
+//! Point-in-time snapshots of the library.
+//!
+//! A snapshot consists of a copy of the index file and hard links to all documents referenced by
+//! the index. Because the documents in the store are never modified in place, hard links are
+//! sufficient to preserve their contents. If hard links are not supported, the documents are
+//! copied instead.
+
+use {
+    super::{LibraryIndex, LookupError, Operation, DOCUMENT_STORE_DIR, INDEX_FILE},
+    crate::{sha256, Library},
+    anyhow::{bail, Context},
+    std::{
+        collections::HashSet,
+        fs, io,
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// The location of the snapshot directory within the library directory.
+const SNAPSHOT_DIR: &str = "snapshots";
+
+/// Information about a snapshot of the library.
+#[derive(Debug, Clone)]
+pub struct SnapshotInfo {
+    name: String,
+    document_count: usize,
+}
+
+impl SnapshotInfo {
+    /// Return the name of the snapshot.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the number of documents in the snapshot.
+    #[must_use]
+    pub fn document_count(&self) -> usize {
+        self.document_count
+    }
+}
+
+/// Check that `name` can be used as the name of a snapshot.
+///
+/// Snapshot names are used as directory names, so only a conservative set of characters is
+/// allowed.
+fn validate_snapshot_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty() {
+        bail!("Snapshot name cannot be an empty string");
+    }
+    if name.starts_with('.') {
+        bail!("Snapshot name cannot start with '.'");
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_' || *c == '.'))
+    {
+        bail!("Invalid character in snapshot name: '{}'", c);
+    }
+    Ok(())
+}
+
+/// Hard link `from` to `to`, falling back to a copy if hard links are not supported.
+fn link_or_copy(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if fs::hard_link(from, to).is_err() {
+        fs::copy(from, to)
+            .with_context(|| format!("Failed to copy {} to {}", from.display(), to.display()))?;
+    }
+    Ok(())
+}
+
+impl Library {
+    /// Return the path to the snapshot directory of the library.
+    fn snapshot_dir(&self) -> PathBuf {
+        self.path.join(SNAPSHOT_DIR)
+    }
+
+    /// Create a snapshot of the current state of the library.
+    ///
+    /// If no name is given, the current time (in seconds since the Unix epoch) is used as the name
+    /// of the snapshot.
+    ///
+    /// The snapshot is first written to a temporary directory which is then renamed, so a
+    /// snapshot is either complete or not present at all.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - The name is invalid or a snapshot with the same name already exists.
+    /// - The index file cannot be read.
+    /// - The snapshot cannot be written.
+    pub fn create_snapshot(&self, name: Option<&str>) -> anyhow::Result<SnapshotInfo> {
+        let name = match name {
+            Some(name) => name.to_owned(),
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .context("System time is before the Unix epoch")?
+                .as_secs()
+                .to_string(),
+        };
+        validate_snapshot_name(&name)?;
+
+        let snapshot_path = self.snapshot_dir().join(&name);
+        let exists = snapshot_path.try_exists().with_context(|| {
+            format!(
+                "Could not determine if snapshot exists at {}",
+                snapshot_path.display()
+            )
+        })?;
+        if exists {
+            bail!("Snapshot {} already exists", name);
+        }
+
+        let tmp_path = self.snapshot_dir().join(format!(".tmp-{name}"));
+        match self.try_create_snapshot(&tmp_path) {
+            Ok(document_count) => {
+                fs::rename(&tmp_path, &snapshot_path).with_context(|| {
+                    format!(
+                        "Failed to move snapshot from {} to {}",
+                        tmp_path.display(),
+                        snapshot_path.display()
+                    )
+                })?;
+                Ok(SnapshotInfo {
+                    name,
+                    document_count,
+                })
+            }
+            Err(error) => {
+                // Clean up the partially written snapshot.
+                let _ = fs::remove_dir_all(&tmp_path);
+                Err(error.context(format!("Failed to create snapshot {name}")))
+            }
+        }
+    }
+
+    /// Helper function to write a snapshot to the specified directory.
+    ///
+    /// Returns the number of documents in the snapshot.
+    ///
+    /// This function should only be called by [`Library::create_snapshot()`].
+    fn try_create_snapshot(&self, path: &Path) -> anyhow::Result<usize> {
+        let snapshot_store = path.join(DOCUMENT_STORE_DIR);
+        fs::create_dir_all(&snapshot_store).with_context(|| {
+            format!(
+                "Failed to create snapshot directory at {}",
+                snapshot_store.display()
+            )
+        })?;
+
+        let index = LibraryIndex::open(&self.index_path())?;
+
+        let document_store_dir = self.document_store_dir();
+        for entry in &index.documents {
+            let file_name = entry.hash().to_string();
+            link_or_copy(
+                &document_store_dir.join(&file_name),
+                &snapshot_store.join(&file_name),
+            )?;
+        }
+
+        index.save(&path.join(INDEX_FILE))?;
+
+        Ok(index.documents.len())
+    }
+
+    /// Return information about all snapshots of the library, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the snapshot directory or any of the snapshot indexes cannot
+    /// be read.
+    pub fn snapshots(&self) -> anyhow::Result<Vec<SnapshotInfo>> {
+        let snapshot_dir = self.snapshot_dir();
+        let dir = match fs::read_dir(&snapshot_dir) {
+            Ok(dir) => dir,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(error).context(format!(
+                    "Failed to read snapshot directory at {}",
+                    snapshot_dir.display()
+                ))
+            }
+        };
+
+        let mut snapshots = Vec::new();
+        for entry in dir {
+            let entry = entry.context("Failed to read directory entry of snapshot directory")?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Skip temporary directories of snapshots that are being created.
+            if name.starts_with('.') {
+                continue;
+            }
+            let index = LibraryIndex::open(&entry.path().join(INDEX_FILE))?;
+            snapshots.push(SnapshotInfo {
+                name,
+                document_count: index.documents.len(),
+            });
+        }
+        snapshots.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(snapshots)
+    }
+
+    /// Restore the library to the state recorded in a snapshot.
+    ///
+    /// Documents that are referenced by the snapshot but missing from the document store are
+    /// restored from the snapshot. Afterwards, the index is replaced by the index of the snapshot
+    /// and documents that are not part of the snapshot are removed from the document store.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - The snapshot does not exist or its index cannot be read.
+    /// - A document cannot be restored from the snapshot.
+    /// - The index file cannot be written.
+    /// - A document that is not part of the snapshot cannot be removed.
+    pub fn restore_snapshot(&self, name: &str) -> anyhow::Result<()> {
+        validate_snapshot_name(name)?;
+        let snapshot_path = self.snapshot_dir().join(name);
+        let exists = snapshot_path.try_exists().with_context(|| {
+            format!(
+                "Could not determine if snapshot exists at {}",
+                snapshot_path.display()
+            )
+        })?;
+        if !exists {
+            return Err(LookupError::NotFound(format!("Snapshot {name} does not exist")).into());
+        }
+
+        let index = LibraryIndex::open(&snapshot_path.join(INDEX_FILE))?;
+
+        let document_store_dir = self.document_store_dir();
+        fs::create_dir_all(&document_store_dir).with_context(|| {
+            format!(
+                "Failed to create document store directory at {}",
+                document_store_dir.display()
+            )
+        })?;
+
+        let snapshot_store = snapshot_path.join(DOCUMENT_STORE_DIR);
+        let mut restored = HashSet::new();
+        // Hashes of the documents that were added back or removed by the restore.
+        let mut changed = Vec::new();
+        for entry in &index.documents {
+            let file_name = entry.hash().to_string();
+            let store_path = document_store_dir.join(&file_name);
+            let exists = store_path.try_exists().with_context(|| {
+                format!(
+                    "Could not determine if document exists at {}",
+                    store_path.display()
+                )
+            })?;
+            if !exists {
+                link_or_copy(&snapshot_store.join(&file_name), &store_path)?;
+                changed.push(*entry.hash());
+            }
+            restored.insert(*entry.hash());
+        }
+
+        // Documents that were added after the snapshot was taken.
+        let mut remove = Vec::new();
+        for entry in fs::read_dir(&document_store_dir).with_context(|| {
+            format!(
+                "Failed to read document store directory at {}",
+                document_store_dir.display()
+            )
+        })? {
+            let entry = entry.context("Failed to read directory entry of document store")?;
+            let path = entry.path();
+            // Anything that is not a regular file named after a hash is left alone; `validate`
+            // reports it.
+            let is_file = entry
+                .file_type()
+                .with_context(|| format!("Failed to determine file type of {}", path.display()))?
+                .is_file();
+            let hash = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<sha256::Hash>().ok());
+            if let Some(hash) = hash.filter(|hash| is_file && !restored.contains(hash)) {
+                remove.push(hash);
+            }
+        }
+
+        // The files are moved to the trash, so that the restore can be undone.
+        self.begin_index_update(&index, &[], &remove)?;
+        super::wal::trash_documents(&self.path, &remove)?;
+        self.finish_index_update(&index)?;
+        changed.extend(remove);
+
+        self.log_event(Operation::Restore, changed);
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! Lightweight view of the index for listing documents.
+
+use {
+    super::{deserialize_index_documents, read_index_file},
+    crate::{sha256, DocType, Library, Timestamp},
+    anyhow::Context,
+    serde::Deserialize,
+};
+
+/// The minimum number of hexadecimal digits of the hashes shown by [`Library::short_hash()`].
+///
+/// This is the same as the default of git for abbreviated commit hashes.
+const MIN_SHORT_HASH_LENGTH: usize = 7;
+
+/// The fields of an [`crate::IndexEntry`] that are needed to list documents.
+///
+/// Only these fields are deserialized when reading the index, so summaries are much cheaper to
+/// load than full entries for large libraries. See [`Library::document_summaries()`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct DocumentSummary {
+    hash: sha256::Hash,
+    title: String,
+    authors: Vec<String>,
+    #[serde(default)]
+    doc_type: Option<DocType>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    last_accessed: Option<Timestamp>,
+    #[serde(default)]
+    access_count: u64,
+}
+
+impl DocumentSummary {
+    /// Return the hash of the document.
+    #[must_use]
+    pub fn hash(&self) -> &sha256::Hash {
+        &self.hash
+    }
+
+    /// Return the title of the document.
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Return the authors of the document.
+    pub fn authors(&self) -> impl Iterator<Item = &str> {
+        self.authors.iter().map(String::as_str)
+    }
+
+    /// Return the type of the document, if known.
+    #[must_use]
+    pub fn doc_type(&self) -> Option<DocType> {
+        self.doc_type
+    }
+
+    /// Return true if the document is archived.
+    #[must_use]
+    pub fn is_archived(&self) -> bool {
+        self.archived
+    }
+
+    /// Return the time at which the document was last retrieved, if ever.
+    #[must_use]
+    pub fn last_accessed(&self) -> Option<Timestamp> {
+        self.last_accessed
+    }
+
+    /// Return the number of times the document has been retrieved.
+    #[must_use]
+    pub fn access_count(&self) -> u64 {
+        self.access_count
+    }
+}
+
+impl Library {
+    /// Iterate over summaries of all documents in the library.
+    ///
+    /// This works like [`Library::documents()`], but only reads the fields of the index that are
+    /// needed to list documents (see [`DocumentSummary`]).
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the index file cannot be read.
+    pub fn document_summaries(&self) -> anyhow::Result<impl Iterator<Item = DocumentSummary>> {
+        let index_path = self.index_path();
+        let contents = read_index_file(&index_path)?;
+        let mut deserializer = serde_json::Deserializer::from_slice(&contents);
+        let summaries: Vec<DocumentSummary> = deserialize_index_documents(&mut deserializer)
+            .and_then(|summaries| deserializer.end().map(|()| summaries))
+            .with_context(|| {
+                format!("Failed to read library index from {}", index_path.display())
+            })?;
+        Ok(summaries.into_iter())
+    }
+
+    /// Return the number of hexadecimal digits needed to tell the documents in the library apart
+    /// by their hash prefixes.
+    ///
+    /// This is at least 7, and grows with the library, so that a hash shortened to this length is
+    /// an unambiguous identifier of the document. Archived documents are taken into account as
+    /// well.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the index file cannot be read.
+    pub fn short_hash_length(&self) -> anyhow::Result<usize> {
+        let mut hashes: Vec<_> = self.document_summaries()?.map(|doc| doc.hash).collect();
+        hashes.sort_unstable();
+        Ok(hashes
+            .windows(2)
+            .map(|pair| pair[0].common_prefix_length(&pair[1]) + 1)
+            .fold(MIN_SHORT_HASH_LENGTH, usize::max))
+    }
+
+    /// Return `hash` shortened to the length given by [`Library::short_hash_length()`].
+    ///
+    /// To shorten many hashes, call [`Library::short_hash_length()`] once and use
+    /// [`sha256::Hash::to_prefix_string()`] instead, since this reads the index every time.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the index file cannot be read.
+    pub fn short_hash(&self, hash: &sha256::Hash) -> anyhow::Result<String> {
+        Ok(hash.to_prefix_string(self.short_hash_length()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::TempLibrary;
+
+    #[test]
+    fn summaries_match_documents() {
+        let library = TempLibrary::with_documents(4).expect("Failed to create library");
+        let summaries: Vec<_> = library
+            .document_summaries()
+            .expect("Failed to read index")
+            .map(|summary| {
+                let authors: Vec<_> = summary.authors().map(str::to_owned).collect();
+                (*summary.hash(), summary.title().to_owned(), authors)
+            })
+            .collect();
+        let documents: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| {
+                let authors: Vec<_> = entry.authors().map(str::to_owned).collect();
+                (*entry.hash(), entry.title().to_owned(), authors)
+            })
+            .collect();
+        assert_eq!(summaries, documents);
+    }
+
+    #[test]
+    fn short_hashes_are_unambiguous() {
+        let library = TempLibrary::with_documents(20).expect("Failed to create library");
+        let length = library.short_hash_length().expect("Failed to read index");
+        assert!(length >= 7);
+        for doc in library.documents().expect("Failed to read index") {
+            let prefix = library
+                .short_hash(doc.hash())
+                .expect("Failed to read index");
+            assert_eq!(prefix.len(), length);
+            let matches = library
+                .documents()
+                .expect("Failed to read index")
+                .filter(|other| other.hash().starts_with(&prefix))
+                .count();
+            assert_eq!(matches, 1);
+        }
+    }
+}
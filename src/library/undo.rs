@@ -0,0 +1,500 @@
+//! Undoing the most recent operations on the library.
+//!
+//! Every index update remembers the index entries it changes. When the operation is recorded in
+//! the event log, these entries are pushed onto the undo log, together with the hashes of the
+//! documents the operation added. Undoing an operation puts the remembered entries back and moves
+//! the added documents to the trash. Removed and replaced documents are kept in the trash, from
+//! where undoing moves them back into the document store, until the operation that removed them
+//! drops off the undo log. The documents moved to the trash by undoing an operation are kept until
+//! the operation before it drops off the undo log, so that they can still be recovered.
+
+use {
+    super::{LibraryIndex, Operation, DOCUMENT_STORE_DIR, TRASH_DIR},
+    crate::{sha256, IndexEntry, Library, Timestamp},
+    anyhow::{bail, Context},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{HashMap, HashSet},
+        fmt::{self, Display, Formatter},
+        fs::{self, File},
+        io::{self, BufRead, BufReader, Write},
+        path::{Path, PathBuf},
+        sync::PoisonError,
+    },
+};
+
+/// The location of the undo log within the library directory.
+const UNDO_LOG_FILE: &str = "undo.log";
+
+/// The number of operations that can be undone. Older operations are dropped from the undo log.
+const UNDO_LIMIT: usize = 20;
+
+/// The changes made to the index by an update, as needed to undo them.
+#[derive(Debug, Clone, Default)]
+pub(super) struct IndexChanges {
+    /// The entries that the update changed or removed, as they were before the update.
+    previous: Vec<IndexEntry>,
+    /// The positions of the entries of `previous` in the index before the update.
+    positions: Vec<usize>,
+    /// The hashes of the documents that the update added.
+    added: Vec<sha256::Hash>,
+}
+
+impl IndexChanges {
+    /// Return the changes between `old` and `new`.
+    pub(super) fn between(old: &LibraryIndex, new: &LibraryIndex) -> Self {
+        let old_hashes: HashSet<_> = old.documents.iter().map(|entry| entry.hash).collect();
+        let new_entries: HashMap<_, _> = new
+            .documents
+            .iter()
+            .map(|entry| (entry.hash, entry))
+            .collect();
+        let (positions, previous) = old
+            .documents
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| new_entries.get(&entry.hash) != Some(entry))
+            .map(|(position, entry)| (position, entry.clone()))
+            .unzip();
+        let added = new
+            .documents
+            .iter()
+            .map(|entry| entry.hash)
+            .filter(|hash| !old_hashes.contains(hash))
+            .collect();
+        Self {
+            previous,
+            positions,
+            added,
+        }
+    }
+
+    /// Return true if the update did not change the index.
+    fn is_empty(&self) -> bool {
+        self.previous.is_empty() && self.added.is_empty()
+    }
+}
+
+/// Delete the files in the trash directory of the library at `path` that undoing one of `records`
+/// does not need.
+fn purge_trash(path: &Path, records: &[UndoRecord]) -> anyhow::Result<()> {
+    let needed: HashSet<_> = records
+        .iter()
+        .flat_map(|record| {
+            record
+                .previous
+                .iter()
+                .map(|entry| entry.hash)
+                .chain(record.trashed.iter().copied())
+        })
+        .collect();
+    let trash_dir = path.join(TRASH_DIR);
+    let entries = match fs::read_dir(&trash_dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => {
+            return Err(error).with_context(|| {
+                format!("Failed to read trash directory at {}", trash_dir.display())
+            })
+        }
+    };
+    for entry in entries {
+        let entry = entry.context("Failed to read directory entry of trash")?;
+        let hash = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<sha256::Hash>().ok());
+        // Anything that is not named after a hash was not put there by burette.
+        if hash.is_some_and(|hash| !needed.contains(&hash)) {
+            let path = entry.path();
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to delete {} from the trash", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// An operation that can be undone with [`Library::undo()`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoRecord {
+    timestamp: Timestamp,
+    operation: Operation,
+    hashes: Vec<sha256::Hash>,
+    previous: Vec<IndexEntry>,
+    /// The positions of the entries of `previous` in the index before the operation. Entries
+    /// without a position are put back at the end of the index.
+    #[serde(default)]
+    positions: Vec<usize>,
+    added: Vec<sha256::Hash>,
+    /// The documents moved to the trash by undoing later operations, which are kept in the trash
+    /// as long as this operation can be undone.
+    #[serde(default)]
+    trashed: Vec<sha256::Hash>,
+}
+
+impl UndoRecord {
+    /// Return the time at which the operation was performed.
+    #[must_use]
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Return the kind of operation.
+    #[must_use]
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Return the hashes of the documents affected by the operation, as in the event log.
+    pub fn hashes(&self) -> impl Iterator<Item = &sha256::Hash> {
+        self.hashes.iter()
+    }
+}
+
+impl Display for UndoRecord {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.timestamp, self.operation)?;
+        for hash in &self.hashes {
+            write!(f, " {}", hash.to_short_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Read the undo log at `path`, oldest operation first.
+fn read_undo_log(path: &Path) -> anyhow::Result<Vec<UndoRecord>> {
+    let context = || format!("Failed to read undo log at {}", path.display());
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error).with_context(context),
+    };
+    let mut records = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(context)?;
+        if !line.trim().is_empty() {
+            records.push(serde_json::from_str(&line).with_context(context)?);
+        }
+    }
+    Ok(records)
+}
+
+/// Replace the undo log at `path` by `records`.
+fn write_undo_log(path: &Path, records: &[UndoRecord]) -> anyhow::Result<()> {
+    let context = || format!("Failed to write undo log to {}", path.display());
+    let mut contents = String::new();
+    for record in records {
+        contents.push_str(&serde_json::to_string(record).with_context(context)?);
+        contents.push('\n');
+    }
+    // The log is written to a temporary file first, so that it is never partially written.
+    let tmp_path = path.with_extension("log.tmp");
+    File::create(&tmp_path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .with_context(context)?;
+    fs::rename(&tmp_path, path).with_context(context)
+}
+
+impl Library {
+    /// Return the path to the undo log of the library.
+    fn undo_log_path(&self) -> PathBuf {
+        self.path.join(UNDO_LOG_FILE)
+    }
+
+    /// Remember the changes of the index update from `old` to `index`, so that they are recorded
+    /// in the undo log with the next event.
+    ///
+    /// If the current index could not be read, i.e. `old` is `None`, the update cannot be undone.
+    pub(super) fn remember_index_changes(&self, old: Option<&LibraryIndex>, index: &LibraryIndex) {
+        let changes = old.map(|old| IndexChanges::between(old, index));
+        *self
+            .pending_changes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = changes;
+    }
+
+    /// Push the changes remembered by [`Library::remember_index_changes()`] onto the undo log as
+    /// the changes of `operation`.
+    pub(super) fn push_undo_record(
+        &self,
+        operation: Operation,
+        timestamp: Timestamp,
+        hashes: &[sha256::Hash],
+    ) -> anyhow::Result<()> {
+        let changes = self
+            .pending_changes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        let Some(changes) = changes.filter(|changes| !changes.is_empty()) else {
+            return Ok(());
+        };
+        // Undoing an operation cannot be undone itself.
+        if operation == Operation::Undo {
+            return Ok(());
+        }
+
+        let path = self.undo_log_path();
+        let mut records = read_undo_log(&path)?;
+        records.push(UndoRecord {
+            timestamp,
+            operation,
+            hashes: hashes.to_vec(),
+            previous: changes.previous,
+            positions: changes.positions,
+            added: changes.added,
+            trashed: Vec::new(),
+        });
+        let dropped = records.len().saturating_sub(UNDO_LIMIT);
+        let records = &records[dropped..];
+        write_undo_log(&path, records)?;
+        if dropped > 0 {
+            purge_trash(&self.path, records)?;
+        }
+        Ok(())
+    }
+
+    /// Return the operations that can be undone, most recent first.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the undo log cannot be read.
+    pub fn undo_history(&self) -> anyhow::Result<Vec<UndoRecord>> {
+        let mut records = read_undo_log(&self.undo_log_path())?;
+        records.reverse();
+        Ok(records)
+    }
+
+    /// Undo the most recent operation on the library, and return it.
+    ///
+    /// The index entries changed or removed by the operation are put back as they were, at their
+    /// previous positions in the index, and the documents it added are moved to the trash.
+    /// Documents the operation removed or replaced are moved back from the trash into the document
+    /// store. Changes made to the same documents by other processes since the operation, e.g.
+    /// recording that a document was opened, are undone as well. Only the last 20 operations can
+    /// be undone.
+    ///
+    /// The documents added by the undone operation are kept in the trash until the operation
+    /// before it drops off the undo log, like the documents removed by that operation.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there is nothing to undo, if the file of a document that the
+    /// operation removed is no longer in the trash, or if the undo log or the index cannot be
+    /// read or written.
+    pub fn undo(&self) -> anyhow::Result<UndoRecord> {
+        let path = self.undo_log_path();
+        let mut records = read_undo_log(&path)?;
+        let Some(record) = records.pop() else {
+            bail!("Nothing to undo");
+        };
+
+        let mut index = LibraryIndex::open(&self.index_path())?;
+        let previous: HashSet<_> = record.previous.iter().map(|entry| entry.hash).collect();
+        index
+            .documents
+            .retain(|entry| previous.contains(&entry.hash) || !record.added.contains(&entry.hash));
+        // Entries that are still in the index are replaced in place, the others are inserted at
+        // their previous positions, so that undoing does not reorder the index.
+        let mut restore: HashMap<_, _> = record
+            .previous
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let position = record.positions.get(i).copied().unwrap_or(usize::MAX);
+                (entry.hash, (position, entry))
+            })
+            .collect();
+        for entry in &mut index.documents {
+            if let Some((_, previous)) = restore.remove(&entry.hash) {
+                entry.clone_from(previous);
+            }
+        }
+        let mut missing: Vec<_> = restore.into_values().collect();
+        missing.sort_unstable_by_key(|(position, _)| *position);
+        for (position, entry) in missing {
+            let position = position.min(index.documents.len());
+            index.documents.insert(position, entry.clone());
+        }
+        let trash: Vec<_> = record
+            .added
+            .iter()
+            .filter(|hash| !previous.contains(hash))
+            .copied()
+            .collect();
+
+        let untrashed = self.untrash_documents(&record.previous)?;
+        if let Err(error) = self.begin_index_update(&index, &[], &trash) {
+            // Move the documents back to the trash on a best-effort basis and propagate the
+            // original error.
+            let _ = super::wal::trash_documents(&self.path, &untrashed);
+            return Err(error);
+        }
+        super::wal::trash_documents(&self.path, &trash)?;
+        self.finish_index_update(&index)?;
+        if let Some(before) = records.last_mut() {
+            before
+                .trashed
+                .extend(record.trashed.iter().chain(&trash).copied());
+        }
+        write_undo_log(&path, &records)?;
+
+        let mut hashes: Vec<_> = previous.into_iter().chain(trash).collect();
+        hashes.sort_unstable();
+        self.log_event(Operation::Undo, hashes);
+        Ok(record)
+    }
+
+    /// Move the files of `entries` that are not in the document store back from the trash, and
+    /// return their hashes.
+    fn untrash_documents(&self, entries: &[IndexEntry]) -> anyhow::Result<Vec<sha256::Hash>> {
+        let document_store_dir = self.path.join(DOCUMENT_STORE_DIR);
+        let trash_dir = self.path.join(TRASH_DIR);
+        let missing: Vec<_> = entries
+            .iter()
+            .map(|entry| entry.hash)
+            .filter(|hash| !document_store_dir.join(hash.to_string()).exists())
+            .collect();
+        if let Some(hash) = missing
+            .iter()
+            .find(|hash| !trash_dir.join(hash.to_string()).exists())
+        {
+            bail!(
+                "Cannot undo, the file of {} is no longer in the trash",
+                hash.to_short_string()
+            );
+        }
+
+        fs::create_dir_all(&document_store_dir).with_context(|| {
+            format!(
+                "Failed to create document store directory at {}",
+                document_store_dir.display()
+            )
+        })?;
+        let mut untrashed = Vec::with_capacity(missing.len());
+        for hash in missing {
+            let trash_path = trash_dir.join(hash.to_string());
+            let doc_path = document_store_dir.join(hash.to_string());
+            if let Err(error) = fs::rename(&trash_path, &doc_path) {
+                let _ = super::wal::trash_documents(&self.path, &untrashed);
+                return Err(error).with_context(|| {
+                    format!(
+                        "Failed to move document from {} to {}",
+                        trash_path.display(),
+                        doc_path.display()
+                    )
+                });
+            }
+            untrashed.push(hash);
+        }
+        Ok(untrashed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{UndoRecord, TRASH_DIR, UNDO_LIMIT},
+        crate::{
+            testing::{self, TempLibrary},
+            FileFormat, Operation,
+        },
+        std::{fs, iter},
+    };
+
+    #[test]
+    fn undo_operations_in_reverse() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let titles = || -> Vec<String> {
+            library
+                .documents()
+                .expect("Failed to read index")
+                .map(|entry| entry.title().to_owned())
+                .collect()
+        };
+        let original = titles();
+        let hash = library
+            .documents()
+            .expect("Failed to read index")
+            .next()
+            .expect("Library has documents")
+            .hash()
+            .to_string();
+
+        let added = library
+            .add(testing::metadata("Added", FileFormat::Pdf))
+            .expect("Failed to add document");
+        library
+            .edit_metadata(&hash, |entry| {
+                entry.set_title("Edited".to_owned());
+                Ok(())
+            })
+            .expect("Failed to edit");
+        let replacement = library.path().join("replacement.pdf");
+        fs::write(
+            &replacement,
+            testing::synthetic_document("Replacement", FileFormat::Pdf),
+        )
+        .expect("Failed to write document");
+        library
+            .replace_blob(&hash, &replacement)
+            .expect("Failed to replace");
+        let added = added.to_string();
+        let removed = library
+            .remove_all(iter::once(added.as_str()))
+            .expect("Failed to remove");
+        assert_eq!(removed.removed().len(), 1);
+
+        let operations: Vec<_> = library
+            .undo_history()
+            .expect("Failed to read undo log")
+            .iter()
+            .take(4)
+            .map(UndoRecord::operation)
+            .collect();
+        assert_eq!(
+            operations,
+            [
+                Operation::Remove,
+                Operation::Replace,
+                Operation::Edit,
+                Operation::Add
+            ]
+        );
+
+        for operation in operations {
+            assert_eq!(
+                library.undo().expect("Failed to undo").operation(),
+                operation
+            );
+            assert!(library.validate().expect("Failed to validate").is_valid());
+        }
+        assert_eq!(titles(), original);
+    }
+
+    #[test]
+    fn purge_trash_when_undo_log_is_full() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let hashes: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| entry.hash().to_string())
+            .collect();
+        library
+            .remove_all(iter::once(hashes[0].as_str()))
+            .expect("Failed to remove");
+        let trash = library.path().join(TRASH_DIR).join(&hashes[0]);
+        assert!(trash.exists());
+
+        for i in 0..UNDO_LIMIT {
+            library
+                .edit_metadata(&hashes[1], |entry| {
+                    entry.set_title(format!("Edit {i}"));
+                    Ok(())
+                })
+                .expect("Failed to edit");
+            // The removal can be undone until it is dropped from the undo log.
+            assert_eq!(trash.exists(), i + 1 < UNDO_LIMIT);
+        }
+    }
+}
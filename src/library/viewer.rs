@@ -0,0 +1,95 @@
+//! Opening documents in a viewer.
+
+use {
+    super::{output_file_name, LibraryIndex, RetrievedDocument},
+    crate::{Library, OutputConflict, RetrieveOptions},
+    anyhow::{bail, Context},
+    std::{env, fs, path::Path, process},
+};
+
+/// Return the command that opens a file with the default application of the system.
+fn default_open_command() -> Vec<String> {
+    let command: &[&str] = if cfg!(windows) {
+        // The empty argument is the window title, `start` would use a quoted path as title.
+        &["cmd", "/C", "start", "", "{path}"]
+    } else if cfg!(target_os = "macos") {
+        &["open", "{path}"]
+    } else {
+        &["xdg-open", "{path}"]
+    };
+    command.iter().map(|&arg| arg.to_owned()).collect()
+}
+
+/// Run the command `command` to open the file at `path`.
+fn run_open_command(command: &[String], path: &Path) -> anyhow::Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("Open command is empty");
+    };
+    let status = process::Command::new(program)
+        .args(
+            args.iter()
+                .map(|arg| arg.replace("{path}", &path.to_string_lossy())),
+        )
+        .status()
+        .with_context(|| format!("Failed to run open command {program}"))?;
+    if !status.success() {
+        bail!("Open command {} failed ({})", program, status);
+    }
+    Ok(())
+}
+
+impl Library {
+    /// Open a document in a viewer.
+    ///
+    /// The document matching `identifier` (see [`Library::find_document()`]) is copied to the
+    /// directory `burette-open` in the temporary directory of the system, under the name it would
+    /// get from [`Library::retrieve_document()`], and opened with `command`. The placeholder
+    /// `{path}` in the arguments of `command` is replaced by the path of the copy. If `command`
+    /// is `None`, the default application of the system is used. Archived documents are
+    /// considered as well. The returned [`RetrievedDocument`] holds the path of the copy.
+    ///
+    /// Like retrievals, opening a document is not recorded in the index, use
+    /// [`Library::record_access()`] for that.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - Multiple or no documents match the identifier.
+    /// - The document cannot be copied to the temporary directory.
+    /// - The open command cannot be run or fails.
+    /// - The index file cannot be read.
+    pub fn view_document(
+        &self,
+        identifier: &str,
+        command: Option<&[String]>,
+    ) -> anyhow::Result<RetrievedDocument> {
+        let index_path = self.index_path();
+        let index = LibraryIndex::open(&index_path)?;
+
+        let entry = index.find_document(identifier, true)?;
+
+        let view_dir = env::temp_dir().join("burette-open");
+        fs::create_dir_all(&view_dir).with_context(|| {
+            format!(
+                "Failed to create temporary directory at {}",
+                view_dir.display()
+            )
+        })?;
+        // A copy from an earlier call may still be open in a viewer, it is replaced regardless.
+        let options = RetrieveOptions {
+            on_conflict: OutputConflict::Overwrite,
+            ..RetrieveOptions::default()
+        };
+        let file_name = output_file_name(entry, &options)?;
+        let path = self.copy_out(entry, view_dir.join(file_name), &options)?;
+
+        match command {
+            Some(command) => run_open_command(command, &path)?,
+            None => run_open_command(&default_open_command(), &path)?,
+        }
+        Ok(RetrievedDocument {
+            entry: entry.clone(),
+            path,
+        })
+    }
+}
@@ -0,0 +1,189 @@
+//! Documents consisting of multiple files, e.g. multi-volume editions and split scans.
+
+use {
+    super::{output_file_name, resolve_output_conflict, IndexEntry, LibraryIndex, Operation},
+    crate::{sha256, Library, OutputConflict, RetrieveOptions},
+    anyhow::{bail, Context},
+    std::{ffi::OsString, fs, path::PathBuf},
+};
+
+impl Library {
+    /// Make the documents matching `volumes` the further volumes of the document matching
+    /// `identifier`, in the given order, and return the hash of the document.
+    ///
+    /// The document itself is the first volume. Each volume stays a document of its own, with
+    /// its own metadata, but retrieving the first volume retrieves all volumes (see
+    /// [`Library::retrieve_document()`]). Passing no volumes turns the document back into a
+    /// single-volume document. Archived documents are considered as well.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned in any of the following cases:
+    /// - An identifier does not match exactly one document.
+    /// - A volume is the document itself, is given twice, has volumes of its own or already is a
+    ///   volume of another document.
+    /// - The document itself is a volume of another document.
+    /// - The index file cannot be read or written.
+    pub fn set_volumes(&self, identifier: &str, volumes: &[&str]) -> anyhow::Result<sha256::Hash> {
+        let index_path = self.index_path();
+        let mut index = LibraryIndex::open(&index_path)?;
+
+        let hash = *index.find_document(identifier, true)?.hash();
+        if let Some(parent) = volume_of(&index, &hash) {
+            bail!(
+                "Document {} is already a volume of {}: {}",
+                hash.to_short_string(),
+                parent.hash.to_short_string(),
+                parent.title()
+            );
+        }
+
+        let mut volume_hashes: Vec<sha256::Hash> = Vec::with_capacity(volumes.len());
+        for volume in volumes {
+            let entry = index.find_document(volume, true)?;
+            if entry.hash == hash {
+                bail!("A document cannot be a volume of itself");
+            }
+            if volume_hashes.contains(&entry.hash) {
+                bail!("Volume {} is given twice", entry.hash.to_short_string());
+            }
+            if !entry.volumes.is_empty() {
+                bail!(
+                    "Document {} has volumes of its own and cannot be a volume",
+                    entry.hash.to_short_string()
+                );
+            }
+            if let Some(parent) = volume_of(&index, &entry.hash).filter(|p| p.hash != hash) {
+                bail!(
+                    "Document {} is already a volume of {}: {}",
+                    entry.hash.to_short_string(),
+                    parent.hash.to_short_string(),
+                    parent.title()
+                );
+            }
+            volume_hashes.push(entry.hash);
+        }
+
+        if let Some(entry) = index.documents.iter_mut().find(|entry| entry.hash == hash) {
+            entry.volumes = volume_hashes;
+        }
+        self.update_index(&index)?;
+        self.log_event(Operation::Edit, vec![hash]);
+
+        Ok(hash)
+    }
+
+    /// Copy the document of `entry` and all of its volumes to `out_path`, and return the path
+    /// they were copied to.
+    ///
+    /// Documents without volumes are copied like by [`Library::copy_out()`]. Otherwise, a
+    /// directory named like `out_path` without its extension is created, and the volumes are
+    /// copied into it in order, each under the name it would get from
+    /// [`Library::retrieve_document()`] prefixed with its number, e.g. `01_moby_dick.pdf`. If the
+    /// directory already exists, [`RetrieveOptions::on_conflict`] decides what happens.
+    pub(super) fn copy_out_volumes(
+        &self,
+        index: &LibraryIndex,
+        entry: &IndexEntry,
+        out_path: PathBuf,
+        options: &RetrieveOptions,
+    ) -> anyhow::Result<PathBuf> {
+        if entry.volumes.is_empty() {
+            return self.copy_out(entry, out_path, options);
+        }
+
+        let mut volumes = vec![entry];
+        for hash in &entry.volumes {
+            let volume = index
+                .documents
+                .iter()
+                .find(|volume| volume.hash == *hash)
+                .with_context(|| format!("Volume {hash} is not in the library"))?;
+            volumes.push(volume);
+        }
+
+        let out_dir = resolve_output_conflict(out_path.with_extension(""), options.on_conflict)?;
+        fs::create_dir_all(&out_dir).with_context(|| {
+            format!("Failed to create output directory at {}", out_dir.display())
+        })?;
+        // Conflicts were resolved for the directory as a whole.
+        let volume_options = RetrieveOptions {
+            on_conflict: OutputConflict::Overwrite,
+            ..options.clone()
+        };
+        let width = volumes.len().to_string().len().max(2);
+        for (number, volume) in volumes.into_iter().enumerate() {
+            let file_name = output_file_name(volume, options)?;
+            let mut numbered = OsString::from(format!("{:0width$}_", number + 1));
+            numbered.push(file_name);
+            self.copy_out(volume, out_dir.join(numbered), &volume_options)?;
+        }
+        Ok(out_dir)
+    }
+}
+
+/// Return the document that has the document with hash `hash` as one of its volumes.
+fn volume_of<'a>(index: &'a LibraryIndex, hash: &sha256::Hash) -> Option<&'a IndexEntry> {
+    index
+        .documents
+        .iter()
+        .find(|entry| entry.volumes.contains(hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::TempLibrary;
+
+    #[test]
+    fn set_and_clear_volumes() {
+        let library = TempLibrary::with_documents(4).expect("Failed to create library");
+        let hashes: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| entry.hash().to_string())
+            .collect();
+
+        library
+            .set_volumes(&hashes[0], &[&hashes[2], &hashes[1]])
+            .expect("Failed to set volumes");
+        let entry = library
+            .find_document(&hashes[0], false)
+            .expect("Document not found");
+        let volumes: Vec<_> = entry.volumes().map(ToString::to_string).collect();
+        assert_eq!(volumes, [hashes[2].clone(), hashes[1].clone()]);
+
+        // Volumes cannot be nested or shared.
+        assert!(library.set_volumes(&hashes[1], &[&hashes[3]]).is_err());
+        assert!(library.set_volumes(&hashes[3], &[&hashes[1]]).is_err());
+        assert!(library.set_volumes(&hashes[3], &[&hashes[0]]).is_err());
+        assert!(library.set_volumes(&hashes[3], &[&hashes[3]]).is_err());
+
+        library
+            .set_volumes(&hashes[0], &[])
+            .expect("Failed to clear volumes");
+        library
+            .set_volumes(&hashes[3], &[&hashes[1]])
+            .expect("Volume was not released");
+    }
+
+    #[test]
+    fn removed_volumes_are_forgotten() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let hashes: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| entry.hash().to_string())
+            .collect();
+
+        library
+            .set_volumes(&hashes[0], &[&hashes[1]])
+            .expect("Failed to set volumes");
+        library
+            .remove_all([hashes[1].as_str()].into_iter())
+            .expect("Failed to remove volume");
+        let entry = library
+            .find_document(&hashes[0], false)
+            .expect("Document not found");
+        assert_eq!(entry.volumes().count(), 0);
+    }
+}
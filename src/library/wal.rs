@@ -0,0 +1,420 @@
+//! Write-ahead log for index updates.
+//!
+//! Before the index file is rewritten, the change to the index (and the documents that the update
+//! removes or moves to the trash) is written to the write-ahead log. Only once the log is safely
+//! on disk is the index file replaced. If burette is interrupted in the middle of an update, the
+//! log is replayed the next time the library is opened, which completes the interrupted update.
+//!
+//! The log only contains the entries that the update adds or changes, and the order of the hashes
+//! of all entries. The other entries are taken from the index file, which is not replaced before
+//! the log is complete. This keeps the log small, since most updates only change a few entries.
+//!
+//! While an update is in progress, the updating process holds an exclusive lock on the lock file.
+//! The log is only replayed if no process holds the lock, so that opening the library does not
+//! complete an update that another process is still performing.
+
+use {
+    super::{IndexEntry, LibraryIndex, DOCUMENT_STORE_DIR, INDEX_FILE, TRASH_DIR},
+    crate::{sha256, Library},
+    anyhow::Context,
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        fs::{self, File, OpenOptions, TryLockError},
+        io::{self, Write},
+        path::{Path, PathBuf},
+        sync::PoisonError,
+    },
+};
+
+/// The location of the write-ahead log within the library directory.
+const WAL_FILE: &str = "index.wal";
+
+/// The location of the lock file within the library directory.
+const LOCK_FILE: &str = "index.lock";
+
+/// Open the lock file of the library at `path`, creating it if it does not exist.
+fn open_lock_file(path: &Path) -> anyhow::Result<File> {
+    let lock_path = path.join(LOCK_FILE);
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open lock file at {}", lock_path.display()))
+}
+
+/// A pending update of the index, as stored in the write-ahead log.
+#[derive(Debug, Serialize)]
+struct WalRecord<'a> {
+    /// The hashes of all documents in the new index, in order.
+    documents: Vec<sha256::Hash>,
+    /// The entries of the new index that are not in the index file, or differ from it.
+    changed: Vec<&'a IndexEntry>,
+    remove_documents: &'a [sha256::Hash],
+    trash_documents: &'a [sha256::Hash],
+}
+
+/// Owned version of [`WalRecord`] used when replaying the log.
+#[derive(Debug, Deserialize)]
+struct OwnedWalRecord {
+    documents: Vec<sha256::Hash>,
+    changed: Vec<IndexEntry>,
+    remove_documents: Vec<sha256::Hash>,
+    #[serde(default)]
+    trash_documents: Vec<sha256::Hash>,
+}
+
+impl OwnedWalRecord {
+    /// Return the new index, reading the unchanged entries from the index file at `index_path`.
+    ///
+    /// The index file is only read if some entries are unchanged, so that the update can be
+    /// replayed even if the index file was unreadable before it.
+    fn new_index(self, index_path: &Path) -> anyhow::Result<LibraryIndex> {
+        let mut entries: HashMap<_, _> = self
+            .changed
+            .into_iter()
+            .map(|entry| (entry.hash, entry))
+            .collect();
+        if self
+            .documents
+            .iter()
+            .any(|hash| !entries.contains_key(hash))
+        {
+            for entry in LibraryIndex::open(index_path)?.documents {
+                entries.entry(entry.hash).or_insert(entry);
+            }
+        }
+        let documents = self
+            .documents
+            .iter()
+            .map(|hash| {
+                entries.remove(hash).with_context(|| {
+                    format!(
+                        "Write-ahead log refers to document {}, which is not in the index",
+                        hash.to_short_string()
+                    )
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(LibraryIndex { documents })
+    }
+}
+
+/// Remove the documents with the given hashes from the document store of the library at `path`.
+///
+/// Documents that do not exist are ignored.
+pub(super) fn remove_documents(path: &Path, hashes: &[sha256::Hash]) -> anyhow::Result<()> {
+    let document_store_dir = path.join(DOCUMENT_STORE_DIR);
+    for hash in hashes {
+        let doc_path = document_store_dir.join(hash.to_string());
+        match fs::remove_file(&doc_path) {
+            Ok(()) => (),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+            Err(error) => {
+                return Err(error).context(format!(
+                    "Failed to remove document at {}",
+                    doc_path.display()
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Move the documents with the given hashes from the document store of the library at `path` to
+/// its trash directory.
+///
+/// Documents that do not exist in the document store are ignored.
+pub(super) fn trash_documents(path: &Path, hashes: &[sha256::Hash]) -> anyhow::Result<()> {
+    if hashes.is_empty() {
+        return Ok(());
+    }
+    let document_store_dir = path.join(DOCUMENT_STORE_DIR);
+    let trash_dir = path.join(TRASH_DIR);
+    fs::create_dir_all(&trash_dir).with_context(|| {
+        format!(
+            "Failed to create trash directory at {}",
+            trash_dir.display()
+        )
+    })?;
+    for hash in hashes {
+        let doc_path = document_store_dir.join(hash.to_string());
+        let trash_path = trash_dir.join(hash.to_string());
+        match fs::rename(&doc_path, &trash_path) {
+            Ok(()) => (),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+            Err(error) => {
+                return Err(error).context(format!(
+                    "Failed to move document from {} to {}",
+                    doc_path.display(),
+                    trash_path.display()
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Open the write-ahead log of the library at `path`, or return `None` if there is none.
+fn open_wal(path: &Path) -> anyhow::Result<Option<File>> {
+    let wal_path = path.join(WAL_FILE);
+    match File::open(&wal_path) {
+        Ok(file) => Ok(Some(file)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).context(format!(
+            "Failed to open write-ahead log at {}",
+            wal_path.display()
+        )),
+    }
+}
+
+/// Return true if the library at `path` has a write-ahead log that is not being written by a
+/// running process, i.e. an interrupted update that [`replay()`] would complete.
+///
+/// This does not write to the library.
+pub(super) fn has_pending_update(path: &Path) -> anyhow::Result<bool> {
+    if open_wal(path)?.is_none() {
+        return Ok(false);
+    }
+    let lock_path = path.join(LOCK_FILE);
+    let lock = match File::open(&lock_path) {
+        Ok(lock) => lock,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(true),
+        Err(error) => return Err(error).context(format!("Failed to open {}", lock_path.display())),
+    };
+    match lock.try_lock() {
+        Ok(()) => Ok(true),
+        Err(TryLockError::WouldBlock) => Ok(false),
+        Err(TryLockError::Error(error)) => {
+            Err(error).context(format!("Failed to lock {}", lock_path.display()))
+        }
+    }
+}
+
+/// Replay the write-ahead log of the library at `path`, if there is one.
+///
+/// This completes an index update that was interrupted before it finished. If another process is
+/// still performing the update, nothing is done.
+pub(super) fn replay(path: &Path) -> anyhow::Result<()> {
+    // The lock file is only created if there is a log, so that libraries without pending updates
+    // can be opened without write access.
+    if open_wal(path)?.is_none() {
+        return Ok(());
+    }
+    let lock = open_lock_file(path)?;
+    match lock.try_lock() {
+        Ok(()) => (),
+        Err(TryLockError::WouldBlock) => return Ok(()),
+        Err(TryLockError::Error(error)) => {
+            return Err(error).context(format!("Failed to lock {}", path.join(LOCK_FILE).display()))
+        }
+    }
+    // The update may have been finished while waiting for the lock.
+    let Some(file) = open_wal(path)? else {
+        return Ok(());
+    };
+    let wal_path = path.join(WAL_FILE);
+    let record: OwnedWalRecord = serde_json::from_reader(file)
+        .with_context(|| format!("Failed to read write-ahead log from {}", wal_path.display()))?;
+
+    let remove = record.remove_documents.clone();
+    let trash = record.trash_documents.clone();
+    let index_path = path.join(INDEX_FILE);
+    record.new_index(&index_path)?.save(&index_path)?;
+    remove_documents(path, &remove)?;
+    trash_documents(path, &trash)?;
+
+    fs::remove_file(&wal_path)
+        .with_context(|| format!("Failed to remove write-ahead log at {}", wal_path.display()))
+}
+
+impl Library {
+    /// Return the path to the write-ahead log of the library.
+    fn wal_path(&self) -> PathBuf {
+        self.path.join(WAL_FILE)
+    }
+
+    /// Record an index update in the write-ahead log.
+    ///
+    /// `index` is the complete new index, `remove_documents` are the hashes of the documents that
+    /// the update removes from the document store and `trash_documents` are the hashes of the
+    /// documents that the update moves to the trash.
+    ///
+    /// After this function returns successfully, the update must be finished with
+    /// [`Library::finish_index_update()`].
+    pub(super) fn begin_index_update(
+        &self,
+        index: &LibraryIndex,
+        remove_documents: &[sha256::Hash],
+        trash_documents: &[sha256::Hash],
+    ) -> anyhow::Result<()> {
+        // The current index is read once, to find the changes for both the log and undo. If it
+        // cannot be read, e.g. because it is being restored from a backup, the log contains the
+        // complete new index and the update cannot be undone.
+        let old = LibraryIndex::open(&self.index_path()).ok();
+        self.remember_index_changes(old.as_ref(), index);
+        self.write_ahead(old.as_ref(), index, remove_documents, trash_documents)
+    }
+
+    /// Write the changes from `old` to `index` to the write-ahead log, without remembering them
+    /// for [`Library::undo()`].
+    ///
+    /// `old` must be the current index file, or `None` to write all entries of `index`. The lock
+    /// of the library is acquired first and held until [`Library::finish_index_update()`].
+    fn write_ahead(
+        &self,
+        old: Option<&LibraryIndex>,
+        index: &LibraryIndex,
+        remove_documents: &[sha256::Hash],
+        trash_documents: &[sha256::Hash],
+    ) -> anyhow::Result<()> {
+        let mut lock = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if lock.is_none() {
+            let file = open_lock_file(&self.path)?;
+            file.lock().with_context(|| {
+                format!("Failed to lock {}", self.path.join(LOCK_FILE).display())
+            })?;
+            *lock = Some(file);
+        }
+        let result = self.write_wal(old, index, remove_documents, trash_documents);
+        // Without a log, there is no update in progress.
+        if result.is_err() {
+            *lock = None;
+        }
+        result
+    }
+
+    /// Write the write-ahead log for [`Library::write_ahead()`].
+    fn write_wal(
+        &self,
+        old: Option<&LibraryIndex>,
+        index: &LibraryIndex,
+        remove_documents: &[sha256::Hash],
+        trash_documents: &[sha256::Hash],
+    ) -> anyhow::Result<()> {
+        let wal_path = self.wal_path();
+        let tmp_path = self.path.join(format!("{WAL_FILE}.tmp"));
+        let context = || format!("Failed to write write-ahead log to {}", wal_path.display());
+
+        let old: HashMap<_, _> = old
+            .iter()
+            .flat_map(|old| &old.documents)
+            .map(|entry| (entry.hash, entry))
+            .collect();
+        let record = WalRecord {
+            documents: index.documents.iter().map(|entry| entry.hash).collect(),
+            changed: index
+                .documents
+                .iter()
+                .filter(|entry| old.get(&entry.hash) != Some(entry))
+                .collect(),
+            remove_documents,
+            trash_documents,
+        };
+        let mut file = File::create(&tmp_path).with_context(context)?;
+        serde_json::to_writer(&mut file, &record).with_context(context)?;
+        file.flush().with_context(context)?;
+        file.sync_all().with_context(context)?;
+
+        // Renaming is atomic, so the log is never observed in a partially written state.
+        fs::rename(&tmp_path, &wal_path).with_context(context)
+    }
+
+    /// Write the new index to disk and clear the write-ahead log.
+    ///
+    /// `index` is the index as it should be after the update. It may differ from the index passed
+    /// to [`Library::begin_index_update()`], e.g. if some documents could not be removed. The lock
+    /// acquired by [`Library::begin_index_update()`] is released afterwards.
+    pub(super) fn finish_index_update(&self, index: &LibraryIndex) -> anyhow::Result<()> {
+        let _lock = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        index.save(&self.index_path())?;
+        let wal_path = self.wal_path();
+        match fs::remove_file(&wal_path) {
+            Ok(()) => Ok(()),
+            // The log may have been replayed by a process that does not take the lock, e.g. an
+            // older version of burette.
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error).context(format!(
+                "Failed to remove write-ahead log at {}",
+                wal_path.display()
+            )),
+        }
+    }
+
+    /// Replace the index of the library by `index`, going through the write-ahead log.
+    pub(super) fn update_index(&self, index: &LibraryIndex) -> anyhow::Result<()> {
+        self.begin_index_update(index, &[], &[])?;
+        self.finish_index_update(index)
+    }
+
+    /// Replace the index of the library by `index` like [`Library::update_index()`], but without
+    /// recording the update for [`Library::undo()`].
+    ///
+    /// `old` is the index as it was read before the update. This is meant for bookkeeping that is
+    /// not an operation of its own and is therefore not followed by [`Library::log_event()`], e.g.
+    /// recording when documents were accessed.
+    pub(super) fn update_index_untracked(
+        &self,
+        old: &LibraryIndex,
+        index: &LibraryIndex,
+    ) -> anyhow::Result<()> {
+        self.write_ahead(Some(old), index, &[], &[])?;
+        self.finish_index_update(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::WAL_FILE,
+        crate::{testing::TempLibrary, Library, LibraryIndex},
+        std::fs,
+    };
+
+    #[test]
+    fn do_not_replay_update_in_progress() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let mut index = LibraryIndex::open(&library.index_path()).expect("Failed to read index");
+        index.documents.pop();
+        library
+            .begin_index_update(&index, &[], &[])
+            .expect("Failed to begin update");
+
+        // Another process opening the library must not complete the update.
+        let other = Library::open(library.path()).expect("Failed to open library");
+        assert_eq!(other.documents().expect("Failed to read index").count(), 2);
+        assert!(library.path().join(WAL_FILE).exists());
+
+        library
+            .finish_index_update(&index)
+            .expect("Failed to finish update");
+        assert_eq!(other.documents().expect("Failed to read index").count(), 1);
+        assert!(!library.path().join(WAL_FILE).exists());
+    }
+
+    #[test]
+    fn finish_replayed_update() {
+        let library = TempLibrary::with_documents(2).expect("Failed to create library");
+        let mut index = LibraryIndex::open(&library.index_path()).expect("Failed to read index");
+        index.documents.pop();
+        library
+            .begin_index_update(&index, &[], &[])
+            .expect("Failed to begin update");
+        super::replay(library.path()).expect("Failed to replay");
+        // The log is only replayed if no update is in progress.
+        assert!(library.path().join(WAL_FILE).exists());
+
+        fs::remove_file(library.path().join(WAL_FILE)).expect("Failed to remove log");
+        library
+            .finish_index_update(&index)
+            .expect("Failed to finish update");
+    }
+}
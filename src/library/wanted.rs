@@ -0,0 +1,213 @@
+//! Wanted documents.
+//!
+//! Wanted documents are metadata-only entries for documents that are not in the library yet, e.g.
+//! books on a wishlist. They are stored in a separate file, since they have no file and thus no
+//! hash to be identified by. Instead, each wanted document gets a number. Once a file for a
+//! wanted document is available, it can be added to the library with
+//! [`Library::fulfill_wanted()`], which uses the metadata of the wanted document.
+
+use {
+    super::{AddOptions, DocMetadata, IndexEntry, LookupError},
+    crate::{DocType, FileFormat, Isbn13, Library},
+    anyhow::Context,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs::{self, File},
+        io::{self, Write},
+        path::{Path, PathBuf},
+    },
+};
+
+/// The location of the wanted documents file within the library directory.
+const WANTED_FILE: &str = "wanted.json";
+
+/// Metadata of a wanted document.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WantedMetadata {
+    /// Title of the document.
+    pub title: String,
+    /// List of authors of the document. May be empty.
+    pub authors: Vec<String>,
+    /// List of ISBNs for the document. May be empty.
+    pub isbns: Vec<Isbn13>,
+    /// DOI of the document.
+    pub doi: Option<String>,
+    /// Type of the document, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_type: Option<DocType>,
+}
+
+/// A document that is wanted, but not in the library yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WantedEntry {
+    id: u64,
+    #[serde(flatten)]
+    metadata: WantedMetadata,
+}
+
+impl WantedEntry {
+    /// Return the number identifying the wanted document.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Return the metadata of the wanted document.
+    #[must_use]
+    pub fn metadata(&self) -> &WantedMetadata {
+        &self.metadata
+    }
+}
+
+/// Read the wanted documents from the file at `path`.
+///
+/// A missing file is equivalent to an empty list.
+fn read_wanted(path: &Path) -> anyhow::Result<Vec<WantedEntry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).context(format!(
+                "Failed to open wanted documents file at {}",
+                path.display()
+            ))
+        }
+    };
+    serde_json::from_reader(file)
+        .with_context(|| format!("Failed to read wanted documents from {}", path.display()))
+}
+
+/// Write the wanted documents to the file at `path`.
+///
+/// Like the index, the file is first written to a temporary file which then replaces the
+/// original, so it is never left in a partially written state.
+fn write_wanted(path: &Path, wanted: &[WantedEntry]) -> anyhow::Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    let context = || {
+        format!(
+            "Failed to write wanted documents file at {}",
+            tmp_path.display()
+        )
+    };
+    let mut file = File::create(&tmp_path).with_context(context)?;
+    serde_json::to_writer_pretty(&mut file, wanted).with_context(context)?;
+    file.flush().with_context(context)?;
+    file.sync_all().with_context(context)?;
+    fs::rename(&tmp_path, path).with_context(|| {
+        format!(
+            "Failed to replace wanted documents file at {}",
+            path.display()
+        )
+    })
+}
+
+impl Library {
+    /// Return the path to the wanted documents file of the library.
+    fn wanted_path(&self) -> PathBuf {
+        self.path.join(WANTED_FILE)
+    }
+
+    /// Add a wanted document and return its entry.
+    ///
+    /// The wanted document is assigned a number that is one larger than the largest number of
+    /// the existing wanted documents.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the wanted documents file cannot be read or written.
+    pub fn add_wanted(&self, metadata: WantedMetadata) -> anyhow::Result<WantedEntry> {
+        let path = self.wanted_path();
+        let mut wanted = read_wanted(&path)?;
+        let id = wanted.iter().map(WantedEntry::id).max().unwrap_or(0) + 1;
+        let entry = WantedEntry { id, metadata };
+        wanted.push(entry.clone());
+        write_wanted(&path, &wanted)?;
+        Ok(entry)
+    }
+
+    /// Return all wanted documents, sorted by their number.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the wanted documents file cannot be read.
+    pub fn wanted(&self) -> anyhow::Result<Vec<WantedEntry>> {
+        let mut wanted = read_wanted(&self.wanted_path())?;
+        wanted.sort_unstable_by_key(WantedEntry::id);
+        Ok(wanted)
+    }
+
+    /// Remove a wanted document and return its entry.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there is no wanted document with the number `id` or if the wanted
+    /// documents file cannot be read or written.
+    pub fn remove_wanted(&self, id: u64) -> anyhow::Result<WantedEntry> {
+        let path = self.wanted_path();
+        let mut wanted = read_wanted(&path)?;
+        let position = wanted
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or_else(|| LookupError::NotFound(format!("No wanted document with number {id}")))?;
+        let entry = wanted.remove(position);
+        write_wanted(&path, &wanted)?;
+        Ok(entry)
+    }
+
+    /// Add the file at `path` to the library as the wanted document with the number `id`.
+    ///
+    /// The document is added with the metadata of the wanted document, as with
+    /// [`Library::add_document_checked()`]. Once the document has been added, the wanted
+    /// document is removed. If `confirm_similar` returns false, the document is not added, the
+    /// wanted document is kept and `false` is returned.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there is no wanted document with the number `id`, if the file
+    /// format of the file is not supported, or in any of the cases described in
+    /// [`Library::add_document_checked()`].
+    pub fn fulfill_wanted<P, F>(
+        &self,
+        id: u64,
+        path: P,
+        options: &AddOptions,
+        confirm_similar: F,
+    ) -> anyhow::Result<bool>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&[&IndexEntry]) -> anyhow::Result<bool>,
+    {
+        let entry = read_wanted(&self.wanted_path())?
+            .into_iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| LookupError::NotFound(format!("No wanted document with number {id}")))?;
+
+        let file_format = FileFormat::from_path(&path)?;
+        let WantedMetadata {
+            title,
+            authors,
+            isbns,
+            doi,
+            doc_type,
+        } = entry.metadata;
+        let metadata = DocMetadata {
+            title,
+            authors,
+            isbns,
+            file_format,
+            doi,
+            doc_type,
+            rights: None,
+            journal: None,
+            volume: None,
+            issue: None,
+            pages: None,
+        };
+
+        let added = self.add_document_checked(path, metadata, options, confirm_similar)?;
+        if added {
+            self.remove_wanted(id)?;
+        }
+        Ok(added)
+    }
+}
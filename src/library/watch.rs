@@ -0,0 +1,178 @@
+//! Notification about changes made to the library by other processes.
+//!
+//! All mutating operations append to the event log, so following the log is enough to learn
+//! about the changes of every burette process working on the library.
+
+use {
+    super::event_log::{ChangeEvent, Event},
+    crate::Library,
+    anyhow::Context,
+    std::{
+        fs::File,
+        io::{self, Read, Seek, SeekFrom},
+        mem,
+        path::PathBuf,
+        sync::mpsc::{self, Receiver, Sender},
+        thread,
+        time::Duration,
+    },
+};
+
+/// Follows the event log of a library from a position in the file.
+struct LogFollower {
+    path: PathBuf,
+    /// Position up to which the log has been read.
+    position: u64,
+    /// Contents of an incomplete last line, read before it was completely written.
+    partial_line: Vec<u8>,
+}
+
+impl LogFollower {
+    /// Start following the log at `path` at its current end.
+    fn at_end(path: PathBuf) -> anyhow::Result<Self> {
+        let position = match path.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => 0,
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("Failed to read event log at {}", path.display()))
+            }
+        };
+        Ok(Self {
+            path,
+            position,
+            partial_line: Vec::new(),
+        })
+    }
+
+    /// Return the events that were appended to the log since the last call.
+    fn read_new_events(&mut self) -> io::Result<Vec<Event>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        // The log is append-only, but it may have been replaced, e.g. by restoring a backup.
+        if file.metadata()?.len() < self.position {
+            self.position = 0;
+            self.partial_line.clear();
+        }
+        file.seek(SeekFrom::Start(self.position))?;
+        let mut contents = mem::take(&mut self.partial_line);
+        self.position += file.read_to_end(&mut contents)? as u64;
+
+        let complete = contents
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(0, |newline| newline + 1);
+        self.partial_line = contents.split_off(complete);
+        // Lines that cannot be parsed are skipped, `Library::events()` reports them.
+        Ok(contents
+            .split(|&byte| byte == b'\n')
+            .filter_map(|line| serde_json::from_slice(line).ok())
+            .collect())
+    }
+}
+
+/// Poll the event log every `interval` and send its new events to `sender`, until sending fails
+/// because the receiver was dropped.
+fn follow(mut follower: LogFollower, interval: Duration, sender: &Sender<ChangeEvent>) {
+    loop {
+        thread::sleep(interval);
+        // Errors are most likely temporary, e.g. because the library is on a network drive that
+        // is not reachable. The log is read again after the next interval.
+        let Ok(events) = follower.read_new_events() else {
+            continue;
+        };
+        for event in events {
+            let hashes: Vec<_> = event.hashes().copied().collect();
+            for change in ChangeEvent::from_operation(event.operation(), &hashes) {
+                if sender.send(change).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Library {
+    /// Receive the changes made to the library by any process from now on.
+    ///
+    /// A background thread checks the event log of the library for new events every `interval`
+    /// and sends them in the same form as [`Library::subscribe()`]. Unlike with
+    /// [`Library::subscribe()`], changes made by other burette processes are received as well,
+    /// including changes made through this [`Library`]. The thread stops at the first change
+    /// after the receiver has been dropped.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the event log cannot be accessed or the thread cannot be started.
+    pub fn watch(&self, interval: Duration) -> anyhow::Result<Receiver<ChangeEvent>> {
+        let follower = LogFollower::at_end(self.event_log_path())?;
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("burette-watch".to_owned())
+            .spawn(move || follow(follower, interval, &sender))
+            .context("Failed to start thread to watch the library")?;
+        Ok(receiver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::LogFollower,
+        crate::{
+            testing::{self, TempLibrary},
+            ChangeEvent, FileFormat, Library,
+        },
+        std::{
+            fs::{self, OpenOptions},
+            io::Write,
+            time::Duration,
+        },
+    };
+
+    #[test]
+    fn follow_partial_lines() {
+        let library = TempLibrary::with_documents(1).expect("Failed to create library");
+        let log_path = library.event_log_path();
+        let mut follower = LogFollower::at_end(log_path.clone()).expect("Failed to open log");
+        assert!(follower
+            .read_new_events()
+            .expect("Failed to read")
+            .is_empty());
+
+        library
+            .add(testing::metadata("Followed", FileFormat::Pdf))
+            .expect("Failed to add document");
+        let line = fs::read_to_string(&log_path).expect("Failed to read log");
+        let line = line.lines().last().expect("Log is not empty").to_owned();
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(&log_path)
+            .expect("Failed to open log");
+        log.write_all(&line.as_bytes()[..10])
+            .expect("Failed to write log");
+        assert_eq!(follower.read_new_events().expect("Failed to read").len(), 1);
+        writeln!(log, "{}", &line[10..]).expect("Failed to write log");
+        assert_eq!(follower.read_new_events().expect("Failed to read").len(), 1);
+    }
+
+    #[test]
+    fn watch_other_library() {
+        let library = TempLibrary::new().expect("Failed to create library");
+        let other = Library::open(library.path()).expect("Failed to open library");
+        let receiver = other
+            .watch(Duration::from_millis(10))
+            .expect("Failed to watch library");
+
+        let hash = library
+            .add(testing::metadata("Watched", FileFormat::Pdf))
+            .expect("Failed to add document");
+        let event = receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("No event received");
+        assert_eq!(event, ChangeEvent::DocumentAdded(hash));
+    }
+}
@@ -0,0 +1,142 @@
+//! Writing the metadata of documents into retrieved files, or into sidecar files next to them.
+
+use {
+    super::{DocMetadata, IndexEntry},
+    crate::{sha256, OutputTemplate},
+    anyhow::{bail, Context},
+    serde::Serialize,
+    std::{
+        ffi::OsString,
+        fs,
+        path::{Path, PathBuf},
+        process,
+    },
+};
+
+/// Suffix appended to the name of a retrieved file to get the name of its sidecar file.
+const SIDECAR_SUFFIX: &str = ".burette.json";
+
+/// Contents of a sidecar file, see [`crate::RetrieveOptions::write_sidecar`].
+#[derive(Debug, Serialize)]
+struct Sidecar<'a> {
+    hash: &'a sha256::Hash,
+    #[serde(flatten)]
+    metadata: &'a DocMetadata,
+}
+
+/// Expand an argument of a metadata command for the document `entry` stored at `path`.
+///
+/// `{file}` is replaced by `path`, everything else is rendered as an [`OutputTemplate`].
+fn expand_argument(arg: &str, entry: &IndexEntry, path: &Path) -> anyhow::Result<String> {
+    let mut expanded = String::new();
+    for (i, part) in arg.split("{file}").enumerate() {
+        if i > 0 {
+            expanded.push_str(&path.to_string_lossy());
+        }
+        let template: OutputTemplate = part
+            .parse()
+            .with_context(|| format!("Invalid argument {arg} of metadata command"))?;
+        expanded.push_str(&template.render(entry));
+    }
+    Ok(expanded)
+}
+
+/// Run the metadata command `command` to write the metadata of `entry` into the file at `path`.
+///
+/// The arguments of `command` are expanded by [`expand_argument()`]. This is used to apply
+/// [`crate::RetrieveOptions::metadata_command`]. If the command fails, the file is removed, so
+/// that no file is left behind that looks like a successful retrieval.
+pub(super) fn write_metadata(
+    command: &[String],
+    entry: &IndexEntry,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let result = run_metadata_command(command, entry, path);
+    if result.is_err() {
+        // The error of the command is more useful than an error during cleanup.
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+/// Return the path of the sidecar file of the retrieved file at `path`.
+fn sidecar_path(path: &Path) -> PathBuf {
+    let mut sidecar = OsString::from(path);
+    sidecar.push(SIDECAR_SUFFIX);
+    PathBuf::from(sidecar)
+}
+
+/// Write the hash and metadata of `entry` into a sidecar file next to the retrieved file at
+/// `path`.
+///
+/// This is used to apply [`crate::RetrieveOptions::write_sidecar`]. An existing sidecar file is
+/// overwritten, since it belongs to the file at `path`, which was just written.
+pub(super) fn write_sidecar(entry: &IndexEntry, path: &Path) -> anyhow::Result<()> {
+    let sidecar = Sidecar {
+        hash: &entry.hash,
+        metadata: &entry.metadata,
+    };
+    let sidecar_path = sidecar_path(path);
+    let mut contents =
+        serde_json::to_string_pretty(&sidecar).context("Failed to serialize sidecar file")?;
+    contents.push('\n');
+    fs::write(&sidecar_path, contents)
+        .with_context(|| format!("Failed to write sidecar file at {}", sidecar_path.display()))
+}
+
+/// Run the metadata command `command` for the document `entry` stored at `path`.
+fn run_metadata_command(command: &[String], entry: &IndexEntry, path: &Path) -> anyhow::Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("Metadata command is empty");
+    };
+    let args = args
+        .iter()
+        .map(|arg| expand_argument(arg, entry, path))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let status = process::Command::new(program)
+        .args(args)
+        .stdout(process::Stdio::null())
+        .status()
+        .with_context(|| format!("Failed to run metadata command {program}"))?;
+    if !status.success() {
+        bail!(
+            "Metadata command {} failed for {} ({})",
+            program,
+            path.display(),
+            status
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{expand_argument, sidecar_path},
+        crate::library::tests::moby_dick,
+        std::path::Path,
+    };
+
+    #[test]
+    fn sidecar_next_to_file() {
+        assert_eq!(
+            sidecar_path(Path::new("/tmp/out/moby_dick.epub")),
+            Path::new("/tmp/out/moby_dick.epub.burette.json")
+        );
+    }
+
+    #[test]
+    fn expand_metadata_arguments() {
+        let entry = moby_dick();
+        let path = Path::new("/tmp/out/moby_dick.epub");
+        assert_eq!(
+            expand_argument("{file}", &entry, path).expect("Valid argument"),
+            "/tmp/out/moby_dick.epub"
+        );
+        assert_eq!(
+            expand_argument("--title={title}", &entry, path).expect("Valid argument"),
+            "--title=Moby Dick; Or, The Whale"
+        );
+        assert!(expand_argument("{unknown}", &entry, path).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! MD5 hash functions.
+//!
+//! MD5 is broken as a cryptographic hash function and burette identifies documents by their
+//! SHA-256 hash. MD5 hashes are only recorded because shadow libraries such as Library Genesis
+//! and Anna's Archive identify their files by them, so that files seen there can be looked up in
+//! the library.
+
+use {
+    anyhow::anyhow,
+    md5::{Digest, Md5},
+    serde::{de::Error, Deserialize, Serialize},
+    std::{
+        fmt::{self, Debug, Display, Formatter},
+        io::{self, Read},
+        str::FromStr,
+    },
+};
+
+/// [MD5 hash](https://en.wikipedia.org/wiki/MD5).
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Hash {
+    bytes: [u8; 16],
+}
+
+fn hex_digit(byte: u8) -> anyhow::Result<u8> {
+    match byte {
+        b'0'..=b'9' => Ok(byte - b'0'),
+        b'a'..=b'f' => Ok(byte - b'a' + 10),
+        b'A'..=b'F' => Ok(byte - b'A' + 10),
+        _ => Err(anyhow!("Invalid character in MD5 hash")),
+    }
+}
+
+impl Hash {
+    /// Creates a new [`struct@Hash`] object from a hex-encoded string.
+    ///
+    /// Both lowercase and uppercase hexadecimal digits are accepted.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the input string is not 32 characters long or contains characters
+    /// other than hexadecimal digits.
+    pub fn from_hex(hex: &str) -> anyhow::Result<Self> {
+        let slice = hex.as_bytes();
+        if slice.len() != 32 {
+            return Err(anyhow!("Invalid MD5 hash length"));
+        }
+        let mut bytes = [0; 16];
+
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let hi = slice[i * 2];
+            let lo = slice[i * 2 + 1];
+            *byte = hex_digit(hi)? << 4 | hex_digit(lo)?;
+        }
+
+        Ok(Hash { bytes })
+    }
+}
+
+impl Debug for Hash {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for Hash {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for byte in &self.bytes {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Hash {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+impl Serialize for Hash {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_hex(&s).map_err(Error::custom)
+    }
+}
+
+/// Hashes the contents of the given reader and returns the MD5 hash.
+///
+/// # Errors
+///
+/// This function fails if it cannot read from the reader.
+///
+/// # Examples
+///
+/// ```
+/// # use burette::md5;
+/// let source = b"Hello, World!";
+/// let hash = md5::hash_reader(&source[..]).unwrap();
+/// assert_eq!(hash.to_string(), "65a8e27d8879283831b664bd8b7f0ad4");
+/// ```
+pub fn hash_reader<R: Read>(mut reader: R) -> io::Result<Hash> {
+    let mut hasher = Md5::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(Hash {
+        bytes: hasher.finalize().into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{hash_reader, Hash},
+        proptest::{prop_assert_eq, proptest},
+        std::{
+            io::Write,
+            process::{Command, Stdio},
+        },
+    };
+
+    /// Hash `data` with the `md5sum` program.
+    fn hash_reference_impl(data: &[u8]) -> String {
+        let mut md5sum = Command::new("md5sum")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("md5sum should be available");
+        md5sum
+            .stdin
+            .take()
+            .expect("Failed to open stdin")
+            .write_all(data)
+            .expect("Failed to write to md5sum");
+        let output = md5sum.wait_with_output().expect("Failed to run md5sum");
+        String::from_utf8(output.stdout)
+            .expect("md5sum output is ASCII")
+            .split_whitespace()
+            .next()
+            .expect("md5sum output format always contains 2 parts")
+            .to_owned()
+    }
+
+    #[test]
+    fn known_hashes() {
+        let empty = hash_reader(&b""[..]).expect("Reading from a slice cannot fail");
+        assert_eq!(empty.to_string(), "d41d8cd98f00b204e9800998ecf8427e");
+        let fox = hash_reader(&b"The quick brown fox jumps over the lazy dog"[..])
+            .expect("Reading from a slice cannot fail");
+        assert_eq!(fox.to_string(), "9e107d9d372bb6826bd81d3542a419d6");
+    }
+
+    #[test]
+    fn parse_hex() {
+        let hash: Hash = "9E107D9D372BB6826BD81D3542A419D6"
+            .parse()
+            .expect("Valid hash");
+        assert_eq!(hash.to_string(), "9e107d9d372bb6826bd81d3542a419d6");
+        assert!("9e107d9d".parse::<Hash>().is_err());
+        assert!("+e107d9d372bb6826bd81d3542a419d6".parse::<Hash>().is_err());
+        assert!("9e107d9d372bb6826bd81d3542a419dg".parse::<Hash>().is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn hash_reader_matches_md5sum(data: Vec<u8>) {
+            let hash = hash_reader(data.as_slice()).expect("Reading from a slice cannot fail");
+            prop_assert_eq!(hash.to_string(), hash_reference_impl(&data));
+        }
+    }
+}
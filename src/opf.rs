@@ -0,0 +1,531 @@
+//! OPF package documents, which hold the metadata of EPUB files and of the books in a Calibre
+//! library.
+//!
+//! Only the Dublin Core metadata that burette records is read and written: the title, the
+//! authors, the identifiers and the rights. Both OPF 2, where the scheme of an identifier and the
+//! role of a creator are attributes, and OPF 3, where identifiers are URNs like
+//! `urn:isbn:9780262510875` and roles are given by `<meta refines="#id" property="role">`
+//! elements, are understood. Packages are written in the OPF 2 format that Calibre writes.
+
+use {
+    crate::{bibtex::parse_isbn, DocMetadata, FileFormat, IndexEntry},
+    anyhow::{bail, Context},
+    std::{
+        collections::HashMap,
+        fmt::{self, Display, Formatter},
+        fs,
+        path::Path,
+        str::FromStr,
+    },
+};
+
+/// Identifier schemes that OPF 3 packages give as a prefix of the identifier, e.g. `isbn:` in
+/// `urn:isbn:9780262510875`.
+const URN_SCHEMES: [&str; 5] = ["isbn", "doi", "uuid", "calibre", "sha256"];
+
+/// The metadata of an OPF package document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpfPackage {
+    /// Title of the publication.
+    pub title: Option<String>,
+    /// Authors of the publication, i.e. the creators whose role is `aut` or not given.
+    pub authors: Vec<String>,
+    /// Identifiers of the publication as pairs of scheme and value, e.g. `("isbn",
+    /// "9780262510875")`. Schemes are lowercase, and empty if unknown.
+    pub identifiers: Vec<(String, String)>,
+    /// Usage rights of the publication.
+    pub rights: Option<String>,
+}
+
+impl OpfPackage {
+    /// Read the OPF package document at `path`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file cannot be read or is not a valid OPF package document.
+    pub fn read_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read OPF file at {}", path.display()))?;
+        contents
+            .parse()
+            .with_context(|| format!("Invalid OPF file at {}", path.display()))
+    }
+
+    /// Return the values of the identifiers with the given scheme.
+    pub fn identifiers<'a>(&'a self, scheme: &'a str) -> impl Iterator<Item = &'a str> {
+        self.identifiers
+            .iter()
+            .filter(move |(identifier_scheme, _)| identifier_scheme == scheme)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Return the metadata of a document of format `file_format` described by the package.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the package has no title or contains an invalid ISBN.
+    pub fn metadata(&self, file_format: FileFormat) -> anyhow::Result<DocMetadata> {
+        let title = self.title.clone().context("Package has no title")?;
+        let isbns = self
+            .identifiers("isbn")
+            .map(|isbn| parse_isbn(isbn).with_context(|| format!("Invalid ISBN {isbn}")))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(DocMetadata {
+            title,
+            authors: self.authors.clone(),
+            isbns,
+            file_format,
+            doi: self.identifiers("doi").next().map(ToOwned::to_owned),
+            doc_type: None,
+            rights: self.rights.clone(),
+            journal: None,
+            volume: None,
+            issue: None,
+            pages: None,
+        })
+    }
+
+    /// Create a package describing the document of `entry`.
+    ///
+    /// The SHA-256 hash of the document is included as an identifier with the scheme `sha256`.
+    #[must_use]
+    pub fn from_document(entry: &IndexEntry) -> Self {
+        let mut identifiers = vec![("sha256".to_owned(), entry.hash().to_string())];
+        identifiers.extend(
+            entry
+                .isbns()
+                .map(|isbn| ("isbn".to_owned(), isbn.to_string())),
+        );
+        identifiers.extend(entry.doi().map(|doi| ("doi".to_owned(), doi.to_owned())));
+        Self {
+            title: Some(entry.title().to_owned()),
+            authors: entry.authors().map(ToOwned::to_owned).collect(),
+            identifiers,
+            rights: entry.rights().map(ToOwned::to_owned),
+        }
+    }
+}
+
+impl FromStr for OpfPackage {
+    type Err = anyhow::Error;
+
+    /// Parse an OPF package document.
+    ///
+    /// Elements other than the Dublin Core metadata burette records are ignored.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut package = Self::default();
+        // Creators with their ids, and the roles given to ids by `<meta refines>` in OPF 3.
+        let mut creators = Vec::new();
+        let mut refined_roles = HashMap::new();
+        let mut found_package = false;
+
+        let mut tokens = Tokenizer::new(s);
+        while let Some(token) = tokens.next_token()? {
+            let Token::Start {
+                name,
+                attributes,
+                self_closing,
+            } = token
+            else {
+                continue;
+            };
+            let attribute = |name: &str| {
+                attributes
+                    .iter()
+                    .find(|(attribute, _)| local_name(attribute) == name)
+                    .map(|(_, value)| value.as_str())
+            };
+            let name = local_name(name);
+            found_package |= name == "package";
+            if !matches!(name, "title" | "creator" | "identifier" | "rights" | "meta") {
+                continue;
+            }
+            let text = if self_closing {
+                String::new()
+            } else {
+                tokens.text_until_end(name)?
+            };
+            let text = text.trim().to_owned();
+            match name {
+                "title" => {
+                    package.title.get_or_insert(text);
+                }
+                "creator" => creators.push((
+                    attribute("id").map(ToOwned::to_owned),
+                    attribute("role").map(ToOwned::to_owned),
+                    text,
+                )),
+                "identifier" => package
+                    .identifiers
+                    .push(identifier(attribute("scheme"), &text)),
+                "rights" => {
+                    package.rights.get_or_insert(text);
+                }
+                _ => {
+                    if let (Some("role"), Some(id)) = (attribute("property"), attribute("refines"))
+                    {
+                        refined_roles.insert(id.trim_start_matches('#').to_owned(), text);
+                    }
+                }
+            }
+        }
+        if !found_package {
+            bail!("Not an OPF package document");
+        }
+
+        package.authors = creators
+            .into_iter()
+            .filter(|(id, role, name)| {
+                let role = role
+                    .as_deref()
+                    .or_else(|| refined_roles.get(id.as_deref()?).map(String::as_str));
+                !name.is_empty() && role.is_none_or(|role| role == "aut")
+            })
+            .map(|(_, _, name)| name)
+            .collect();
+        package.title = package.title.filter(|title| !title.is_empty());
+        package.rights = package.rights.filter(|rights| !rights.is_empty());
+        package.identifiers.retain(|(_, value)| !value.is_empty());
+        Ok(package)
+    }
+}
+
+impl Display for OpfPackage {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(
+            f,
+            r#"<package xmlns="http://www.idpf.org/2007/opf" unique-identifier="uid" version="2.0">"#
+        )?;
+        writeln!(
+            f,
+            r#"  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">"#
+        )?;
+        for (i, (scheme, value)) in self.identifiers.iter().enumerate() {
+            let id = if i == 0 { r#" id="uid""# } else { "" };
+            let scheme = if scheme.is_empty() {
+                String::new()
+            } else {
+                format!(r#" opf:scheme="{}""#, escape_xml(scheme))
+            };
+            writeln!(
+                f,
+                "    <dc:identifier{id}{scheme}>{}</dc:identifier>",
+                escape_xml(value)
+            )?;
+        }
+        if let Some(title) = &self.title {
+            writeln!(f, "    <dc:title>{}</dc:title>", escape_xml(title))?;
+        }
+        for author in &self.authors {
+            writeln!(
+                f,
+                r#"    <dc:creator opf:role="aut">{}</dc:creator>"#,
+                escape_xml(author)
+            )?;
+        }
+        if let Some(rights) = &self.rights {
+            writeln!(f, "    <dc:rights>{}</dc:rights>", escape_xml(rights))?;
+        }
+        writeln!(f, "  </metadata>")?;
+        writeln!(f, "</package>")
+    }
+}
+
+/// Return the identifier with the scheme attribute `scheme` and the text `text` as a pair of
+/// scheme and value.
+///
+/// Without a scheme attribute, the scheme is taken from a prefix like `urn:isbn:` or `isbn:`.
+fn identifier(scheme: Option<&str>, text: &str) -> (String, String) {
+    if let Some(scheme) = scheme {
+        return (scheme.to_lowercase(), text.to_owned());
+    }
+    let unprefixed = text.strip_prefix("urn:").unwrap_or(text);
+    if let Some((scheme, value)) = unprefixed.split_once(':') {
+        let scheme = scheme.to_lowercase();
+        if URN_SCHEMES.contains(&scheme.as_str()) {
+            return (scheme, value.to_owned());
+        }
+    }
+    (String::new(), text.to_owned())
+}
+
+/// Return the name of an element or attribute without its namespace prefix.
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Escape the characters of `s` that have a special meaning in XML.
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Replace the entity and character references in `s` by the characters they stand for.
+fn unescape_xml(s: &str) -> anyhow::Result<String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .with_context(|| format!("Unterminated reference in {s}"))?
+            + start;
+        let reference = &rest[start + 1..end];
+        let c = match reference {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => reference
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| reference.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32)
+                .with_context(|| format!("Unknown reference &{reference};"))?,
+        };
+        unescaped.push(c);
+        rest = &rest[end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+/// A part of an XML document, as returned by [`Tokenizer::next_token()`].
+#[derive(Debug)]
+enum Token<'a> {
+    /// A start tag, or an empty element tag if `self_closing` is true.
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, String)>,
+        self_closing: bool,
+    },
+    /// An end tag.
+    End { name: &'a str },
+    /// Text between tags, with references replaced.
+    Text(String),
+}
+
+/// Splits an XML document into tags and text.
+///
+/// This is not a complete XML parser: it does not check that tags are balanced or that names are
+/// valid, and ignores declarations, processing instructions and comments. That is enough for the
+/// metadata of OPF package documents, which are simple.
+struct Tokenizer<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Create a tokenizer for the document `s`.
+    fn new(s: &'a str) -> Self {
+        Self { rest: s }
+    }
+
+    /// Skip past the next occurrence of `end`.
+    fn skip_past(&mut self, end: &str) -> anyhow::Result<()> {
+        let position = self
+            .rest
+            .find(end)
+            .with_context(|| format!("Missing {end}"))?;
+        self.rest = &self.rest[position + end.len()..];
+        Ok(())
+    }
+
+    /// Return the next token, or `None` at the end of the document.
+    fn next_token(&mut self) -> anyhow::Result<Option<Token<'a>>> {
+        loop {
+            if self.rest.is_empty() {
+                return Ok(None);
+            }
+            if let Some(rest) = self.rest.strip_prefix("<![CDATA[") {
+                let end = rest.find("]]>").context("Unterminated CDATA section")?;
+                self.rest = &rest[end + 3..];
+                return Ok(Some(Token::Text(rest[..end].to_owned())));
+            }
+            if self.rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+                continue;
+            }
+            if self.rest.starts_with("<?") {
+                self.skip_past("?>")?;
+                continue;
+            }
+            if self.rest.starts_with("<!") {
+                self.skip_past(">")?;
+                continue;
+            }
+            if let Some(rest) = self.rest.strip_prefix("</") {
+                let end = rest.find('>').context("Unterminated end tag")?;
+                self.rest = &rest[end + 1..];
+                return Ok(Some(Token::End {
+                    name: rest[..end].trim(),
+                }));
+            }
+            if let Some(rest) = self.rest.strip_prefix('<') {
+                self.rest = rest;
+                return self.start_tag().map(Some);
+            }
+            let end = self.rest.find('<').unwrap_or(self.rest.len());
+            let text = unescape_xml(&self.rest[..end])?;
+            self.rest = &self.rest[end..];
+            return Ok(Some(Token::Text(text)));
+        }
+    }
+
+    /// Parse a start tag after its `<`.
+    fn start_tag(&mut self) -> anyhow::Result<Token<'a>> {
+        let name_end = self
+            .rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .context("Unterminated start tag")?;
+        let name = &self.rest[..name_end];
+        self.rest = &self.rest[name_end..];
+
+        let mut attributes = Vec::new();
+        loop {
+            self.rest = self.rest.trim_start();
+            if let Some(rest) = self.rest.strip_prefix("/>") {
+                self.rest = rest;
+                return Ok(Token::Start {
+                    name,
+                    attributes,
+                    self_closing: true,
+                });
+            }
+            if let Some(rest) = self.rest.strip_prefix('>') {
+                self.rest = rest;
+                return Ok(Token::Start {
+                    name,
+                    attributes,
+                    self_closing: false,
+                });
+            }
+            let (attribute, rest) = self
+                .rest
+                .split_once('=')
+                .with_context(|| format!("Invalid attribute in tag {name}"))?;
+            let rest = rest.trim_start();
+            let quote = rest
+                .chars()
+                .next()
+                .filter(|&c| c == '"' || c == '\'')
+                .with_context(|| format!("Unquoted attribute value in tag {name}"))?;
+            let value_end = rest[1..]
+                .find(quote)
+                .with_context(|| format!("Unterminated attribute value in tag {name}"))?
+                + 1;
+            attributes.push((attribute.trim(), unescape_xml(&rest[1..value_end])?));
+            self.rest = &rest[value_end + 1..];
+        }
+    }
+
+    /// Return the text up to the end tag of the element with the local name `name`, ignoring the
+    /// tags of nested elements.
+    fn text_until_end(&mut self, name: &str) -> anyhow::Result<String> {
+        let mut text = String::new();
+        loop {
+            match self.next_token()? {
+                Some(Token::Text(part)) => text.push_str(&part),
+                Some(Token::End { name: end }) if local_name(end) == name => return Ok(text),
+                Some(_) => (),
+                None => bail!("Missing end tag of {name}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::OpfPackage,
+        crate::{FileFormat, Isbn13},
+    };
+
+    #[test]
+    fn parse_opf2() {
+        let opf = r#"<?xml version='1.0' encoding='utf-8'?>
+            <package xmlns="http://www.idpf.org/2007/opf" unique-identifier="uuid_id" version="2.0">
+              <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+                <dc:identifier opf:scheme="calibre" id="calibre_id">12</dc:identifier>
+                <dc:title>Structure &amp; Interpretation of Computer Programs</dc:title>
+                <dc:creator opf:file-as="Abelson, Harold" opf:role="aut">Harold Abelson</dc:creator>
+                <dc:creator opf:role="aut">Gerald Jay Sussman</dc:creator>
+                <dc:contributor opf:role="bkp">calibre (5.0.0)</dc:contributor>
+                <dc:creator opf:role="edt">Some Editor</dc:creator>
+                <dc:identifier opf:scheme="ISBN">0262510871</dc:identifier>
+                <!-- <dc:rights>commented out</dc:rights> -->
+                <meta name="calibre:timestamp" content="2020-01-01T00:00:00+00:00"/>
+              </metadata>
+              <guide/>
+            </package>"#;
+        let package: OpfPackage = opf.parse().expect("Valid OPF");
+        assert_eq!(
+            package.title.as_deref(),
+            Some("Structure & Interpretation of Computer Programs")
+        );
+        assert_eq!(package.authors, ["Harold Abelson", "Gerald Jay Sussman"]);
+        assert_eq!(package.identifiers("calibre").collect::<Vec<_>>(), ["12"]);
+        assert_eq!(package.rights, None);
+
+        let metadata = package.metadata(FileFormat::Pdf).expect("Valid metadata");
+        let isbn: Isbn13 = "9780262510875".parse().expect("Valid ISBN");
+        assert_eq!(metadata.isbns, [isbn]);
+    }
+
+    #[test]
+    fn parse_opf3() {
+        let opf = r##"<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+              <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <dc:identifier id="id">urn:uuid:0b4a6c1e-6a4e-4c4f-9f2a-3b5b8f1d2c3e</dc:identifier>
+                <dc:identifier>urn:doi:10.1000/182</dc:identifier>
+                <dc:title id="t1"><![CDATA[Faust]]></dc:title>
+                <dc:creator id="c1">Johann Wolfgang von Goethe</dc:creator>
+                <meta refines="#c1" property="role" scheme="marc:relators">aut</meta>
+                <dc:creator id="c2">A Translator</dc:creator>
+                <meta refines="#c2" property="role" scheme="marc:relators">trl</meta>
+                <dc:rights>Public domain</dc:rights>
+              </metadata>
+            </package>"##;
+        let package: OpfPackage = opf.parse().expect("Valid OPF");
+        assert_eq!(package.title.as_deref(), Some("Faust"));
+        assert_eq!(package.authors, ["Johann Wolfgang von Goethe"]);
+        assert_eq!(
+            package.identifiers("doi").collect::<Vec<_>>(),
+            ["10.1000/182"]
+        );
+        assert_eq!(package.identifiers("uuid").count(), 1);
+        assert_eq!(package.rights.as_deref(), Some("Public domain"));
+
+        assert!("<html><title>Not OPF</title></html>"
+            .parse::<OpfPackage>()
+            .is_err());
+    }
+
+    #[test]
+    fn write_and_parse() {
+        let package = OpfPackage {
+            title: Some("Tom & Jerry <\"Live\">".to_owned()),
+            authors: vec!["O'Brien".to_owned()],
+            identifiers: vec![
+                ("calibre".to_owned(), "1".to_owned()),
+                ("isbn".to_owned(), "9780262510875".to_owned()),
+            ],
+            rights: Some("CC-BY-4.0".to_owned()),
+        };
+        let parsed: OpfPackage = package.to_string().parse().expect("Valid OPF");
+        assert_eq!(parsed, package);
+    }
+}
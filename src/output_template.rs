@@ -0,0 +1,415 @@
+//! Templates for printing the metadata of documents in a custom format.
+
+use {
+    crate::IndexEntry,
+    anyhow::{anyhow, bail},
+    std::{mem, str::FromStr},
+};
+
+/// Separator used when a list is printed without the `join` filter.
+const DEFAULT_SEPARATOR: &str = ", ";
+
+/// A template for printing the metadata of a document.
+///
+/// The template is copied to the output, except for placeholders in curly braces, which are
+/// replaced by a field of the document. The following fields are supported:
+/// - `hash`, `hash.short`: the full or short hash of the document
+/// - `title`
+/// - `authors`, `aliases`, `isbns`: lists of the authors, aliases and ISBNs
+/// - `doi`, `type`, `rights`, `journal`, `volume`, `issue`, `pages`
+/// - `format`, `mime`: the file extension and MIME type of the file format
+/// - `size`: the size of the file in bytes
+/// - `filename`, `path`: the name and path of the file the document was added from
+/// - `modified`: the modification time of the file the document was added from
+/// - `archived`: `true` or `false`
+/// - `last_accessed`, `access_count`: when and how often the document was retrieved
+/// - `progress`: the reading progress in percent
+///
+/// Fields that are not set are replaced by an empty string. A field can be followed by filters
+/// separated by `|`, which are applied from left to right:
+/// - `join:<separator>`: join a list with `<separator>`
+/// - `first`: the first element of a list
+/// - `default:<text>`: `<text>` if the value is empty
+/// - `upper`, `lower`: convert the value to upper or lower case
+///
+/// Lists that are not joined explicitly are joined with `, `. Filter arguments can be enclosed in
+/// double quotes, e.g. `{authors|join:" & "}`. The escape sequences `\t`, `\n`, `\\`, `\{` and
+/// `\}` can be used outside of placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    parts: Vec<Part>,
+}
+
+/// A part of an [`OutputTemplate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Placeholder(Field, Vec<TemplateFilter>),
+}
+
+/// A field of a document that can be used in an [`OutputTemplate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Hash,
+    ShortHash,
+    Title,
+    Authors,
+    Aliases,
+    Isbns,
+    Doi,
+    Type,
+    Rights,
+    Journal,
+    Volume,
+    Issue,
+    Pages,
+    Format,
+    Mime,
+    Size,
+    Filename,
+    Path,
+    Modified,
+    Archived,
+    LastAccessed,
+    AccessCount,
+    Progress,
+}
+
+/// A filter applied to the value of a placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplateFilter {
+    Join(String),
+    First,
+    Default(String),
+    Upper,
+    Lower,
+}
+
+/// The value of a field, while filters are applied.
+enum Value {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl OutputTemplate {
+    /// Return the template with its placeholders replaced by the fields of `entry`.
+    #[must_use]
+    pub fn render(&self, entry: &IndexEntry) -> String {
+        let mut output = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => output.push_str(text),
+                Part::Placeholder(field, filters) => {
+                    let value = filters
+                        .iter()
+                        .fold(field.value(entry), |value, filter| filter.apply(value));
+                    output.push_str(&value.into_text());
+                }
+            }
+        }
+        output
+    }
+}
+
+impl Field {
+    /// Return the value of the field for `entry`.
+    fn value(self, entry: &IndexEntry) -> Value {
+        let text = |value: Option<&str>| Value::Text(value.unwrap_or_default().to_owned());
+        match self {
+            Field::Hash => Value::Text(entry.hash().to_string()),
+            Field::ShortHash => Value::Text(entry.hash().to_short_string()),
+            Field::Title => Value::Text(entry.title().to_owned()),
+            Field::Authors => Value::List(entry.authors().map(str::to_owned).collect()),
+            Field::Aliases => Value::List(entry.aliases().map(str::to_owned).collect()),
+            Field::Isbns => Value::List(entry.isbns().map(ToString::to_string).collect()),
+            Field::Doi => text(entry.doi()),
+            Field::Type => Value::Text(
+                entry
+                    .doc_type()
+                    .map(|doc_type| doc_type.to_string())
+                    .unwrap_or_default(),
+            ),
+            Field::Rights => text(entry.rights()),
+            Field::Journal => text(entry.journal()),
+            Field::Volume => text(entry.volume()),
+            Field::Issue => text(entry.issue()),
+            Field::Pages => text(entry.pages()),
+            Field::Format => Value::Text(entry.file_format().extension().to_owned()),
+            Field::Mime => Value::Text(entry.file_format().mime_type().to_owned()),
+            Field::Size => Value::Text(
+                entry
+                    .size_bytes()
+                    .map(|size| size.to_string())
+                    .unwrap_or_default(),
+            ),
+            Field::Filename => text(entry.source_filename()),
+            Field::Path => text(entry.source_path()),
+            Field::Modified => Value::Text(
+                entry
+                    .source_modified()
+                    .map(|timestamp| timestamp.to_string())
+                    .unwrap_or_default(),
+            ),
+            Field::Archived => Value::Text(entry.is_archived().to_string()),
+            Field::LastAccessed => Value::Text(
+                entry
+                    .last_accessed()
+                    .map(|timestamp| timestamp.to_string())
+                    .unwrap_or_default(),
+            ),
+            Field::AccessCount => Value::Text(entry.access_count().to_string()),
+            Field::Progress => Value::Text(
+                entry
+                    .reading_progress()
+                    .map(|progress| progress.to_string())
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+impl TemplateFilter {
+    /// Apply the filter to `value`.
+    fn apply(&self, value: Value) -> Value {
+        match (self, value) {
+            (TemplateFilter::Join(separator), Value::List(list)) => {
+                Value::Text(list.join(separator))
+            }
+            (TemplateFilter::First, Value::List(list)) => {
+                Value::Text(list.into_iter().next().unwrap_or_default())
+            }
+            (TemplateFilter::Default(default), value) if value.is_empty() => {
+                Value::Text(default.clone())
+            }
+            (TemplateFilter::Upper, value) => value.map(str::to_uppercase),
+            (TemplateFilter::Lower, value) => value.map(str::to_lowercase),
+            // Joining or taking the first element of a single value leaves it unchanged.
+            (_, value) => value,
+        }
+    }
+}
+
+impl Value {
+    /// Return true if the value is an empty string or an empty list.
+    fn is_empty(&self) -> bool {
+        match self {
+            Value::Text(text) => text.is_empty(),
+            Value::List(list) => list.is_empty(),
+        }
+    }
+
+    /// Apply `f` to the value or to each element of the list.
+    fn map<F: Fn(&str) -> String>(self, f: F) -> Value {
+        match self {
+            Value::Text(text) => Value::Text(f(&text)),
+            Value::List(list) => Value::List(list.iter().map(|text| f(text)).collect()),
+        }
+    }
+
+    /// Return the value as text, joining lists with [`DEFAULT_SEPARATOR`].
+    fn into_text(self) -> String {
+        match self {
+            Value::Text(text) => text,
+            Value::List(list) => list.join(DEFAULT_SEPARATOR),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Ok(match s {
+            "hash" => Field::Hash,
+            "hash.short" => Field::ShortHash,
+            "title" => Field::Title,
+            "authors" => Field::Authors,
+            "aliases" => Field::Aliases,
+            "isbns" => Field::Isbns,
+            "doi" => Field::Doi,
+            "type" => Field::Type,
+            "rights" => Field::Rights,
+            "journal" => Field::Journal,
+            "volume" => Field::Volume,
+            "issue" => Field::Issue,
+            "pages" => Field::Pages,
+            "format" => Field::Format,
+            "mime" => Field::Mime,
+            "size" => Field::Size,
+            "filename" => Field::Filename,
+            "path" => Field::Path,
+            "modified" => Field::Modified,
+            "archived" => Field::Archived,
+            "last_accessed" => Field::LastAccessed,
+            "access_count" => Field::AccessCount,
+            "progress" => Field::Progress,
+            _ => bail!("Unknown field {{{}}} in output template", s),
+        })
+    }
+}
+
+impl FromStr for TemplateFilter {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (name, argument) = match s.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(unquote(argument.trim()))),
+            None => (s.trim(), None),
+        };
+        Ok(match (name, argument) {
+            ("join", Some(separator)) => TemplateFilter::Join(separator.to_owned()),
+            ("default", Some(default)) => TemplateFilter::Default(default.to_owned()),
+            ("first", None) => TemplateFilter::First,
+            ("upper", None) => TemplateFilter::Upper,
+            ("lower", None) => TemplateFilter::Lower,
+            ("join" | "default", None) => bail!("Filter {} requires an argument", name),
+            ("first" | "upper" | "lower", Some(_)) => {
+                bail!("Filter {} does not take an argument", name)
+            }
+            _ => bail!("Unknown filter {} in output template", name),
+        })
+    }
+}
+
+/// Remove the double quotes around `s`, if there are any.
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+/// Parse the contents of a placeholder, i.e. the text between the curly braces.
+fn parse_placeholder(s: &str) -> anyhow::Result<Part> {
+    // Split at `|` outside of double quotes.
+    let mut segments = Vec::new();
+    let mut segment = String::new();
+    let mut in_quotes = false;
+    for c in s.chars() {
+        match c {
+            '|' if !in_quotes => segments.push(mem::take(&mut segment)),
+            c => {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                segment.push(c);
+            }
+        }
+    }
+    segments.push(segment);
+
+    let mut segments = segments.into_iter();
+    let field = segments.next().unwrap_or_default().trim().parse()?;
+    let filters = segments
+        .map(|segment| segment.parse())
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Part::Placeholder(field, filters))
+}
+
+impl FromStr for OutputTemplate {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => literal.push(match chars.next() {
+                    Some('t') => '\t',
+                    Some('n') => '\n',
+                    Some(c @ ('\\' | '{' | '}')) => c,
+                    Some(c) => bail!("Invalid escape sequence \\{} in output template", c),
+                    None => bail!("Output template must not end with a backslash"),
+                }),
+                '{' => {
+                    let mut placeholder = String::new();
+                    let mut in_quotes = false;
+                    loop {
+                        let c = chars.next().ok_or_else(|| {
+                            anyhow!("Unterminated placeholder in output template")
+                        })?;
+                        match c {
+                            '}' if !in_quotes => break,
+                            '"' => in_quotes = !in_quotes,
+                            _ => (),
+                        }
+                        placeholder.push(c);
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(mem::take(&mut literal)));
+                    }
+                    parts.push(parse_placeholder(&placeholder)?);
+                }
+                '}' => bail!("Unmatched }} in output template"),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Field, OutputTemplate, Part, TemplateFilter},
+        proptest::{prop_assert_eq, proptest},
+    };
+
+    proptest! {
+        #[test]
+        fn parse_arbitrary_string(s in "\\PC*") {
+            let _ = s.parse::<OutputTemplate>();
+        }
+
+        #[test]
+        fn parse_literal(s in "[^{}\\\\]+") {
+            let template: Option<OutputTemplate> = s.parse().ok();
+            prop_assert_eq!(template.map(|template| template.parts), Some(vec![Part::Literal(s)]));
+        }
+    }
+
+    #[test]
+    fn parse_template() {
+        let template: OutputTemplate = r#"{hash.short}\t{authors|join:", "|default:unknown}\n"#
+            .parse()
+            .expect("Valid template");
+        assert_eq!(
+            template.parts,
+            vec![
+                Part::Placeholder(Field::ShortHash, Vec::new()),
+                Part::Literal("\t".to_owned()),
+                Part::Placeholder(
+                    Field::Authors,
+                    vec![
+                        TemplateFilter::Join(", ".to_owned()),
+                        TemplateFilter::Default("unknown".to_owned()),
+                    ]
+                ),
+                Part::Literal("\n".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_quoted_pipe() {
+        let template: OutputTemplate = r#"{isbns|join:" | "}"#.parse().expect("Valid template");
+        assert_eq!(
+            template.parts,
+            vec![Part::Placeholder(
+                Field::Isbns,
+                vec![TemplateFilter::Join(" | ".to_owned())]
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_templates() {
+        assert!("{year}".parse::<OutputTemplate>().is_err());
+        assert!("{title".parse::<OutputTemplate>().is_err());
+        assert!("title}".parse::<OutputTemplate>().is_err());
+        assert!("{title|reverse}".parse::<OutputTemplate>().is_err());
+        assert!("{authors|join}".parse::<OutputTemplate>().is_err());
+        assert!("{title|upper:x}".parse::<OutputTemplate>().is_err());
+        assert!(r"\x".parse::<OutputTemplate>().is_err());
+    }
+}
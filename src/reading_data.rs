@@ -0,0 +1,693 @@
+//! Reading progress and annotations exported by e-readers, and reading history exported by
+//! book tracking sites.
+//!
+//! Three sources are supported:
+//! - Kindle `My Clippings.txt` files, which contain the highlights and notes of all books on the
+//!   device.
+//! - `KOReader` metadata sidecars (`metadata.<ext>.lua` in the `.sdr` directory next to a book),
+//!   which contain the reading progress and annotations of a single book.
+//! - CSV exports of Goodreads and The `StoryGraph`, which contain the reading status, rating and
+//!   date finished of all books on the account.
+
+use {
+    crate::Isbn13,
+    anyhow::{bail, Context},
+    serde::{Deserialize, Serialize},
+    std::{
+        ffi::OsStr,
+        fmt::{self, Display, Formatter},
+        fs,
+        path::Path,
+    },
+};
+
+mod csv;
+
+mod lua;
+
+/// Line separating the clippings in a Kindle `My Clippings.txt` file.
+const CLIPPING_SEPARATOR: &str = "==========";
+
+/// Kind of an [`Annotation`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    /// A passage of the document that was highlighted.
+    Highlight,
+    /// A note written by the reader.
+    Note,
+}
+
+impl Display for AnnotationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AnnotationKind::Highlight => write!(f, "highlight"),
+            AnnotationKind::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// Whether a document was read, as tracked by Goodreads or The `StoryGraph`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReadingStatus {
+    /// The document is to be read.
+    ToRead,
+    /// The document is being read.
+    Reading,
+    /// The document was read to the end.
+    Read,
+    /// Reading the document was abandoned.
+    Abandoned,
+}
+
+impl Display for ReadingStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadingStatus::ToRead => write!(f, "to read"),
+            ReadingStatus::Reading => write!(f, "reading"),
+            ReadingStatus::Read => write!(f, "read"),
+            ReadingStatus::Abandoned => write!(f, "abandoned"),
+        }
+    }
+}
+
+/// A highlight or note made while reading a document.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Whether this is a highlight or a note.
+    pub kind: AnnotationKind,
+    /// The highlighted passage or the text of the note.
+    pub text: String,
+    /// Where in the document the annotation was made, e.g. `page 12` or `Location 170-172`, in
+    /// the format used by the e-reader.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// Reading progress and annotations of a single document, as exported by an e-reader.
+///
+/// E-readers do not know the hash of a document, so the document is identified by its title and
+/// authors, or by its ISBNs if the export contains them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadingData {
+    /// Title of the document.
+    pub title: String,
+    /// Authors of the document. May be empty.
+    pub authors: Vec<String>,
+    /// ISBNs of the document. May be empty.
+    pub isbns: Vec<Isbn13>,
+    /// How much of the document has been read, in percent.
+    pub progress: Option<u8>,
+    /// Highlights and notes, in the order they appear in the export.
+    pub annotations: Vec<Annotation>,
+    /// Whether the document was read.
+    pub status: Option<ReadingStatus>,
+    /// Rating of the document, from 1 to 5 stars.
+    pub rating: Option<u8>,
+    /// Date the document was finished, e.g. `2024-01-31`.
+    pub finished: Option<String>,
+}
+
+impl ReadingData {
+    /// Read the reading data from a file exported by an e-reader.
+    ///
+    /// Files with the extension `lua` are read as `KOReader` metadata sidecars, files with the
+    /// extension `csv` as Goodreads or The `StoryGraph` exports, and all other files as Kindle
+    /// clippings. If `path` is a directory (e.g. a `KOReader` `.sdr` directory), all
+    /// `metadata.*.lua` files in it are read.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a file cannot be read or is not in the expected format.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<Self>> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            let mut sidecars = Vec::new();
+            let dir = fs::read_dir(path)
+                .with_context(|| format!("Failed to read directory {}", path.display()))?;
+            for entry in dir {
+                let entry = entry
+                    .with_context(|| format!("Failed to read directory {}", path.display()))?;
+                let file_name = entry.file_name();
+                let file_name = file_name.to_string_lossy();
+                if file_name.starts_with("metadata.") && file_name.ends_with(".lua") {
+                    sidecars.push(entry.path());
+                }
+            }
+            sidecars.sort_unstable();
+            return sidecars
+                .iter()
+                .map(|sidecar| read_koreader_sidecar(sidecar))
+                .collect();
+        }
+
+        if path.extension() == Some(OsStr::new("lua")) {
+            return read_koreader_sidecar(path).map(|data| vec![data]);
+        }
+        if path.extension() == Some(OsStr::new("csv")) {
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            return Self::from_reading_log_csv(&contents)
+                .with_context(|| format!("Invalid reading log {}", path.display()));
+        }
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_kindle_clippings(&contents)
+            .with_context(|| format!("Invalid Kindle clippings file {}", path.display()))
+    }
+
+    /// Parse the contents of a Kindle `My Clippings.txt` file.
+    ///
+    /// Highlights and notes are grouped by document. Bookmarks and clippings of unknown kind
+    /// (e.g. from devices set to a language other than English) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a clipping does not consist of a title line, a description line
+    /// and the clipped text.
+    pub fn from_kindle_clippings(contents: &str) -> anyhow::Result<Vec<Self>> {
+        let mut documents: Vec<Self> = Vec::new();
+        for (number, clipping) in contents.split(CLIPPING_SEPARATOR).enumerate() {
+            let mut lines = clipping
+                .lines()
+                .map(|line| line.trim_start_matches('\u{feff}').trim_end());
+            let Some(title_line) = lines.find(|line| !line.trim().is_empty()) else {
+                continue;
+            };
+            let Some(description) = lines.next().and_then(|line| line.strip_prefix("- ")) else {
+                bail!("Clipping {} has no description line", number + 1);
+            };
+            let text = lines.collect::<Vec<_>>().join("\n").trim().to_owned();
+
+            let Some((kind, location)) = parse_clipping_description(description) else {
+                continue;
+            };
+            if text.is_empty() {
+                continue;
+            }
+            let (title, authors) = parse_clipping_title(title_line);
+            let annotation = Annotation {
+                kind,
+                text,
+                location,
+            };
+
+            match documents
+                .iter_mut()
+                .find(|doc| doc.title == title && doc.authors == authors)
+            {
+                Some(doc) => doc.annotations.push(annotation),
+                None => documents.push(Self {
+                    title,
+                    authors,
+                    annotations: vec![annotation],
+                    ..Self::default()
+                }),
+            }
+        }
+        Ok(documents)
+    }
+
+    /// Parse the contents of a `KOReader` metadata sidecar.
+    ///
+    /// Both the current `annotations` table and the `highlight` table written by older versions
+    /// of `KOReader` are supported.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the contents are not a valid sidecar or if the sidecar does not
+    /// contain the title of the document.
+    pub fn from_koreader_sidecar(contents: &str) -> anyhow::Result<Self> {
+        let root = lua::parse(contents)?;
+        let root = root
+            .as_table()
+            .context("Metadata file does not return a table")?;
+
+        let doc_props = root.get("doc_props").and_then(lua::Value::as_table);
+        let Some(title) = doc_props
+            .and_then(|props| props.get("title"))
+            .and_then(lua::Value::as_str)
+            .filter(|title| !title.trim().is_empty())
+        else {
+            bail!("Metadata file does not contain the title of the document");
+        };
+        // Multiple authors are separated by newlines.
+        let authors = doc_props
+            .and_then(|props| props.get("authors"))
+            .and_then(lua::Value::as_str)
+            .map(|authors| {
+                authors
+                    .lines()
+                    .map(str::trim)
+                    .filter(|author| !author.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let progress = root
+            .get("percent_finished")
+            .and_then(lua::Value::as_number)
+            .map(fraction_to_percent);
+
+        let mut annotations = Vec::new();
+        if let Some(table) = root.get("annotations").and_then(lua::Value::as_table) {
+            for item in table.values().filter_map(lua::Value::as_table) {
+                push_koreader_annotation(&mut annotations, item);
+            }
+        } else if let Some(table) = root.get("highlight").and_then(lua::Value::as_table) {
+            // Older versions group the highlights by page.
+            for page in table.values().filter_map(lua::Value::as_table) {
+                for item in page.values().filter_map(lua::Value::as_table) {
+                    push_koreader_annotation(&mut annotations, item);
+                }
+            }
+        }
+
+        Ok(Self {
+            title: title.trim().to_owned(),
+            authors,
+            progress,
+            annotations,
+            ..Self::default()
+        })
+    }
+
+    /// Parse a CSV export of Goodreads or The `StoryGraph`.
+    ///
+    /// The format is detected from the header. Each book becomes one [`ReadingData`] with its
+    /// reading status, rating and date finished. Goodreads ratings of 0 mean that the book was not
+    /// rated, and the half and quarter stars of The `StoryGraph` are rounded to whole stars.
+    /// Series information that Goodreads appends to titles, e.g. `(The Lord of the Rings, #1)`,
+    /// is removed.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the contents are not a valid CSV file, if the header does not
+    /// match either format or if a row contains an invalid rating or date.
+    pub fn from_reading_log_csv(contents: &str) -> anyhow::Result<Vec<Self>> {
+        let mut records = csv::parse(contents)?.into_iter();
+        let Some(header) = records.next() else {
+            return Ok(Vec::new());
+        };
+        let column = |name: &str| header.iter().position(|column| column == name);
+
+        let goodreads = column("Exclusive Shelf").is_some();
+        let names = if goodreads {
+            [
+                "Title",
+                "Author",
+                "ISBN13",
+                "Exclusive Shelf",
+                "My Rating",
+                "Date Read",
+            ]
+        } else {
+            [
+                "Title",
+                "Authors",
+                "ISBN/UID",
+                "Read Status",
+                "Star Rating",
+                "Last Date Read",
+            ]
+        };
+        let mut columns = [0; 6];
+        for (index, name) in columns.iter_mut().zip(names) {
+            *index = column(name).with_context(|| {
+                format!("Missing column {name}, expected a Goodreads or StoryGraph export")
+            })?;
+        }
+        let [title, author, isbn, status, rating, date] = columns;
+        let additional_authors = column("Additional Authors");
+        let isbn10 = column("ISBN");
+
+        let mut documents = Vec::new();
+        for (number, record) in records.enumerate() {
+            let field = |index: usize| record.get(index).map_or("", |field| field.trim());
+            let row = number + 2;
+
+            let mut authors: Vec<String> = if goodreads {
+                [Some(author), additional_authors]
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|index| field(index).split(','))
+                    .map(str::trim)
+                    .filter(|author| !author.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            } else {
+                field(author)
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|author| !author.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            };
+            authors.dedup();
+            let title = if goodreads {
+                strip_series(field(title))
+            } else {
+                field(title)
+            };
+            // Goodreads writes ISBNs as formulas, e.g. `="9780140449136"`, so that spreadsheets
+            // keep the leading zeros. Identifiers that are not ISBNs are ignored.
+            let isbns = [Some(isbn), isbn10.filter(|_| goodreads)]
+                .into_iter()
+                .flatten()
+                .filter_map(|index| {
+                    field(index)
+                        .trim_start_matches('=')
+                        .trim_matches('"')
+                        .parse::<Isbn13>()
+                        .ok()
+                })
+                .collect();
+            let status = match field(status) {
+                "read" => Some(ReadingStatus::Read),
+                "currently-reading" | "paused" => Some(ReadingStatus::Reading),
+                "to-read" => Some(ReadingStatus::ToRead),
+                "did-not-finish" => Some(ReadingStatus::Abandoned),
+                _ => None,
+            };
+            let rating = match field(rating) {
+                "" => None,
+                rating => {
+                    let stars: f64 = rating
+                        .parse()
+                        .with_context(|| format!("Invalid rating {rating} in row {row}"))?;
+                    (stars > 0.0).then(|| stars_to_rating(stars))
+                }
+            };
+            let finished = match field(date) {
+                "" => None,
+                date => Some(
+                    normalize_date(date)
+                        .with_context(|| format!("Invalid date {date} in row {row}"))?,
+                ),
+            };
+
+            if title.is_empty() {
+                continue;
+            }
+            documents.push(Self {
+                title: title.to_owned(),
+                authors,
+                isbns,
+                status,
+                rating,
+                finished,
+                ..Self::default()
+            });
+        }
+        Ok(documents)
+    }
+}
+
+/// Remove the series information that Goodreads appends to titles, e.g. `(Discworld, #1)`.
+fn strip_series(title: &str) -> &str {
+    if let Some(rest) = title.strip_suffix(')') {
+        if let Some(start) = rest.rfind(" (") {
+            if rest[start..].contains('#') {
+                return rest[..start].trim();
+            }
+        }
+    }
+    title
+}
+
+/// Convert a rating in stars (e.g. `4.5`) to whole stars between 1 and 5.
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "The value is rounded and clamped to 1..=5"
+)]
+fn stars_to_rating(stars: f64) -> u8 {
+    stars.round().clamp(1.0, 5.0) as u8
+}
+
+/// Convert a date of the form `2024/01/31` (or `2024-01-31`) to `2024-01-31`.
+fn normalize_date(date: &str) -> Option<String> {
+    let parts: Vec<_> = date.split(['/', '-']).collect();
+    let [year, month, day] = parts.as_slice() else {
+        return None;
+    };
+    let year: u16 = year.parse().ok()?;
+    let month: u8 = month
+        .parse()
+        .ok()
+        .filter(|month| (1..=12).contains(month))?;
+    let day: u8 = day.parse().ok().filter(|day| (1..=31).contains(day))?;
+    Some(format!("{year:04}-{month:02}-{day:02}"))
+}
+
+/// Read the `KOReader` metadata sidecar at `path`.
+fn read_koreader_sidecar(path: &Path) -> anyhow::Result<ReadingData> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    ReadingData::from_koreader_sidecar(&contents)
+        .with_context(|| format!("Invalid KOReader metadata file {}", path.display()))
+}
+
+/// Add the highlight and the note of a `KOReader` annotation table to `annotations`.
+fn push_koreader_annotation(annotations: &mut Vec<Annotation>, item: &lua::Table) {
+    let location = item
+        .get("pageno")
+        .or_else(|| item.get("page"))
+        .and_then(lua::Value::as_number)
+        .map(|page| format!("page {page}"))
+        .or_else(|| {
+            item.get("chapter")
+                .and_then(lua::Value::as_str)
+                .map(str::to_owned)
+        });
+    let fields = [
+        ("text", AnnotationKind::Highlight),
+        ("note", AnnotationKind::Note),
+    ];
+    for (field, kind) in fields {
+        if let Some(text) = item
+            .get(field)
+            .and_then(lua::Value::as_str)
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+        {
+            annotations.push(Annotation {
+                kind,
+                text: text.to_owned(),
+                location: location.clone(),
+            });
+        }
+    }
+}
+
+/// Convert a fraction between 0 and 1 to a percentage between 0 and 100.
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "The value is rounded and clamped to 0..=100"
+)]
+fn fraction_to_percent(fraction: f64) -> u8 {
+    (fraction * 100.0).round().clamp(0.0, 100.0) as u8
+}
+
+/// Split the title line of a Kindle clipping into the title and the authors.
+///
+/// The authors are given in parentheses at the end of the line and separated by `;`.
+fn parse_clipping_title(line: &str) -> (String, Vec<String>) {
+    let line = line.trim();
+    if let Some(rest) = line.strip_suffix(')') {
+        if let Some(start) = rest.rfind('(') {
+            let title = rest[..start].trim();
+            if !title.is_empty() {
+                let authors = rest[start + 1..]
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|author| !author.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+                return (title.to_owned(), authors);
+            }
+        }
+    }
+    (line.to_owned(), Vec::new())
+}
+
+/// Parse the description line of a Kindle clipping, without the leading `- `.
+///
+/// The description has the form `Your Highlight on page 12 | Location 170-172 | Added on ...`.
+/// Returns the kind of the clipping and its location, or `None` if the clipping is not a
+/// highlight or a note.
+fn parse_clipping_description(description: &str) -> Option<(AnnotationKind, Option<String>)> {
+    let mut parts = description.split(" | ").map(str::trim);
+    let first = parts.next()?;
+    let (kind, rest) = if let Some(rest) = first.strip_prefix("Your Highlight") {
+        (AnnotationKind::Highlight, rest)
+    } else if let Some(rest) = first.strip_prefix("Your Note") {
+        (AnnotationKind::Note, rest)
+    } else {
+        return None;
+    };
+
+    let rest = rest.trim();
+    let rest = rest
+        .strip_prefix("at ")
+        .or_else(|| rest.strip_prefix("on "))
+        .unwrap_or(rest);
+    let location: Vec<_> = [rest]
+        .into_iter()
+        .chain(parts.filter(|part| !part.starts_with("Added on")))
+        .filter(|part| !part.is_empty())
+        .collect();
+    let location = (!location.is_empty()).then(|| location.join(", "));
+    Some((kind, location))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Annotation, AnnotationKind, ReadingData, ReadingStatus},
+        proptest::proptest,
+    };
+
+    proptest! {
+        #[test]
+        fn kindle_clippings_arbitrary_string(s in "\\PC*") {
+            let _ = ReadingData::from_kindle_clippings(&s);
+        }
+
+        #[test]
+        fn koreader_sidecar_arbitrary_string(s in "return \\{\\PC*") {
+            let _ = ReadingData::from_koreader_sidecar(&s);
+        }
+    }
+
+    const CLIPPINGS: &str = "\u{feff}Moby Dick; Or, The Whale (Melville, Herman)
+- Your Highlight on page 1 | Location 14-15 | Added on Monday, 1 January 2024 10:00:00
+
+Call me Ishmael.
+==========
+Moby Dick; Or, The Whale (Melville, Herman)
+- Your Bookmark on page 3 | Location 40 | Added on Monday, 1 January 2024 10:05:00
+
+
+==========
+Faust
+- Your Note on Location 7 | Added on Tuesday, 2 January 2024 09:00:00
+
+Read again.
+==========
+";
+
+    #[test]
+    fn kindle_clippings() {
+        let data = ReadingData::from_kindle_clippings(CLIPPINGS).expect("Valid clippings");
+        assert_eq!(
+            data,
+            vec![
+                ReadingData {
+                    title: "Moby Dick; Or, The Whale".to_owned(),
+                    authors: vec!["Melville, Herman".to_owned()],
+                    progress: None,
+                    annotations: vec![Annotation {
+                        kind: AnnotationKind::Highlight,
+                        text: "Call me Ishmael.".to_owned(),
+                        location: Some("page 1, Location 14-15".to_owned()),
+                    }],
+                    ..ReadingData::default()
+                },
+                ReadingData {
+                    title: "Faust".to_owned(),
+                    authors: Vec::new(),
+                    progress: None,
+                    annotations: vec![Annotation {
+                        kind: AnnotationKind::Note,
+                        text: "Read again.".to_owned(),
+                        location: Some("Location 7".to_owned()),
+                    }],
+                    ..ReadingData::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn koreader_sidecar() {
+        let sidecar = r#"-- we can read Lua syntax here!
+return {
+    ["annotations"] = {
+        [1] = {
+            ["chapter"] = "Loomings",
+            ["note"] = "Famous \"first\" line",
+            ["pageno"] = 5,
+            ["text"] = "Call me Ishmael.",
+        },
+    },
+    ["doc_props"] = {
+        ["authors"] = "Herman Melville",
+        ["title"] = "Moby Dick; Or, The Whale",
+    },
+    ["percent_finished"] = 0.256,
+}
+"#;
+        let data = ReadingData::from_koreader_sidecar(sidecar).expect("Valid sidecar");
+        assert_eq!(
+            data,
+            ReadingData {
+                title: "Moby Dick; Or, The Whale".to_owned(),
+                authors: vec!["Herman Melville".to_owned()],
+                progress: Some(26),
+                annotations: vec![
+                    Annotation {
+                        kind: AnnotationKind::Highlight,
+                        text: "Call me Ishmael.".to_owned(),
+                        location: Some("page 5".to_owned()),
+                    },
+                    Annotation {
+                        kind: AnnotationKind::Note,
+                        text: "Famous \"first\" line".to_owned(),
+                        location: Some("page 5".to_owned()),
+                    },
+                ],
+                ..ReadingData::default()
+            }
+        );
+    }
+
+    #[test]
+    fn koreader_sidecar_without_title() {
+        assert!(ReadingData::from_koreader_sidecar("return { [\"doc_props\"] = {} }").is_err());
+    }
+
+    #[test]
+    fn goodreads_csv() {
+        let csv = "Book Id,Title,Author,Additional Authors,ISBN,ISBN13,My Rating,Date Read,Exclusive Shelf
+1,\"The Fellowship of the Ring (The Lord of the Rings, #1)\",J.R.R. Tolkien,,\"=\"\"0618346252\"\"\",\"=\"\"9780618346257\"\"\",5,2021/05/03,read
+2,Moby Dick,Herman Melville,,,,0,,to-read
+";
+        let data = ReadingData::from_reading_log_csv(csv).expect("Valid export");
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].title, "The Fellowship of the Ring");
+        assert_eq!(data[0].authors, ["J.R.R. Tolkien"]);
+        assert_eq!(data[0].isbns.len(), 1);
+        assert_eq!(data[0].status, Some(ReadingStatus::Read));
+        assert_eq!(data[0].rating, Some(5));
+        assert_eq!(data[0].finished.as_deref(), Some("2021-05-03"));
+        assert_eq!(data[1].status, Some(ReadingStatus::ToRead));
+        assert_eq!(data[1].rating, None);
+        assert_eq!(data[1].finished, None);
+    }
+
+    #[test]
+    fn storygraph_csv() {
+        let csv = "Title,Authors,Contributors,ISBN/UID,Format,Read Status,Date Added,Last Date Read,Dates Read,Read Count,Star Rating
+Faust,Johann Wolfgang von Goethe,,9783150000014,paperback,did-not-finish,2020/01/01,,,0,3.5
+";
+        let data = ReadingData::from_reading_log_csv(csv).expect("Valid export");
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].status, Some(ReadingStatus::Abandoned));
+        assert_eq!(data[0].rating, Some(4));
+        assert!(ReadingData::from_reading_log_csv("Title,Author\nFaust,Goethe\n").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+//! Parser for CSV files as exported by Goodreads and The `StoryGraph`.
+//!
+//! Fields are separated by commas and may be quoted with `"`. Quoted fields may contain commas,
+//! line breaks and quotes, which are written as `""`.
+
+use {anyhow::bail, std::mem};
+
+/// Parse the contents of a CSV file into its records.
+///
+/// Empty lines are skipped.
+pub(super) fn parse(contents: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut chars = contents.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                let start = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                        None => bail!("Unterminated quoted field starting on line {}", start),
+                    }
+                }
+            }
+            ',' => record.push(mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line += 1;
+                record.push(mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn quoted_fields() {
+        let records = parse("Title,Review\r\n\"Moby Dick, Or\",\"A \"\"whale\"\"\nof a book\"\n\n")
+            .expect("Valid CSV");
+        assert_eq!(
+            records,
+            [
+                vec!["Title", "Review"],
+                vec!["Moby Dick, Or", "A \"whale\"\nof a book"],
+            ]
+        );
+        assert!(parse("\"Unterminated").is_err());
+    }
+}
@@ -0,0 +1,393 @@
+//! Parser for the subset of Lua used by `KOReader` metadata sidecars.
+//!
+//! A sidecar is a Lua chunk of the form `return { ... }` that only contains literals: tables,
+//! strings, numbers, booleans and `nil`. Expressions, function calls and long strings are not
+//! supported.
+
+use anyhow::{anyhow, bail, Context};
+
+/// A Lua value.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Value {
+    /// `nil`, `true` or `false`. Sidecars only use these for settings burette does not read.
+    Other,
+    Number(f64),
+    String(String),
+    Table(Table),
+}
+
+/// A Lua table, as a list of key-value pairs in the order they appear in the source.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(super) struct Table {
+    entries: Vec<(Value, Value)>,
+}
+
+impl Value {
+    /// Return the value as a table, if it is one.
+    pub(super) fn as_table(&self) -> Option<&Table> {
+        match self {
+            Value::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+
+    /// Return the value as a string, if it is one.
+    pub(super) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Return the value as a number, if it is one.
+    pub(super) fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+impl Table {
+    /// Return the value stored under the string key `key`.
+    pub(super) fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(entry_key, _)| entry_key.as_str() == Some(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Iterate over the values of the table.
+    pub(super) fn values(&self) -> impl Iterator<Item = &Value> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+}
+
+/// Parse a Lua chunk of the form `return <value>` and return the value.
+pub(super) fn parse(source: &str) -> anyhow::Result<Value> {
+    let mut parser = Parser { source, pos: 0 };
+    parser.skip_whitespace();
+    if !parser.eat_keyword("return") {
+        bail!("Expected `return` on line {}", parser.line());
+    }
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    parser.eat(';');
+    parser.skip_whitespace();
+    if parser.pos < source.len() {
+        bail!("Unexpected input on line {}", parser.line());
+    }
+    Ok(value)
+}
+
+/// State of the parser.
+struct Parser<'a> {
+    source: &'a str,
+    /// Byte offset of the next character to parse.
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Return the part of the source that has not been parsed yet.
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    /// Return the next character without consuming it.
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Consume and return the next character.
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Consume the next character if it is `c`.
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume `c` or return an error.
+    fn expect(&mut self, c: char) -> anyhow::Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            bail!("Expected `{}` on line {}", c, self.line())
+        }
+    }
+
+    /// Return the line number of the current position, starting at 1.
+    fn line(&self) -> usize {
+        self.source[..self.pos].matches('\n').count() + 1
+    }
+
+    /// Skip whitespace and comments.
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if let Some(comment) = trimmed.strip_prefix("--[[") {
+                self.pos += comment.find("]]").map_or(trimmed.len(), |end| end + 6);
+            } else if trimmed.starts_with("--") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Consume an identifier, if there is one.
+    fn identifier(&mut self) -> Option<&'a str> {
+        let rest = self.rest();
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            return None;
+        }
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        let start = self.pos;
+        self.pos += len;
+        Some(&self.source[start..self.pos])
+    }
+
+    /// Consume the keyword `keyword`, if it is next.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let start = self.pos;
+        if self.identifier() == Some(keyword) {
+            true
+        } else {
+            self.pos = start;
+            false
+        }
+    }
+
+    /// Parse a value.
+    fn parse_value(&mut self) -> anyhow::Result<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.parse_table().map(Value::Table),
+            Some(quote @ ('"' | '\'')) => self.parse_string(quote).map(Value::String),
+            Some(c) if c == '-' || c == '.' || c.is_ascii_digit() => {
+                self.parse_number().map(Value::Number)
+            }
+            _ => {
+                let line = self.line();
+                match self.identifier() {
+                    Some("nil" | "true" | "false") => Ok(Value::Other),
+                    _ => bail!("Expected a value on line {}", line),
+                }
+            }
+        }
+    }
+
+    /// Parse a table constructor.
+    fn parse_table(&mut self) -> anyhow::Result<Table> {
+        self.expect('{')?;
+        let mut table = Table::default();
+        // Key of the next value without an explicit key. Lua arrays start at 1.
+        let mut next_index = 1.0;
+        loop {
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Ok(table);
+            }
+
+            let key = if self.eat('[') {
+                let key = self.parse_value()?;
+                self.skip_whitespace();
+                self.expect(']')?;
+                self.skip_whitespace();
+                self.expect('=')?;
+                Some(key)
+            } else {
+                let start = self.pos;
+                let name = self.identifier().map(str::to_owned);
+                self.skip_whitespace();
+                match name {
+                    Some(name) if self.peek() == Some('=') && !self.rest().starts_with("==") => {
+                        self.expect('=')?;
+                        Some(Value::String(name))
+                    }
+                    _ => {
+                        self.pos = start;
+                        None
+                    }
+                }
+            };
+            let value = self.parse_value()?;
+            let key = key.unwrap_or_else(|| {
+                let key = Value::Number(next_index);
+                next_index += 1.0;
+                key
+            });
+            table.entries.push((key, value));
+
+            self.skip_whitespace();
+            if !self.eat(',') && !self.eat(';') {
+                self.expect('}')?;
+                return Ok(table);
+            }
+        }
+    }
+
+    /// Parse a string literal delimited by `quote`.
+    fn parse_string(&mut self, quote: char) -> anyhow::Result<String> {
+        let line = self.line();
+        self.expect(quote)?;
+        // Escape sequences may produce arbitrary bytes, so the string is built from bytes.
+        let mut bytes = Vec::new();
+        loop {
+            let c = self
+                .next_char()
+                .ok_or_else(|| anyhow!("Unterminated string starting on line {}", line))?;
+            match c {
+                c if c == quote => break,
+                '\n' => bail!("Unterminated string starting on line {}", line),
+                '\\' => self.parse_escape(&mut bytes)?,
+                c => {
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                }
+            }
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Parse the escape sequence following a backslash and append the result to `bytes`.
+    fn parse_escape(&mut self, bytes: &mut Vec<u8>) -> anyhow::Result<()> {
+        let line = self.line();
+        let c = self
+            .next_char()
+            .ok_or_else(|| anyhow!("Unterminated string on line {}", line))?;
+        let byte = match c {
+            'n' | '\n' => b'\n',
+            't' => b'\t',
+            'r' => b'\r',
+            'a' => 0x07,
+            'b' => 0x08,
+            'f' => 0x0c,
+            'v' => 0x0b,
+            '\\' => b'\\',
+            '"' => b'"',
+            '\'' => b'\'',
+            'z' => {
+                let rest = self.rest();
+                self.pos += rest.len() - rest.trim_start().len();
+                return Ok(());
+            }
+            'x' => {
+                let digits = self.rest().get(..2).unwrap_or_default();
+                let byte = u8::from_str_radix(digits, 16)
+                    .with_context(|| format!("Invalid escape sequence on line {line}"))?;
+                self.pos += 2;
+                byte
+            }
+            c if c.is_ascii_digit() => {
+                let mut digits = String::from(c);
+                while digits.len() < 3 {
+                    match self.peek() {
+                        Some(c) if c.is_ascii_digit() => {
+                            digits.push(c);
+                            self.pos += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                digits
+                    .parse()
+                    .with_context(|| format!("Invalid escape sequence on line {line}"))?
+            }
+            _ => bail!("Invalid escape sequence on line {}", line),
+        };
+        bytes.push(byte);
+        Ok(())
+    }
+
+    /// Parse a numeric literal, including a leading minus sign.
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "Lua numbers are doubles as well"
+    )]
+    fn parse_number(&mut self) -> anyhow::Result<f64> {
+        let line = self.line();
+        let negative = self.eat('-');
+        let rest = self.rest();
+        let mut len = 0;
+        let mut prev = None;
+        let is_hex = rest.starts_with("0x") || rest.starts_with("0X");
+        for c in rest.chars() {
+            let is_exponent_sign =
+                !is_hex && (c == '+' || c == '-') && matches!(prev, Some('e' | 'E'));
+            if !(c.is_ascii_alphanumeric() || c == '.' || is_exponent_sign) {
+                break;
+            }
+            len += c.len_utf8();
+            prev = Some(c);
+        }
+        let literal = &rest[..len];
+        self.pos += len;
+
+        let value = match literal
+            .strip_prefix("0x")
+            .or_else(|| literal.strip_prefix("0X"))
+        {
+            Some(hex) => u64::from_str_radix(hex, 16).ok().map(|value| value as f64),
+            None => literal.parse::<f64>().ok(),
+        }
+        .ok_or_else(|| anyhow!("Invalid number on line {}", line))?;
+        Ok(if negative { -value } else { value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{parse, Value},
+        proptest::proptest,
+    };
+
+    proptest! {
+        #[test]
+        fn parse_arbitrary_string(s in "(return )?[{}\\[\\]=,;'\"\\\\a-z0-9.x -]*") {
+            let _ = parse(&s);
+        }
+    }
+
+    #[test]
+    fn parse_nested_tables() {
+        let value = parse(
+            "-- comment\nreturn {\n  [\"a\"] = { 1, 2.5, -3e2 },\n  b = true,\n  [3] = nil; \
+             'x\\65\\n',\n}\n",
+        )
+        .expect("Valid chunk");
+        let table = value.as_table().expect("Table");
+        let a: Vec<_> = table
+            .get("a")
+            .and_then(Value::as_table)
+            .expect("Table")
+            .values()
+            .filter_map(Value::as_number)
+            .collect();
+        assert_eq!(a, vec![1.0, 2.5, -300.0]);
+        assert_eq!(table.get("b"), Some(&Value::Other));
+        assert_eq!(table.values().last().and_then(Value::as_str), Some("xA\n"));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse("{}").is_err());
+        assert!(parse("return { \"unterminated }").is_err());
+        assert!(parse("return { a = }").is_err());
+        assert!(parse("return {} trailing").is_err());
+        assert!(parse("return f()").is_err());
+    }
+}
@@ -5,7 +5,7 @@ use {
     serde::{de::Error, Deserialize, Serialize},
     sha2::{Digest, Sha256},
     std::{
-        fmt::{self, Debug, Display, Formatter, Write},
+        fmt::{self, Debug, Display, Formatter},
         io::{self, Read},
         str::FromStr,
     },
@@ -14,7 +14,7 @@ use {
 /// [SHA-256 hash](https://en.wikipedia.org/wiki/SHA-2).
 #[derive(Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct Hash {
-    bytes: [u8; 32],
+    bytes: [u8; Hash::LEN],
 }
 
 fn hex_digit(byte: u8) -> anyhow::Result<u8> {
@@ -27,20 +27,74 @@ fn hex_digit(byte: u8) -> anyhow::Result<u8> {
 }
 
 impl Hash {
+    /// The length of a hash in bytes.
+    pub const LEN: usize = 32;
+
+    /// The length of the hex representation of a hash.
+    pub const HEX_LEN: usize = 2 * Self::LEN;
+
     const SHORT_STRING_LENGTH: usize = 12;
 
+    /// Creates a new [`struct@Hash`] object from the raw bytes of a hash.
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; Self::LEN]) -> Self {
+        Hash { bytes }
+    }
+
+    /// Returns the raw bytes of the hash.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; Self::LEN] {
+        &self.bytes
+    }
+
     /// Returns a short string representation of the hash.
     ///
     /// This is useful for displaying the hash in a user interface.
     #[must_use]
     pub fn to_short_string(&self) -> String {
-        let mut result = String::with_capacity(Self::SHORT_STRING_LENGTH);
-        for byte in &self.bytes[..Self::SHORT_STRING_LENGTH / 2] {
-            write!(result, "{byte:02x}").expect("Writing to string cannot fail");
-        }
+        self.to_prefix_string(Self::SHORT_STRING_LENGTH)
+    }
+
+    /// Returns the first `length` hexadecimal digits of the hash.
+    ///
+    /// If `length` is larger than [`Hash::HEX_LEN`], the whole hash is returned.
+    #[must_use]
+    pub fn to_prefix_string(&self, length: usize) -> String {
+        let mut result = self.to_string();
+        result.truncate(length);
         result
     }
 
+    /// Returns the number of leading hexadecimal digits that the hash has in common with `other`.
+    #[must_use]
+    pub fn common_prefix_length(&self, other: &Hash) -> usize {
+        self.bytes
+            .iter()
+            .zip(&other.bytes)
+            .position(|(a, b)| a != b)
+            .map_or(Self::HEX_LEN, |i| {
+                let same_high_digit = self.bytes[i] >> 4 == other.bytes[i] >> 4;
+                2 * i + usize::from(same_high_digit)
+            })
+    }
+
+    /// Returns true if the hex representation of the hash starts with `prefix`.
+    ///
+    /// The comparison ignores case and leading and trailing whitespace in `prefix`, so prefixes
+    /// copied from other tools match as well.
+    #[must_use]
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        let prefix = prefix.trim().as_bytes();
+        if prefix.len() > Self::HEX_LEN {
+            return false;
+        }
+        prefix.iter().enumerate().all(|(i, &digit)| {
+            let byte = self.bytes[i / 2];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+            hex_digit(digit).is_ok_and(|digit| digit == nibble)
+        })
+    }
+
     /// Creates a new [`struct@Hash`] object from a hex-encoded string.
     ///
     /// # Errors
@@ -49,10 +103,10 @@ impl Hash {
     /// other than hexadecimal digits.
     pub fn from_hex(hex: &str) -> anyhow::Result<Self> {
         let slice = hex.as_bytes();
-        if slice.len() != 64 {
+        if slice.len() != Self::HEX_LEN {
             return Err(anyhow!("Invalid SHA-256 hash length"));
         }
-        let mut bytes = [0; 32];
+        let mut bytes = [0; Self::LEN];
 
         for (i, byte) in bytes.iter_mut().enumerate() {
             let hi = slice[i * 2];
@@ -86,6 +140,22 @@ impl FromStr for Hash {
     }
 }
 
+impl From<[u8; Hash::LEN]> for Hash {
+    fn from(bytes: [u8; Hash::LEN]) -> Self {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for Hash {
+    type Error = anyhow::Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| anyhow!("Invalid SHA-256 hash length"))?;
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
 impl Serialize for Hash {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         serializer.serialize_str(&self.to_string())
@@ -135,9 +205,11 @@ mod tests {
     use {
         super::*,
         anyhow::anyhow,
+        proptest::{prop_assert, prop_assert_eq, proptest, test_runner::TestCaseError},
         std::{
-            io::Read,
+            io::{self, Read},
             process::{Command, Stdio},
+            thread,
         },
     };
 
@@ -157,8 +229,8 @@ mod tests {
             .stdout
             .ok_or_else(|| anyhow!("Failed to open stdout"))?;
 
-        std::thread::spawn(move || {
-            std::io::copy(&mut reader, &mut stdin).expect("Failed to copy data");
+        thread::spawn(move || {
+            io::copy(&mut reader, &mut stdin).expect("Failed to copy data");
         });
 
         let mut output = String::new();
@@ -174,14 +246,66 @@ mod tests {
     #[test]
     fn hash_reader_test() {
         let source = "Some test data...";
-        let hash = hash_reader(source.as_bytes()).unwrap();
+        let hash = hash_reader(source.as_bytes()).expect("Reading from a slice cannot fail");
         let hash_str = hash.to_string();
 
-        let hash_ref = hash_reader_reference_impl(source.as_bytes()).unwrap();
+        let hash_ref =
+            hash_reader_reference_impl(source.as_bytes()).expect("sha256sum should be available");
 
         assert_eq!(hash_str, hash_ref);
     }
 
+    #[test]
+    fn starts_with() {
+        let hash =
+            Hash::from_hex("dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f")
+                .expect("Valid hash");
+        assert!(hash.starts_with(""));
+        assert!(hash.starts_with("d"));
+        assert!(hash.starts_with("dffd60"));
+        assert!(hash.starts_with("DFFD60"));
+        assert!(hash.starts_with("  dFfD60\n"));
+        assert!(
+            hash.starts_with("dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f")
+        );
+        assert!(!hash.starts_with("dffd61"));
+        assert!(!hash.starts_with("dffd 60"));
+        assert!(!hash.starts_with("dffg"));
+        assert!(
+            !hash.starts_with("dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f0")
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        let hash = hash_reader(&b"Some test data..."[..]).expect("Reading cannot fail");
+        assert_eq!(Hash::from_bytes(*hash.as_bytes()), hash);
+        assert_eq!(Hash::from(*hash.as_bytes()), hash);
+        assert_eq!(
+            Hash::try_from(&hash.as_bytes()[..]).expect("32 bytes"),
+            hash
+        );
+        assert!(Hash::try_from(&hash.as_bytes()[1..]).is_err());
+        assert!(Hash::try_from(&[0; Hash::LEN + 1][..]).is_err());
+        assert_eq!(hash.to_string().len(), Hash::HEX_LEN);
+    }
+
+    #[test]
+    fn prefixes() {
+        let hash =
+            Hash::from_hex("dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f")
+                .expect("Valid hash");
+        let other =
+            Hash::from_hex("dffd6121bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f")
+                .expect("Valid hash");
+        assert_eq!(hash.to_short_string(), "dffd6021bb2b");
+        assert_eq!(hash.to_prefix_string(7), "dffd602");
+        assert_eq!(hash.to_prefix_string(100), hash.to_string());
+        assert_eq!(hash.common_prefix_length(&other), 5);
+        assert_eq!(other.common_prefix_length(&hash), 5);
+        assert_eq!(hash.common_prefix_length(&hash), 64);
+    }
+
     #[test]
     fn from_to_string() {
         let hash =
@@ -193,10 +317,35 @@ mod tests {
         );
     }
 
+    proptest! {
+        #[test]
+        fn from_hex_arbitrary_string(s in "\\PC*") {
+            let _ = Hash::from_hex(&s);
+        }
+
+        #[test]
+        fn from_hex_to_string(hex in "[0-9a-f]{64}") {
+            prop_assert_eq!(Hash::from_hex(&hex).map(|hash| hash.to_string()).ok(), Some(hex));
+        }
+
+        #[test]
+        fn starts_with_prefix(hex in "[0-9a-f]{64}", len in 0_usize..=64) {
+            let hash = Hash::from_hex(&hex).map_err(|error| TestCaseError::fail(error.to_string()))?;
+            prop_assert!(hash.starts_with(&hex[..len]));
+            prop_assert!(hash.starts_with(&hex[..len].to_uppercase()));
+        }
+
+        #[test]
+        fn starts_with_arbitrary_prefix(prefix in "\\PC*") {
+            let hash = hash_reader(&b"Call me Ishmael."[..]).map_err(|error| TestCaseError::fail(error.to_string()))?;
+            let _ = hash.starts_with(&prefix);
+        }
+    }
+
     #[test]
     fn to_from_string() {
         let source = "hui";
-        let original = hash_reader(source.as_bytes()).unwrap();
+        let original = hash_reader(source.as_bytes()).expect("Reading from a slice cannot fail");
         let hash_str = original.to_string();
         let hash = Hash::from_str(&hash_str).expect("Valid hash");
         assert_eq!(hash, original);
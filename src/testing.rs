@@ -0,0 +1,345 @@
+//! Fixtures for tests of burette and of applications built on top of it.
+//!
+//! This module is only available if the `testing` feature is enabled. It provides
+//! [`TempLibrary`], a library in a temporary directory that is removed when it is dropped, and
+//! functions to create synthetic documents that burette recognizes as EPUB or PDF files.
+//!
+//! ```
+//! use burette::testing::TempLibrary;
+//!
+//! let library = TempLibrary::with_documents(3)?;
+//! assert_eq!(library.documents()?.count(), 3);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use {
+    crate::{sha256, AddOptions, DocMetadata, FileFormat, Library},
+    anyhow::Context,
+    std::{
+        env, fs,
+        ops::Deref,
+        path::{Path, PathBuf},
+        process,
+        sync::atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// Counter to give each [`TempLibrary`] of a process its own directory.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A library in a temporary directory.
+///
+/// The directory, including all documents in the library, is removed when the `TempLibrary` is
+/// dropped. `TempLibrary` dereferences to [`Library`], so all library operations can be called on
+/// it directly.
+#[derive(Debug)]
+pub struct TempLibrary {
+    root: PathBuf,
+    path: PathBuf,
+    library: Library,
+}
+
+impl TempLibrary {
+    /// Create an empty library in a new temporary directory.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the temporary directory or the library cannot be created.
+    pub fn new() -> anyhow::Result<Self> {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let root = env::temp_dir().join(format!("burette-testing-{}-{id}", process::id()));
+        // Leftovers from a previous process with the same ID would make `Library::new()` fail.
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).with_context(|| {
+            format!("Failed to create temporary directory at {}", root.display())
+        })?;
+        let path = root.join("library");
+        let library = match Library::new(&path) {
+            Ok(library) => library,
+            Err(error) => {
+                let _ = fs::remove_dir_all(&root);
+                return Err(error);
+            }
+        };
+        Ok(Self {
+            root,
+            path,
+            library,
+        })
+    }
+
+    /// Create a library in a new temporary directory that contains `count` synthetic documents.
+    ///
+    /// The documents are titled `Synthetic Document 1` to `Synthetic Document <count>` and
+    /// written by `Author 1` to `Author <count>`. Documents with odd numbers are EPUB files,
+    /// documents with even numbers are PDF files.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the library cannot be created or a document cannot be added.
+    pub fn with_documents(count: usize) -> anyhow::Result<Self> {
+        let library = Self::new()?;
+        for number in 1..=count {
+            let file_format = if number % 2 == 1 {
+                FileFormat::Epub
+            } else {
+                FileFormat::Pdf
+            };
+            let mut metadata = metadata(&format!("Synthetic Document {number}"), file_format);
+            metadata.authors = vec![format!("Author {number}")];
+            library.add(metadata)?;
+        }
+        Ok(library)
+    }
+
+    /// Return the library.
+    #[must_use]
+    pub fn library(&self) -> &Library {
+        &self.library
+    }
+
+    /// Return the path to the library directory.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Add a synthetic document with the given metadata to the library and return its hash.
+    ///
+    /// The contents of the document are created by [`synthetic_document()`] from the title and
+    /// the file format in `metadata`, so titles must be unique within a library.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the document cannot be added, e.g. because a document with
+    /// the same title and file format was added before.
+    pub fn add(&self, metadata: DocMetadata) -> anyhow::Result<sha256::Hash> {
+        let contents = synthetic_document(&metadata.title, metadata.file_format);
+        let hash = sha256::hash_reader(contents.as_slice())
+            .context("Failed to hash synthetic document")?;
+
+        let path = self
+            .root
+            .join(format!("{hash}.{}", metadata.file_format.extension()));
+        fs::write(&path, &contents)
+            .with_context(|| format!("Failed to write synthetic document to {}", path.display()))?;
+        let options = AddOptions {
+            move_file: true,
+            ..AddOptions::default()
+        };
+        self.library
+            .add_document_checked(&path, metadata, &options, |_| Ok(true))?;
+        Ok(hash)
+    }
+}
+
+impl Deref for TempLibrary {
+    type Target = Library;
+
+    fn deref(&self) -> &Library {
+        &self.library
+    }
+}
+
+impl Drop for TempLibrary {
+    fn drop(&mut self) {
+        // Errors cannot be reported from `drop()`, and a leftover temporary directory is harmless.
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Return metadata with the given title and file format, and all other fields empty.
+#[must_use]
+pub fn metadata(title: &str, file_format: FileFormat) -> DocMetadata {
+    DocMetadata {
+        title: title.to_owned(),
+        authors: Vec::new(),
+        isbns: Vec::new(),
+        file_format,
+        doi: None,
+        doc_type: None,
+        rights: None,
+        journal: None,
+        volume: None,
+        issue: None,
+        pages: None,
+    }
+}
+
+/// Return the contents of a small document in the given file format.
+///
+/// The document contains `title`, so documents with different titles have different hashes. The
+/// contents are detected as `file_format` by [`FileFormat::from_path()`], but the documents have
+/// no pages or chapters.
+#[must_use]
+pub fn synthetic_document(title: &str, file_format: FileFormat) -> Vec<u8> {
+    match file_format {
+        FileFormat::Epub => synthetic_epub(title),
+        FileFormat::Pdf => synthetic_pdf(title),
+    }
+}
+
+/// Return a PDF file whose document information contains `title`.
+fn synthetic_pdf(title: &str) -> Vec<u8> {
+    let mut escaped = String::with_capacity(title.len());
+    for c in title.chars() {
+        if matches!(c, '(' | ')' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    format!(
+        "%PDF-1.4\n\
+         1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+         2 0 obj\n<< /Type /Pages /Kids [] /Count 0 >>\nendobj\n\
+         3 0 obj\n<< /Title ({escaped}) >>\nendobj\n\
+         trailer\n<< /Root 1 0 R /Info 3 0 R >>\n%%EOF\n"
+    )
+    .into_bytes()
+}
+
+/// Return an EPUB file whose package document contains `title`.
+fn synthetic_epub(title: &str) -> Vec<u8> {
+    let title = title
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    let container = "<?xml version=\"1.0\"?>\n\
+        <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+        <rootfiles><rootfile full-path=\"content.opf\" media-type=\"application/oebps-package+xml\"/></rootfiles>\n\
+        </container>\n";
+    let package = format!(
+        "<?xml version=\"1.0\"?>\n\
+         <package version=\"3.0\" xmlns=\"http://www.idpf.org/2007/opf\">\n\
+         <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\"><dc:title>{title}</dc:title></metadata>\n\
+         <manifest/><spine/>\n\
+         </package>\n"
+    );
+    // The `mimetype` entry must come first and be stored uncompressed, this is how EPUB files are
+    // recognized.
+    zip_stored(&[
+        ("mimetype", b"application/epub+zip"),
+        ("META-INF/container.xml", container.as_bytes()),
+        ("content.opf", package.as_bytes()),
+    ])
+}
+
+/// Return a ZIP archive containing `entries` (name and contents) without compression.
+fn zip_stored(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    fn u16_le(value: usize) -> [u8; 2] {
+        u16::try_from(value)
+            .expect("Synthetic documents are small")
+            .to_le_bytes()
+    }
+    fn u32_le(value: usize) -> [u8; 4] {
+        u32::try_from(value)
+            .expect("Synthetic documents are small")
+            .to_le_bytes()
+    }
+
+    let mut archive = Vec::new();
+    let mut central_directory = Vec::new();
+    for (name, contents) in entries {
+        let offset = archive.len();
+        let crc = crc32(contents).to_le_bytes();
+
+        // Local file header, followed by the contents
+        archive.extend_from_slice(&[0x50, 0x4B, 0x03, 0x04, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        archive.extend_from_slice(&crc);
+        archive.extend_from_slice(&u32_le(contents.len()));
+        archive.extend_from_slice(&u32_le(contents.len()));
+        archive.extend_from_slice(&u16_le(name.len()));
+        archive.extend_from_slice(&[0, 0]);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(contents);
+
+        // Central directory header
+        central_directory
+            .extend_from_slice(&[0x50, 0x4B, 0x01, 0x02, 20, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        central_directory.extend_from_slice(&crc);
+        central_directory.extend_from_slice(&u32_le(contents.len()));
+        central_directory.extend_from_slice(&u32_le(contents.len()));
+        central_directory.extend_from_slice(&u16_le(name.len()));
+        central_directory.extend_from_slice(&[0; 12]);
+        central_directory.extend_from_slice(&u32_le(offset));
+        central_directory.extend_from_slice(name.as_bytes());
+    }
+
+    // End of central directory record
+    let directory_offset = archive.len();
+    archive.extend_from_slice(&central_directory);
+    archive.extend_from_slice(&[0x50, 0x4B, 0x05, 0x06, 0, 0, 0, 0]);
+    archive.extend_from_slice(&u16_le(entries.len()));
+    archive.extend_from_slice(&u16_le(entries.len()));
+    archive.extend_from_slice(&u32_le(central_directory.len()));
+    archive.extend_from_slice(&u32_le(directory_offset));
+    archive.extend_from_slice(&[0, 0]);
+    archive
+}
+
+/// Compute the CRC-32 checksum of `data` as used by ZIP archives.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{crc32, metadata, synthetic_document, TempLibrary},
+        crate::FileFormat,
+        std::fs,
+    };
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn synthetic_documents_are_detected() {
+        let library = TempLibrary::new().expect("Failed to create library");
+        for file_format in [FileFormat::Epub, FileFormat::Pdf] {
+            let contents = synthetic_document("Moby Dick (Abridged)", file_format);
+            let path = library
+                .path()
+                .join(format!("detect.{}", file_format.extension()));
+            fs::write(&path, contents).expect("Failed to write document");
+            assert_eq!(
+                FileFormat::from_path(&path).expect("Unknown format"),
+                file_format
+            );
+        }
+    }
+
+    #[test]
+    fn temp_library_is_removed_on_drop() {
+        let library = TempLibrary::with_documents(3).expect("Failed to create library");
+        let path = library.path().to_owned();
+        let titles: Vec<_> = library
+            .documents()
+            .expect("Failed to read index")
+            .map(|entry| entry.title().to_owned())
+            .collect();
+        assert_eq!(titles.len(), 3);
+        assert!(titles.contains(&"Synthetic Document 2".to_owned()));
+
+        let hash = library
+            .add(metadata("Another Document", FileFormat::Pdf))
+            .expect("Failed to add document");
+        assert!(library.open_document(&hash).is_ok());
+
+        drop(library);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,136 @@
+use {
+    anyhow::Context,
+    serde::{Deserialize, Serialize},
+    std::{
+        fmt::{self, Display, Formatter},
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// A point in time with a precision of one second.
+///
+/// Timestamps are stored as the number of seconds since the Unix epoch and displayed in UTC using
+/// the ISO 8601 format (e.g. `2025-01-31T12:34:56Z`).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timestamp {
+    secs: u64,
+}
+
+impl Timestamp {
+    /// Return the current time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system time is before the Unix epoch.
+    pub fn now() -> anyhow::Result<Self> {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("System time is before the Unix epoch")?
+            .as_secs();
+        Ok(Self { secs })
+    }
+
+    /// Create a timestamp from the number of seconds since the Unix epoch.
+    #[must_use]
+    pub fn from_unix_secs(secs: u64) -> Self {
+        Self { secs }
+    }
+
+    /// Return the number of seconds since the Unix epoch.
+    #[must_use]
+    pub fn unix_secs(&self) -> u64 {
+        self.secs
+    }
+
+    /// Create a timestamp from a [`SystemTime`], dropping fractions of a second.
+    ///
+    /// Returns `None` if the time is before the Unix epoch.
+    #[must_use]
+    pub fn from_system_time(time: SystemTime) -> Option<Self> {
+        let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(Self { secs })
+    }
+
+    /// Return the timestamp as a [`SystemTime`].
+    #[must_use]
+    pub fn to_system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.secs)
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let days = self.secs / 86_400;
+        let secs_of_day = self.secs % 86_400;
+
+        // Convert the number of days since the epoch to a civil date.
+        // See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z % 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + u64::from(month <= 2);
+
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            secs_of_day / 3600,
+            secs_of_day % 3600 / 60,
+            secs_of_day % 60,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::Timestamp,
+        std::time::{Duration, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn display_epoch() {
+        assert_eq!(
+            Timestamp::from_unix_secs(0).to_string(),
+            "1970-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn display_leap_day() {
+        assert_eq!(
+            Timestamp::from_unix_secs(951_782_400).to_string(),
+            "2000-02-29T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn display_time_of_day() {
+        assert_eq!(
+            Timestamp::from_unix_secs(1_738_326_896).to_string(),
+            "2025-01-31T12:34:56Z"
+        );
+    }
+
+    #[test]
+    fn system_time_round_trip() {
+        let time = UNIX_EPOCH + Duration::from_millis(951_782_400_500);
+        let timestamp = Timestamp::from_system_time(time).expect("After the epoch");
+        assert_eq!(timestamp, Timestamp::from_unix_secs(951_782_400));
+        let secs = timestamp
+            .to_system_time()
+            .duration_since(UNIX_EPOCH)
+            .expect("After the epoch")
+            .as_secs();
+        assert_eq!(secs, 951_782_400);
+        assert_eq!(
+            Timestamp::from_system_time(UNIX_EPOCH - Duration::from_secs(1)),
+            None
+        );
+    }
+}
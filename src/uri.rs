@@ -0,0 +1,203 @@
+//! `burette://` URIs and links to documents for note-taking applications.
+
+use {
+    crate::sha256,
+    anyhow::{bail, Context},
+    std::{
+        fmt::{self, Display, Formatter},
+        str::{self, FromStr},
+    },
+};
+
+/// URI referring to a document in the library, e.g. `burette://<hash>`.
+///
+/// The part after `burette://` is an identifier as accepted by
+/// [`crate::Library::find_document()`]. URIs created from a hash always contain the full hash,
+/// so they keep working when more documents are added to the library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentUri {
+    identifier: String,
+}
+
+impl DocumentUri {
+    /// The scheme of document URIs.
+    pub const SCHEME: &'static str = "burette";
+
+    /// Return the URI referring to the document with the given hash.
+    #[must_use]
+    pub fn from_hash(hash: &sha256::Hash) -> Self {
+        Self {
+            identifier: hash.to_string(),
+        }
+    }
+
+    /// Return the identifier of the document the URI refers to.
+    #[must_use]
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Return a link to the URI with the text `title` in the given style.
+    #[must_use]
+    pub fn link(&self, title: &str, style: LinkStyle) -> String {
+        match style {
+            LinkStyle::Markdown => format!("[{}]({self})", escape_markdown(title, "[]\\")),
+            LinkStyle::Obsidian => format!("[{}]({self})", escape_markdown(title, "[]\\|")),
+            LinkStyle::Org => {
+                // Org mode has no way to escape brackets in link descriptions.
+                let title: String = title
+                    .chars()
+                    .map(|c| match c {
+                        '[' => '(',
+                        ']' => ')',
+                        c => c,
+                    })
+                    .collect();
+                format!("[[{self}][{title}]]")
+            }
+        }
+    }
+}
+
+/// Escape all characters of `text` that are contained in `special` with a backslash.
+fn escape_markdown(text: &str, special: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Decode percent-encoded bytes (e.g. `%20`) in `s`.
+fn percent_decode(s: &str) -> anyhow::Result<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .and_then(|hex| str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .with_context(|| format!("Invalid percent-encoding in {s}"))?;
+            bytes.push(hex);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).with_context(|| format!("Invalid percent-encoding in {s}"))
+}
+
+impl Display for DocumentUri {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}://{}", Self::SCHEME, self.identifier)
+    }
+}
+
+impl FromStr for DocumentUri {
+    type Err = anyhow::Error;
+
+    /// Parse a URI of the form `burette://<identifier>`.
+    ///
+    /// `burette:<identifier>` is accepted as well, and a trailing slash is ignored, since some
+    /// applications add one when passing the URI on.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let Some((scheme, rest)) = s.split_once(':') else {
+            bail!(
+                "Invalid document URI {}, expected burette://<identifier>",
+                s
+            );
+        };
+        if !scheme.eq_ignore_ascii_case(Self::SCHEME) {
+            bail!(
+                "Invalid document URI {}, expected burette://<identifier>",
+                s
+            );
+        }
+        let rest = rest.strip_prefix("//").unwrap_or(rest);
+        let rest = rest.strip_suffix('/').unwrap_or(rest);
+        let identifier = percent_decode(rest)?;
+        if identifier.is_empty() {
+            bail!("Document URI {} does not contain an identifier", s);
+        }
+        Ok(Self { identifier })
+    }
+}
+
+/// Syntax of the links created by [`DocumentUri::link()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinkStyle {
+    /// Markdown, e.g. `[Moby Dick](burette://<hash>)`.
+    #[default]
+    Markdown,
+    /// Org mode, e.g. `[[burette://<hash>][Moby Dick]]`.
+    Org,
+    /// Markdown as understood by Obsidian, which additionally escapes `|`.
+    Obsidian,
+}
+
+impl Display for LinkStyle {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            LinkStyle::Markdown => "markdown",
+            LinkStyle::Org => "org",
+            LinkStyle::Obsidian => "obsidian",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for LinkStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "markdown" => Ok(LinkStyle::Markdown),
+            "org" => Ok(LinkStyle::Org),
+            "obsidian" => Ok(LinkStyle::Obsidian),
+            _ => bail!("Invalid link style: {}", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DocumentUri, LinkStyle};
+
+    #[test]
+    fn parse_uri() {
+        let uri: DocumentUri = "burette://moby-dick".parse().expect("Valid URI");
+        assert_eq!(uri.identifier(), "moby-dick");
+        assert_eq!(uri.to_string(), "burette://moby-dick");
+
+        let uri: DocumentUri = "burette:10.1000%2F182/".parse().expect("Valid URI");
+        assert_eq!(uri.identifier(), "10.1000/182");
+
+        assert!("https://example.com".parse::<DocumentUri>().is_err());
+        assert!("burette://".parse::<DocumentUri>().is_err());
+        assert!("burette://a%2".parse::<DocumentUri>().is_err());
+        assert!("moby-dick".parse::<DocumentUri>().is_err());
+    }
+
+    #[test]
+    fn link_styles() {
+        let uri: DocumentUri = "burette://abc".parse().expect("Valid URI");
+        let title = "[Draft] A|B";
+        assert_eq!(
+            uri.link(title, LinkStyle::Markdown),
+            "[\\[Draft\\] A|B](burette://abc)"
+        );
+        assert_eq!(
+            uri.link(title, LinkStyle::Obsidian),
+            "[\\[Draft\\] A\\|B](burette://abc)"
+        );
+        assert_eq!(
+            uri.link(title, LinkStyle::Org),
+            "[[burette://abc][(Draft) A|B]]"
+        );
+    }
+}
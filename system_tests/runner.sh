@@ -25,6 +25,8 @@ cargo build || exit 1
 # directory.
 TMP_DIR=$(mktemp -d)
 export HOME="$TMP_DIR"
+# The configuration is read from $XDG_CONFIG_HOME if it is set
+unset XDG_CONFIG_HOME
 
 export TEST_DOCS="$TEST_DIR/test_docs/"
 export LIBRARY_PATH="$HOME/.book-store/"
@@ -14,4 +14,8 @@ tree "$LIBRARY_PATH"
 
 echo
 echo 'index.json:'
-cat "$LIBRARY_PATH"/index.json
+# The original paths and modification times of the documents depend on the
+# location and checkout of the test documents, so they are replaced by
+# placeholders.
+sed -e "s|$(realpath "$TEST_DOCS")|\$TEST_DOCS|" \
+    -e 's|"source_modified": [0-9]*|"source_modified": $MTIME|' "$LIBRARY_PATH"/index.json
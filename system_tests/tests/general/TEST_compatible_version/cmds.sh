@@ -0,0 +1,11 @@
+#!/bin/sh
+
+# Libraries can be shared between versions of burette that only differ in their
+# patch version.
+
+set -e
+
+add_faust && echo
+echo '0.1.0' > $LIBRARY_PATH/burette_version
+
+burette list
@@ -0,0 +1,18 @@
+#!/bin/sh
+
+# Simulate an interrupted removal of Faust: the write-ahead log records the
+# removal, but neither the index nor the document store reflect it yet. The
+# entry of Darwin is unchanged, so it is taken from the index file.
+
+set -e
+
+add_darwin > /dev/null
+add_faust > /dev/null
+
+printf '{"documents":["%s"],"changed":[],"remove_documents":["%s"]}' \
+    "$HASH_DARWIN" "$HASH_FAUST" > "$LIBRARY_PATH/index.wal"
+
+burette list
+burette validate
+cd "$LIBRARY_PATH"
+ls . documents
@@ -0,0 +1,19 @@
+#!/bin/sh
+
+# `burette validate` does not write to the library, so it reports an
+# interrupted update instead of completing it.
+
+set -e
+
+add_darwin > /dev/null
+add_faust > /dev/null
+
+printf '{"documents":["%s"],"changed":[],"remove_documents":["%s"]}' \
+    "$HASH_DARWIN" "$HASH_FAUST" > "$LIBRARY_PATH/index.wal"
+
+! burette validate
+test -e "$LIBRARY_PATH/index.wal"
+echo
+
+burette list
+burette validate
@@ -0,0 +1,15 @@
+#!/bin/sh
+
+# Libraries created before the index had a schema version store the index as a
+# plain list of documents. They can still be read, and the index is converted
+# to the current format the next time it is written.
+
+set -e
+
+echo '[]' > "$LIBRARY_PATH"/index.json
+rm "$LIBRARY_PATH"/index.json.sha256
+
+burette list
+add_faust > /dev/null
+burette list
+head -n 2 "$LIBRARY_PATH"/index.json
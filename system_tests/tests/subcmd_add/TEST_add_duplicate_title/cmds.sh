@@ -0,0 +1,31 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+echo
+
+# Same title, but with different punctuation and case
+! burette add $TEST_DOCS/moby_dick_2.epub << EOF
+moby dick -- or the whale
+yes
+Herman Melville
+no
+no
+no
+no
+EOF
+echo
+burette list
+echo
+
+burette add --allow-duplicate-title $TEST_DOCS/moby_dick_2.epub << EOF
+Moby Dick; Or, The Whale
+yes
+Herman Melville
+no
+no
+no
+EOF
+echo
+burette list
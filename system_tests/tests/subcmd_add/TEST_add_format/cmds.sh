@@ -0,0 +1,17 @@
+#!/bin/sh
+
+set -e
+
+! burette add --format mobi $TEST_DOCS/markdown_file.md
+
+# The file format given by the user is trusted
+burette add --format pdf $TEST_DOCS/markdown_file.md << EOF
+This is a markdown file
+n
+n
+n
+n
+EOF
+
+echo
+burette list --format "{format}: {title}"
@@ -0,0 +1,16 @@
+#!/bin/sh
+
+set -e
+
+# PDF files with garbage before the header are recognized
+{ printf 'garbage\n'; cat "$TEST_DOCS/var_chrom.pdf"; } > garbage.pdf
+burette add garbage.pdf << EOF
+Variations Chromatiques de concert
+n
+n
+n
+n
+EOF
+
+echo
+burette list --format "{format}: {title}"
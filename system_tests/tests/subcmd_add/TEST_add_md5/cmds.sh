@@ -0,0 +1,17 @@
+#!/bin/sh
+
+set -e
+
+burette add --md5 $TEST_DOCS/faust_teil_1.epub << EOF
+Faust: Eine Tragödie [erster Teil]
+y
+Johann Wolfgang von Goethe
+n
+n
+n
+EOF
+echo
+
+MD5=$(md5sum $TEST_DOCS/faust_teil_1.epub | cut -d ' ' -f 1)
+burette show "md5:$MD5" | sed "s|$(realpath "$TEST_DOCS")|\$TEST_DOCS|"
+! burette show md5:00000000000000000000000000000000
@@ -0,0 +1,19 @@
+#!/bin/sh
+
+set -e
+
+cp $TEST_DOCS/faust_teil_1.epub faust.epub
+
+burette add --move faust.epub << EOF
+Faust: Eine Tragödie [erster Teil]
+Yes
+Johann Wolfgang von Goethe
+No
+No
+No
+EOF
+echo
+
+! test -e faust.epub
+burette list
+burette validate
@@ -0,0 +1,38 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+
+burette add --type article "$TEST_DOCS/var_chrom.pdf" << EOF
+Variations Chromatiques de concert
+yes
+Georges Bizet
+no
+no
+yes
+Journal of Chromatic Studies
+yes
+12
+no
+yes
+101-117
+EOF
+echo
+burette show "$HASH_VAR_CHROM" | grep -e '^Type' -e '^Journal' -e '^Volume' -e '^Issue' -e '^Pages'
+echo
+
+burette list --type article
+echo
+burette list --type book
+echo
+burette edit --yes "$HASH_DARWIN" type << EOF
+yes
+Book
+EOF
+echo
+burette list --type book
+echo
+burette get --filter 'type:article' -d articles
+echo
+! burette list --type paper
@@ -0,0 +1,15 @@
+#!/bin/sh
+
+# The document is added even if the event log cannot be written, which is only
+# reported as a warning.
+
+set -e
+
+rm -f "$LIBRARY_PATH/events.log"
+mkdir "$LIBRARY_PATH/events.log"
+
+add_darwin > /dev/null 2> stderr.txt
+sed "s|$HOME|~|" stderr.txt >&2
+burette list
+rmdir "$LIBRARY_PATH/events.log"
+burette validate
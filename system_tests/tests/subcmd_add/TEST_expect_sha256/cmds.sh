@@ -0,0 +1,34 @@
+#!/bin/sh
+
+set -e
+
+# The hash is checked before asking for metadata
+! burette add --expect-sha256 $HASH_DARWIN $TEST_DOCS/faust_teil_1.epub 2>&1 \
+    | sed "s|$TEST_DOCS|\$TEST_DOCS|"
+echo
+
+burette add --expect-sha256 $HASH_FAUST $TEST_DOCS/faust_teil_1.epub > /dev/null << EOF
+Faust: Eine Tragödie [erster Teil]
+y
+Johann Wolfgang von Goethe
+n
+n
+n
+EOF
+
+(cd $TEST_DOCS && sha256sum darwin.epub moby_dick_1.epub) > SHA256SUMS
+echo "$HASH_FAUST *var_chrom.pdf" >> SHA256SUMS
+burette add --checksums SHA256SUMS $TEST_DOCS/moby_dick_1.epub > /dev/null << EOF
+Moby Dick; Or, The Whale
+y
+Herman Melville
+n
+n
+n
+EOF
+! burette add --checksums SHA256SUMS $TEST_DOCS/var_chrom.pdf 2>&1 \
+    | sed "s|$TEST_DOCS|\$TEST_DOCS|"
+! burette add --checksums SHA256SUMS $TEST_DOCS/faust_teil_1.epub 2>&1 \
+    | sed "s|$TEST_DOCS|\$TEST_DOCS|"
+echo
+burette list
@@ -0,0 +1,21 @@
+#!/bin/sh
+
+set -e
+
+cat > faust.json << 'END'
+{
+  "title": "Faust: Eine Tragödie [erster Teil]",
+  "authors": ["Johann Wolfgang von Goethe"],
+  "isbns": [],
+  "file_format": "application/epub+zip",
+  "doi": null,
+  "rights": "public-domain"
+}
+END
+burette add "$TEST_DOCS"/faust_teil_1.epub --metadata faust.json
+burette list
+
+echo '{"title": "Moby Dick"}' > moby_dick.json
+! burette add "$TEST_DOCS"/moby_dick_1.epub --metadata moby_dick.json
+! burette add "$TEST_DOCS"/moby_dick_1.epub --metadata missing.json
+burette list
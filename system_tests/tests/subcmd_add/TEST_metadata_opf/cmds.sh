@@ -0,0 +1,32 @@
+#!/bin/sh
+
+set -e
+
+cat > faust.opf << 'END'
+<?xml version="1.0" encoding="utf-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="id">urn:uuid:4c2f7a0e-8a1b-4e0c-9c2d-6f1e3b5a7d90</dc:identifier>
+    <dc:title>Faust: Eine Trag&#246;die [erster Teil]</dc:title>
+    <dc:creator id="author">Johann Wolfgang von Goethe</dc:creator>
+    <meta refines="#author" property="role" scheme="marc:relators">aut</meta>
+    <dc:creator id="editor">Project Gutenberg</dc:creator>
+    <meta refines="#editor" property="role" scheme="marc:relators">edt</meta>
+    <dc:rights>public-domain</dc:rights>
+  </metadata>
+</package>
+END
+burette add "$TEST_DOCS"/faust_teil_1.epub --metadata faust.opf
+burette list
+
+# Documents exported for Calibre can be added again with their metadata
+add_moby_dick > /dev/null
+burette export-calibre calibre > /dev/null
+burette remove "$HASH_MOBY_DICK" > /dev/null
+burette add calibre/Herman\ Melville/*/*.epub --metadata calibre/Herman\ Melville/*/metadata.opf
+burette list
+
+# The document must have the hash given in the package
+sed -i 's/opf:scheme="sha256">2e/opf:scheme="sha256">3e/' calibre/Herman\ Melville/*/metadata.opf
+cp "$TEST_DOCS"/moby_dick_1.epub moby_dick.epub
+! burette add moby_dick.epub --metadata calibre/Herman\ Melville/*/metadata.opf
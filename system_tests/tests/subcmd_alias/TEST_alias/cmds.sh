@@ -0,0 +1,30 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+
+burette alias set origin 10.5962/bhl.title.59991
+burette alias set whale "$HASH_MOBY_DICK"
+burette alias set moby whale
+burette alias list
+echo
+
+burette show --format "{hash.short}: {title} ({aliases|join:\", \"})" origin
+echo
+burette get whale
+ls
+echo
+
+# Invalid and duplicate aliases
+! burette alias set cafe "$HASH_DARWIN"
+! burette alias set "two words" "$HASH_DARWIN"
+! burette alias set whale "$HASH_DARWIN"
+echo
+
+burette alias remove moby
+! burette alias remove moby
+! burette get moby
+burette alias list
+burette log | cut -d ' ' -f 2- | grep -c edit
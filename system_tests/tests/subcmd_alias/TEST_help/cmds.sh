@@ -0,0 +1,4 @@
+#!/bin/sh
+
+burette alias --help
+burette alias set --help
@@ -0,0 +1,30 @@
+#!/bin/sh
+
+set -e
+
+add_faust
+cp "$TEST_DOCS"/moby_dick_1.epub moby_dick.epub
+
+cat > ops.json << 'END'
+[
+  {
+    "op": "add",
+    "path": "moby_dick.epub",
+    "title": "Moby Dick; Or, The Whale",
+    "authors": ["Herman Melville"],
+    "isbns": [],
+    "file_format": "application/epub+zip",
+    "doi": null
+  },
+  {"op": "edit", "document": "e611fe8", "rights": "public-domain"},
+  {"op": "remove", "document": "missing"}
+]
+END
+! burette apply ops.json
+burette list
+
+sed -i 's/"missing"/"e611fe8"/' ops.json
+burette apply --dry-run ops.json
+burette list
+burette apply ops.json
+burette list
@@ -0,0 +1,5 @@
+#!/bin/sh
+
+burette apply -h
+burette apply --help
+burette help apply
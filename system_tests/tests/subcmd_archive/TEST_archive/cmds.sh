@@ -0,0 +1,17 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_faust > /dev/null
+burette archive "$HASH_DARWIN"
+burette list
+echo
+burette list --all
+echo
+burette get 10.5962/bhl.title.59991 || true
+burette get --all 10.5962/bhl.title.59991
+ls
+burette unarchive "$HASH_DARWIN"
+burette list
+burette validate
@@ -0,0 +1,5 @@
+#!/bin/sh
+burette archive -h
+burette archive --help
+burette help archive
+burette help unarchive
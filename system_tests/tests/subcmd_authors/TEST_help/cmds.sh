@@ -0,0 +1,6 @@
+#!/bin/sh
+burette authors -h
+burette authors --help
+burette help authors
+burette authors list --help
+burette authors merge --help
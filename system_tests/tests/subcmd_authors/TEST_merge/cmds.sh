@@ -0,0 +1,32 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_faust > /dev/null
+add_darwin > /dev/null
+
+burette edit --yes "$HASH_FAUST" authors > /dev/null << EOF
+y
+H. Melville
+y
+Melville, Herman
+y
+Herman Melville
+n
+EOF
+burette edit --yes "$HASH_DARWIN" authors > /dev/null << EOF
+y
+Charles Darwin
+y
+H. Melville
+n
+EOF
+
+burette authors list
+burette authors merge "H. Melville" "Melville, Herman" "Herman Melville"
+burette authors list
+burette show "$HASH_FAUST" | grep Author
+! burette authors merge "Herman Melville"
+burette authors list --documents-of "Herman Melville"
+burette authors list --documents-of "Nobody"
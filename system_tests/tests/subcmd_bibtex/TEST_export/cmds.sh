@@ -0,0 +1,23 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_var_chrom > /dev/null
+burette alias set moby $HASH_MOBY_DICK
+burette edit --yes $HASH_VAR_CHROM type > /dev/null << EOF2
+y
+report
+EOF2
+
+burette bibtex export
+
+mkdir jabref
+burette bibtex export --output jabref/library.bib --files jabref/files
+cat jabref/library.bib
+ls jabref/files
+
+# Exported files can be imported into another library.
+burette --library other new
+burette --library other bibtex import jabref/library.bib
+burette --library other list
@@ -0,0 +1,6 @@
+#!/bin/sh
+burette bibtex -h
+burette bibtex --help
+burette help bibtex
+burette bibtex import --help
+burette bibtex export --help
@@ -0,0 +1,49 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+
+mkdir -p jabref/papers
+cp $TEST_DOCS/moby_dick_1.epub jabref/papers/
+cp $TEST_DOCS/var_chrom.pdf jabref/
+cat > jabref/refs.bib << 'EOF2'
+% Encoding: UTF-8
+
+@Book{melville,
+  author = {Melville, Herman},
+  title  = {Moby Dick; {Or}, The Whale},
+  isbn   = {978-0198853695},
+  file   = {:papers/moby_dick_1.epub:ePUB},
+}
+
+@Misc{bizet,
+  author = {Georges Bizet},
+  title  = {Variations Chromatiques de concert},
+  file   = {Score:var\_chrom.pdf:PDF},
+}
+
+@Book{goethe,
+  author = {von Goethe, Johann Wolfgang},
+  title  = {Faust: Eine Trag{\"o}die [erster Teil]},
+  file   = {:../faust_teil_1.epub:ePUB},
+}
+
+@Book{unlinked,
+  title = {No File},
+}
+
+@Comment{jabref-meta: databaseType:bibtex;}
+EOF2
+cp $TEST_DOCS/faust_teil_1.epub .
+
+burette bibtex import --jobs 2 jabref/refs.bib
+burette list
+# The original path depends on $HOME.
+burette show $HASH_MOBY_DICK | sed "s|$HOME|\$HOME|"
+
+# Files that were imported before are skipped.
+burette bibtex import jabref/refs.bib
+
+echo '@Book{broken, title = {Unbalanced}' > broken.bib
+! burette bibtex import broken.bib
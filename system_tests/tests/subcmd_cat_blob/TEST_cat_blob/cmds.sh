@@ -0,0 +1,17 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_var_chrom > /dev/null
+burette archive "$HASH_VAR_CHROM"
+
+burette cat-blob "$HASH_MOBY_DICK" | cmp - "$TEST_DOCS/moby_dick_1.epub"
+# Archived documents can be read as well
+burette cat-blob "$HASH_VAR_CHROM" | sha256sum | cut -d ' ' -f 1
+# Reading a document does not count as a retrieval
+burette show "$HASH_MOBY_DICK" | grep '^Retrievals'
+
+# The full hash is required
+! burette cat-blob "$(echo $HASH_MOBY_DICK | cut -c 1-12)"
+! burette cat-blob "$HASH_DARWIN"
@@ -0,0 +1,4 @@
+#!/bin/sh
+burette cat-blob -h
+burette cat-blob --help
+burette help cat-blob
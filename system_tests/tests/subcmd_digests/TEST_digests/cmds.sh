@@ -0,0 +1,27 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_faust > /dev/null
+
+# Nothing is computed until digests are configured.
+burette digests update
+echo '{"digests": ["md5", "sha1"]}' > .book-store/settings.json
+burette digests update
+burette digests update
+
+# Digests of new documents are computed when they are added.
+add_darwin > /dev/null
+burette show $HASH_DARWIN | grep 'MD5\|Digest'
+
+burette digests manifest --algorithm md5
+burette digests manifest
+(cd .book-store && burette digests manifest --algorithm sha1 | sha1sum -c)
+
+burette validate --all-digests
+# MD5 hashes are recorded in the index, all other digests in checksums.json.
+! grep md5 .book-store/checksums.json
+sed -i "s|\"sha1\": \"[0-9a-f]*\"|\"sha1\": \"0000000000000000000000000000000000000000\"|" .book-store/checksums.json
+burette validate
+! burette validate --all-digests
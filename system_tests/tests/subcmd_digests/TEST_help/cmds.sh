@@ -0,0 +1,6 @@
+#!/bin/sh
+burette digests -h
+burette digests --help
+burette help digests
+burette digests update --help
+burette digests manifest --help
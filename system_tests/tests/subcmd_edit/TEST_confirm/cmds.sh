@@ -0,0 +1,33 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+
+# Declining the changes leaves the document as it was
+! burette edit "$HASH_FAUST" authors << EOF
+n
+n
+EOF
+echo
+burette list
+echo
+
+burette edit "$HASH_FAUST" authors << EOF
+y
+Goethe
+n
+y
+EOF
+echo
+burette list
+echo
+
+# Nothing to confirm if the value does not change
+burette edit "$HASH_FAUST" title << EOF
+Faust: Eine Tragödie [erster Teil]
+EOF
+echo
+
+echo "Faust" | burette edit --yes "$HASH_FAUST" title
+burette list
@@ -0,0 +1,39 @@
+#!/bin/sh
+
+set -e
+
+add_var_chrom > /dev/null
+
+burette edit "$HASH_VAR_CHROM" journal << EOF
+yes
+Journal of Chromatic Studies
+y
+EOF
+echo
+burette edit "$HASH_VAR_CHROM" volume << EOF
+yes
+12
+y
+EOF
+echo
+burette edit "$HASH_VAR_CHROM" issue << EOF
+yes
+3
+y
+EOF
+echo
+burette edit "$HASH_VAR_CHROM" pages << EOF
+yes
+101-117
+y
+EOF
+echo
+burette show "$HASH_VAR_CHROM" | grep -e '^Journal' -e '^Volume' -e '^Issue' -e '^Pages'
+echo
+
+burette edit "$HASH_VAR_CHROM" issue << EOF
+no
+y
+EOF
+echo
+burette show "$HASH_VAR_CHROM" | grep -e '^Journal' -e '^Volume' -e '^Issue' -e '^Pages'
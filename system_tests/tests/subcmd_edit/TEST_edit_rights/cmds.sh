@@ -0,0 +1,27 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_darwin > /dev/null
+
+burette edit "$HASH_DARWIN" rights << EOF
+yes
+public-domain
+y
+EOF
+echo
+burette show "$HASH_DARWIN" | grep '^Rights'
+echo
+
+# Only share the documents in the public domain
+burette get --filter 'rights:Public-Domain' -d public
+ls public
+echo
+
+burette edit "$HASH_DARWIN" rights << EOF
+no
+y
+EOF
+echo
+burette show "$HASH_DARWIN" | grep -c '^Rights' || true
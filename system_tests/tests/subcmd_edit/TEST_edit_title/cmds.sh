@@ -10,6 +10,7 @@ burette list && echo
 # Edit the document
 burette edit "$HASH_DARWIN" title << EOF
 Some new title for Darwin's old bookkk
+y
 EOF
 echo
 burette list && echo
@@ -17,6 +18,7 @@ burette list && echo
 # Change it back
 burette edit "$HASH_DARWIN" title << EOF
 On the Origin of Species blabla
+y
 EOF
 echo
 burette list && echo
@@ -0,0 +1,30 @@
+#!/bin/sh
+
+burette exit-codes
+echo
+
+add_moby_dick > /dev/null
+add_var_chrom > /dev/null
+
+# Print the exit code of a command, followed by a description of the command
+status() {
+    description=$1
+    shift
+    "$@" > /dev/null 2>&1
+    echo "$?: $description"
+}
+
+status "show existing document" burette show "$HASH_MOBY_DICK"
+status "show unknown hash prefix" burette show 0000
+status "show ambiguous hash prefix" burette show 2
+status "get with invalid hash prefix" burette get ""
+status "cat-blob of unknown document" burette cat-blob "$HASH_DARWIN"
+status "fulfill unknown wanted document" burette fulfill 1 "$TEST_DOCS/darwin.epub"
+status "restore unknown snapshot" burette snapshot restore missing
+status "remove with unknown identifier" burette remove "$HASH_MOBY_DICK" 0000
+status "validate valid library" burette validate
+rm "$LIBRARY_PATH/documents/$HASH_VAR_CHROM"
+status "validate invalid library" burette validate
+status "unknown command" burette unknown-command
+status "missing argument" burette show
+status "version" burette --version
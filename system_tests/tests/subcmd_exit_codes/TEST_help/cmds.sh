@@ -0,0 +1,4 @@
+#!/bin/sh
+burette exit-codes -h
+burette exit-codes --help
+burette help exit-codes
@@ -0,0 +1,19 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+add_moby_dick > /dev/null
+add_darwin > /dev/null
+burette archive "$HASH_DARWIN"
+
+burette export-calibre calibre
+(cd calibre && find . -type f | sort)
+echo
+cat calibre/Herman\ Melville/*/metadata.opf
+echo
+
+burette export-calibre --all --filter 'author:darwin' darwin
+(cd darwin && find . -type f | sort)
+
+! burette export-calibre calibre
@@ -0,0 +1,5 @@
+#!/bin/sh
+
+burette export-calibre -h
+burette export-calibre --help
+burette help export-calibre
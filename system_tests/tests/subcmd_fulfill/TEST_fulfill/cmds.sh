@@ -0,0 +1,35 @@
+#!/bin/sh
+
+set -e
+
+burette want add << EOF > /dev/null
+Moby Dick; Or, The Whale
+yes
+Herman Melville
+no
+yes
+978-0198853695
+no
+no
+EOF
+burette want add --type article << EOF > /dev/null
+Variations Chromatiques de concert
+yes
+Georges Bizet
+no
+no
+EOF
+
+burette fulfill 1 "$TEST_DOCS/moby_dick_1.epub"
+burette want list
+echo
+burette list
+echo
+burette show "$HASH_MOBY_DICK" | grep -e '^Title' -e '^Authors' -e '^ISBNs'
+echo
+
+# Wanted documents that do not exist cannot be fulfilled
+! burette fulfill 1 "$TEST_DOCS/var_chrom.pdf"
+# Unsupported files are rejected and the wanted document is kept
+! burette fulfill 2 "$TEST_DOCS/../helpers.sh"
+burette want list
@@ -0,0 +1,4 @@
+#!/bin/sh
+burette fulfill -h
+burette fulfill --help
+burette help fulfill
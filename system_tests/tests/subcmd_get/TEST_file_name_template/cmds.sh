@@ -0,0 +1,21 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_faust > /dev/null
+
+mkdir -p .config/burette
+echo '{"file_name_template": "{authors} - {title} ({hash}).{ext}"}' > .config/burette/config.json
+
+burette get $HASH_MOBY_DICK
+ls *.epub
+
+echo '{"file_name_template": "{year}.{ext}"}' > .config/burette/config.json
+! burette get $HASH_FAUST
+
+echo '{"file_name_templat": "{title}.{ext}"}' > .config/burette/config.json
+# The error message contains the path of the configuration file, which depends
+# on $HOME.
+! burette get $HASH_FAUST 2> error.txt
+sed "s|$HOME|\$HOME|" error.txt >&2
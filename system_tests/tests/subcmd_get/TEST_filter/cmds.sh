@@ -0,0 +1,16 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+add_faust > /dev/null
+
+burette get --filter 'author:melville'
+echo
+burette get --filter 'format:epub' -d epubs
+ls epubs
+echo
+burette get --filter 'title:"no such title"'
+echo
+! burette get --filter 'year:1851'
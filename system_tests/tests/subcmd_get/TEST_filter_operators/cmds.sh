@@ -0,0 +1,22 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+add_faust > /dev/null
+add_var_chrom > /dev/null
+
+burette get --filter 'author:darwin OR author:melville' -d or
+ls or
+echo
+burette get --filter 'NOT format:pdf AND NOT author:goethe' -d not
+ls not
+echo
+burette get --filter '(author:darwin OR author:goethe) format:epub' -d grouped
+ls grouped
+echo
+burette get --filter '"OR"'
+echo
+! burette get --filter 'author:darwin OR'
+! burette get --filter '(author:darwin'
@@ -0,0 +1,17 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+add_faust > /dev/null
+
+burette get "$HASH_DARWIN" "$HASH_MOBY_DICK" "$HASH_DARWIN" -d out
+ls out
+echo
+
+! burette get "$HASH_FAUST" 0000 -d out
+ls out
+echo
+
+! burette get "$HASH_FAUST" "$HASH_MOBY_DICK" -o faust.pdf
@@ -0,0 +1,19 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_faust > /dev/null
+
+mkdir -p .config/burette
+echo '{"file_name_template": "{authors} - {title}.{ext}"}' > .config/burette/config.json
+
+# The library settings take precedence over the user configuration.
+echo '{"file_name_template": "{title} ({hash}).{ext}"}' > .book-store/settings.json
+burette get $HASH_MOBY_DICK
+ls *.epub
+
+echo '{"file_name_templat": "{title}.{ext}"}' > .book-store/settings.json
+# The error message contains the path of the library, which depends on $HOME.
+! burette get $HASH_FAUST 2> error.txt
+sed "s|$HOME|\$HOME|" error.txt >&2
@@ -0,0 +1,8 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+
+burette get --original-name $HASH_FAUST
+ls
@@ -0,0 +1,17 @@
+#!/bin/sh
+
+set -e
+
+add_var_chrom > /dev/null
+
+burette get $HASH_VAR_CHROM
+! burette get $HASH_VAR_CHROM
+burette get --number $HASH_VAR_CHROM
+burette get -n $HASH_VAR_CHROM
+ls
+
+echo 'some text' > text.pdf
+burette get --force --output text.pdf $HASH_VAR_CHROM
+cmp text.pdf variations_chromatiques_de_concert.pdf
+
+! burette get --force --number $HASH_VAR_CHROM
@@ -0,0 +1,24 @@
+#!/bin/sh
+
+set -e
+
+cp $TEST_DOCS/faust_teil_1.epub faust.epub
+touch -d @951782400 faust.epub
+
+burette add faust.epub > /dev/null << EOF
+Faust: Eine Tragödie [erster Teil]
+Yes
+Johann Wolfgang von Goethe
+No
+No
+No
+EOF
+rm faust.epub
+
+burette show --format '{modified}' $HASH_FAUST
+
+burette get --output default.epub $HASH_FAUST
+test "$(date -r default.epub +%s)" != 951782400
+
+burette get --preserve-mtime --output preserved.epub $HASH_FAUST
+date -u -r preserved.epub +%Y-%m-%dT%H:%M:%SZ
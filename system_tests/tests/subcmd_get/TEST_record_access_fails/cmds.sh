@@ -0,0 +1,16 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+
+# Failing to record the access, e.g. in a read-only library, does not fail the retrieval.
+mkdir .book-store/index.wal.tmp
+burette get "$HASH_FAUST" --output faust.epub 2> warning
+sed "s|$HOME|~|" warning >&2
+sha256sum faust.epub | cut -d ' ' -f 1
+burette list --format '{hash.short} {access_count}'
+
+rmdir .book-store/index.wal.tmp
+burette get "$HASH_FAUST" --output faust_2.epub
+burette list --format '{hash.short} {access_count}'
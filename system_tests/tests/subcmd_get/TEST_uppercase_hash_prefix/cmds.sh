@@ -0,0 +1,10 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+
+burette get 1904714F169D
+burette get ' 2E511b '
+ls
@@ -0,0 +1,22 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+add_moby_dick > /dev/null
+
+burette get "$HASH_FAUST" --output faust.epub --with-sidecar
+cat faust.epub.burette.json
+echo
+
+# The sidecar carries the metadata to another library, and the hash makes
+# sure that it is added with the right file.
+burette -l other new
+burette -l other add faust.epub --metadata faust.epub.burette.json
+burette -l other list
+! burette -l other add "$TEST_DOCS"/moby_dick_1.epub \
+    --metadata faust.epub.burette.json 2>&1 | sed "s|$TEST_DOCS|\$TEST_DOCS|"
+echo
+
+burette get --filter 'author:melville' --directory out --with-sidecar
+ls out
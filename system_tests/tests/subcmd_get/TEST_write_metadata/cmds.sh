@@ -0,0 +1,31 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_var_chrom > /dev/null
+
+# A metadata command is required
+! burette get --write-metadata "$HASH_MOBY_DICK"
+
+mkdir -p .config/burette
+cat > .config/burette/config.json << EOF
+{
+    "metadata_command": ["sh", "-c", "printf '\\\\n%s\\\\n%s\\\\n' \"\$1\" \"\$2\" >> \"\$0\"", "{file}", "Title: {title}", "Authors: {authors|join:\" & \"}"]
+}
+EOF
+
+burette get --write-metadata --output moby_dick.epub "$HASH_MOBY_DICK"
+tail -n 2 moby_dick.epub
+# Without --write-metadata, the file is retrieved unchanged
+burette get --output var_chrom.pdf "$HASH_VAR_CHROM"
+cmp var_chrom.pdf "$TEST_DOCS/var_chrom.pdf"
+
+# No file is left behind if the metadata command fails
+cat > .config/burette/config.json << EOF
+{
+    "metadata_command": ["false", "{file}"]
+}
+EOF
+! burette get --write-metadata --output failed.epub "$HASH_MOBY_DICK"
+ls
@@ -0,0 +1,4 @@
+#!/bin/sh
+burette link-for -h
+burette link-for --help
+burette help link-for
@@ -0,0 +1,14 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+burette alias set moby "$HASH_MOBY_DICK"
+
+burette link-for moby
+burette link-for --style markdown "$HASH_MOBY_DICK"
+burette link-for --style org moby
+burette link-for -s obsidian moby
+
+! burette link-for --style html moby
+! burette link-for does-not-exist
@@ -0,0 +1,23 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_darwin > /dev/null
+burette lint-metadata
+
+add_var_chrom > /dev/null
+burette add $TEST_DOCS/faust_teil_1.epub > /dev/null << EOF
+faust_teil_1.epub
+y
+Johann Wolfgang von Goethe
+y
+johann wolfgang von goethe
+n
+n
+y
+10.1007/978-3-540-45117-4
+EOF
+
+! burette lint-metadata
+! burette lint-metadata --json
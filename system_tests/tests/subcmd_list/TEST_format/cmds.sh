@@ -0,0 +1,17 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_darwin > /dev/null
+add_var_chrom > /dev/null
+
+burette list --format '{hash.short}\t{title}\t{authors|join:", "}'
+echo
+burette list --format '{format|upper}: {title} ({isbns|first|default:"no ISBN"})'
+echo
+burette list --format '\{{doi|default:-}\}'
+
+! burette list --format '{year}'
+! burette list --format '{title'
+! burette list --format '{authors|reverse}'
@@ -0,0 +1,20 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_faust > /dev/null
+add_moby_dick > /dev/null
+burette get -o moby_dick_1.epub "$HASH_MOBY_DICK"
+burette get -o moby_dick_2.epub "$HASH_MOBY_DICK"
+# Make sure that the retrievals happen at different times.
+sleep 1
+burette get -o faust.epub "$HASH_FAUST"
+
+burette list --sort title
+echo
+burette list --sort access-count
+echo
+burette list --sort last-opened
+echo
+burette list --sort invalid || true
@@ -0,0 +1,4 @@
+#!/bin/sh
+burette log -h
+burette log --help
+burette help log
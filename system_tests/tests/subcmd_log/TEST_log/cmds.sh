@@ -0,0 +1,21 @@
+#!/bin/sh
+
+set -e
+
+# The first two columns contain the timestamp and the user, which differ between runs.
+log() {
+    burette log "$@" | cut -d ' ' -f 3-
+}
+
+burette log
+add_darwin > /dev/null
+add_faust > /dev/null
+echo "New title" | burette edit --yes "$HASH_FAUST" title > /dev/null
+burette remove "$HASH_DARWIN" > /dev/null
+log
+echo
+log --operation add
+echo
+log --document "$HASH_FAUST"
+echo
+log --user nobody
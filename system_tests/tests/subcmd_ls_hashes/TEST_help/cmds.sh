@@ -0,0 +1,4 @@
+#!/bin/sh
+burette ls-hashes -h
+burette ls-hashes --help
+burette help ls-hashes
@@ -0,0 +1,18 @@
+#!/bin/sh
+
+set -e
+
+# An empty library prints nothing
+burette ls-hashes
+
+add_moby_dick > /dev/null
+add_darwin > /dev/null
+add_faust > /dev/null
+burette archive "$HASH_DARWIN"
+
+burette ls-hashes
+echo
+burette ls-hashes --all
+echo
+burette ls-hashes -z -a | od -c | grep -c '\\0'
+burette ls-hashes -z | xargs -0 -n 1 echo
@@ -0,0 +1,4 @@
+#!/bin/sh
+burette open-uri -h
+burette open-uri --help
+burette help open-uri
@@ -0,0 +1,26 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+burette alias set moby "$HASH_MOBY_DICK"
+
+mkdir -p .config/burette
+cat > .config/burette/config.json << EOF
+{
+    "open_command": ["sh", "-c", "echo \"Opened \$(basename \"\$1\")\"; wc -c < \"\$1\"", "sh", "{path}"]
+}
+EOF
+
+# Links printed by `link-for` can be opened
+burette open-uri "$(burette link-for moby | sed 's/.*(\(.*\))/\1/')"
+burette open-uri burette://moby
+burette open-uri burette:moby/
+burette show --format "{access_count}" moby
+
+# Archived documents can be opened as well
+burette archive "$HASH_MOBY_DICK" > /dev/null
+burette open-uri "burette://$HASH_MOBY_DICK"
+
+! burette open-uri https://example.com
+! burette open-uri burette://does-not-exist
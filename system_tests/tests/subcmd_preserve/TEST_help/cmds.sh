@@ -0,0 +1,4 @@
+#!/bin/sh
+burette preserve -h
+burette preserve --help
+burette help preserve
@@ -0,0 +1,37 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_var_chrom > /dev/null
+
+# The time of the check depends on when the test is run
+hide_time() {
+    sed 's/ on [0-9T:-]*Z)/ on $TIME)/'
+}
+
+# Only PDF documents can be checked
+! burette preserve "$HASH_MOBY_DICK"
+
+# Without a validator, a heuristic is used
+burette preserve "$HASH_VAR_CHROM" | hide_time
+! burette preserve --convert "$HASH_VAR_CHROM"
+
+# The conversion adds a PDF/A identification
+mkdir -p .config/burette
+cat > .config/burette/config.json << EOF
+{
+    "pdfa_convert": ["sh", "-c", "cp \"\$0\" \"\$1\" && echo '<rdf:Description pdfaid:part=\"2\" pdfaid:conformance=\"b\"/>' >> \"\$1\"", "{input}", "{output}"]
+}
+EOF
+burette preserve --convert "$HASH_VAR_CHROM" | hide_time
+burette show --format "{filename}" "$HASH_VAR_CHROM"
+burette show "$HASH_VAR_CHROM" | grep '^PDF/A' | hide_time
+
+# A configured validator decides about conformance
+cat > .config/burette/config.json << EOF
+{
+    "pdfa_validator": ["false", "{file}"]
+}
+EOF
+burette preserve "$HASH_VAR_CHROM" | hide_time
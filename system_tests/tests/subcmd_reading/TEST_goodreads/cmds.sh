@@ -0,0 +1,21 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_faust > /dev/null
+add_darwin > /dev/null
+
+# Moby Dick is matched by ISBN, although the title differs.
+cat > goodreads_library_export.csv << 'EOF2'
+Book Id,Title,Author,Author l-f,Additional Authors,ISBN,ISBN13,My Rating,Average Rating,Publisher,Binding,Number of Pages,Year Published,Original Publication Year,Date Read,Date Added,Bookshelves,Bookshelves with positions,Exclusive Shelf,My Review,Spoiler,Private Notes,Read Count,Owned Copies
+153747,"Moby-Dick or, the Whale",Herman Melville,"Melville, Herman",,"=""""","=""9780198853695""",4,3.53,Oxford,Paperback,720,2022,1851,2023/08/14,2023/01/02,,,read,"Long, but ""worth it"".
+Really.",,,1,0
+14082,"Faust: Eine Tragödie [erster Teil] (Faust, #1)",Johann Wolfgang von Goethe,"von Goethe, Johann Wolfgang",,"=""""","=""""",0,3.98,,,,,1808,,2023/01/02,currently-reading,currently-reading (#1),currently-reading,,,,0,0
+1,Unknown Book,Nobody,"Nobody",,"=""""","=""""",2,3.00,,,,,,,2023/01/02,,,to-read,,,,0,0
+EOF2
+
+burette reading import goodreads_library_export.csv
+burette reading show $HASH_MOBY_DICK
+burette reading show $HASH_FAUST
+burette show $HASH_MOBY_DICK | grep -e Reading -e Rating
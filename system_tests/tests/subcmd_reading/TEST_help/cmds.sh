@@ -0,0 +1,6 @@
+#!/bin/sh
+burette reading -h
+burette reading --help
+burette help reading
+burette reading import --help
+burette reading show --help
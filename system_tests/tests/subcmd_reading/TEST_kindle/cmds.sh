@@ -0,0 +1,40 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_darwin > /dev/null
+
+cat > "My Clippings.txt" << EOF
+Moby Dick; Or, The Whale (Melville, Herman)
+- Your Highlight on page 1 | Location 12-13 | Added on Monday, 3 June 2024 20:14:07
+
+Call me Ishmael.
+==========
+Moby Dick; Or, The Whale (Melville, Herman)
+- Your Bookmark on page 3 | Location 40 | Added on Monday, 3 June 2024 20:15:00
+
+
+==========
+Moby Dick; Or, The Whale (Melville, Herman)
+- Your Note on page 1 | Location 13 | Added on Monday, 3 June 2024 20:16:31
+
+Great opening line
+==========
+The Art of War (Sun Tzu)
+- Your Highlight on page 5 | Location 70-71 | Added on Tuesday, 4 June 2024 08:01:12
+
+All warfare is based on deception.
+==========
+EOF
+
+burette reading import "My Clippings.txt"
+echo
+# Importing the same clippings again does not duplicate the annotations
+burette reading import "My Clippings.txt"
+echo
+burette reading show "$HASH_MOBY_DICK"
+echo
+burette reading show "$HASH_DARWIN"
+echo
+burette show "$HASH_MOBY_DICK" | grep -e '^Reading progress' -e '^Annotations'
@@ -0,0 +1,56 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+add_var_chrom > /dev/null
+
+mkdir faust.sdr
+cat > faust.sdr/metadata.epub.lua << EOF
+-- we can read Lua syntax here!
+return {
+    ["doc_props"] = {
+        ["authors"] = "Johann Wolfgang von Goethe",
+        ["title"] = "Faust: Eine Tragödie [erster Teil]",
+    },
+    ["percent_finished"] = 0.42,
+    ["annotations"] = {
+        [1] = {
+            ["chapter"] = "Nacht",
+            ["datetime"] = "2024-06-03 20:14:07",
+            ["pageno"] = 12,
+            ["text"] = "Habe nun, ach! Philosophie,\nJuristerei und Medizin",
+        },
+        [2] = {
+            ["chapter"] = "Vor dem Tor",
+            ["note"] = "Osterspaziergang",
+            ["pageno"] = 30,
+            ["text"] = "Vom Eise befreit sind Strom und Bäche",
+        },
+    },
+}
+EOF
+cat > other.lua << EOF
+return {
+    ["doc_props"] = {
+        ["title"] = "Some Unrelated Book",
+    },
+    ["percent_finished"] = 0.1,
+}
+EOF
+
+burette reading import faust.sdr other.lua
+echo
+burette reading show "$HASH_FAUST"
+echo
+# Reading data can be attached to a specific document
+burette reading import --document "$HASH_VAR_CHROM" other.lua
+echo
+burette reading show "$HASH_VAR_CHROM"
+echo
+burette show "$HASH_FAUST" | grep -e '^Reading progress' -e '^Annotations'
+echo
+
+# Invalid sidecars are rejected
+echo 'return {' > broken.lua
+! burette reading import broken.lua
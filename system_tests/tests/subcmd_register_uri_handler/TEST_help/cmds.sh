@@ -0,0 +1,4 @@
+#!/bin/sh
+burette register-uri-handler -h
+burette register-uri-handler --help
+burette help register-uri-handler
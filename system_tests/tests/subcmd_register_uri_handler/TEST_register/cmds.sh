@@ -0,0 +1,28 @@
+#!/bin/sh
+
+set -e
+
+# Record calls to xdg-mime instead of changing the handler of the system
+mkdir bin
+cat > bin/xdg-mime << EOF
+#!/bin/sh
+echo "xdg-mime \$*"
+EOF
+chmod +x bin/xdg-mime
+export PATH="$HOME/bin:$PATH"
+export XDG_DATA_HOME="$HOME/data"
+
+burette register-uri-handler | sed "s|$HOME|\$HOME|"
+sed 's|^Exec=[^ ]*|Exec=$BURETTE|' data/applications/burette-uri-handler.desktop
+
+# The library is passed on to the handler if it is given explicitly
+burette --library "$HOME/My Library" register-uri-handler > /dev/null
+grep '^Exec=' data/applications/burette-uri-handler.desktop \
+    | sed -e 's|^Exec=[^ ]*|Exec=$BURETTE|' -e "s|$HOME|\$HOME|"
+
+# Without xdg-mime, the desktop entry is still installed
+rm data/applications/burette-uri-handler.desktop
+BURETTE_DIR=$(dirname "$(command -v burette)")
+! PATH="$BURETTE_DIR" burette register-uri-handler 2> error
+sed "s|$HOME|\$HOME|" error
+ls data/applications
@@ -0,0 +1,26 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+add_faust > /dev/null
+add_var_chrom > /dev/null
+
+# Removing by filter asks for confirmation
+! echo no | burette remove --filter 'format:epub'
+echo
+burette list
+echo
+
+echo yes | burette remove --filter 'author:goethe' "$HASH_DARWIN"
+echo
+burette list
+echo
+
+! burette remove --json --filter 'format:pdf'
+burette remove --json --yes --filter 'format:pdf' > results.json
+sed -e "s|$(realpath "$TEST_DOCS")|\$TEST_DOCS|" \
+    -e 's|"source_modified": [0-9]*|"source_modified": $MTIME|' results.json
+echo
+burette list
@@ -0,0 +1,18 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+add_faust > /dev/null
+burette archive "$HASH_DARWIN"
+
+# Archived documents only match the filter with --all
+burette remove --yes --filter 'format:epub' "$HASH_FAUST"
+echo
+burette list --all
+echo
+
+burette remove --yes --all --filter 'format:epub'
+echo
+burette list --all
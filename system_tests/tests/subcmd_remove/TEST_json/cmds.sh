@@ -0,0 +1,18 @@
+#!/bin/sh
+
+set -e
+
+add_darwin && echo
+add_moby_dick && echo
+add_var_chrom && echo
+echo
+
+# $HASH_MOBY_DICK and $HASH_VAR_CHROM both start with 2
+! burette remove --json 2 $HASH_DARWIN abcdef > results.json
+# The original paths and modification times of the documents depend on the
+# location and checkout of the test documents, so they are replaced by
+# placeholders.
+sed -e "s|$(realpath "$TEST_DOCS")|\$TEST_DOCS|" \
+    -e 's|"source_modified": [0-9]*|"source_modified": $MTIME|' results.json
+echo
+burette list
@@ -0,0 +1,13 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+add_faust > /dev/null
+
+burette remove 9788417517212 '10.5962/bhl.title.59991'
+echo
+! burette remove 978-0198853695 '10.1000/no-such-doi'
+echo
+burette list
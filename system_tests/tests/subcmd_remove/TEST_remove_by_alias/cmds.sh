@@ -0,0 +1,13 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+
+burette alias set moby "$HASH_MOBY_DICK"
+echo y | burette remove moby
+echo
+! burette remove moby
+echo
+burette list
@@ -0,0 +1,23 @@
+#!/bin/sh
+
+# Documents can be removed by any identifier that `burette get` accepts.
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+burette add --md5 $TEST_DOCS/faust_teil_1.epub > /dev/null << EOF
+Faust: Eine Tragödie [erster Teil]
+y
+Johann Wolfgang von Goethe
+n
+n
+n
+EOF
+
+burette replace "$HASH_MOBY_DICK" "$TEST_DOCS/moby_dick_2.epub" > /dev/null
+MD5_FAUST=$(md5sum $TEST_DOCS/faust_teil_1.epub | cut -d ' ' -f 1)
+
+burette remove "$(echo "$HASH_MOBY_DICK" | cut -c 1-8)" "md5:$MD5_FAUST"
+echo
+burette list
@@ -0,0 +1,4 @@
+#!/bin/sh
+burette replace -h
+burette replace --help
+burette help replace
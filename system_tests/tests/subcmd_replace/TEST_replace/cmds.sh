@@ -0,0 +1,17 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_darwin > /dev/null
+burette replace "$HASH_MOBY_DICK" "$TEST_DOCS/moby_dick_2.epub"
+burette list
+burette validate
+ls "$LIBRARY_PATH/trash"
+burette log --operation replace | cut -d ' ' -f 3-
+
+# The old hash still refers to the document
+burette get -o moby_dick.epub "$HASH_MOBY_DICK"
+sha256sum moby_dick.epub | cut -d ' ' -f 1
+echo "Moby-Dick" | burette edit --yes "$(echo "$HASH_MOBY_DICK" | cut -c 1-8)" title > /dev/null
+burette list
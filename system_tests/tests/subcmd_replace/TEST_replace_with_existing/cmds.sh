@@ -0,0 +1,8 @@
+#!/bin/sh
+
+add_moby_dick > /dev/null
+add_darwin > /dev/null
+burette replace "$HASH_MOBY_DICK" "$TEST_DOCS/darwin.epub"
+burette replace "$HASH_FAUST" "$TEST_DOCS/faust_teil_1.epub"
+burette replace "$HASH_MOBY_DICK" "$TEST_DOCS/markdown_file.md"
+burette list
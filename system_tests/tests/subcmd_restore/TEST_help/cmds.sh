@@ -0,0 +1,4 @@
+#!/bin/sh
+burette restore -h
+burette restore --help
+burette help restore
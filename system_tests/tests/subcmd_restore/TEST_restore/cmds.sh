@@ -0,0 +1,31 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_faust > /dev/null
+add_moby_dick > /dev/null
+cp -r $HOME/.book-store $HOME/backup
+burette restore $HOME/backup
+
+# Damage the library after the backup was taken
+truncate -s 1000 $HOME/.book-store/documents/$HASH_FAUST
+rm $HOME/.book-store/documents/$HASH_DARWIN
+cp $HOME/.book-store/documents/$HASH_MOBY_DICK .
+burette remove $HASH_MOBY_DICK > /dev/null
+mv $HASH_MOBY_DICK $HOME/.book-store/documents/
+add_var_chrom > /dev/null
+
+echo
+burette restore $HOME/backup --only $(echo $HASH_FAUST | cut -c 1-8)
+! burette validate > /dev/null 2>&1
+echo
+burette restore $HOME/backup
+burette validate
+burette list
+
+# Documents that are not in the backup cannot be restored
+rm $HOME/.book-store/documents/$HASH_VAR_CHROM
+echo
+! burette restore $HOME/backup
+burette restore $HOME/does_not_exist 2>&1 | sed "s|$HOME|\$HOME|g"
@@ -0,0 +1,41 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_var_chrom > /dev/null
+
+mkdir -p .config/burette kindle kobo
+cat > .config/burette/config.json << EOF
+{
+    "devices": {
+        "kindle": {
+            "path": "kindle",
+            "format": "mobi",
+            "convert": ["cp", "{input}", "{output}"]
+        },
+        "kobo": {
+            "path": "kobo",
+            "format": "epub"
+        },
+        "broken": {
+            "path": "kindle",
+            "format": "azw3",
+            "convert": ["false", "{input}", "{output}"]
+        }
+    }
+}
+EOF
+
+# With multiple devices, --device is required
+! burette send "$HASH_MOBY_DICK"
+
+# Documents in other formats are converted
+burette send --device kindle "$HASH_MOBY_DICK"
+# Documents are sent unchanged if there is no conversion command
+burette send --device kobo "$HASH_VAR_CHROM"
+ls kindle kobo
+
+# Failed conversions do not leave files behind
+! burette send --device broken "$HASH_MOBY_DICK"
+ls kindle
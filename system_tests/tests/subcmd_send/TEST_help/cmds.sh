@@ -0,0 +1,4 @@
+#!/bin/sh
+burette send -h
+burette send --help
+burette help send
@@ -0,0 +1,36 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_var_chrom > /dev/null
+
+# No devices are configured yet
+! burette send "$HASH_MOBY_DICK"
+
+mkdir -p .config/burette
+cat > .config/burette/config.json << EOF
+{
+    "devices": {
+        "kobo": {
+            "path": "kobo",
+            "file_name_template": "{authors} - {title}.{ext}"
+        }
+    }
+}
+EOF
+
+# The device is not connected
+! burette send "$HASH_MOBY_DICK"
+
+mkdir kobo
+# With a single device, --device can be omitted
+burette send "$HASH_MOBY_DICK"
+! burette send --device kobo "$HASH_MOBY_DICK"
+burette send --device kobo --number "$HASH_MOBY_DICK"
+burette send -d kobo "$HASH_VAR_CHROM"
+ls kobo
+cmp "kobo/Herman Melville - Moby Dick; Or, The Whale.epub" "$TEST_DOCS/moby_dick_1.epub"
+
+! burette send --device kindle "$HASH_MOBY_DICK"
+burette show "$HASH_MOBY_DICK" | grep '^Retrievals' | cut -d ' ' -f 1,2
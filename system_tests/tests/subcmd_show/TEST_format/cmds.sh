@@ -0,0 +1,9 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+
+burette show "$HASH_MOBY_DICK" --format '{hash}'
+burette show "$HASH_MOBY_DICK" --format '{title}\n{authors}\n{isbns|join:" | "}'
+burette show "$HASH_MOBY_DICK" --format '{mime} {size} {archived} {access_count} [{last_accessed}] [{progress}]'
@@ -0,0 +1,5 @@
+#!/bin/sh
+
+burette show -h
+burette show --help
+burette help show
@@ -0,0 +1,14 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_moby_dick > /dev/null
+
+# The original paths of the documents depend on the location of the test
+# documents, so they are replaced by a placeholder.
+burette show 1904 | sed "s|$(realpath "$TEST_DOCS")|\$TEST_DOCS|"
+echo
+burette show 9788417517212 | sed "s|$(realpath "$TEST_DOCS")|\$TEST_DOCS|"
+echo
+! burette show 0000
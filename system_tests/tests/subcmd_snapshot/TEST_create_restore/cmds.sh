@@ -0,0 +1,19 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+burette snapshot create before_faust
+add_faust > /dev/null
+burette snapshot create after_faust
+burette snapshot list
+echo
+burette list
+echo
+burette snapshot restore before_faust
+burette list
+burette validate
+echo
+burette snapshot restore after_faust
+burette list
+burette validate
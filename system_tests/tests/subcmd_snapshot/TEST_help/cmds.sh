@@ -0,0 +1,4 @@
+#!/bin/sh
+burette snapshot -h
+burette snapshot --help
+burette help snapshot
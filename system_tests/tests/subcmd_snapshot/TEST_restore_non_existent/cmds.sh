@@ -0,0 +1,5 @@
+#!/bin/sh
+
+burette snapshot restore does_not_exist
+burette snapshot create ../escape
+burette snapshot list
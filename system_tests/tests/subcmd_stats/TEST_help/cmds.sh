@@ -0,0 +1,4 @@
+#!/bin/sh
+burette stats -h
+burette stats --help
+burette help stats
@@ -0,0 +1,14 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_faust > /dev/null
+add_moby_dick > /dev/null
+burette get -o darwin_1.epub "$HASH_DARWIN"
+burette get -o darwin_2.epub "$HASH_DARWIN"
+burette get -o faust.epub "$HASH_FAUST"
+burette archive "$HASH_MOBY_DICK"
+
+# The time of the last retrieval differs between runs.
+burette stats | sed 's/last on [^)]*/last on <time>/'
@@ -0,0 +1,5 @@
+#!/bin/sh
+
+burette undo -h
+burette undo --help
+burette help undo
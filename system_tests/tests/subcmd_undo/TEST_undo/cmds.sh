@@ -0,0 +1,29 @@
+#!/bin/sh
+
+set -e
+
+# The first column contains the timestamp, which differs between runs.
+history() {
+    burette undo --list | cut -d ' ' -f 2-
+}
+
+! burette undo
+add_faust > /dev/null
+add_darwin > /dev/null
+echo "New title" | burette edit --yes "$HASH_FAUST" title > /dev/null
+burette remove "$HASH_DARWIN" > /dev/null
+history
+burette list
+echo
+
+burette undo
+burette list
+burette undo
+burette list
+burette undo
+burette list
+echo
+
+history
+ls .book-store/trash
+burette validate
@@ -0,0 +1,29 @@
+#!/bin/sh
+
+# Undoing an addition keeps the file in the trash, even if it was moved into
+# the library and the library holds the only copy.
+
+set -e
+
+cp $TEST_DOCS/faust_teil_1.epub faust.epub
+
+burette add --move faust.epub > /dev/null << EOF
+Faust: Eine Tragödie [erster Teil]
+Yes
+Johann Wolfgang von Goethe
+No
+No
+No
+EOF
+
+burette undo
+burette list
+! test -e faust.epub
+ls .book-store/trash
+cmp .book-store/trash/$HASH_FAUST $TEST_DOCS/faust_teil_1.epub
+
+# The file is kept while further operations are performed.
+add_darwin > /dev/null
+burette remove "$HASH_DARWIN" > /dev/null
+ls .book-store/trash
+burette validate
@@ -0,0 +1,18 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+burette snapshot create s1
+add_darwin > /dev/null
+burette snapshot restore s1
+burette list
+echo
+
+# Documents removed by the restore are kept in the trash until it is undone.
+ls .book-store/trash
+burette undo
+burette list
+burette undo
+burette list
+burette validate
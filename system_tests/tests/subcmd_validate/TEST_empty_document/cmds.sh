@@ -0,0 +1,13 @@
+#!/bin/sh
+
+set -e
+
+add_darwin && echo
+add_faust && echo
+echo
+burette list
+
+: > $HOME/.book-store/documents/$HASH_DARWIN
+
+echo
+! burette validate
@@ -0,0 +1,12 @@
+#!/bin/sh
+
+# `burette validate` also works on libraries created by incompatible versions.
+
+set -e
+
+add_faust && echo
+echo '0.0.0' > $LIBRARY_PATH/burette_version
+
+! burette list
+echo
+burette validate
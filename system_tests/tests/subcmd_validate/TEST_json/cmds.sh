@@ -0,0 +1,14 @@
+#!/bin/sh
+
+set -e
+
+add_darwin && echo
+add_faust && echo
+echo
+burette validate --json
+
+mv $HOME/.book-store/documents/$HASH_DARWIN $HOME/.book-store/documents/evolution_book
+mkdir $HOME/.book-store/documents/unexpected_directory
+
+echo
+! burette validate --json
@@ -0,0 +1,21 @@
+#!/bin/sh
+
+set -e
+
+add_darwin > /dev/null
+add_faust > /dev/null
+
+# Edit the index without going through burette
+sed -i 's/Faust: Eine Tragödie/Faust/' "$LIBRARY_PATH"/index.json
+
+# We need to set the library path to a relative path so that the test output
+# will not contain $HOME which may vary.
+! burette -l ./.book-store list
+echo
+! burette -l ./.book-store validate
+echo
+
+# Accept the modification
+rm "$LIBRARY_PATH"/index.json.sha256
+burette -l ./.book-store validate
+burette -l ./.book-store list
@@ -0,0 +1,18 @@
+#!/bin/sh
+
+set -e
+
+add_faust > /dev/null
+
+# Fields that burette does not know are ignored, so their values would be lost
+# the next time the index is written.
+sed -i -e 's/"schema_version": 1,/"schema_version": 1, "owner": "me",/' \
+    -e 's/"title": /"notes": "Read in school", "title": /' \
+    "$LIBRARY_PATH"/index.json
+rm "$LIBRARY_PATH"/index.json.sha256
+
+burette validate
+echo
+! burette validate --strict
+echo
+! burette validate --strict --json
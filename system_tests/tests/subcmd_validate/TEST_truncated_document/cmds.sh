@@ -0,0 +1,13 @@
+#!/bin/sh
+
+set -e
+
+add_darwin && echo
+add_faust && echo
+echo
+burette list
+
+truncate -s 1000 $HOME/.book-store/documents/$HASH_FAUST
+
+echo
+! burette validate
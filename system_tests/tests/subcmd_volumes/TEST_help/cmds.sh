@@ -0,0 +1,4 @@
+#!/bin/sh
+burette volumes -h
+burette volumes --help
+burette help volumes
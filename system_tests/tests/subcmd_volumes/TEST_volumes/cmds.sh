@@ -0,0 +1,30 @@
+#!/bin/sh
+
+set -e
+
+add_moby_dick > /dev/null
+add_var_chrom > /dev/null
+add_faust > /dev/null
+
+! burette volumes "$HASH_MOBY_DICK"
+burette volumes "$HASH_MOBY_DICK" "$HASH_FAUST" "$HASH_VAR_CHROM"
+burette show "$HASH_MOBY_DICK" | grep '^Volume'
+
+# All volumes are retrieved into a directory
+burette get "$HASH_MOBY_DICK"
+ls moby_dick_or_the_whale
+burette get --number "$HASH_MOBY_DICK"
+ls -d moby_dick_or_the_whale*
+# Volumes can still be retrieved on their own
+burette get "$HASH_FAUST"
+ls
+
+# Volumes cannot be shared
+! burette volumes "$HASH_VAR_CHROM" "$HASH_FAUST"
+
+# Removed volumes are forgotten
+burette remove --yes "$HASH_FAUST" > /dev/null
+burette show "$HASH_MOBY_DICK" | grep '^Volume'
+
+burette volumes --clear "$HASH_MOBY_DICK"
+burette show "$HASH_MOBY_DICK" | grep -c '^Volume' || true
@@ -0,0 +1,4 @@
+#!/bin/sh
+burette want -h
+burette want --help
+burette help want
@@ -0,0 +1,37 @@
+#!/bin/sh
+
+set -e
+
+burette want add << EOF
+Moby Dick; Or, The Whale
+yes
+Herman Melville
+no
+yes
+978-0198853695
+no
+no
+EOF
+echo
+burette want add --type article << EOF
+Variations Chromatiques de concert
+yes
+Georges Bizet
+no
+no
+EOF
+echo
+burette want add << EOF
+Some book I changed my mind about
+no
+no
+no
+EOF
+echo
+burette want list
+echo
+burette want remove 3
+echo
+burette want list
+echo
+! burette want remove 3